@@ -0,0 +1,485 @@
+//! Clipping of paths to axis-aligned rectangles.
+
+use super::command::Command;
+use super::geometry::{Bounds, Point};
+use super::path_builder::{Arc, ConicQuads, PathBuilder};
+use super::path_data::PathData;
+#[cfg(not(feature = "std"))]
+use super::F32Ext;
+
+use crate::lib::Vec;
+use core::cmp::Ordering;
+
+/// Clips the filled area of a path to a rectangle and emits the result to
+/// the specified sink.
+///
+/// Each subpath is clipped independently against the four edges of the
+/// rectangle which preserves the result of both the non-zero and even-odd
+/// fill rules. Curves that lie entirely within the rectangle are emitted
+/// unchanged while those that cross an edge are flattened before clipping.
+///
+/// ```rust
+/// use zeno::{clip_to_rect, Bounds, Command};
+///
+/// let mut clipped: Vec<Command> = Vec::new();
+/// let rect = Bounds::new([0.0, 0.0].into(), [10.0, 10.0].into());
+/// clip_to_rect("M-5,-5 5,-5 5,5 -5,5 Z", rect, &mut clipped);
+/// assert_eq!(clipped.len(), 6);
+/// ```
+pub fn clip_to_rect(data: impl PathData, rect: Bounds, sink: &mut impl PathBuilder) {
+    let mut clipper = Clipper::new(rect, sink.tolerance());
+    let mut edges = Vec::new();
+    let mut start = Point::ZERO;
+    let mut current = Point::ZERO;
+    for cmd in data.commands() {
+        match cmd {
+            Command::MoveTo(p) => {
+                clipper.fill(start, &mut edges, sink);
+                start = p;
+                current = p;
+            }
+            Command::Close => {
+                clipper.fill(start, &mut edges, sink);
+                current = start;
+            }
+            _ => {
+                clipper.edge(current, cmd, &mut edges);
+                current = end_point(&cmd, current);
+            }
+        }
+    }
+    clipper.fill(start, &mut edges, sink);
+}
+
+/// Clips the center line of a path to a rectangle and emits the visible
+/// portions to the specified sink as open subpaths.
+///
+/// This is useful for clipping paths that will later be stroked. The
+/// rectangle should be expanded by the extent of the stroke to avoid
+/// clipping the stroke outline near the edges.
+pub fn clip_centerline_to_rect(data: impl PathData, rect: Bounds, sink: &mut impl PathBuilder) {
+    let mut clipper = Clipper::new(rect, sink.tolerance());
+    let mut edges = Vec::new();
+    let mut start = Point::ZERO;
+    let mut current = Point::ZERO;
+    for cmd in data.commands() {
+        match cmd {
+            Command::MoveTo(p) => {
+                clipper.lines(start, &mut edges, false, sink);
+                start = p;
+                current = p;
+            }
+            Command::Close => {
+                if current != start {
+                    clipper.edge(current, Command::LineTo(start), &mut edges);
+                }
+                clipper.lines(start, &mut edges, true, sink);
+                current = start;
+            }
+            _ => {
+                clipper.edge(current, cmd, &mut edges);
+                current = end_point(&cmd, current);
+            }
+        }
+    }
+    clipper.lines(start, &mut edges, false, sink);
+}
+
+/// Emits a text decoration such as an underline or strikethrough as a set of
+/// rectangles that leave gaps wherever the decoration would intersect the
+/// glyph outlines.
+///
+/// The glyph outlines are clipped to the decoration rectangle expanded
+/// vertically by the clearance, and the horizontal extent of the ink within
+/// that band, expanded by the clearance, is removed from the decoration. This
+/// produces the "skip ink" behavior commonly applied to underlines that
+/// cross descenders.
+///
+/// ```rust
+/// use zeno::{skip_ink, Bounds, Command};
+///
+/// // A descender that crosses the middle of the underline.
+/// let glyphs = "M10,0 12,0 12,20 10,20 Z";
+/// let rect = Bounds::new([0.0, 15.0].into(), [30.0, 16.0].into());
+/// let mut underline: Vec<Command> = Vec::new();
+/// skip_ink(rect, glyphs, 1.0, &mut underline);
+/// assert_eq!(underline[1], Command::LineTo([9.0, 15.0].into()));
+/// assert_eq!(underline[5], Command::MoveTo([13.0, 15.0].into()));
+/// ```
+pub fn skip_ink(rect: Bounds, glyphs: impl PathData, clearance: f32, sink: &mut impl PathBuilder) {
+    let band = Bounds::new(
+        Point::new(rect.min.x - clearance, rect.min.y - clearance),
+        Point::new(rect.max.x + clearance, rect.max.y + clearance),
+    );
+    let mut clipped = Vec::new();
+    clip_to_rect(glyphs, band, &mut clipped);
+    // The ink within the band spans the horizontal extent of the boundary of
+    // the clipped outlines. Edges that lie along the top or bottom of the band
+    // are only part of that boundary where they do not cancel out, as parts of
+    // outlines outside of the band collapse onto its edges.
+    let mut gaps = Vec::new();
+    let mut top = Vec::new();
+    let mut bottom = Vec::new();
+    let mut add = |a: Point, b: Point, points: &[Point]| {
+        if a.y == b.y && points.is_empty() {
+            if a.y == band.min.y {
+                top.push((a.x, 1));
+                top.push((b.x, -1));
+                return;
+            } else if a.y == band.max.y {
+                bottom.push((a.x, 1));
+                bottom.push((b.x, -1));
+                return;
+            }
+        }
+        if a.x == b.x && points.is_empty() && (a.x == band.min.x || a.x == band.max.x) {
+            return;
+        }
+        let (mut min, mut max) = (a.x.min(b.x), a.x.max(b.x));
+        for p in points {
+            min = min.min(p.x);
+            max = max.max(p.x);
+        }
+        gaps.push((min, max));
+    };
+    let mut start = Point::ZERO;
+    let mut current = Point::ZERO;
+    for cmd in &clipped {
+        match *cmd {
+            Command::MoveTo(p) => {
+                start = p;
+                current = p;
+            }
+            Command::LineTo(p) | Command::ArcTo(.., p) => {
+                add(current, p, &[]);
+                current = p;
+            }
+            Command::QuadTo(c, p) | Command::ConicTo(c, p, _) => {
+                add(current, p, &[c]);
+                current = p;
+            }
+            Command::CurveTo(c1, c2, p) => {
+                add(current, p, &[c1, c2]);
+                current = p;
+            }
+            Command::Close => {
+                if current != start {
+                    add(current, start, &[]);
+                }
+                current = start;
+            }
+        }
+    }
+    for edges in [&mut top, &mut bottom] {
+        // Orient each edge from left to right, tracking the winding.
+        for i in (0..edges.len()).step_by(2) {
+            if edges[i].0 > edges[i + 1].0 {
+                edges.swap(i, i + 1);
+                edges[i].1 = -1;
+                edges[i + 1].1 = 1;
+            }
+        }
+        edges.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal));
+        let mut winding = 0;
+        let mut span_start = 0.;
+        for &(x, delta) in edges.iter() {
+            if winding == 0 {
+                span_start = x;
+            }
+            winding += delta;
+            if winding == 0 && x > span_start {
+                gaps.push((span_start, x));
+            }
+        }
+    }
+    gaps.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal));
+    let mut x = rect.min.x;
+    let mut emit = |x0: f32, x1: f32| {
+        if x1 > x0 {
+            sink.move_to((x0, rect.min.y));
+            sink.line_to((x1, rect.min.y));
+            sink.line_to((x1, rect.max.y));
+            sink.line_to((x0, rect.max.y));
+            sink.close();
+        }
+    };
+    for (start, end) in gaps {
+        let (start, end) = (start - clearance, end + clearance);
+        if start > x {
+            emit(x, start.min(rect.max.x));
+        }
+        x = x.max(end);
+        if x >= rect.max.x {
+            return;
+        }
+    }
+    emit(x, rect.max.x);
+}
+
+/// Edge of a subpath. Only lines may cross the clip rectangle.
+#[derive(Copy, Clone)]
+enum Edge {
+    Line(Point),
+    Curve(Point, Point, Point),
+}
+
+impl Edge {
+    fn end(&self) -> Point {
+        match self {
+            Self::Line(p) | Self::Curve(_, _, p) => *p,
+        }
+    }
+}
+
+#[derive(Copy, Clone)]
+enum Side {
+    Left,
+    Top,
+    Right,
+    Bottom,
+}
+
+struct Clipper {
+    rect: Bounds,
+    tolerance: f32,
+    buf: Vec<Edge>,
+}
+
+impl Clipper {
+    fn new(rect: Bounds, tolerance: f32) -> Self {
+        Self {
+            rect,
+            tolerance,
+            buf: Vec::new(),
+        }
+    }
+
+    fn contains(&self, p: Point) -> bool {
+        p.x >= self.rect.min.x
+            && p.x <= self.rect.max.x
+            && p.y >= self.rect.min.y
+            && p.y <= self.rect.max.y
+    }
+
+    /// Adds an edge to the subpath, flattening curves that are not entirely
+    /// inside or entirely beyond one side of the rectangle.
+    fn edge(&self, from: Point, cmd: Command, edges: &mut Vec<Edge>) {
+        let (c1, c2, to) = match cmd {
+            Command::ConicTo(c, p, w) => {
+                let mut from = from;
+                for (c, p) in ConicQuads::new(from, c, p, w, self.tolerance) {
+                    self.edge(from, Command::QuadTo(c, p), edges);
+                    from = p;
+                }
+                return;
+            }
+            Command::ArcTo(rx, ry, a, size, sweep, p) => {
+                let mut from = from;
+                let arc = Arc::new(from, rx, ry, a.to_radians(), size, sweep, p);
+                for cmd in arc.with_tolerance(self.tolerance) {
+                    self.edge(from, cmd, edges);
+                    from = end_point(&cmd, from);
+                }
+                return;
+            }
+            Command::LineTo(p) => {
+                edges.push(Edge::Line(p));
+                return;
+            }
+            Command::QuadTo(c, p) => (from + (c - from) * (2. / 3.), p + (c - p) * (2. / 3.), p),
+            Command::CurveTo(c1, c2, p) => (c1, c2, p),
+            _ => return,
+        };
+        let hull = [from, c1, c2, to];
+        if hull.iter().all(|p| self.contains(*p)) {
+            edges.push(Edge::Curve(c1, c2, to));
+            return;
+        }
+        let r = &self.rect;
+        if hull.iter().all(|p| p.x < r.min.x)
+            || hull.iter().all(|p| p.x > r.max.x)
+            || hull.iter().all(|p| p.y < r.min.y)
+            || hull.iter().all(|p| p.y > r.max.y)
+        {
+            // Only the end points contribute to the clipped result.
+            edges.push(Edge::Line(to));
+            return;
+        }
+        let dd = (from - c1 * 2. + c2)
+            .length()
+            .max((c1 - c2 * 2. + to).length());
+        let count = ((dd * 0.75 / FLATTEN_TOLERANCE).sqrt().ceil() as usize).clamp(1, 64);
+        let step = 1. / count as f32;
+        for i in 1..count {
+            let t = i as f32 * step;
+            let mt = 1. - t;
+            let p = from * (mt * mt * mt)
+                + c1 * (3. * mt * mt * t)
+                + c2 * (3. * mt * t * t)
+                + to * (t * t * t);
+            edges.push(Edge::Line(p));
+        }
+        edges.push(Edge::Line(to));
+    }
+
+    /// Clips a closed subpath and emits the result.
+    fn fill(&mut self, start: Point, edges: &mut Vec<Edge>, sink: &mut impl PathBuilder) {
+        if edges.is_empty() {
+            return;
+        }
+        // Close the polygon so that the last edge ends at the first vertex.
+        if edges[edges.len() - 1].end() != start {
+            edges.push(Edge::Line(start));
+        }
+        for side in [Side::Left, Side::Top, Side::Right, Side::Bottom] {
+            self.clip_side(side, edges);
+            if edges.is_empty() {
+                return;
+            }
+        }
+        sink.move_to(edges[edges.len() - 1].end());
+        for edge in edges.iter() {
+            match *edge {
+                Edge::Line(p) => sink.line_to(p),
+                Edge::Curve(c1, c2, p) => sink.curve_to(c1, c2, p),
+            };
+        }
+        sink.close();
+        edges.clear();
+    }
+
+    /// Performs one pass of Sutherland-Hodgman clipping against a side of
+    /// the rectangle.
+    fn clip_side(&mut self, side: Side, edges: &mut Vec<Edge>) {
+        let r = self.rect;
+        let inside = |p: Point| match side {
+            Side::Left => p.x >= r.min.x,
+            Side::Top => p.y >= r.min.y,
+            Side::Right => p.x <= r.max.x,
+            Side::Bottom => p.y <= r.max.y,
+        };
+        let intersect = |a: Point, b: Point| match side {
+            Side::Left => intersect_x(a, b, r.min.x),
+            Side::Top => intersect_y(a, b, r.min.y),
+            Side::Right => intersect_x(a, b, r.max.x),
+            Side::Bottom => intersect_y(a, b, r.max.y),
+        };
+        self.buf.clear();
+        let mut prev = edges[edges.len() - 1].end();
+        for edge in edges.iter() {
+            let end = edge.end();
+            match (inside(prev), inside(end)) {
+                (true, true) => self.buf.push(*edge),
+                (true, false) => self.buf.push(Edge::Line(intersect(prev, end))),
+                (false, true) => {
+                    self.buf.push(Edge::Line(intersect(prev, end)));
+                    self.buf.push(Edge::Line(end));
+                }
+                (false, false) => {}
+            }
+            prev = end;
+        }
+        core::mem::swap(edges, &mut self.buf);
+    }
+
+    /// Clips the edges of a subpath as an open polyline and emits the visible
+    /// runs.
+    fn lines(
+        &mut self,
+        start: Point,
+        edges: &mut Vec<Edge>,
+        closed: bool,
+        sink: &mut impl PathBuilder,
+    ) {
+        let mut prev = start;
+        let mut pen_down = false;
+        let mut clipped = false;
+        for edge in edges.iter() {
+            let end = edge.end();
+            match *edge {
+                Edge::Curve(c1, c2, p) => {
+                    if !pen_down {
+                        sink.move_to(prev);
+                        pen_down = true;
+                    }
+                    sink.curve_to(c1, c2, p);
+                }
+                Edge::Line(p) => match self.clip_line(prev, p) {
+                    Some((a, b)) => {
+                        if !pen_down || a != prev {
+                            sink.move_to(a);
+                            clipped |= a != prev;
+                        }
+                        sink.line_to(b);
+                        pen_down = b == p;
+                        clipped |= !pen_down;
+                    }
+                    None => {
+                        pen_down = false;
+                        clipped = true;
+                    }
+                },
+            }
+            prev = end;
+        }
+        if closed && pen_down && !clipped {
+            sink.close();
+        }
+        edges.clear();
+    }
+
+    /// Clips a line segment to the rectangle using the Liang-Barsky
+    /// algorithm.
+    fn clip_line(&self, a: Point, b: Point) -> Option<(Point, Point)> {
+        let r = &self.rect;
+        let d = b - a;
+        let mut t0 = 0f32;
+        let mut t1 = 1f32;
+        for (p, q) in [
+            (-d.x, a.x - r.min.x),
+            (d.x, r.max.x - a.x),
+            (-d.y, a.y - r.min.y),
+            (d.y, r.max.y - a.y),
+        ] {
+            if p == 0. {
+                if q < 0. {
+                    return None;
+                }
+            } else {
+                let t = q / p;
+                if p < 0. {
+                    t0 = t0.max(t);
+                } else {
+                    t1 = t1.min(t);
+                }
+            }
+        }
+        if t0 > t1 {
+            return None;
+        }
+        let start = if t0 > 0. { a + d * t0 } else { a };
+        let end = if t1 < 1. { a + d * t1 } else { b };
+        Some((start, end))
+    }
+}
+
+const FLATTEN_TOLERANCE: f32 = 0.25;
+
+fn end_point(cmd: &Command, current: Point) -> Point {
+    match *cmd {
+        Command::MoveTo(p) | Command::LineTo(p) | Command::QuadTo(_, p) => p,
+        Command::ConicTo(_, p, _) | Command::ArcTo(.., p) => p,
+        Command::CurveTo(_, _, p) => p,
+        Command::Close => current,
+    }
+}
+
+fn intersect_x(a: Point, b: Point, x: f32) -> Point {
+    let t = (x - a.x) / (b.x - a.x);
+    Point::new(x, a.y + (b.y - a.y) * t)
+}
+
+fn intersect_y(a: Point, b: Point, y: f32) -> Point {
+    let t = (y - a.y) / (b.y - a.y);
+    Point::new(a.x + (b.x - a.x) * t, y)
+}
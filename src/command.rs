@@ -1,12 +1,13 @@
 //! Path commands.
 
-use super::geometry::{Point, Transform};
-use super::path_builder::PathBuilder;
+use super::geometry::{Angle, Point, Transform};
+use super::path_builder::{transform_arc, ArcSize, ArcSweep, PathBuilder};
 
 use core::borrow::Borrow;
 
 /// Path command.
 #[derive(Copy, Clone, PartialEq, Debug)]
+#[non_exhaustive]
 pub enum Command {
     /// Begins a new subpath at the specified point.
     MoveTo(Point),
@@ -18,6 +19,14 @@ pub enum Command {
     /// A quadratic curve from the previous point to the final point with one
     /// intermediate control point.
     QuadTo(Point, Point),
+    /// A conic section (rational quadratic curve) from the previous point to
+    /// the final point with one intermediate control point and a weight. A
+    /// weight of 1 describes a quadratic curve, less than 1 an ellipse and
+    /// greater than 1 a hyperbola.
+    ConicTo(Point, Point, f32),
+    /// An elliptical arc from the previous point to the final point with the
+    /// specified x- and y-radius, rotation angle, arc size and arc sweep.
+    ArcTo(f32, f32, Angle, ArcSize, ArcSweep, Point),
     /// Closes a subpath, connecting the final point to the initial point.
     Close,
 }
@@ -30,6 +39,8 @@ impl Command {
             MoveTo(..) => Verb::MoveTo,
             LineTo(..) => Verb::LineTo,
             QuadTo(..) => Verb::QuadTo,
+            ConicTo(..) => Verb::ConicTo,
+            ArcTo(..) => Verb::ArcTo,
             CurveTo(..) => Verb::CurveTo,
             Close => Verb::CurveTo,
         }
@@ -44,6 +55,11 @@ impl Command {
             MoveTo(p) => MoveTo(t.transform_point(*p)),
             LineTo(p) => LineTo(t.transform_point(*p)),
             QuadTo(c, p) => QuadTo(t.transform_point(*c), t.transform_point(*p)),
+            ConicTo(c, p, w) => ConicTo(t.transform_point(*c), t.transform_point(*p), *w),
+            ArcTo(rx, ry, a, size, sweep, p) => {
+                let (rx, ry, a, sweep) = transform_arc(*rx, *ry, *a, *sweep, t);
+                ArcTo(rx, ry, a, *size, sweep, t.transform_point(*p))
+            }
             CurveTo(c1, c2, p) => CurveTo(
                 t.transform_point(*c1),
                 t.transform_point(*c2),
@@ -56,11 +72,20 @@ impl Command {
 
 /// Action of a path command.
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[non_exhaustive]
 pub enum Verb {
     MoveTo,
     LineTo,
     CurveTo,
     QuadTo,
+    /// Conic section. This consumes two points, the control point and the
+    /// final point, and one scalar, the weight.
+    ConicTo,
+    /// Elliptical arc. This consumes one point, the final point, and four
+    /// scalars: the x- and y-radius, the rotation angle in radians and the arc
+    /// flags. The flags are the sum of 1 for a large arc and 2 for a positive
+    /// sweep.
+    ArcTo,
     Close,
 }
 
@@ -68,17 +93,21 @@ pub enum Verb {
 pub struct PointsCommands<'a> {
     points: &'a [Point],
     verbs: &'a [Verb],
+    scalars: &'a [f32],
     point: usize,
     verb: usize,
+    scalar: usize,
 }
 
 impl<'a> PointsCommands<'a> {
-    pub(super) fn new(points: &'a [Point], verbs: &'a [Verb]) -> Self {
+    pub(super) fn new(points: &'a [Point], verbs: &'a [Verb], scalars: &'a [f32]) -> Self {
         Self {
             points,
             verbs,
+            scalars,
             point: 0,
             verb: 0,
+            scalar: 0,
         }
     }
 
@@ -90,6 +119,7 @@ impl<'a> PointsCommands<'a> {
     #[inline(always)]
     fn copy_to_inner(&self, sink: &mut impl PathBuilder) -> Option<()> {
         let mut i = 0;
+        let mut j = 0;
         for verb in self.verbs {
             match verb {
                 Verb::MoveTo => {
@@ -108,6 +138,21 @@ impl<'a> PointsCommands<'a> {
                     i += 2;
                     sink.quad_to(*c, *p);
                 }
+                Verb::ConicTo => {
+                    let p = self.points.get(i + 1)?;
+                    let c = self.points.get(i)?;
+                    let w = self.scalars.get(j)?;
+                    i += 2;
+                    j += 1;
+                    sink.conic_to(*c, *p, *w);
+                }
+                Verb::ArcTo => {
+                    let p = self.points.get(i)?;
+                    let (rx, ry, a, size, sweep) = arc_params(self.scalars.get(j..j + 4)?);
+                    i += 1;
+                    j += 4;
+                    sink.arc_to(rx, ry, a, size, sweep, *p);
+                }
                 Verb::CurveTo => {
                     let p = self.points.get(i + 2)?;
                     let c2 = self.points.get(i + 1)?;
@@ -148,6 +193,21 @@ impl<'a> Iterator for PointsCommands<'a> {
                 self.point += 2;
                 QuadTo(p[0], p[1])
             }
+            Verb::ConicTo => {
+                let p = self.points.get(self.point..self.point + 2)?;
+                let w = self.scalars.get(self.scalar)?;
+                self.point += 2;
+                self.scalar += 1;
+                ConicTo(p[0], p[1], *w)
+            }
+            Verb::ArcTo => {
+                let p = self.points.get(self.point)?;
+                let params = self.scalars.get(self.scalar..self.scalar + 4)?;
+                self.point += 1;
+                self.scalar += 4;
+                let (rx, ry, a, size, sweep) = arc_params(params);
+                ArcTo(rx, ry, a, size, sweep, *p)
+            }
             Verb::CurveTo => {
                 let p = self.points.get(self.point..self.point + 3)?;
                 self.point += 3;
@@ -158,6 +218,28 @@ impl<'a> Iterator for PointsCommands<'a> {
     }
 }
 
+/// Decodes the parameters of an arc from the scalars of a `Verb::ArcTo`.
+fn arc_params(params: &[f32]) -> (f32, f32, Angle, ArcSize, ArcSweep) {
+    let flags = params[3] as u32;
+    let size = if flags & 1 != 0 {
+        ArcSize::Large
+    } else {
+        ArcSize::Small
+    };
+    let sweep = if flags & 2 != 0 {
+        ArcSweep::Positive
+    } else {
+        ArcSweep::Negative
+    };
+    (
+        params[0],
+        params[1],
+        Angle::from_radians(params[2]),
+        size,
+        sweep,
+    )
+}
+
 #[derive(Clone)]
 pub struct TransformCommands<D> {
     pub data: D,
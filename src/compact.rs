@@ -0,0 +1,322 @@
+//! Compact binary path encoding.
+
+use super::command::{Command, Verb};
+use super::geometry::{Angle, Point};
+use super::path_builder::{ArcSize, ArcSweep, PathBuilder};
+use super::path_data::PathData;
+#[cfg(not(feature = "std"))]
+use super::F32Ext;
+
+use crate::lib::Vec;
+
+/// Path data in a compact binary encoding.
+///
+/// The encoding consists of a header containing the quantization precision
+/// followed by a sequence of verbs, each followed by the coordinates of its
+/// points. Coordinates are quantized to a multiple of the precision, delta
+/// encoded against the previous coordinate and stored as zigzag variable
+/// length integers. Conic weights are stored as raw 32-bit floats. Decoding
+/// is performed lazily when iterating the commands.
+///
+/// ```rust
+/// use zeno::{CompactPath, PathData};
+///
+/// let mut buf = Vec::new();
+/// CompactPath::encode("M1,2 L3.5,4 Q5,6 7,8 Z", 1.0 / 64.0, &mut buf);
+///
+/// let path = CompactPath::new(&buf);
+/// assert!(path.commands().eq("M1,2 L3.5,4 Q5,6 7,8 Z".commands()));
+/// ```
+#[derive(Copy, Clone, Debug)]
+pub struct CompactPath<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> CompactPath<'a> {
+    /// Creates a new compact path from previously encoded data.
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    /// Encodes the path data with the specified coordinate precision and
+    /// appends the result to the buffer.
+    pub fn encode(data: impl PathData, precision: f32, buffer: &mut Vec<u8>) {
+        data.copy_to(&mut CompactEncoder::new(buffer, precision));
+    }
+
+    /// Returns the underlying encoded data.
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+}
+
+impl<'a> PathData for CompactPath<'a> {
+    type Commands = CompactCommands<'a>;
+
+    fn commands(&self) -> Self::Commands {
+        CompactCommands::new(self.data)
+    }
+}
+
+/// Path builder that incrementally writes the compact binary encoding of a
+/// path into a buffer.
+///
+/// This is useful for capturing the result of [apply](fn.apply.html) or
+/// for building paths directly into the compact representation.
+pub struct CompactEncoder<'a> {
+    buffer: &'a mut Vec<u8>,
+    scale: f32,
+    last: (i32, i32),
+    start: Point,
+    current: Point,
+}
+
+impl<'a> CompactEncoder<'a> {
+    /// Creates a new encoder that appends to the specified buffer using the
+    /// given coordinate precision. A precision less than or equal to zero is
+    /// replaced by a precision of 1/64.
+    pub fn new(buffer: &'a mut Vec<u8>, precision: f32) -> Self {
+        let precision = if precision > 0. && precision.is_finite() {
+            precision
+        } else {
+            DEFAULT_PRECISION
+        };
+        buffer.extend_from_slice(&precision.to_le_bytes());
+        Self {
+            buffer,
+            scale: 1. / precision,
+            last: (0, 0),
+            start: Point::ZERO,
+            current: Point::ZERO,
+        }
+    }
+
+    fn verb(&mut self, verb: Verb) {
+        self.buffer.push(match verb {
+            Verb::MoveTo => 0,
+            Verb::LineTo => 1,
+            Verb::QuadTo => 2,
+            Verb::CurveTo => 3,
+            Verb::Close => 4,
+            Verb::ConicTo => 5,
+            Verb::ArcTo => 6,
+        });
+    }
+
+    fn point(&mut self, p: Point) {
+        let x = (p.x * self.scale).round() as i32;
+        let y = (p.y * self.scale).round() as i32;
+        write_varint(self.buffer, x.wrapping_sub(self.last.0));
+        write_varint(self.buffer, y.wrapping_sub(self.last.1));
+        self.last = (x, y);
+    }
+}
+
+impl PathBuilder for CompactEncoder<'_> {
+    fn current_point(&self) -> Point {
+        self.current
+    }
+
+    fn move_to(&mut self, to: impl Into<Point>) -> &mut Self {
+        let to = to.into();
+        self.verb(Verb::MoveTo);
+        self.point(to);
+        self.start = to;
+        self.current = to;
+        self
+    }
+
+    fn line_to(&mut self, to: impl Into<Point>) -> &mut Self {
+        let to = to.into();
+        self.verb(Verb::LineTo);
+        self.point(to);
+        self.current = to;
+        self
+    }
+
+    fn quad_to(&mut self, control: impl Into<Point>, to: impl Into<Point>) -> &mut Self {
+        let to = to.into();
+        self.verb(Verb::QuadTo);
+        self.point(control.into());
+        self.point(to);
+        self.current = to;
+        self
+    }
+
+    fn conic_to(
+        &mut self,
+        control: impl Into<Point>,
+        to: impl Into<Point>,
+        weight: f32,
+    ) -> &mut Self {
+        let to = to.into();
+        self.verb(Verb::ConicTo);
+        self.point(control.into());
+        self.point(to);
+        self.buffer.extend_from_slice(&weight.to_le_bytes());
+        self.current = to;
+        self
+    }
+
+    fn arc_to(
+        &mut self,
+        rx: f32,
+        ry: f32,
+        angle: Angle,
+        size: ArcSize,
+        sweep: ArcSweep,
+        to: impl Into<Point>,
+    ) -> &mut Self {
+        let to = to.into();
+        self.verb(Verb::ArcTo);
+        for value in [rx, ry, angle.to_radians()] {
+            self.buffer.extend_from_slice(&value.to_le_bytes());
+        }
+        let mut flags = 0;
+        if size == ArcSize::Large {
+            flags |= 1;
+        }
+        if sweep == ArcSweep::Positive {
+            flags |= 2;
+        }
+        self.buffer.push(flags);
+        self.point(to);
+        self.current = to;
+        self
+    }
+
+    fn curve_to(
+        &mut self,
+        control1: impl Into<Point>,
+        control2: impl Into<Point>,
+        to: impl Into<Point>,
+    ) -> &mut Self {
+        let to = to.into();
+        self.verb(Verb::CurveTo);
+        self.point(control1.into());
+        self.point(control2.into());
+        self.point(to);
+        self.current = to;
+        self
+    }
+
+    fn close(&mut self) -> &mut Self {
+        self.verb(Verb::Close);
+        self.current = self.start;
+        self
+    }
+}
+
+/// Iterator over the commands of a compact path.
+#[derive(Clone)]
+pub struct CompactCommands<'a> {
+    data: &'a [u8],
+    pos: usize,
+    precision: f32,
+    last: (i32, i32),
+}
+
+impl<'a> CompactCommands<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        let (precision, pos) = match data.get(..4) {
+            Some(header) => (
+                f32::from_le_bytes([header[0], header[1], header[2], header[3]]),
+                4,
+            ),
+            None => (DEFAULT_PRECISION, data.len()),
+        };
+        Self {
+            data,
+            pos,
+            precision,
+            last: (0, 0),
+        }
+    }
+
+    fn varint(&mut self) -> Option<i32> {
+        let mut value = 0u32;
+        let mut shift = 0;
+        loop {
+            let b = *self.data.get(self.pos)?;
+            self.pos += 1;
+            if shift < 32 {
+                value |= ((b & 0x7F) as u32) << shift;
+            }
+            if b & 0x80 == 0 {
+                break;
+            }
+            shift += 7;
+        }
+        Some(((value >> 1) as i32) ^ -((value & 1) as i32))
+    }
+
+    fn float(&mut self) -> Option<f32> {
+        let bytes = self.data.get(self.pos..self.pos + 4)?;
+        self.pos += 4;
+        Some(f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn arc(&mut self) -> Option<Command> {
+        let rx = self.float()?;
+        let ry = self.float()?;
+        let angle = Angle::from_radians(self.float()?);
+        let flags = *self.data.get(self.pos)?;
+        self.pos += 1;
+        let size = if flags & 1 != 0 {
+            ArcSize::Large
+        } else {
+            ArcSize::Small
+        };
+        let sweep = if flags & 2 != 0 {
+            ArcSweep::Positive
+        } else {
+            ArcSweep::Negative
+        };
+        Some(Command::ArcTo(rx, ry, angle, size, sweep, self.point()?))
+    }
+
+    fn point(&mut self) -> Option<Point> {
+        let x = self.last.0.wrapping_add(self.varint()?);
+        let y = self.last.1.wrapping_add(self.varint()?);
+        self.last = (x, y);
+        Some(Point::new(
+            x as f32 * self.precision,
+            y as f32 * self.precision,
+        ))
+    }
+}
+
+impl Iterator for CompactCommands<'_> {
+    type Item = Command;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        use Command::*;
+        let verb = *self.data.get(self.pos)?;
+        self.pos += 1;
+        let cmd = match verb {
+            0 => MoveTo(self.point()?),
+            1 => LineTo(self.point()?),
+            2 => QuadTo(self.point()?, self.point()?),
+            3 => CurveTo(self.point()?, self.point()?, self.point()?),
+            4 => Close,
+            5 => ConicTo(self.point()?, self.point()?, self.float()?),
+            6 => self.arc()?,
+            _ => {
+                self.pos = self.data.len();
+                return None;
+            }
+        };
+        Some(cmd)
+    }
+}
+
+const DEFAULT_PRECISION: f32 = 1. / 64.;
+
+fn write_varint(buffer: &mut Vec<u8>, value: i32) {
+    let mut v = ((value << 1) ^ (value >> 31)) as u32;
+    while v >= 0x80 {
+        buffer.push((v as u8) | 0x80);
+        v >>= 7;
+    }
+    buffer.push(v as u8);
+}
@@ -0,0 +1,214 @@
+//! Replacement of the center line of a stroke with a repeating template.
+
+use super::command::Command;
+use super::geometry::{normal, Point, Transform};
+use super::path_builder::PathBuilder;
+use super::segment::{segments, Segment};
+use super::style::Decoration;
+#[cfg(not(feature = "std"))]
+use super::F32Ext;
+
+use crate::lib::Vec;
+use core::borrow::Borrow;
+
+/// Emits the path with each subpath replaced by repetitions of the template
+/// of the decoration, mapped along the flattened subpath.
+pub(super) fn decorate_into<I>(
+    commands: I,
+    decoration: &Decoration,
+    flatness: f32,
+    sink: &mut impl PathBuilder,
+) where
+    I: Iterator + Clone,
+    I::Item: Borrow<Command>,
+{
+    let period = decoration.period;
+    let amplitude = decoration.amplitude;
+    // Flatten the template at its nominal size.
+    let transform = Transform::scale(period, amplitude);
+    let mut template = flatten(
+        decoration.path.iter().map(|cmd| cmd.transform(&transform)),
+        flatness,
+    );
+    template.retain(|(points, _)| points.len() > 1);
+    let eps = period * 1e-4;
+    let connected: Vec<bool> = template
+        .iter()
+        .map(|(points, closed)| match (points.first(), points.last()) {
+            (Some(first), Some(last)) => {
+                !closed
+                    && first.x.abs() <= eps
+                    && (last.x - period).abs() <= eps
+                    && (last.y - first.y).abs() <= eps
+            }
+            _ => false,
+        })
+        .collect();
+    let mut rails: Vec<Vec<Point>> = template.iter().map(|_| Vec::new()).collect();
+    let mut dist = Vec::new();
+    for (mut points, closed) in flatten(commands, flatness) {
+        if closed && points.last() != points.first() {
+            points.push(points[0]);
+        }
+        if points.len() < 2 {
+            continue;
+        }
+        dist.clear();
+        dist.push(0.);
+        for pair in points.windows(2) {
+            let d = dist[dist.len() - 1] + (pair[1] - pair[0]).length();
+            dist.push(d);
+        }
+        let length = dist[dist.len() - 1];
+        let line = CenterLine {
+            points: &points,
+            dist: &dist,
+        };
+        let count = (length / period).round().max(1.);
+        let sx = length / (count * period);
+        for rail in rails.iter_mut() {
+            rail.clear();
+        }
+        for k in 0..count as usize {
+            let base = k as f32 * period;
+            for (i, (subpath, subpath_closed)) in template.iter().enumerate() {
+                let rail = &mut rails[i];
+                let mut prev = (subpath[0].x + base) * sx;
+                let mut prev_y = subpath[0].y;
+                if !connected[i] || k == 0 {
+                    rail.push(line.map(line.segment(prev), prev, prev_y));
+                }
+                for p in &subpath[1..] {
+                    let d = (p.x + base) * sx;
+                    line.map_edge((prev, prev_y), (d, p.y), rail);
+                    prev = d;
+                    prev_y = p.y;
+                }
+                if !connected[i] {
+                    emit(rail, *subpath_closed, sink);
+                    rail.clear();
+                }
+            }
+        }
+        for (i, rail) in rails.iter().enumerate() {
+            if connected[i] {
+                emit(rail, closed, sink);
+            }
+        }
+    }
+}
+
+/// Flattens the commands into subpaths of points along with a flag that is
+/// true if the subpath is closed. Coincident consecutive points are
+/// removed.
+pub(super) fn flatten<I>(commands: I, flatness: f32) -> Vec<(Vec<Point>, bool)>
+where
+    I: Iterator + Clone,
+    I::Item: Borrow<Command>,
+{
+    let mut subpaths = Vec::new();
+    let mut points: Vec<Point> = Vec::new();
+    let push = |points: &mut Vec<Point>, p: Point| {
+        if points.last() != Some(&p) {
+            points.push(p);
+        }
+    };
+    let mut buf = Vec::new();
+    for segment in segments(commands, false) {
+        match segment {
+            Segment::Line(_, line) => {
+                if points.is_empty() {
+                    points.push(line.a);
+                }
+                push(&mut points, line.b);
+            }
+            Segment::Curve(_, curve) => {
+                if points.is_empty() {
+                    points.push(curve.a);
+                }
+                buf.clear();
+                curve.flatten(flatness, &mut buf);
+                for &p in &buf {
+                    push(&mut points, p);
+                }
+            }
+            Segment::End(closed) => {
+                if !points.is_empty() {
+                    subpaths.push((core::mem::take(&mut points), closed));
+                }
+            }
+        }
+    }
+    if !points.is_empty() {
+        subpaths.push((points, false));
+    }
+    subpaths
+}
+
+fn emit(points: &[Point], closed: bool, sink: &mut impl PathBuilder) {
+    if let Some((first, rest)) = points.split_first() {
+        sink.move_to(*first);
+        for &p in rest {
+            sink.line_to(p);
+        }
+        if closed {
+            sink.close();
+        }
+    }
+}
+
+/// Flattened center line of a subpath with the distance along the line at
+/// each point.
+struct CenterLine<'a> {
+    points: &'a [Point],
+    dist: &'a [f32],
+}
+
+impl CenterLine<'_> {
+    /// Returns the index of the line containing the specified distance.
+    fn segment(&self, distance: f32) -> usize {
+        let last = self.points.len() - 2;
+        self.dist
+            .partition_point(|&d| d <= distance)
+            .saturating_sub(1)
+            .min(last)
+    }
+
+    /// Returns the point at the distance along the line with the specified
+    /// index, offset along its normal.
+    fn map(&self, index: usize, distance: f32, offset: f32) -> Point {
+        let (a, b) = (self.points[index], self.points[index + 1]);
+        let (d0, d1) = (self.dist[index], self.dist[index + 1]);
+        let t = ((distance - d0) / (d1 - d0)).clamp(0., 1.);
+        a + (b - a) * t + normal(a, b) * offset
+    }
+
+    /// Appends points for a template edge between two distances and offsets,
+    /// excluding the start, with additional points at each vertex of the
+    /// center line that the edge crosses.
+    fn map_edge(&self, from: (f32, f32), to: (f32, f32), points: &mut Vec<Point>) {
+        let (i0, i1) = (self.segment(from.0), self.segment(to.0));
+        let offset = |d: f32| {
+            let span = to.0 - from.0;
+            if span == 0. {
+                to.1
+            } else {
+                from.1 + (to.1 - from.1) * ((d - from.0) / span)
+            }
+        };
+        if i1 > i0 {
+            for v in i0 + 1..=i1 {
+                let (d, y) = (self.dist[v], offset(self.dist[v]));
+                points.push(self.map(v - 1, d, y));
+                points.push(self.map(v, d, y));
+            }
+        } else if i0 > i1 {
+            for v in (i1 + 1..=i0).rev() {
+                let (d, y) = (self.dist[v], offset(self.dist[v]));
+                points.push(self.map(v, d, y));
+                points.push(self.map(v - 1, d, y));
+            }
+        }
+        points.push(self.map(i1, to.0, to.1));
+    }
+}
@@ -13,13 +13,23 @@ impl Angle {
     /// Angle of zero degrees.
     pub const ZERO: Self = Self(0.);
 
+    // Conversions that perform floating point arithmetic are not const as
+    // that would require Rust 1.82.
+
     /// Creates a new angle from degrees.
     pub fn from_degrees(degrees: f32) -> Self {
         Self(degrees * core::f32::consts::PI / 180.)
     }
 
     /// Creates a new angle from radians.
-    pub fn from_radians(radians: f32) -> Self {
+    ///
+    /// ```rust
+    /// use zeno::Angle;
+    ///
+    /// const RIGHT: Angle = Angle::from_radians(core::f32::consts::FRAC_PI_2);
+    /// assert_eq!(RIGHT.to_degrees(), 90.);
+    /// ```
+    pub const fn from_radians(radians: f32) -> Self {
         Self(radians)
     }
 
@@ -33,8 +43,21 @@ impl Angle {
         Self::from_degrees(turns * 360.)
     }
 
+    /// Creates a new angle describing the direction of the specified vector
+    /// relative to the positive x axis.
+    pub fn from_vector(v: impl Into<Vector>) -> Self {
+        let v = v.into();
+        Self(F32Ext::atan2(v.y, v.x))
+    }
+
+    /// Returns the signed angle from the first vector to the second in the
+    /// range [-180, 180] degrees.
+    pub fn between(v0: impl Into<Vector>, v1: impl Into<Vector>) -> Self {
+        v0.into().angle_to(v1.into())
+    }
+
     /// Returns the angle in radians.
-    pub fn to_radians(self) -> f32 {
+    pub const fn to_radians(self) -> f32 {
         self.0
     }
 
@@ -42,6 +65,28 @@ impl Angle {
     pub fn to_degrees(self) -> f32 {
         self.0 * 180. / core::f32::consts::PI
     }
+
+    /// Returns the angle in gradians.
+    pub fn to_gradians(self) -> f32 {
+        self.to_degrees() / 360. * 400.
+    }
+
+    /// Returns the angle in turns.
+    pub fn to_turns(self) -> f32 {
+        self.to_degrees() / 360.
+    }
+
+    /// Returns an equivalent angle in the range (-180, 180] degrees.
+    pub fn normalized(self) -> Self {
+        use core::f32::consts::PI;
+        let mut a = self.0 % (2. * PI);
+        if a > PI {
+            a -= 2. * PI;
+        } else if a <= -PI {
+            a += 2. * PI;
+        }
+        Self(a)
+    }
 }
 
 /// Two dimensional vector.
@@ -116,7 +161,7 @@ impl Vector {
 
     /// Returns the angle to the specified vector.
     pub fn angle_to(self, other: Self) -> Angle {
-        Angle::from_radians(self.cross(other).atan2(self.dot(other)))
+        Angle::from_radians(F32Ext::atan2(self.cross(other), self.dot(other)))
     }
 
     /// Returns true if this vector is approximately equal to other using a
@@ -293,7 +338,7 @@ impl Transform {
 
     /// Creates a rotation transform.
     pub fn rotation(angle: Angle) -> Self {
-        let (sin, cos) = angle.0.sin_cos();
+        let (sin, cos) = F32Ext::sin_cos(angle.0);
         Self {
             xx: cos,
             xy: sin,
@@ -321,8 +366,8 @@ impl Transform {
     pub fn skew(x: Angle, y: Angle) -> Self {
         Self {
             xx: 1.,
-            xy: y.0.tan(),
-            yx: x.0.tan(),
+            xy: F32Ext::tan(y.0),
+            yx: F32Ext::tan(x.0),
             yy: 1.,
             x: 0.,
             y: 0.,
@@ -396,6 +441,26 @@ impl Transform {
         self.xx * self.yy - self.yx * self.xy
     }
 
+    /// Returns the largest factor by which the transform stretches a vector
+    /// in any direction.
+    pub(super) fn max_scale(&self) -> f32 {
+        let half =
+            (self.xx * self.xx + self.xy * self.xy + self.yx * self.yx + self.yy * self.yy) * 0.5;
+        let det = self.determinant();
+        (half + (half * half - det * det).max(0.).sqrt()).sqrt()
+    }
+
+    /// Returns the smallest factor by which the transform stretches a vector
+    /// in any direction.
+    pub(super) fn min_scale(&self) -> f32 {
+        let max = self.max_scale();
+        if max > 0. {
+            self.determinant().abs() / max
+        } else {
+            0.
+        }
+    }
+
     /// Returns the inverse of the transform, if any.
     pub fn invert(&self) -> Option<Transform> {
         let det = self.determinant();
@@ -419,6 +484,44 @@ impl Transform {
         })
     }
 
+    /// Returns a transform that is interpolated between this transform and
+    /// other by the specified factor. See [interpolate](#method.interpolate).
+    pub fn lerp(&self, other: &Transform, t: f32) -> Self {
+        Self::interpolate(self, other, t)
+    }
+
+    /// Interpolates between two transforms by the specified factor.
+    ///
+    /// Both transforms are decomposed into translation, rotation, scale and
+    /// shear components which are interpolated separately. Rotation follows
+    /// the shortest path between the two angles and scale is interpolated
+    /// logarithmically so that zooming proceeds at a constant rate. This
+    /// avoids the shrinking and distortion produced by interpolating the
+    /// matrix elements directly.
+    pub fn interpolate(a: &Transform, b: &Transform, t: f32) -> Self {
+        use core::f32::consts::PI;
+        let da = Decomposed::new(a);
+        let db = Decomposed::new(b);
+        let mut delta = db.angle - da.angle;
+        if delta > PI {
+            delta -= 2. * PI;
+        } else if delta < -PI {
+            delta += 2. * PI;
+        }
+        let (sin, cos) = F32Ext::sin_cos(da.angle + delta * t);
+        let sx = lerp_scale(da.scale.x, db.scale.x, t);
+        let sy = lerp_scale(da.scale.y, db.scale.y, t);
+        let shear = da.shear + (db.shear - da.shear) * t;
+        Self {
+            xx: sx * cos,
+            xy: sx * sin,
+            yx: shear * cos - sy * sin,
+            yy: shear * sin + sy * cos,
+            x: a.x + (b.x - a.x) * t,
+            y: a.y + (b.y - a.y) * t,
+        }
+    }
+
     /// Returns the result of applying this transform to a point.
     #[inline(always)]
     pub fn transform_point(&self, point: Point) -> Point {
@@ -438,6 +541,41 @@ impl Transform {
     }
 }
 
+/// Linear part of a transform decomposed into a rotation followed by an
+/// upper triangular scale and shear.
+struct Decomposed {
+    angle: f32,
+    scale: Vector,
+    shear: f32,
+}
+
+impl Decomposed {
+    fn new(t: &Transform) -> Self {
+        let sx = Vector::new(t.xx, t.xy).length();
+        if sx == 0. {
+            return Self {
+                angle: 0.,
+                scale: Vector::new(0., t.yy),
+                shear: t.yx,
+            };
+        }
+        let (cos, sin) = (t.xx / sx, t.xy / sx);
+        Self {
+            angle: F32Ext::atan2(t.xy, t.xx),
+            scale: Vector::new(sx, t.determinant() / sx),
+            shear: t.yx * cos + t.yy * sin,
+        }
+    }
+}
+
+fn lerp_scale(a: f32, b: f32, t: f32) -> f32 {
+    if a * b > 0. {
+        a * F32Ext::powf(b / a, t)
+    } else {
+        a + (b - a) * t
+    }
+}
+
 /// The origin of the coordinate system for rendering.
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
 pub enum Origin {
@@ -466,6 +604,12 @@ pub struct Placement {
     pub width: u32,
     /// Height in pixels.
     pub height: u32,
+    /// Fractional part of the device space position of the top left corner
+    /// of the content bounds (bottom left for a bottom-left origin). This
+    /// sub-pixel offset is already included in the rendered coverage and is
+    /// reported so that it can be accounted for when compositing. This is
+    /// zero when the placement is not computed from the bounds.
+    pub fract: Vector,
 }
 
 impl Placement {
@@ -477,30 +621,81 @@ impl Placement {
         bounds: &Bounds,
     ) -> (Vector, Placement) {
         let offset = offset.into();
+        let fract = bounds_fract(origin, offset, bounds);
         let mut bounds = *bounds;
         bounds.min = (bounds.min + offset).floor();
         bounds.max = (bounds.max + offset).ceil();
-        let offset = Vector::new(-bounds.min.x + 1., -bounds.min.y);
-        let width = bounds.width() as u32 + 2;
+        let width = (bounds.width() as u32).saturating_add(2);
         let height = bounds.height() as u32;
-        let left = -offset.x as i32;
+        let left = bounds.min.x as i32 - 1;
         let top = if origin == Origin::BottomLeft {
-            (-offset.y).floor() + height as f32
+            bounds.max.y
         } else {
-            -offset.y
+            bounds.min.y
         } as i32;
         (
-            offset,
+            offset - Vector::new(bounds.min.x - 1., bounds.min.y),
             Placement {
                 left,
                 top,
                 width,
                 height,
+                fract,
             },
         )
     }
 }
 
+/// Rounding applied to the position of a computed placement.
+///
+/// The position is that of the corner of the offset bounds of the content
+/// that corresponds to the origin. Rounding it to a whole pixel moves the
+/// content so that the corner lies on a pixel boundary.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Rounding {
+    /// Keeps the fractional part of the position in the rendered coverage
+    /// and reports it in [Placement::fract](struct.Placement.html#structfield.fract).
+    Fractional,
+    /// Moves the content to the pixel boundary at or before the position.
+    Floor,
+    /// Moves the content to the nearest pixel boundary.
+    Round,
+    /// Moves the content to the pixel boundary at or after the position.
+    Ceil,
+}
+
+impl Rounding {
+    /// Returns the vector that moves the corner of the offset bounds
+    /// corresponding to the origin to its rounded position.
+    pub(super) fn snap(self, origin: Origin, offset: Vector, bounds: &Bounds) -> Vector {
+        let x = bounds.min.x + offset.x;
+        let y = if origin == Origin::BottomLeft {
+            bounds.max.y
+        } else {
+            bounds.min.y
+        } + offset.y;
+        let round = |v: f32| match self {
+            Self::Fractional => v,
+            Self::Floor => v.floor(),
+            Self::Round => v.round(),
+            Self::Ceil => v.ceil(),
+        };
+        Vector::new(round(x) - x, round(y) - y)
+    }
+}
+
+/// Returns the fractional part of the position of the corner of the offset
+/// bounds that corresponds to the origin.
+pub(super) fn bounds_fract(origin: Origin, offset: Vector, bounds: &Bounds) -> Vector {
+    let x = bounds.min.x + offset.x;
+    let y = if origin == Origin::BottomLeft {
+        bounds.max.y
+    } else {
+        bounds.min.y
+    } + offset.y;
+    Vector::new(x - x.floor(), y - y.floor())
+}
+
 /// Axis-aligned bounding box.
 #[derive(Copy, Clone, Default, Debug)]
 pub struct Bounds {
@@ -547,9 +742,56 @@ impl Bounds {
         let p = point.into();
         p.x > self.min.x && p.x < self.max.x && p.y > self.min.y && p.y < self.max.y
     }
+
+    /// Returns the bounding box of the result of applying the transform to
+    /// the corners of this box.
+    pub fn transform(&self, transform: &Transform) -> Self {
+        let (min, max) = (self.min, self.max);
+        Self::from_points([
+            transform.transform_point(min),
+            transform.transform_point(Point::new(max.x, min.y)),
+            transform.transform_point(max),
+            transform.transform_point(Point::new(min.x, max.y)),
+        ])
+    }
+}
+
+/// Conservative and exact bounding boxes of a styled path.
+#[derive(Copy, Clone, Default, Debug)]
+pub struct StyledBounds {
+    /// Bounding box that is cheap to compute and is guaranteed to contain
+    /// the exact bounds. This is suitable for sizing buffers.
+    pub conservative: Bounds,
+    /// Tight bounding box of the styled path, including curve extrema. This
+    /// is suitable for culling.
+    pub exact: Bounds,
+}
+
+/// Returns the times of the extrema of a one dimensional cubic curve, or
+/// negative values for missing extrema.
+pub(super) fn cubic_extrema(p0: f32, p1: f32, p2: f32, p3: f32) -> [f32; 2] {
+    // Roots of the derivative: qa*t^2 + qb*t + qc = 0
+    let qa = -p0 + 3. * p1 - 3. * p2 + p3;
+    let qb = 2. * (p0 - 2. * p1 + p2);
+    let qc = p1 - p0;
+    let mut ts = [-1f32; 2];
+    if qa.abs() < 1e-12 {
+        if qb != 0. {
+            ts[0] = -qc / qb;
+        }
+    } else {
+        let disc = qb * qb - 4. * qa * qc;
+        if disc >= 0. {
+            let sq = disc.sqrt();
+            ts[0] = (-qb + sq) / (2. * qa);
+            ts[1] = (-qb - sq) / (2. * qa);
+        }
+    }
+    ts
 }
 
 pub(super) struct BoundsBuilder {
+    pub exact: bool,
     pub count: usize,
     pub start: Point,
     pub current: Point,
@@ -560,6 +802,7 @@ pub(super) struct BoundsBuilder {
 impl BoundsBuilder {
     pub fn new() -> Self {
         Self {
+            exact: false,
             count: 0,
             start: Point::ZERO,
             current: Point::ZERO,
@@ -587,6 +830,46 @@ impl BoundsBuilder {
         self
     }
 
+    /// Creates a builder that computes tight bounds using the extrema of
+    /// curves rather than their control points.
+    pub fn exact() -> Self {
+        let mut b = Self::new();
+        b.exact = true;
+        b
+    }
+
+    /// Adds the extrema of a quadratic curve, excluding the end points.
+    pub fn add_quad_extrema(&mut self, a: Point, b: Point, c: Point) {
+        let eval = |t: f32| {
+            let u = 1. - t;
+            a * (u * u) + b * (2. * u * t) + c * (t * t)
+        };
+        for (a, b, c) in [(a.x, b.x, c.x), (a.y, b.y, c.y)] {
+            let d = a - 2. * b + c;
+            if d != 0. {
+                let t = (a - b) / d;
+                if t > 0. && t < 1. {
+                    self.add(eval(t));
+                }
+            }
+        }
+    }
+
+    /// Adds the extrema of a cubic curve, excluding the end points.
+    pub fn add_curve_extrema(&mut self, a: Point, b: Point, c: Point, d: Point) {
+        let eval = |t: f32| {
+            let u = 1. - t;
+            a * (u * u * u) + b * (3. * u * u * t) + c * (3. * u * t * t) + d * (t * t * t)
+        };
+        for (p0, p1, p2, p3) in [(a.x, b.x, c.x, d.x), (a.y, b.y, c.y, d.y)] {
+            for &t in &cubic_extrema(p0, p1, p2, p3) {
+                if t > 0. && t < 1. {
+                    self.add(eval(t));
+                }
+            }
+        }
+    }
+
     pub fn build(&self) -> Bounds {
         if self.count != 0 {
             Bounds {
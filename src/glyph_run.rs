@@ -0,0 +1,124 @@
+//! Runs of positioned paths.
+
+use super::command::Command;
+use super::geometry::{Point, Transform, Vector};
+use super::path_builder::{PathBuilder, TransformSink};
+use super::path_data::PathData;
+
+/// A path positioned within a glyph run.
+#[derive(Copy, Clone, Debug)]
+pub struct Glyph<D> {
+    /// The outline of the glyph.
+    pub path: D,
+    /// Subpixel offset applied to the glyph.
+    pub offset: Vector,
+    /// Pen position of the glyph within the run.
+    pub position: Point,
+}
+
+impl<D> Glyph<D> {
+    /// Creates a new glyph with the specified path, subpixel offset and
+    /// position.
+    pub fn new(path: D, offset: impl Into<Vector>, position: impl Into<Point>) -> Self {
+        Self {
+            path,
+            offset: offset.into(),
+            position: position.into(),
+        }
+    }
+
+    #[inline(always)]
+    fn translation(&self) -> Vector {
+        self.position + self.offset
+    }
+}
+
+/// Path data for a sequence of positioned glyphs.
+///
+/// The glyphs are emitted as a single path which allows an entire run to be
+/// rendered into one mask. Overlapping glyphs are merged by the rasterizer
+/// rather than composited after the fact so there are no seams or doubled
+/// coverage where glyphs touch. For this reason, runs should be rendered
+/// with a non-zero fill.
+///
+/// ```rust
+/// use zeno::{Format, Glyph, GlyphRun, Mask};
+///
+/// let glyphs = [
+///     Glyph::new("M0,0 8,0 8,10 0,10 Z", 0.0, [0, 0]),
+///     Glyph::new("M0,0 8,0 8,10 0,10 Z", 0.25, [7, 0]),
+/// ];
+///
+/// let (strip, placement) = Mask::new(GlyphRun::new(&glyphs))
+///     .format(Format::Subpixel)
+///     .render();
+/// ```
+#[derive(Copy, Clone, Debug)]
+pub struct GlyphRun<'a, D> {
+    glyphs: &'a [Glyph<D>],
+}
+
+impl<'a, D> GlyphRun<'a, D> {
+    /// Creates a new run from the specified glyphs.
+    pub fn new(glyphs: &'a [Glyph<D>]) -> Self {
+        Self { glyphs }
+    }
+}
+
+impl<'a, D> PathData for GlyphRun<'a, D>
+where
+    D: PathData,
+{
+    type Commands = GlyphRunCommands<'a, D>;
+
+    fn commands(&self) -> Self::Commands {
+        GlyphRunCommands {
+            glyphs: self.glyphs,
+            current: None,
+        }
+    }
+
+    fn copy_to(&self, sink: &mut impl PathBuilder) {
+        for glyph in self.glyphs {
+            let t = glyph.translation();
+            let mut sink = TransformSink {
+                sink: &mut *sink,
+                transform: Transform::translation(t.x, t.y),
+            };
+            glyph.path.copy_to(&mut sink);
+        }
+    }
+}
+
+/// Iterator over the commands of a glyph run.
+pub struct GlyphRunCommands<'a, D: PathData> {
+    glyphs: &'a [Glyph<D>],
+    current: Option<(D::Commands, Vector)>,
+}
+
+impl<D: PathData> Clone for GlyphRunCommands<'_, D> {
+    fn clone(&self) -> Self {
+        Self {
+            glyphs: self.glyphs,
+            current: self.current.clone(),
+        }
+    }
+}
+
+impl<D: PathData> Iterator for GlyphRunCommands<'_, D> {
+    type Item = Command;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((commands, translation)) = &mut self.current {
+                if let Some(cmd) = commands.next() {
+                    let t = *translation;
+                    return Some(cmd.transform(&Transform::translation(t.x, t.y)));
+                }
+            }
+            let (glyph, rest) = self.glyphs.split_first()?;
+            self.glyphs = rest;
+            self.current = Some((glyph.path.commands(), glyph.translation()));
+        }
+    }
+}
@@ -0,0 +1,220 @@
+//! Stable hashing of path data.
+
+use super::geometry::{Angle, Point};
+use super::path_builder::{ArcSize, ArcSweep, PathBuilder};
+use super::path_data::PathData;
+use super::snap::quantize;
+
+/// Computes a stable 64-bit hash of the commands of a path.
+///
+/// The hash is computed with 64-bit FNV-1a over a canonical encoding of
+/// the commands: a verb tag byte (using the same numbering as
+/// [CompactPath](struct.CompactPath.html)) followed by the little endian
+/// bits of each coordinate and parameter. Negative zero is hashed as zero
+/// and all NaN values are hashed identically. The result depends only on the
+/// commands, so it is suitable as a cache key that persists across
+/// processes and platforms.
+///
+/// Equivalent paths expressed in different forms, such as SVG data and a
+/// command list, produce the same hash.
+///
+/// ```rust
+/// use zeno::{hash, Command, PathBuilder};
+///
+/// let mut path: Vec<Command> = Vec::new();
+/// path.move_to([1.0, 2.0]).line_to([3.0, 4.0]).close();
+/// assert_eq!(hash("M1,2 L3,4 Z"), hash(&path));
+/// assert_ne!(hash("M1,2 L3,4 Z"), hash("M1,2 L3,4"));
+/// ```
+pub fn hash(data: impl PathData) -> u64 {
+    let mut hasher = PathHasher::new();
+    data.copy_to(&mut hasher);
+    hasher.finish()
+}
+
+/// Computes a stable 64-bit hash of the path after rounding all coordinates
+/// to the nearest multiple of the specified precision.
+///
+/// This normalizes paths with insignificant numerical differences to the
+/// same hash. See [quantize](fn.quantize.html) for the details of rounding
+/// and [hash](fn.hash.html) for the hashing scheme.
+///
+/// ```rust
+/// use zeno::hash_quantized;
+///
+/// assert_eq!(
+///     hash_quantized("M1.001,2 L3,3.999", 0.01),
+///     hash_quantized("M1,2 L3,4", 0.01),
+/// );
+/// ```
+pub fn hash_quantized(data: impl PathData, precision: f32) -> u64 {
+    let mut hasher = PathHasher::new();
+    quantize(data, None, precision, &mut hasher);
+    hasher.finish()
+}
+
+/// Path builder that incrementally computes a stable hash of the commands it
+/// receives.
+///
+/// This may be used to hash the output of [apply](fn.apply.html) or other
+/// functions that emit to a sink without first collecting the commands.
+/// See [hash](fn.hash.html) for a description of the hashing scheme.
+///
+/// ```rust
+/// use zeno::{hash, Command, PathBuilder, PathHasher};
+///
+/// let mut path: Vec<Command> = Vec::new();
+/// let mut hasher = PathHasher::new();
+/// path.move_to([0, 0]).line_to([10, 10]).close().rel_line_to([5, 5]);
+/// hasher.move_to([0, 0]).line_to([10, 10]).close().rel_line_to([5, 5]);
+/// assert_eq!(hasher.finish(), hash(&path));
+/// ```
+#[derive(Copy, Clone, Debug)]
+pub struct PathHasher {
+    state: u64,
+    start: Point,
+    current: Point,
+}
+
+impl PathHasher {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    /// Creates a new path hasher.
+    pub fn new() -> Self {
+        Self {
+            state: Self::OFFSET_BASIS,
+            start: Point::ZERO,
+            current: Point::ZERO,
+        }
+    }
+
+    /// Returns the hash of the commands received so far.
+    pub fn finish(&self) -> u64 {
+        self.state
+    }
+
+    fn byte(&mut self, byte: u8) {
+        self.state ^= byte as u64;
+        self.state = self.state.wrapping_mul(Self::PRIME);
+    }
+
+    fn float(&mut self, value: f32) {
+        let bits = if value == 0. {
+            0
+        } else if value.is_nan() {
+            f32::NAN.to_bits()
+        } else {
+            value.to_bits()
+        };
+        for byte in bits.to_le_bytes().iter() {
+            self.byte(*byte);
+        }
+    }
+
+    fn point(&mut self, point: Point) {
+        self.float(point.x);
+        self.float(point.y);
+    }
+}
+
+impl Default for PathHasher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PathBuilder for PathHasher {
+    fn current_point(&self) -> Point {
+        self.current
+    }
+
+    fn move_to(&mut self, to: impl Into<Point>) -> &mut Self {
+        let to = to.into();
+        self.byte(0);
+        self.point(to);
+        self.start = to;
+        self.current = to;
+        self
+    }
+
+    fn line_to(&mut self, to: impl Into<Point>) -> &mut Self {
+        let to = to.into();
+        self.byte(1);
+        self.point(to);
+        self.current = to;
+        self
+    }
+
+    fn quad_to(&mut self, control: impl Into<Point>, to: impl Into<Point>) -> &mut Self {
+        let to = to.into();
+        self.byte(2);
+        self.point(control.into());
+        self.point(to);
+        self.current = to;
+        self
+    }
+
+    fn curve_to(
+        &mut self,
+        control1: impl Into<Point>,
+        control2: impl Into<Point>,
+        to: impl Into<Point>,
+    ) -> &mut Self {
+        let to = to.into();
+        self.byte(3);
+        self.point(control1.into());
+        self.point(control2.into());
+        self.point(to);
+        self.current = to;
+        self
+    }
+
+    fn close(&mut self) -> &mut Self {
+        self.byte(4);
+        self.current = self.start;
+        self
+    }
+
+    fn conic_to(
+        &mut self,
+        control: impl Into<Point>,
+        to: impl Into<Point>,
+        weight: f32,
+    ) -> &mut Self {
+        let to = to.into();
+        self.byte(5);
+        self.point(control.into());
+        self.point(to);
+        self.float(weight);
+        self.current = to;
+        self
+    }
+
+    fn arc_to(
+        &mut self,
+        rx: f32,
+        ry: f32,
+        angle: Angle,
+        size: ArcSize,
+        sweep: ArcSweep,
+        to: impl Into<Point>,
+    ) -> &mut Self {
+        let to = to.into();
+        self.byte(6);
+        self.float(rx);
+        self.float(ry);
+        self.float(angle.to_radians());
+        let mut flags = 0;
+        if size == ArcSize::Large {
+            flags |= 1;
+        }
+        if sweep == ArcSweep::Positive {
+            flags |= 2;
+        }
+        self.byte(flags);
+        self.point(to);
+        self.current = to;
+        self
+    }
+}
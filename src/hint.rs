@@ -0,0 +1,208 @@
+//! Light grid-fitting of outlines.
+
+use super::command::Command;
+use super::geometry::{Point, Vector};
+#[cfg(not(feature = "std"))]
+use super::F32Ext;
+
+use crate::lib::Vec;
+
+/// Maximum ratio of the cross axis delta to the main axis delta for a
+/// segment to be considered aligned with an axis.
+const ALIGN_RATIO: f32 = 1. / 16.;
+
+/// Minimum length in pixels of an aligned line segment for it to be treated
+/// as an edge.
+const MIN_EDGE_LENGTH: f32 = 0.25;
+
+/// Edges that are closer than this distance are treated as a single edge.
+const MERGE_DISTANCE: f32 = 1. / 64.;
+
+/// Stems that are at least this thick will never collapse to zero pixels.
+const MIN_STEM: f32 = 0.5;
+
+/// Applies light grid-fitting to a path in device space.
+///
+/// Nearly horizontal and nearly vertical edges (along with curve extrema
+/// with axis aligned tangents) are snapped to pixel boundaries after applying
+/// the specified shift. All other points are interpolated between the
+/// surrounding edges so that the shape of the outline is preserved.
+pub fn hint(commands: &mut [Command], shift: Vector) {
+    let mut edges = Vec::new();
+    collect_edges(commands, Axis::X, &mut edges);
+    let x_edges = fit_edges(&mut edges, shift.x);
+    let mut edges = Vec::new();
+    collect_edges(commands, Axis::Y, &mut edges);
+    let y_edges = fit_edges(&mut edges, shift.y);
+    if x_edges.is_empty() && y_edges.is_empty() {
+        return;
+    }
+    let fit_point = |p: &mut Point| {
+        p.x = fit(p.x, &x_edges);
+        p.y = fit(p.y, &y_edges);
+    };
+    for cmd in commands.iter_mut() {
+        use Command::*;
+        match cmd {
+            MoveTo(p) | LineTo(p) | ArcTo(.., p) => fit_point(p),
+            QuadTo(c, p) | ConicTo(c, p, _) => {
+                fit_point(c);
+                fit_point(p);
+            }
+            CurveTo(c1, c2, p) => {
+                fit_point(c1);
+                fit_point(c2);
+                fit_point(p);
+            }
+            Close => {}
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq)]
+enum Axis {
+    X,
+    Y,
+}
+
+impl Axis {
+    /// Returns the component of the point that is snapped for this axis.
+    #[inline(always)]
+    fn along(self, p: Point) -> f32 {
+        match self {
+            Self::X => p.x,
+            Self::Y => p.y,
+        }
+    }
+
+    /// Returns the component of the point that runs parallel to edges
+    /// snapped on this axis.
+    #[inline(always)]
+    fn across(self, p: Point) -> f32 {
+        match self {
+            Self::X => p.y,
+            Self::Y => p.x,
+        }
+    }
+
+    /// Returns true if the vector is aligned with edges snapped on this axis.
+    #[inline(always)]
+    fn is_aligned(self, v: Vector) -> bool {
+        let across = self.across(v).abs();
+        across != 0. && self.along(v).abs() <= across * ALIGN_RATIO
+    }
+}
+
+fn collect_edges(commands: &[Command], axis: Axis, edges: &mut Vec<f32>) {
+    let add_line = |a: Point, b: Point, edges: &mut Vec<f32>| {
+        let d = b - a;
+        if axis.is_aligned(d) && axis.across(d).abs() >= MIN_EDGE_LENGTH {
+            edges.push((axis.along(a) + axis.along(b)) * 0.5);
+        }
+    };
+    let add_tangent = |p: Point, dir: Vector, edges: &mut Vec<f32>| {
+        if axis.is_aligned(dir) {
+            edges.push(axis.along(p));
+        }
+    };
+    let mut start = Point::ZERO;
+    let mut prev = Point::ZERO;
+    for cmd in commands {
+        use Command::*;
+        match *cmd {
+            MoveTo(p) => {
+                start = p;
+                prev = p;
+            }
+            LineTo(p) => {
+                add_line(prev, p, edges);
+                prev = p;
+            }
+            ArcTo(.., p) => {
+                prev = p;
+            }
+            QuadTo(c, p) | ConicTo(c, p, _) => {
+                add_tangent(prev, c - prev, edges);
+                add_tangent(p, p - c, edges);
+                prev = p;
+            }
+            CurveTo(c1, c2, p) => {
+                add_tangent(prev, c1 - prev, edges);
+                add_tangent(p, p - c2, edges);
+                prev = p;
+            }
+            Close => {
+                add_line(prev, start, edges);
+                prev = start;
+            }
+        }
+    }
+}
+
+/// Sorts and merges the edges and returns pairs of original and fitted
+/// coordinates.
+fn fit_edges(edges: &mut Vec<f32>, shift: f32) -> Vec<(f32, f32)> {
+    edges.retain(|e| e.is_finite());
+    edges.sort_unstable_by(|a, b| a.partial_cmp(b).unwrap_or(core::cmp::Ordering::Less));
+    let mut fitted: Vec<(f32, f32)> = Vec::with_capacity(edges.len());
+    for &edge in edges.iter() {
+        let mut target = (edge + shift).round() - shift;
+        if let Some(&(prev, prev_target)) = fitted.last() {
+            if edge - prev < MERGE_DISTANCE {
+                continue;
+            }
+            // Keep the edges ordered and prevent visible stems from
+            // collapsing.
+            if target < prev_target || (target == prev_target && edge - prev >= MIN_STEM) {
+                target = prev_target + if edge - prev >= MIN_STEM { 1. } else { 0. };
+            }
+        }
+        fitted.push((edge, target));
+    }
+    fitted
+}
+
+/// Maps a coordinate through the fitted edges.
+fn fit(v: f32, edges: &[(f32, f32)]) -> f32 {
+    let (first, last) = match (edges.first(), edges.last()) {
+        (Some(first), Some(last)) => (*first, *last),
+        _ => return v,
+    };
+    if !v.is_finite() {
+        // The stroker can produce non-finite points for degenerate input;
+        // they fall outside every interval so leave them untouched.
+        return v;
+    }
+    if v <= first.0 {
+        return v + first.1 - first.0;
+    }
+    if v >= last.0 {
+        return v + last.1 - last.0;
+    }
+    let i = edges.partition_point(|e| e.0 <= v);
+    let (a, b) = (edges[i - 1], edges[i]);
+    let t = (v - a.0) / (b.0 - a.0);
+    a.1 + (b.1 - a.1) * t
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn non_finite_points() {
+        let mut commands = [
+            Command::MoveTo(Point::new(0.2, 0.2)),
+            Command::LineTo(Point::new(10.2, 0.2)),
+            Command::LineTo(Point::new(f32::NAN, 5.)),
+            Command::LineTo(Point::new(0.2, 10.2)),
+            Command::Close,
+        ];
+        hint(&mut commands, Vector::ZERO);
+        match commands[2] {
+            Command::LineTo(p) => assert!(p.x.is_nan()),
+            _ => unreachable!(),
+        }
+        assert_eq!(commands[3], Command::LineTo(Point::new(0., 10.)));
+    }
+}
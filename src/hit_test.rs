@@ -1,11 +1,15 @@
 //! Hit testing.
 
-use super::geometry::{Point, Transform};
+use super::command::Command;
+use super::geometry::{Placement, Point, Transform, Vector};
 use super::mask::Mask;
 use super::path_data::PathData;
 use super::scratch::Scratch;
 use super::style::{Fill, Style};
+#[cfg(not(feature = "std"))]
+use super::F32Ext;
 
+use crate::lib::Vec;
 use core::cell::RefCell;
 
 /// Builder for configuring and executing a hit test.
@@ -15,6 +19,35 @@ pub struct HitTest<'a, 's, D> {
     transform: Option<Transform>,
     threshold: u8,
     scratch: RefCell<Option<&'s mut Scratch>>,
+    mask: Option<(&'a [u8], Placement)>,
+}
+
+impl<'a, 's> HitTest<'a, 's, &'a [Command]> {
+    /// Creates a new hit test that performs lookups in a previously rendered
+    /// alpha mask with the specified placement.
+    ///
+    /// The style and transform of the resulting hit test are ignored as the
+    /// mask already contains the rendered coverage of the path. Points
+    /// outside of the placement never register a hit.
+    ///
+    /// ```rust
+    /// use zeno::{HitTest, Mask};
+    ///
+    /// let (mask, placement) = Mask::new("M1,1 9,1 9,9 1,9 Z").render();
+    /// let hit = HitTest::from_mask(&mask, placement, 0);
+    /// assert!(hit.test([5, 5]));
+    /// assert!(!hit.test([20, 5]));
+    /// ```
+    pub fn from_mask(mask: &'a [u8], placement: Placement, threshold: u8) -> Self {
+        Self {
+            data: &[],
+            style: Style::Fill(Fill::NonZero),
+            transform: None,
+            threshold,
+            scratch: RefCell::new(None),
+            mask: Some((mask, placement)),
+        }
+    }
 }
 
 impl<'a, 's, D> HitTest<'a, 's, D>
@@ -29,6 +62,7 @@ where
             transform: None,
             threshold: 0,
             scratch: RefCell::new(None),
+            mask: None,
         }
     }
 
@@ -40,6 +74,7 @@ where
             transform: None,
             threshold: 0,
             scratch: RefCell::new(Some(scratch)),
+            mask: None,
         }
     }
 
@@ -63,6 +98,9 @@ where
 
     /// Returns true if the specified point is painted by the path.
     pub fn test(&self, point: impl Into<Point>) -> bool {
+        if let Some((mask, placement)) = self.mask {
+            return self.hit(lookup(mask, &placement, point.into()));
+        }
         let mut scratch = self.scratch.borrow_mut();
         let mut buf = [0u8; 1];
         let p = point.into() * -1.;
@@ -81,10 +119,220 @@ where
                 .size(1, 1)
                 .render_into(&mut buf, None);
         }
+        self.hit(buf[0])
+    }
+
+    /// Returns the first point along the line segment from `p0` to `p1` that
+    /// is painted by the path, or `None` if the segment does not cross the
+    /// painted region.
+    ///
+    /// The path is rasterized once over the bounding box of the segment and
+    /// every pixel that the segment passes through is tested in order, so
+    /// features thinner than a pixel are not missed. The returned point is
+    /// where the segment enters the first painted pixel. The segment is
+    /// clipped to the bounds of the path and long segments are processed in
+    /// pieces to bound the size of the rendered area.
+    ///
+    /// ```rust
+    /// use zeno::HitTest;
+    ///
+    /// // A hairline at x = 50.5.
+    /// let hit = HitTest::new("M50.4,0 50.6,0 50.6,10 50.4,10 Z");
+    /// let p = hit.test_segment([0, 5], [100, 5]).unwrap();
+    /// assert!((p.x - 50.).abs() < 0.001);
+    /// assert!(hit.test_segment([0, 20], [100, 20]).is_none());
+    ///
+    /// // Only the part of the segment near the path is rendered.
+    /// let p = hit.test_segment([-1e9, 5.], [1e9, 5.]).unwrap();
+    /// assert!((p.x - 50.).abs() < 0.001);
+    /// ```
+    pub fn test_segment(&self, p0: impl Into<Point>, p1: impl Into<Point>) -> Option<Point> {
+        let p0 = p0.into();
+        let p1 = p1.into();
+        if !(p1 - p0).length_squared().is_finite() {
+            return None;
+        }
+        let (min, max) = match self.mask {
+            Some((_, placement)) => {
+                let min = Point::new(placement.left as f32, placement.top as f32);
+                (
+                    min,
+                    min + Vector::new(placement.width as f32, placement.height as f32),
+                )
+            }
+            None => {
+                let mut scratch = self.scratch.borrow_mut();
+                let bounds = match scratch.as_mut() {
+                    Some(scratch) => scratch.bounds(&self.data, self.style, self.transform),
+                    None => Scratch::new().bounds(&self.data, self.style, self.transform),
+                };
+                (bounds.min.floor(), bounds.max.ceil())
+            }
+        };
+        let margin = Vector::new(1., 1.);
+        let (p0, p1) = clip_segment(p0, p1, min - margin, max + margin)?;
+        let d = p1 - p0;
+        let len = d.x.abs().max(d.y.abs());
+        let pieces = (len / MAX_PIECE_SIZE).ceil().max(1.) as u32;
+        let step = d * (1. / pieces as f32);
+        let mut buf = Vec::new();
+        let mut a = p0;
+        for i in 1..=pieces {
+            let b = if i == pieces {
+                p1
+            } else {
+                p0 + step * i as f32
+            };
+            if let Some(p) = self.test_piece(a, b, &mut buf) {
+                return Some(p);
+            }
+            a = b;
+        }
+        None
+    }
+
+    /// Tests the pixels along a segment against the coverage of the path
+    /// over the bounding box of the segment.
+    fn test_piece(&self, a: Point, b: Point, buf: &mut Vec<u8>) -> Option<Point> {
+        let left = a.x.min(b.x).floor();
+        let top = a.y.min(b.y).floor();
+        let placement = Placement {
+            left: left as i32,
+            top: top as i32,
+            width: (a.x.max(b.x).floor() - left) as u32 + 1,
+            height: (a.y.max(b.y).floor() - top) as u32 + 1,
+            fract: Vector::ZERO,
+        };
+        let mask = match self.mask {
+            Some((mask, placement)) => return self.trace(a, b, mask, &placement),
+            None => {
+                buf.clear();
+                buf.resize(placement.width as usize * placement.height as usize, 0);
+                buf
+            }
+        };
+        let mut scratch = self.scratch.borrow_mut();
+        let mut builder = match scratch.as_mut() {
+            Some(scratch) => Mask::with_scratch(&self.data, scratch),
+            None => Mask::new(&self.data),
+        };
+        builder
+            .style(self.style)
+            .offset(Vector::new(-left, -top))
+            .transform(self.transform)
+            .size(placement.width, placement.height)
+            .render_into(mask, None);
+        self.trace(a, b, mask, &placement)
+    }
+
+    /// Walks the pixels crossed by the segment from `a` to `b` and returns
+    /// the point where it enters the first one that registers a hit.
+    fn trace(&self, a: Point, b: Point, mask: &[u8], placement: &Placement) -> Option<Point> {
+        let d = b - a;
+        let mut cell = Point::new(a.x.floor(), a.y.floor());
+        let end = Point::new(b.x.floor(), b.y.floor());
+        let (step_x, delta_x, mut next_x) = traversal(a.x, cell.x, d.x);
+        let (step_y, delta_y, mut next_y) = traversal(a.y, cell.y, d.y);
+        let mut t = 0.;
+        let count = (end.x - cell.x).abs() + (end.y - cell.y).abs();
+        for _ in 0..=count as usize {
+            if self.hit(lookup(mask, placement, cell)) {
+                return Some(a + d * t);
+            }
+            if next_x < next_y {
+                cell.x += step_x;
+                t = next_x;
+                next_x += delta_x;
+            } else {
+                cell.y += step_y;
+                t = next_y;
+                next_y += delta_y;
+            }
+            if t > 1. {
+                break;
+            }
+        }
+        None
+    }
+
+    fn hit(&self, value: u8) -> bool {
         if self.threshold == 0xFF {
-            buf[0] >= self.threshold
+            value >= self.threshold
+        } else {
+            value > self.threshold
+        }
+    }
+}
+
+/// Maximum extent of the area rendered at once by a segment hit test.
+const MAX_PIECE_SIZE: f32 = 256.;
+
+/// Returns the part of the segment from `p0` to `p1` that lies within the
+/// rectangle, or `None` if the segment misses it.
+fn clip_segment(p0: Point, p1: Point, min: Point, max: Point) -> Option<(Point, Point)> {
+    let d = p1 - p0;
+    let (mut q0, mut q1) = (p0, p1);
+    let (mut t0, mut t1) = (0f32, 1f32);
+    for axis in 0..2 {
+        let (start, delta, lo, hi) = if axis == 0 {
+            (p0.x, d.x, min.x, max.x)
         } else {
-            buf[0] > self.threshold
+            (p0.y, d.y, min.y, max.y)
+        };
+        if delta == 0. {
+            if start < lo || start > hi {
+                return None;
+            }
+            continue;
         }
+        let (enter, exit) = if delta > 0. { (lo, hi) } else { (hi, lo) };
+        let (a, b) = ((enter - start) / delta, (exit - start) / delta);
+        // The clipped coordinate is set exactly as the parameter is not
+        // precise enough for long segments.
+        if a > t0 {
+            t0 = a;
+            q0 = p0 + d * a;
+            if axis == 0 {
+                q0.x = enter;
+            } else {
+                q0.y = enter;
+            }
+        }
+        if b < t1 {
+            t1 = b;
+            q1 = p0 + d * b;
+            if axis == 0 {
+                q1.x = exit;
+            } else {
+                q1.y = exit;
+            }
+        }
+    }
+    if t0 > t1 {
+        return None;
+    }
+    Some((q0, q1))
+}
+
+/// Returns the step direction, the parametric distance between cell
+/// boundaries and the parameter of the first boundary crossed along one axis
+/// of a segment.
+fn traversal(start: f32, cell: f32, d: f32) -> (f32, f32, f32) {
+    if d > 0. {
+        (1., 1. / d, (cell + 1. - start) / d)
+    } else if d < 0. {
+        (-1., -1. / d, (cell - start) / d)
+    } else {
+        (0., f32::INFINITY, f32::INFINITY)
+    }
+}
+
+pub(super) fn lookup(mask: &[u8], placement: &Placement, point: Point) -> u8 {
+    let x = point.x.floor() as i64 - placement.left as i64;
+    let y = point.y.floor() as i64 - placement.top as i64;
+    if x < 0 || y < 0 || x >= placement.width as i64 || y >= placement.height as i64 {
+        return 0;
     }
+    let index = y as usize * placement.width as usize + x as usize;
+    mask.get(index).copied().unwrap_or(0)
 }
@@ -239,7 +239,9 @@ crate currently provides two methods of traversal.
 The [Vertices](struct.Vertices.html) iterator yields a variant of the
 [Vertex](enum.Vertex.html) enum at the beginning and end of each subpath and
 between each path command. Each variant provides all the geometric
-information necessary to place SVG style markers.
+information necessary to place SVG style markers. The
+[markers](struct.Vertices.html#method.markers) adapter resolves this into
+marker orientations, including those at the seams of closed subpaths.
 
 The [Walk](struct.Walk.html) type is an iterator-like type that allows for
 stepping along the path by arbitrary distances. Each step yields the position
@@ -262,6 +264,18 @@ allocate. In addition, the
 [HitTest::with_scratch](struct.HitTest.html#method.with_scratch)
 constructors are provided which take a scratch instance as an argument and
 redirect all transient allocations to the reusable storage.
+
+# Determinism
+
+Rasterization itself operates entirely in 24.8 fixed point with integer
+coverage accumulation. The remaining source of variation between platforms is
+the system math library which may round transcendental functions (used for
+arcs, rotations, skews and gamma correction) differently. Enabling the
+`deterministic` feature routes these through the portable implementations
+provided by the `libm` crate so that, given the same input, masks are bit
+identical across targets such as x86, ARM and WebAssembly. Note that this
+relies on IEEE 754 single precision arithmetic, so 32-bit x86 targets must
+make use of SSE2 rather than the x87 floating point unit.
  */
 
 #![cfg_attr(not(feature = "std"), no_std)]
@@ -271,42 +285,85 @@ compile_error! { "Either the std or libm feature must be enabled"  }
 
 extern crate alloc;
 
+mod clip;
 mod command;
+mod compact;
+#[cfg(feature = "eval")]
+mod decoration;
 mod geometry;
+mod glyph_run;
+mod hash;
+#[cfg(feature = "eval")]
+mod hint;
 #[cfg(feature = "eval")]
 mod hit_test;
 #[cfg(feature = "eval")]
 mod mask;
+#[cfg(feature = "eval")]
+mod offset;
 mod path_builder;
 mod path_data;
 #[cfg(feature = "eval")]
 mod raster;
+#[cfg(feature = "scene")]
+mod scene;
 #[cfg(feature = "eval")]
 mod scratch;
 mod segment;
 #[cfg(feature = "eval")]
+mod simd;
+mod snap;
+#[cfg(feature = "eval")]
 mod stroke;
 mod style;
 mod svg_parser;
+mod svg_writer;
+#[cfg(feature = "test-util")]
+mod test_util;
+mod trace;
 #[cfg(feature = "eval")]
 mod traversal;
+#[cfg(feature = "eval")]
+mod union;
 
+pub use clip::{clip_centerline_to_rect, clip_to_rect, skip_ink};
 pub use command::{Command, Verb};
-pub use geometry::{Angle, Bounds, Origin, Placement, Point, Transform, Vector};
+pub use compact::{CompactCommands, CompactEncoder, CompactPath};
+pub use geometry::{
+    Angle, Bounds, Origin, Placement, Point, Rounding, StyledBounds, Transform, Vector,
+};
+pub use glyph_run::{Glyph, GlyphRun, GlyphRunCommands};
+pub use hash::{hash, hash_quantized, PathHasher};
 #[cfg(feature = "eval")]
 pub use hit_test::HitTest;
 #[cfg(feature = "eval")]
-pub use mask::{Format, Mask};
-pub use path_builder::{ArcSize, ArcSweep, PathBuilder};
+pub use mask::{
+    resolve_deltas, ChannelOrder, Composite, EdgeMask, Format, Layer, LayeredMask, LcdFilter, Mask,
+    MaskStorage, Prepared, Quality, RenderError, RenderStats, RowTarget, Span, SubpixelAlpha,
+    SubpixelLayout, Tile, Tiles,
+};
+pub use path_builder::{ArcSize, ArcSweep, PathBuilder, RetainArcs, Tee};
 #[cfg(feature = "eval")]
-pub use path_data::{apply, bounds};
-pub use path_data::{length, PathData};
+pub use path_data::{
+    apply, apply_with_info, bounds, complexity, styled_bounds, ApplyInfo, Complexity,
+};
+pub use path_data::{length, length_with, PathData};
+#[cfg(feature = "scene")]
+pub use scene::Scene;
 #[cfg(feature = "eval")]
-pub use scratch::Scratch;
+pub use scratch::{LimitExceeded, Scratch};
+pub use segment::Measure;
+pub use snap::{quantize, snap, Snap};
 pub use style::*;
-pub use svg_parser::validate_svg;
+pub use svg_parser::{validate_svg, SvgCommands};
+#[cfg(feature = "std")]
+pub use svg_writer::IoWriter;
+pub use svg_writer::SvgWriter;
+#[cfg(feature = "test-util")]
+pub use test_util::Generator;
+pub use trace::Trace;
 #[cfg(feature = "eval")]
-pub use traversal::{Vertex, Vertices, Walk};
+pub use traversal::{DashedWalk, Marker, MarkerPosition, Markers, Step, Vertex, Vertices, Walk};
 
 macro_rules! define_f32_ext {
     ($($fpname:ident($($argname:ident: $argty:ty),*) -> $ret:ty => $libmname:ident;)*) => {
@@ -317,7 +374,7 @@ macro_rules! define_f32_ext {
             )*
         }
 
-        #[cfg(feature = "std")]
+        #[cfg(all(feature = "std", not(feature = "deterministic")))]
         impl F32Ext for f32 {
             $(
             fn $fpname(self, $($argname:$argty),*) -> $ret {
@@ -327,7 +384,7 @@ macro_rules! define_f32_ext {
             )*
         }
 
-        #[cfg(all(not(feature = "std"), feature = "libm"))]
+        #[cfg(any(feature = "deterministic", all(not(feature = "std"), feature = "libm")))]
         impl F32Ext for f32 {
             $(
             fn $fpname(self, $($argname:$argty),*) -> $ret {
@@ -353,16 +410,17 @@ define_f32_ext! {
     sqrt() -> f32 => sqrt;
     powf(x:f32) -> f32 => powf;
     powi(x:i32) -> f32 => pow;
+    round() -> f32 => round;
     tan() -> f32 => tan;
 }
 
-#[cfg(all(not(feature = "std"), feature = "libm"))]
+#[cfg(any(feature = "deterministic", all(not(feature = "std"), feature = "libm")))]
 trait LibmCvt {
     type Input;
     fn libm_cvt(input: Self::Input) -> Self;
 }
 
-#[cfg(all(not(feature = "std"), feature = "libm"))]
+#[cfg(any(feature = "deterministic", all(not(feature = "std"), feature = "libm")))]
 impl LibmCvt for f32 {
     type Input = f64;
     fn libm_cvt(input: f64) -> f32 {
@@ -370,7 +428,7 @@ impl LibmCvt for f32 {
     }
 }
 
-#[cfg(all(not(feature = "std"), feature = "libm"))]
+#[cfg(any(feature = "deterministic", all(not(feature = "std"), feature = "libm")))]
 impl LibmCvt for (f32, f32) {
     type Input = (f64, f64);
     fn libm_cvt((a, b): (f64, f64)) -> (f32, f32) {
@@ -380,5 +438,6 @@ impl LibmCvt for (f32, f32) {
 
 // Prep for no_std support when core supports FP intrinsics.
 mod lib {
+    pub use alloc::boxed::Box;
     pub use alloc::vec::Vec;
 }
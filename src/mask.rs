@@ -1,26 +1,78 @@
 //! Mask generator.
 
-use super::geometry::{Origin, Placement, Transform, Vector};
-use super::path_data::{apply, PathData};
-use super::scratch::Scratch;
+use super::command::Command;
+use super::geometry::{
+    bounds_fract, Bounds, BoundsBuilder, Origin, Placement, Point, Rounding, Transform, Vector,
+};
+use super::hint::hint;
+use super::path_builder::PathBuilder;
+use super::path_data::{apply, PathData, Transformed};
+use super::raster::{
+    clip_rect, AdaptiveStorage, CoverageCurve, Interrupt, LimitedStorage, RasterStorage,
+    Rasterizer, MAX_DIM,
+};
+use super::scratch::{LimitExceeded, LimitedPath, Scratch};
+use super::simd::resolve_row;
+use super::stroke::{hairline_into, hairline_width};
 use super::style::{Fill, Style};
+#[cfg(not(feature = "std"))]
+use super::F32Ext;
 
 use crate::lib::Vec;
 use core::cell::RefCell;
+use core::cmp::Ordering;
+use core::fmt;
+use core::sync::atomic::AtomicBool;
+#[cfg(feature = "std")]
+use std::time::{Duration, Instant};
 
 /// The desired output image format for rendering.
 #[derive(Copy, Clone, PartialEq, Debug)]
 pub enum Format {
     /// 8-bit alpha mask.
     Alpha,
+    /// 16-bit alpha mask. Each value is stored as a native endian `u16` in
+    /// two consecutive bytes.
+    Alpha16,
+    /// 32-bit floating point alpha mask with coverage normalized to the
+    /// range 0 to 1. Each value is stored as a native endian `f32` in four
+    /// consecutive bytes.
+    AlphaF32,
     /// 32-bit RGBA subpixel mask with 1/3 pixel offsets for the red and
     /// blue channels.
     Subpixel,
     /// 32-bit RGBA subpixel mask with custom offsets.
     CustomSubpixel([f32; 3]),
+    /// 32-bit RGBA subpixel mask for vertically striped displays with the
+    /// red channel above the blue channel and 1/3 pixel vertical offsets.
+    SubpixelVRgba,
+    /// 32-bit RGBA subpixel mask for vertically striped displays with the
+    /// blue channel above the red channel and 1/3 pixel vertical offsets.
+    SubpixelVBgra,
+    /// 1-bit mask with eight pixels packed into each byte, starting with the
+    /// most significant bit. A pixel is set when its coverage is at least
+    /// one half. Each row begins on a byte boundary.
+    Packed1,
 }
 
 impl Format {
+    /// Creates a subpixel format for displays with the specified layout.
+    ///
+    /// ```rust
+    /// use zeno::{Format, SubpixelLayout};
+    ///
+    /// assert_eq!(Format::subpixel(SubpixelLayout::Rgb), Format::Subpixel);
+    /// assert_eq!(Format::subpixel(SubpixelLayout::Bgr), Format::subpixel_bgra());
+    /// ```
+    pub fn subpixel(layout: SubpixelLayout) -> Self {
+        match layout {
+            SubpixelLayout::Rgb => Self::Subpixel,
+            SubpixelLayout::Bgr => Self::subpixel_bgra(),
+            SubpixelLayout::VRgb => Self::SubpixelVRgba,
+            SubpixelLayout::VBgr => Self::SubpixelVBgra,
+        }
+    }
+
     /// Creates a format for BGRA subpixel rendering.
     pub fn subpixel_bgra() -> Self {
         Self::CustomSubpixel([0.3, 0., -0.3])
@@ -29,12 +81,35 @@ impl Format {
     /// Returns the necessary buffer size to hold an image of the specified
     /// width and height with this format.
     pub fn buffer_size(self, width: u32, height: u32) -> usize {
-        (width
-            * height
-            * match self {
-                Self::Alpha => 1,
-                _ => 4,
-            }) as usize
+        self.row_size(width) * height as usize
+    }
+
+    /// Returns the number of bytes in a tightly packed row of the specified
+    /// width with this format.
+    ///
+    /// ```rust
+    /// use zeno::Format;
+    ///
+    /// assert_eq!(Format::Alpha16.row_size(10), 20);
+    /// assert_eq!(Format::Packed1.row_size(10), 2);
+    /// ```
+    pub fn row_size(self, width: u32) -> usize {
+        match self {
+            #[allow(clippy::manual_div_ceil)]
+            Self::Packed1 => (width as usize + 7) / 8,
+            _ => width as usize * self.bytes_per_pixel(),
+        }
+    }
+
+    /// Returns the number of bytes occupied by a single pixel. This is 1 for
+    /// [Packed1](#variant.Packed1), which stores a fraction of a byte per
+    /// pixel.
+    pub fn bytes_per_pixel(self) -> usize {
+        match self {
+            Self::Alpha | Self::Packed1 => 1,
+            Self::Alpha16 => 2,
+            _ => 4,
+        }
     }
 }
 
@@ -44,13 +119,168 @@ impl Default for Format {
     }
 }
 
+/// Physical arrangement of the color elements within a display pixel.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum SubpixelLayout {
+    /// Horizontal stripes ordered red, green, blue from left to right.
+    Rgb,
+    /// Horizontal stripes ordered blue, green, red from left to right.
+    Bgr,
+    /// Vertical stripes ordered red, green, blue from top to bottom.
+    VRgb,
+    /// Vertical stripes ordered blue, green, red from top to bottom.
+    VBgr,
+}
+
+/// Contents of the alpha channel of subpixel masks.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum SubpixelAlpha {
+    /// The alpha channel is left at zero.
+    None,
+    /// The alpha channel holds the maximum coverage of the color channels,
+    /// which are left unchanged so that the pixel is premultiplied.
+    Premultiplied,
+    /// The alpha channel holds the maximum coverage of the color channels,
+    /// which are divided by the alpha.
+    Straight,
+}
+
+/// Order of the channels within 32-bit pixels, from the most significant
+/// to the least significant byte.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ChannelOrder {
+    /// Red in the most significant byte and alpha in the least.
+    Rgba,
+    /// Alpha in the most significant byte and blue in the least.
+    Argb,
+    /// Blue in the most significant byte and alpha in the least.
+    Bgra,
+    /// Alpha in the most significant byte and red in the least.
+    Abgr,
+}
+
+impl ChannelOrder {
+    /// Packs the RGBA channels of a pixel into a value.
+    fn pack(self, [r, g, b, a]: [u8; 4]) -> u32 {
+        u32::from_be_bytes(match self {
+            Self::Rgba => [r, g, b, a],
+            Self::Argb => [a, r, g, b],
+            Self::Bgra => [b, g, r, a],
+            Self::Abgr => [a, b, g, r],
+        })
+    }
+
+    /// Unpacks a value into the RGBA channels of a pixel.
+    fn unpack(self, value: u32) -> [u8; 4] {
+        let [x, y, z, w] = value.to_be_bytes();
+        match self {
+            Self::Rgba => [x, y, z, w],
+            Self::Argb => [y, z, w, x],
+            Self::Bgra => [z, y, x, w],
+            Self::Abgr => [w, z, y, x],
+        }
+    }
+}
+
+/// Filter applied to the coverage of subpixel masks to reduce color fringes.
+///
+/// The filters match those of FreeType and operate on the sequence of color
+/// elements of each row, ordered by their horizontal offsets. They have no
+/// effect on formats with vertically arranged color elements.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum LcdFilter {
+    /// The coverage of each color element is left unchanged.
+    None,
+    /// Five tap FIR filter with weights of 8, 77, 86, 77 and 8.
+    Default,
+    /// Three tap FIR filter with weights of 85, 86 and 85 that preserves
+    /// more contrast at the cost of stronger fringes.
+    Light,
+    /// Filter that only distributes coverage among the color elements of
+    /// the same pixel, as in older versions of FreeType.
+    Legacy,
+}
+
+impl LcdFilter {
+    /// Returns the weights of the filter taps, which sum to 256.
+    fn weights(self) -> Option<[u32; 5]> {
+        match self {
+            Self::Default => Some([0x08, 0x4D, 0x56, 0x4D, 0x08]),
+            Self::Light => Some([0x00, 0x55, 0x56, 0x55, 0x00]),
+            _ => None,
+        }
+    }
+}
+
+/// Operation used to combine rendered coverage with the existing contents of
+/// a target buffer.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Composite {
+    /// Replaces the existing coverage.
+    Replace,
+    /// Keeps the maximum of the existing and rendered coverage.
+    Max,
+    /// Adds the rendered coverage, saturating at full coverage.
+    Add,
+    /// Subtracts the rendered coverage, saturating at zero.
+    Subtract,
+    /// Multiplies the existing coverage by the rendered coverage. Pixels
+    /// that are not covered by the path are cleared.
+    Multiply,
+}
+
+impl Composite {
+    /// Combines the existing and rendered coverage where full coverage is
+    /// represented by `one`.
+    fn blend(self, dst: u32, src: u32, one: u32) -> u32 {
+        match self {
+            Self::Replace => src,
+            Self::Max => dst.max(src),
+            Self::Add => (dst + src).min(one),
+            Self::Subtract => dst.saturating_sub(src),
+            Self::Multiply => (dst * src + one / 2) / one,
+        }
+    }
+}
+
+/// Anti-aliasing quality used when rasterizing a mask. Lower qualities
+/// position edges on a coarser subpixel grid and flatten curves with a
+/// larger tolerance, producing fewer edges for curved paths at the cost of
+/// fewer distinct coverage values along edges.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Quality {
+    /// 16x anti-aliasing with edges positioned to 1/4 of a pixel.
+    Low,
+    /// 64x anti-aliasing with edges positioned to 1/8 of a pixel.
+    Medium,
+    /// 256x anti-aliasing with edges positioned to 1/256 of a pixel.
+    High,
+}
+
+impl Quality {
+    /// Returns the number of fractional bits per axis used to position
+    /// edges.
+    fn precision(self) -> i32 {
+        match self {
+            Self::Low => 2,
+            Self::Medium => 3,
+            Self::High => 8,
+        }
+    }
+}
+
 /// Builder for configuring and rendering a mask.
 pub struct Mask<'a, 's, D> {
     data: D,
     style: Style<'a>,
     transform: Option<Transform>,
+    pre_transform: Option<Transform>,
+    post_transform: Option<Transform>,
     format: Format,
+    subpixel_alpha: SubpixelAlpha,
+    lcd_filter: LcdFilter,
     origin: Origin,
+    flip_y: bool,
     offset: Vector,
     render_offset: Vector,
     width: u32,
@@ -58,6 +288,25 @@ pub struct Mask<'a, 's, D> {
     explicit_size: bool,
     has_size: bool,
     bounds_offset: Vector,
+    bounds_position: (i32, i32),
+    bounds_fract: Vector,
+    rounding: Rounding,
+    hint: bool,
+    anti_alias: bool,
+    quality: Quality,
+    gamma: f32,
+    contrast: f32,
+    clip: Option<Bounds>,
+    clip_path: Option<(Vec<Command>, Fill)>,
+    fill_rules: Option<&'a [Fill]>,
+    composite: Composite,
+    invert: bool,
+    trim: u8,
+    morphology: f32,
+    supersample: u32,
+    cancel: Option<&'a AtomicBool>,
+    #[cfg(feature = "std")]
+    time_limit: Option<Duration>,
     scratch: RefCell<Option<&'s mut Scratch>>,
 }
 
@@ -71,8 +320,13 @@ where
             data,
             style: Style::Fill(Fill::NonZero),
             transform: None,
+            pre_transform: None,
+            post_transform: None,
             format: Format::Alpha,
+            subpixel_alpha: SubpixelAlpha::None,
+            lcd_filter: LcdFilter::None,
             origin: Origin::TopLeft,
+            flip_y: false,
             offset: Vector::ZERO,
             render_offset: Vector::ZERO,
             width: 0,
@@ -80,6 +334,25 @@ where
             explicit_size: false,
             has_size: false,
             bounds_offset: Vector::ZERO,
+            bounds_position: (0, 0),
+            bounds_fract: Vector::ZERO,
+            rounding: Rounding::Fractional,
+            hint: false,
+            anti_alias: true,
+            quality: Quality::High,
+            gamma: 1.,
+            contrast: 0.,
+            clip: None,
+            clip_path: None,
+            fill_rules: None,
+            composite: Composite::Replace,
+            invert: false,
+            trim: 0,
+            morphology: 0.,
+            supersample: 1,
+            cancel: None,
+            #[cfg(feature = "std")]
+            time_limit: None,
             scratch: RefCell::new(None),
         }
     }
@@ -90,8 +363,13 @@ where
             data,
             style: Style::Fill(Fill::NonZero),
             transform: None,
+            pre_transform: None,
+            post_transform: None,
             format: Format::Alpha,
+            subpixel_alpha: SubpixelAlpha::None,
+            lcd_filter: LcdFilter::None,
             origin: Origin::TopLeft,
+            flip_y: false,
             offset: Vector::ZERO,
             render_offset: Vector::ZERO,
             width: 0,
@@ -99,22 +377,119 @@ where
             explicit_size: false,
             has_size: false,
             bounds_offset: Vector::ZERO,
+            bounds_position: (0, 0),
+            bounds_fract: Vector::ZERO,
+            rounding: Rounding::Fractional,
+            hint: false,
+            anti_alias: true,
+            quality: Quality::High,
+            gamma: 1.,
+            contrast: 0.,
+            clip: None,
+            clip_path: None,
+            fill_rules: None,
+            composite: Composite::Replace,
+            invert: false,
+            trim: 0,
+            morphology: 0.,
+            supersample: 1,
+            cancel: None,
+            #[cfg(feature = "std")]
+            time_limit: None,
             scratch: RefCell::new(Some(scratch)),
         }
     }
 
     /// Sets the style of the path. The default is a non-zero fill.
+    ///
+    /// Solid strokes that are no wider than one pixel after the transform
+    /// are rendered as hairlines directly from the center line of the path
+    /// with coverage proportional to the width. Hairlines keep the bevel,
+    /// miter and round joins and the caps of the stroke; other joins are
+    /// rendered with the full outline.
+    ///
+    /// ```rust
+    /// use zeno::{apply, Cap, Command, Join, Mask, Stroke};
+    ///
+    /// let coverage = |width| {
+    ///     let (mask, _) = Mask::new("M0,5.5 L20,5.5").style(Stroke::new(width)).render();
+    ///     mask.iter().map(|&c| c as u32).sum::<u32>()
+    /// };
+    /// // A hairline of half the width covers half the area.
+    /// assert!((coverage(0.5) * 2).abs_diff(coverage(1.)) < 40);
+    ///
+    /// // Hairlines match the full outline of the stroke.
+    /// let path = "M5,10 L30,10 L50,25 L60,45";
+    /// let mut stroke = Stroke::new(1.);
+    /// stroke.join(Join::Round).cap(Cap::Round);
+    /// let mut outline: Vec<Command> = Vec::new();
+    /// apply(path, stroke, None, &mut outline);
+    /// let (hairline, _) = Mask::new(path).style(stroke).size(64, 48).render();
+    /// let (full, _) = Mask::new(&outline).size(64, 48).render();
+    /// let sum = |mask: &[u8]| mask.iter().map(|&c| c as u32).sum::<u32>();
+    /// assert!(sum(&hairline).abs_diff(sum(&full)) < 60);
+    /// ```
     pub fn style(&mut self, style: impl Into<Style<'a>>) -> &mut Self {
         self.style = style.into();
         self
     }
 
+    /// Sets the fill rules for the subpaths of a filled path, keyed by
+    /// subpath index. Subpaths beyond the end of the slice use the fill rule
+    /// of the style. The subpaths with each rule are filled separately and
+    /// the coverage of the mask is the union of both, which allows even-odd
+    /// shapes with holes to be combined with non-zero shapes. Ignored for
+    /// strokes and by [render_deltas_into](#method.render_deltas_into).
+    ///
+    /// ```rust
+    /// use zeno::{Fill, Mask};
+    ///
+    /// // Two nested squares with the same winding followed by a third
+    /// // square to the right.
+    /// let path = "M0,0 12,0 12,12 0,12 Z M4,4 8,4 8,8 4,8 Z M12,0 16,0 16,12 12,12 Z";
+    /// let (mask, _) = Mask::new(path)
+    ///     .size(16, 12)
+    ///     .fill_rules(&[Fill::EvenOdd, Fill::EvenOdd])
+    ///     .render();
+    /// assert_eq!(mask[2 * 16 + 2], 255);
+    /// assert_eq!(mask[6 * 16 + 6], 0);
+    /// assert_eq!(mask[6 * 16 + 14], 255);
+    /// ```
+    pub fn fill_rules(&mut self, rules: &'a [Fill]) -> &mut Self {
+        self.fill_rules = Some(rules);
+        self
+    }
+
     /// Sets the transformation matrix of the path.
+    ///
+    /// For strokes, this is applied after the style is evaluated when the
+    /// stroke is [scaled](struct.Stroke.html#method.scale) and before
+    /// otherwise. In the former case, the tolerances used to evaluate the
+    /// stroke are adjusted by the largest scale of the transform so that
+    /// curves remain accurate in device space under extreme scaling or
+    /// shearing.
     pub fn transform(&mut self, transform: Option<Transform>) -> &mut Self {
         self.transform = transform;
         self
     }
 
+    /// Sets a transformation matrix that is always applied to the path before
+    /// the style is evaluated. Stroke widths and dash lengths are measured in
+    /// the transformed space and are not affected by this transform.
+    pub fn pre_transform(&mut self, transform: Option<Transform>) -> &mut Self {
+        self.pre_transform = transform;
+        self
+    }
+
+    /// Sets a transformation matrix that is always applied to the path after
+    /// the style is evaluated and after the primary
+    /// [transform](#method.transform). This affects the final placement and
+    /// shape of the styled path, including stroke widths.
+    pub fn post_transform(&mut self, transform: Option<Transform>) -> &mut Self {
+        self.post_transform = transform;
+        self
+    }
+
     /// Sets the desired format of the mask. The default value is an 8-bit
     /// alpha format.
     pub fn format(&mut self, format: Format) -> &mut Self {
@@ -122,18 +497,375 @@ where
         self
     }
 
+    /// Sets the contents of the alpha channel for subpixel formats. The
+    /// default is [SubpixelAlpha::None](enum.SubpixelAlpha.html#variant.None).
+    /// The alpha channel is left unchanged when compositing with an
+    /// operation other than replacement.
+    ///
+    /// ```rust
+    /// use zeno::{Format, Mask, SubpixelAlpha};
+    ///
+    /// let (mask, _) = Mask::new("M0,0 4,0 4,4 0,4 Z")
+    ///     .format(Format::Subpixel)
+    ///     .subpixel_alpha(SubpixelAlpha::Straight)
+    ///     .render();
+    /// assert_eq!(&mask[..4], &[255, 0, 0, 76]);
+    /// assert_eq!(&mask[4..8], &[255, 255, 179, 255]);
+    /// ```
+    pub fn subpixel_alpha(&mut self, alpha: SubpixelAlpha) -> &mut Self {
+        self.subpixel_alpha = alpha;
+        self
+    }
+
+    /// Sets the filter applied to the coverage of subpixel formats. The
+    /// default is [LcdFilter::None](enum.LcdFilter.html#variant.None). The
+    /// filter is not applied when compositing with an operation other than
+    /// replacement.
+    ///
+    /// ```rust
+    /// use zeno::{Format, LcdFilter, Mask};
+    ///
+    /// let (mask, _) = Mask::new("M0,0 4,0 4,4 0,4 Z")
+    ///     .format(Format::Subpixel)
+    ///     .lcd_filter(LcdFilter::Default)
+    ///     .render();
+    /// // Coverage spreads to the green and blue elements of the padding pixel.
+    /// assert_eq!(&mask[..3], &[25, 30, 87]);
+    /// ```
+    pub fn lcd_filter(&mut self, filter: LcdFilter) -> &mut Self {
+        self.lcd_filter = filter;
+        self
+    }
+
     /// Sets the origin that defines the coordinate system for the mask.
     pub fn origin(&mut self, origin: Origin) -> &mut Self {
         self.origin = origin;
         self
     }
 
+    /// Sets whether the rows of the mask are written bottom-up, with the
+    /// bottom row of the mask first in the buffer, as expected by OpenGL
+    /// textures. The placement and the coordinate system defined by the
+    /// origin are unaffected. The default is false.
+    ///
+    /// ```rust
+    /// use zeno::Mask;
+    ///
+    /// let (mask, _) = Mask::new("M0,0 4,0 4,1 0,1 Z")
+    ///     .size(4, 4)
+    ///     .flip_y(true)
+    ///     .render();
+    /// assert_eq!(mask[0], 0);
+    /// assert_eq!(mask[3 * 4], 255);
+    /// ```
+    pub fn flip_y(&mut self, flip: bool) -> &mut Self {
+        self.flip_y = flip;
+        self
+    }
+
     /// Sets the offset for the path.
     pub fn offset(&mut self, offset: impl Into<Vector>) -> &mut Self {
         self.offset = offset.into();
         self
     }
 
+    /// Sets the rounding applied to the position of the computed placement.
+    /// The default keeps the fractional part of the offset in the rendered
+    /// coverage and reports it in the `fract` field of the placement. The
+    /// other modes snap the content to whole pixels. Has no effect when an
+    /// explicit size is set.
+    ///
+    /// ```rust
+    /// use zeno::{Mask, Rounding, Vector};
+    ///
+    /// let (_, placement) = Mask::new("M0,0 4,0 4,4 0,4 Z")
+    ///     .offset((10.25, 2.75))
+    ///     .render();
+    /// assert_eq!(placement.fract, Vector::new(0.25, 0.75));
+    ///
+    /// let (mask, placement) = Mask::new("M0,0 4,0 4,4 0,4 Z")
+    ///     .offset((10.25, 2.75))
+    ///     .rounding(Rounding::Round)
+    ///     .render();
+    /// assert_eq!((placement.left, placement.top), (9, 3));
+    /// assert_eq!(placement.fract, Vector::ZERO);
+    /// assert!(mask[1..5].iter().all(|&c| c == 255));
+    /// ```
+    pub fn rounding(&mut self, rounding: Rounding) -> &mut Self {
+        self.rounding = rounding;
+        self
+    }
+
+    /// Sets whether a light grid-fitting pass is applied to the path before
+    /// rasterization. This nudges nearly horizontal and vertical edges toward
+    /// pixel boundaries, trading some shape fidelity for sharper output at
+    /// small sizes (such as text). The default is false.
+    pub fn hint(&mut self, hint: bool) -> &mut Self {
+        self.hint = hint;
+        self
+    }
+
+    /// Sets whether the mask is rendered with anti-aliased edges. When
+    /// disabled, every pixel is either fully opaque or fully transparent
+    /// depending on whether at least half of its area is covered by the path.
+    /// This is useful for pixel art and stencil masks where partial coverage
+    /// is undesirable. Deltas produced by
+    /// [render_deltas_into](#method.render_deltas_into) are unaffected. The
+    /// default is true.
+    ///
+    /// ```rust
+    /// use zeno::Mask;
+    ///
+    /// let (mask, _) = Mask::new("M0,0 L9.7,0.2 5,9.6 Z").anti_alias(false).render();
+    /// assert!(mask.iter().all(|&c| c == 0 || c == 255));
+    /// ```
+    pub fn anti_alias(&mut self, anti_alias: bool) -> &mut Self {
+        self.anti_alias = anti_alias;
+        self
+    }
+
+    /// Sets the anti-aliasing quality. Lower qualities trade edge fidelity
+    /// for less work when flattening curves, which can be useful on
+    /// embedded targets. The default is
+    /// [Quality::High](enum.Quality.html#variant.High).
+    ///
+    /// ```rust
+    /// use zeno::{Mask, Quality};
+    ///
+    /// let path = "M0.3,0 L8.3,0 8.3,8 0.3,8 Z";
+    /// let (mask, _) = Mask::new(path).size(10, 8).quality(Quality::Low).render();
+    /// // The left edge is snapped to a quarter of a pixel.
+    /// assert_eq!(mask[0], 191);
+    /// ```
+    pub fn quality(&mut self, quality: Quality) -> &mut Self {
+        self.quality = quality;
+        self
+    }
+
+    /// Sets the gamma used to remap coverage during rasterization. Coverage
+    /// is raised to the power of the reciprocal of the gamma, so values
+    /// greater than 1 thicken anti-aliased edges, as is commonly desired for
+    /// light text on dark backgrounds, and values less than 1 thin them. The
+    /// default is 1.
+    ///
+    /// ```rust
+    /// use zeno::Mask;
+    ///
+    /// let path = "M0,0 L8,0.5 8,8 Z";
+    /// let (linear, _) = Mask::new(path).render();
+    /// let (corrected, _) = Mask::new(path).gamma(2.2).render();
+    /// assert!(linear.iter().zip(&corrected).all(|(a, b)| b >= a));
+    /// ```
+    pub fn gamma(&mut self, gamma: f32) -> &mut Self {
+        self.gamma = gamma.max(f32::EPSILON);
+        self
+    }
+
+    /// Sets the amount of contrast applied to coverage after gamma
+    /// correction. The coverage is blended toward a smoothstep curve by this
+    /// amount, which sharpens edges by pushing partial coverage toward fully
+    /// transparent or fully opaque. The value is clamped to the range 0 to 1.
+    /// The default is 0.
+    pub fn contrast(&mut self, contrast: f32) -> &mut Self {
+        self.contrast = contrast.clamp(0., 1.);
+        self
+    }
+
+    /// Sets a rectangle that restricts rasterization to a region of the
+    /// mask. The rectangle is specified in pixels relative to the corner of
+    /// the mask that corresponds to the origin and is expanded outward to
+    /// whole pixels. Edges that lie entirely above, below or to the right of
+    /// the rectangle are skipped and pixels outside of it are not written.
+    /// The size and placement of the mask are unaffected. When rendering
+    /// with [render_deltas_into](#method.render_deltas_into), only the
+    /// resolved pixels within the rectangle are meaningful. The default is
+    /// `None`.
+    ///
+    /// ```rust
+    /// use zeno::{Bounds, Mask};
+    ///
+    /// let (mask, _) = Mask::new("M0,0 16,0 16,16 0,16 Z")
+    ///     .size(16, 16)
+    ///     .clip(Some(Bounds::new([4., 4.].into(), [8., 8.].into())))
+    ///     .render();
+    /// assert_eq!(mask[4 * 16 + 4], 255);
+    /// assert_eq!(mask[8 * 16 + 8], 0);
+    /// assert_eq!(mask.iter().filter(|&&c| c != 0).count(), 16);
+    /// ```
+    pub fn clip(&mut self, clip: Option<Bounds>) -> &mut Self {
+        self.clip = clip;
+        self
+    }
+
+    /// Sets a path that clips the mask. The coverage of the clip path,
+    /// filled with the specified rule, multiplies the coverage of the path
+    /// during rasterization, similar to the SVG `clip-path` property. The
+    /// clip path is specified in the same coordinate space as the path and
+    /// is transformed along with it. Deltas produced by
+    /// [render_deltas_into](#method.render_deltas_into) are unaffected.
+    ///
+    /// ```rust
+    /// use zeno::{Fill, Mask};
+    ///
+    /// let (mask, _) = Mask::new("M0,0 16,0 16,16 0,16 Z")
+    ///     .size(16, 16)
+    ///     .clip_path("M0,0 8,0 8,16 0,16 Z", Fill::NonZero)
+    ///     .render();
+    /// assert_eq!(mask[4 * 16 + 4], 255);
+    /// assert_eq!(mask[4 * 16 + 12], 0);
+    /// ```
+    pub fn clip_path(&mut self, data: impl PathData, fill: Fill) -> &mut Self {
+        self.clip_path = Some((data.commands().collect(), fill));
+        self
+    }
+
+    /// Sets the operation used to combine the rendered coverage with the
+    /// existing contents of the buffer in [render_into](#method.render_into)
+    /// and [try_render_into](#method.try_render_into). The default is
+    /// [Composite::Replace](enum.Composite.html#variant.Replace).
+    ///
+    /// ```rust
+    /// use zeno::{Composite, Mask};
+    ///
+    /// let mut mask = [0u8; 16 * 16];
+    /// Mask::new("M0,0 16,0 16,16 0,16 Z").size(16, 16).render_into(&mut mask, None);
+    /// Mask::new("M4,4 12,4 12,12 4,12 Z")
+    ///     .size(16, 16)
+    ///     .composite(Composite::Subtract)
+    ///     .render_into(&mut mask, None);
+    /// assert_eq!(mask[0], 255);
+    /// assert_eq!(mask[8 * 16 + 8], 0);
+    /// ```
+    pub fn composite(&mut self, op: Composite) -> &mut Self {
+        self.composite = op;
+        self
+    }
+
+    /// Sets whether the coverage of the mask is inverted, producing full
+    /// coverage outside of the path and no coverage inside of it. Pixels
+    /// outside of the [clip](#method.clip) rectangle are not written. This
+    /// applies to [render](#method.render), [render_into](#method.render_into),
+    /// their fallible variants and [render_into_rows](#method.render_into_rows).
+    /// Other outputs such as spans, tiles and deltas describe the coverage of
+    /// the path and are unaffected. The default is false.
+    ///
+    /// ```rust
+    /// use zeno::Mask;
+    ///
+    /// let (mask, _) = Mask::new("M4,4 12,4 12,12 4,12 Z")
+    ///     .size(16, 16)
+    ///     .invert(true)
+    ///     .render();
+    /// assert_eq!(mask[0], 255);
+    /// assert_eq!(mask[8 * 16 + 8], 0);
+    /// ```
+    pub fn invert(&mut self, invert: bool) -> &mut Self {
+        self.invert = invert;
+        self
+    }
+
+    /// Sets a coverage threshold for trimming the edges of a mask with a
+    /// computed size. Rows and columns at the edges of the mask where every
+    /// value is below the threshold are removed and the placement is
+    /// adjusted accordingly. This only applies to masks rendered with
+    /// [render](#method.render) when an explicit size is not specified. The
+    /// default is 0, which disables trimming.
+    pub fn trim(&mut self, threshold: u8) -> &mut Self {
+        self.trim = threshold;
+        self
+    }
+
+    /// Sets a radius in pixels by which the coverage of the mask is dilated
+    /// after rasterization, replacing any previous [thin](#method.thin)
+    /// radius. Each pixel takes the maximum coverage within a square of the
+    /// specified radius, blending between adjacent whole radii for
+    /// fractional values. A computed size is enlarged to hold the expanded
+    /// coverage. This applies to [render](#method.render) and
+    /// [render_into](#method.render_into) along with their fallible variants
+    /// when compositing by replacement. The default is 0.
+    ///
+    /// ```rust
+    /// use zeno::Mask;
+    ///
+    /// let (mask, _) = Mask::new("M2,2 6,2 6,6 2,6 Z")
+    ///     .size(8, 8)
+    ///     .embolden(1.)
+    ///     .render();
+    /// assert_eq!(mask[8 + 1], 255);
+    /// assert_eq!(mask[0], 0);
+    /// ```
+    pub fn embolden(&mut self, radius: f32) -> &mut Self {
+        self.morphology = radius.max(0.);
+        self
+    }
+
+    /// Sets a radius in pixels by which the coverage of the mask is eroded
+    /// after rasterization, replacing any previous
+    /// [embolden](#method.embolden) radius. Each pixel takes the minimum
+    /// coverage within a square of the specified radius. This follows the
+    /// same rules as [embolden](#method.embolden) but never changes the
+    /// size of the mask.
+    ///
+    /// ```rust
+    /// use zeno::Mask;
+    ///
+    /// let (mask, _) = Mask::new("M2,2 6,2 6,6 2,6 Z")
+    ///     .size(8, 8)
+    ///     .thin(1.)
+    ///     .render();
+    /// assert_eq!(mask[2 * 8 + 2], 0);
+    /// assert_eq!(mask[3 * 8 + 3], 255);
+    /// ```
+    pub fn thin(&mut self, radius: f32) -> &mut Self {
+        self.morphology = -radius.max(0.);
+        self
+    }
+
+    /// Sets the number of samples taken along each axis of a pixel. When
+    /// greater than one, the mask is rasterized at the multiplied resolution
+    /// and the coverage of each pixel is the average of its samples. This
+    /// can reduce artifacts of the analytic coverage for tiny or heavily
+    /// skewed shapes at the cost of memory and time that grow with the
+    /// square of the factor. The placement is unaffected. This applies to
+    /// [render](#method.render) and [render_into](#method.render_into) along
+    /// with their fallible variants when compositing by replacement. The
+    /// default is 1.
+    ///
+    /// ```rust
+    /// use zeno::Mask;
+    ///
+    /// let (mask, _) = Mask::new("M0,0 L8,0 L0,8 Z")
+    ///     .size(8, 8)
+    ///     .anti_alias(false)
+    ///     .supersample(4)
+    ///     .render();
+    /// // Aliased samples are averaged into intermediate coverage.
+    /// assert_eq!(mask[3 * 8 + 4], 96);
+    /// ```
+    pub fn supersample(&mut self, factor: u32) -> &mut Self {
+        self.supersample = factor.max(1);
+        self
+    }
+
+    /// Sets a flag that is polled during rasterization to cancel rendering.
+    /// Once the flag is set, the remainder of the path is skipped and
+    /// [try_render](#method.try_render) and
+    /// [try_render_into](#method.try_render_into) return an error. The
+    /// infallible variants produce a partial result.
+    pub fn cancel(&mut self, flag: &'a AtomicBool) -> &mut Self {
+        self.cancel = Some(flag);
+        self
+    }
+
+    /// Sets the maximum amount of time that a single render may take before
+    /// it is cancelled. This follows the same rules as
+    /// [cancel](#method.cancel).
+    #[cfg(feature = "std")]
+    pub fn time_limit(&mut self, limit: Duration) -> &mut Self {
+        self.time_limit = Some(limit);
+        self
+    }
+
     /// Sets an explicit size for the mask. If left unspecified, the size will
     /// be computed from the bounding box of the path after applying any
     /// relevant style, offset and transform.
@@ -167,284 +899,3618 @@ where
     /// is primarily useful for rendering into tiled images such as texture
     /// atlases. If left unspecified, the buffer is assumed to be linear and
     /// tightly packed.
+    ///
+    /// To render into a sub-region of a larger image, pass the slice
+    /// beginning at the first byte of the region along with the pitch of the
+    /// image:
+    ///
+    /// ```rust
+    /// use zeno::Mask;
+    ///
+    /// // 64x64 atlas with the mask placed at (16, 8).
+    /// let mut atlas = vec![0u8; 64 * 64];
+    /// let placement = Mask::new("M0,0 10,0 10,10 0,10 Z")
+    ///     .size(10, 10)
+    ///     .render_into(&mut atlas[8 * 64 + 16..], Some(64));
+    /// assert_eq!(placement.width, 10);
+    /// assert_eq!(atlas[8 * 64 + 16], 255);
+    /// assert_eq!(atlas[17 * 64 + 25], 255);
+    /// assert_eq!(atlas[18 * 64 + 16], 0);
+    /// assert_eq!(atlas[8 * 64 + 26], 0);
+    /// ```
     pub fn render_into(&self, buffer: &mut [u8], pitch: Option<usize>) -> Placement {
         let (offset, placement) = self.placement();
         let pitch = match pitch {
             Some(pitch) => pitch,
-            _ => {
-                placement.width as usize
-                    * match self.format {
-                        Format::Alpha => 1,
-                        _ => 4,
-                    }
+            _ => self.format.row_size(placement.width),
+        };
+        let _ = if self.composite == Composite::Replace {
+            self.render_replace(offset, &placement, buffer, pitch, 0)
+        } else {
+            let mut target = Blend {
+                buffer,
+                pitch,
+                op: self.composite,
+            };
+            render(self, offset, &placement, &mut target, 0)
+        };
+        placement
+    }
+
+    /// Renders the mask into a buffer of 32-bit pixels, such as a
+    /// framebuffer, at the origin. The pitch is the number of pixels between
+    /// rows and defaults to the width of the mask.
+    ///
+    /// For the subpixel formats, the channels of each pixel are packed into
+    /// a value in the specified order, independent of the byte order of the
+    /// target. For [Alpha](enum.Format.html#variant.Alpha), the coverage is
+    /// written to all four channels of each pixel, producing premultiplied
+    /// white, and the alpha channel of the existing pixels is used as the
+    /// destination for compositing. For other formats, the bytes of each row
+    /// are written in memory order.
+    ///
+    /// ```rust
+    /// use zeno::{ChannelOrder, Format, Mask, SubpixelAlpha};
+    ///
+    /// let mut framebuffer = vec![0u32; 16 * 16];
+    /// Mask::new("M0,0 8,0 8,8 0,8 Z")
+    ///     .format(Format::Subpixel)
+    ///     .subpixel_alpha(SubpixelAlpha::Premultiplied)
+    ///     .size(8, 8)
+    ///     .render_into_u32(&mut framebuffer, Some(16), ChannelOrder::Argb);
+    /// assert_eq!(framebuffer[16 + 4], 0xFFFFFFFF);
+    /// assert_eq!(framebuffer[16 + 8], 0);
+    ///
+    /// let mut framebuffer = vec![0u32; 8 * 8];
+    /// Mask::new("M0,0 4,0 4,8 0,8 Z")
+    ///     .size(8, 8)
+    ///     .render_into_u32(&mut framebuffer, None, ChannelOrder::Rgba);
+    /// assert_eq!(framebuffer[3], 0xFFFFFFFF);
+    /// assert_eq!(framebuffer[4], 0);
+    /// ```
+    pub fn render_into_u32(
+        &self,
+        buffer: &mut [u32],
+        pitch: Option<usize>,
+        order: ChannelOrder,
+    ) -> Placement {
+        let (_, placement) = self.placement();
+        let alpha = self.format == Format::Alpha;
+        let pitch = match pitch {
+            Some(pitch) => pitch,
+            _ if alpha => placement.width as usize,
+            // Avoid div_ceil, which requires Rust 1.73.
+            #[allow(clippy::manual_div_ceil)]
+            _ => (self.format.row_size(placement.width) + 3) / 4,
+        };
+        if !alpha && subpixel_offsets(self.format).is_none() {
+            return self.render_into(as_bytes(buffer), Some(pitch * 4));
+        }
+        let (w, h) = (placement.width as usize, placement.height as usize);
+        let rows = |buffer: &mut [u32], f: &dyn Fn(&mut [u32])| {
+            for y in 0..h {
+                let start = (y * pitch).min(buffer.len());
+                let end = (start + w).min(buffer.len());
+                f(&mut buffer[start..end]);
             }
         };
-        render(self, offset, &placement, buffer, pitch);
+        if alpha {
+            // Gather the alpha channel of each pixel into the leading bytes
+            // of the row, which only overwrites pixels that were already
+            // read, and expand them in reverse after rendering.
+            rows(buffer, &|row| {
+                for x in 0..row.len() {
+                    let a = order.unpack(row[x])[3];
+                    as_bytes(row)[x] = a;
+                }
+            });
+            self.render_into(as_bytes(buffer), Some(pitch * 4));
+            rows(buffer, &|row| {
+                for x in (0..row.len()).rev() {
+                    let a = as_bytes(row)[x];
+                    row[x] = order.pack([a; 4]);
+                }
+            });
+        } else {
+            rows(buffer, &|row| {
+                for pixel in row {
+                    *pixel = u32::from_ne_bytes(order.unpack(*pixel));
+                }
+            });
+            self.render_into(as_bytes(buffer), Some(pitch * 4));
+            rows(buffer, &|row| {
+                for pixel in row {
+                    *pixel = order.pack(pixel.to_ne_bytes());
+                }
+            });
+        }
         placement
     }
 
     /// Renders the mask to a newly allocated buffer.
     pub fn render(&self) -> (Vec<u8>, Placement) {
         let mut buf = Vec::new();
-        let (offset, placement) = self.placement();
-        buf.resize(
-            self.format.buffer_size(placement.width, placement.height),
-            0,
-        );
-        let pitch = placement.width as usize
-            * match self.format {
-                Format::Alpha => 1,
-                _ => 4,
-            };
-        render(self, offset, &placement, &mut buf, pitch);
+        let placement = self.render_into_storage(&mut buf);
         (buf, placement)
     }
 
-    fn ensure_size(&mut self) {
-        if self.has_size {
-            return;
+    /// Renders the mask into a growable buffer such as a pooled or arena
+    /// allocated one. The storage is resized to hold the mask and cleared
+    /// before rendering, and resized again if the mask is trimmed.
+    ///
+    /// ```rust
+    /// use zeno::{Mask, MaskStorage};
+    ///
+    /// struct Slot {
+    ///     data: [u8; 256],
+    ///     len: usize,
+    /// }
+    ///
+    /// impl MaskStorage for Slot {
+    ///     fn resize(&mut self, len: usize) {
+    ///         assert!(len <= self.data.len());
+    ///         self.len = len;
+    ///     }
+    ///
+    ///     fn as_mut_slice(&mut self) -> &mut [u8] {
+    ///         &mut self.data[..self.len]
+    ///     }
+    /// }
+    ///
+    /// let mut slot = Slot { data: [0; 256], len: 0 };
+    /// let placement = Mask::new("M0,0 8,0 8,8 0,8 Z").render_into_storage(&mut slot);
+    /// assert_eq!(slot.len, 10 * 8);
+    /// assert_eq!(slot.as_mut_slice()[1], 255);
+    /// ```
+    pub fn render_into_storage<S>(&self, storage: &mut S) -> Placement
+    where
+        S: MaskStorage + ?Sized,
+    {
+        let (offset, placement) = self.placement();
+        storage.resize(self.format.buffer_size(placement.width, placement.height));
+        let buf = storage.as_mut_slice();
+        buf.fill(0);
+        let pitch = self.format.row_size(placement.width);
+        let _ = self.render_replace(offset, &placement, buf, pitch, 0);
+        self.trimmed(storage, placement)
+    }
+
+    /// Composites a solid color over a buffer of premultiplied RGBA pixels
+    /// using the rendered coverage, with the source-over operator. The color
+    /// is specified as non-premultiplied RGBA. The pitch is the number of
+    /// bytes between rows of the buffer and defaults to four bytes per pixel
+    /// of the mask.
+    ///
+    /// Coverage is always resolved to a single 8-bit value per pixel, so
+    /// the format and composite operation are ignored.
+    ///
+    /// ```rust
+    /// use zeno::Mask;
+    ///
+    /// let mut pixels = [0u8; 8 * 8 * 4];
+    /// Mask::new("M0,0 4,0 4,8 0,8 Z")
+    ///     .size(8, 8)
+    ///     .render_color_into(&mut pixels, None, [255, 0, 0, 255]);
+    /// assert_eq!(&pixels[..4], &[255, 0, 0, 255]);
+    /// assert_eq!(&pixels[16..20], &[0, 0, 0, 0]);
+    /// ```
+    pub fn render_color_into(
+        &self,
+        buffer: &mut [u8],
+        pitch: Option<usize>,
+        color: [u8; 4],
+    ) -> Placement {
+        let (offset, placement) = self.placement();
+        let pitch = pitch.unwrap_or(placement.width as usize * 4);
+        let a = color[3] as u32;
+        let premultiply = |c: u8| (c as u32 * a + 127) / 255;
+        let mut target = Paint {
+            buffer,
+            pitch,
+            color: [
+                premultiply(color[0]),
+                premultiply(color[1]),
+                premultiply(color[2]),
+                a,
+            ],
+            shader: None,
+        };
+        let _ = render(self, offset, &placement, &mut target, 0);
+        placement
+    }
+
+    /// Composites colors produced by a shader over a buffer of premultiplied
+    /// RGBA pixels using the rendered coverage, with the source-over
+    /// operator. See [render_color_into](#method.render_color_into) for a
+    /// description of the pitch.
+    ///
+    /// The shader is invoked for each span of pixels with uniform, non-zero
+    /// coverage with the column and row of the first pixel, relative to the
+    /// mask, and a slice that receives the non-premultiplied RGBA color of
+    /// each pixel in the span. This is suitable for gradients and patterns,
+    /// which can map pixels to user space with the placement of the mask.
+    ///
+    /// ```rust
+    /// use zeno::Mask;
+    ///
+    /// // Horizontal gradient from black to white.
+    /// let mut pixels = [0u8; 8 * 8 * 4];
+    /// Mask::new("M0,0 8,0 8,8 0,8 Z")
+    ///     .size(8, 8)
+    ///     .render_shaded_into(&mut pixels, None, |x, _, colors| {
+    ///         for (i, color) in colors.iter_mut().enumerate() {
+    ///             let v = ((x as usize + i) * 255 / 7) as u8;
+    ///             *color = [v, v, v, 255];
+    ///         }
+    ///     });
+    /// assert_eq!(&pixels[..4], &[0, 0, 0, 255]);
+    /// assert_eq!(&pixels[7 * 4..8 * 4], &[255, 255, 255, 255]);
+    /// ```
+    pub fn render_shaded_into(
+        &self,
+        buffer: &mut [u8],
+        pitch: Option<usize>,
+        mut shader: impl FnMut(u32, u32, &mut [[u8; 4]]),
+    ) -> Placement {
+        let (offset, placement) = self.placement();
+        let pitch = pitch.unwrap_or(placement.width as usize * 4);
+        let mut target = Paint {
+            buffer,
+            pitch,
+            color: [0; 4],
+            shader: Some(&mut shader),
+        };
+        let _ = render(self, offset, &placement, &mut target, 0);
+        placement
+    }
+
+    /// Renders the mask into a byte buffer, returning an error if the path
+    /// data is invalid, the mask is too large, the buffer is too small to hold
+    /// it, or rendering would exceed the memory limit of the associated
+    /// scratch context or was cancelled. See [render_into](#method.render_into)
+    /// for a description of the pitch.
+    ///
+    /// ```rust
+    /// use zeno::{Mask, RenderError};
+    ///
+    /// let mut buf = [0u8; 16];
+    /// let result = Mask::new("M0,0 8,0 8,8 0,8 Z").try_render_into(&mut buf, None);
+    /// assert_eq!(result.unwrap_err(), RenderError::BufferTooSmall);
+    /// ```
+    pub fn try_render_into(
+        &self,
+        buffer: &mut [u8],
+        pitch: Option<usize>,
+    ) -> Result<Placement, RenderError> {
+        let (offset, placement) = self.placement();
+        self.check(offset, &placement)?;
+        let row_size = self.format.row_size(placement.width);
+        let pitch = pitch.unwrap_or(row_size);
+        if placement.height != 0
+            && (pitch < row_size
+                || buffer.len() < pitch * (placement.height as usize - 1) + row_size)
+        {
+            return Err(RenderError::BufferTooSmall);
+        }
+        if self.composite == Composite::Replace {
+            self.render_replace(offset, &placement, buffer, pitch, 0)?;
+        } else {
+            let mut target = Blend {
+                buffer,
+                pitch,
+                op: self.composite,
+            };
+            render(self, offset, &placement, &mut target, 0)?;
         }
+        Ok(placement)
+    }
+
+    /// Renders the mask to a newly allocated buffer, returning an error if the
+    /// path data is invalid, the mask is too large, or rendering would exceed
+    /// the memory limit of the associated scratch context or was cancelled.
+    /// The size of the resulting buffer counts against the limit.
+    ///
+    /// ```rust
+    /// use zeno::{Mask, RenderError};
+    ///
+    /// let result = Mask::new("M0,0 L100000,0 L0,10 Z").try_render();
+    /// assert_eq!(result.unwrap_err(), RenderError::TooLarge);
+    ///
+    /// let result = Mask::new("M0,0 L10,0 L0,10 Z")
+    ///     .offset((f32::NAN, 0.))
+    ///     .try_render();
+    /// assert_eq!(result.unwrap_err(), RenderError::InvalidPath);
+    /// ```
+    pub fn try_render(&self) -> Result<(Vec<u8>, Placement), RenderError> {
         let (offset, placement) = self.placement();
-        self.bounds_offset = offset;
-        self.width = placement.width;
-        self.height = placement.height;
-        self.explicit_size = false;
-        self.has_size = true;
+        self.check(offset, &placement)?;
+        let size = self.format.buffer_size(placement.width, placement.height);
+        if let Some(scratch) = self.scratch.borrow().as_ref() {
+            if !scratch.budget().take(size) {
+                return Err(RenderError::LimitExceeded);
+            }
+        }
+        let mut buf = alloc::vec![0; size];
+        let pitch = self.format.row_size(placement.width);
+        self.render_replace(offset, &placement, &mut buf, pitch, size)?;
+        let placement = self.trimmed(&mut buf, placement);
+        Ok((buf, placement))
     }
 
-    fn placement(&self) -> (Vector, Placement) {
-        let mut placement = Placement {
-            left: 0,
-            top: 0,
-            width: self.width,
-            height: self.height,
+    /// Renders the mask into a byte buffer and returns statistics describing
+    /// the coverage, gathered while rasterizing. See
+    /// [render_into](#method.render_into) for a description of the pitch.
+    ///
+    /// Pixels that are not covered by the mask are assumed to be zero.
+    pub fn render_into_with_stats(
+        &self,
+        buffer: &mut [u8],
+        pitch: Option<usize>,
+    ) -> (Placement, RenderStats) {
+        let (offset, placement) = self.placement();
+        let pitch = match pitch {
+            Some(pitch) => pitch,
+            _ => self.format.row_size(placement.width),
         };
-        let mut offset = self.offset;
-        if self.explicit_size {
-            return (offset, placement);
-        } else if !self.has_size {
-            let mut scratch = self.scratch.borrow_mut();
-            let mut bounds = if let Some(scratch) = scratch.as_mut() {
-                scratch.bounds(&self.data, self.style, self.transform)
+        let mut target = StatsBuffer::new(buffer, pitch);
+        let _ = render(self, offset, &placement, &mut target, 0);
+        (placement, target.finish(placement.width, placement.height))
+    }
+
+    /// Renders the mask to a newly allocated buffer and returns statistics
+    /// describing the coverage, gathered while rasterizing.
+    ///
+    /// When trimming is enabled, the ink bounds are relative to the trimmed
+    /// mask while the counts continue to include any pixels that were trimmed
+    /// away for falling below the threshold.
+    ///
+    /// ```rust
+    /// use zeno::Mask;
+    ///
+    /// let (_, _, stats) = Mask::new("M2,2 6,2 6,6 2,6 Z")
+    ///     .size(8, 8)
+    ///     .render_with_stats();
+    /// assert_eq!(stats.nonzero, 16);
+    /// assert_eq!(stats.histogram[255], 16);
+    /// assert_eq!(stats.histogram[0], 48);
+    /// assert_eq!((stats.ink_x, stats.ink_y), (2, 2));
+    /// assert_eq!((stats.ink_width, stats.ink_height), (4, 4));
+    /// ```
+    pub fn render_with_stats(&self) -> (Vec<u8>, Placement, RenderStats) {
+        let (offset, placement) = self.placement();
+        let mut buf = alloc::vec![0; self.format.buffer_size(placement.width, placement.height)];
+        let pitch = self.format.row_size(placement.width);
+        let mut target = StatsBuffer::new(&mut buf, pitch);
+        let _ = render(self, offset, &placement, &mut target, 0);
+        let mut stats = target.finish(placement.width, placement.height);
+        let trimmed = self.trimmed(&mut buf, placement);
+        if stats.ink_width != 0 {
+            // Move the ink bounds into the trimmed mask.
+            let dx = (trimmed.left - placement.left) as u32;
+            let mut dy = (trimmed.top - placement.top).unsigned_abs();
+            if self.flip_y && trimmed.height != 0 {
+                // Rows removed from the bottom of the mask precede it in the
+                // buffer.
+                dy = placement.height - trimmed.height - dy;
+            }
+            let x0 = stats.ink_x.max(dx) - dx;
+            let y0 = stats.ink_y.max(dy) - dy;
+            let x1 = (stats.ink_x + stats.ink_width).min(dx + trimmed.width);
+            let y1 = (stats.ink_y + stats.ink_height).min(dy + trimmed.height);
+            if x1 > dx + x0 && y1 > dy + y0 {
+                stats.ink_x = x0;
+                stats.ink_y = y0;
+                stats.ink_width = x1 - dx - x0;
+                stats.ink_height = y1 - dy - y0;
+            } else {
+                stats.ink_x = 0;
+                stats.ink_y = 0;
+                stats.ink_width = 0;
+                stats.ink_height = 0;
+            }
+        }
+        (buf, trimmed, stats)
+    }
+
+    /// Renders a chain of masks where the first is rendered at full size and
+    /// each subsequent level is half the size of the previous one.
+    ///
+    /// Levels are produced by filtering the previous level, avoiding any
+    /// further geometry work. Once the larger dimension of a level would fall
+    /// below `native_below` pixels, it is instead rendered directly from the
+    /// path at the reduced scale, which preserves detail in small masks.
+    ///
+    /// The placement of each level is expressed in the coordinate space of
+    /// that level.
+    ///
+    /// ```rust
+    /// use zeno::Mask;
+    ///
+    /// let mips = Mask::new("M0,0 64,0 64,64 0,64 Z").size(64, 64).render_mips(3, 0);
+    /// assert_eq!(mips.len(), 4);
+    /// assert_eq!(mips[3].1.width, 8);
+    /// assert!(mips[3].0.iter().all(|&c| c == 255));
+    /// ```
+    pub fn render_mips(&self, levels: usize, native_below: u32) -> Vec<(Vec<u8>, Placement)> {
+        let y_up = self.origin == Origin::BottomLeft;
+        let flipped = self.flip_y;
+        let mut mips = Vec::with_capacity(levels + 1);
+        mips.push(self.render());
+        for level in 1..=levels {
+            let (buf, placement) = &mips[level - 1];
+            let mip = if placement.width.max(placement.height) / 2 < native_below {
+                self.render_scaled(1. / (1u32 << level.min(31)) as f32)
             } else {
-                super::bounds(&self.data, self.style, self.transform)
+                downsample(buf, placement, self.format, y_up, flipped)
             };
-            bounds.min = (bounds.min + self.offset).floor();
-            bounds.max = (bounds.max + self.offset).ceil();
-            offset = Vector::new(-bounds.min.x + 1., -bounds.min.y);
-            placement.width = bounds.width() as u32 + 2;
-            placement.height = bounds.height() as u32;
-        } else {
-            offset = self.bounds_offset;
+            mips.push(mip);
         }
-        placement.left = -offset.x as i32;
-        placement.top = if self.origin == Origin::BottomLeft {
-            (-offset.y).floor() + self.height as f32
-        } else {
-            -offset.y
-        } as i32;
-        (offset, placement)
+        mips
     }
-}
 
-pub fn render<'a, 'c, D>(
-    mask: &'a Mask<'a, 'c, D>,
-    offset: Vector,
-    placement: &Placement,
-    buf: &mut [u8],
-    pitch: usize,
-) where
-    D: PathData,
-{
-    let y_up = mask.origin == Origin::BottomLeft;
-    let (is_subpx, subpx) = match mask.format {
-        Format::Alpha => (false, [Vector::ZERO; 3]),
-        Format::Subpixel => (
-            true,
-            [Vector::new(-0.3, 0.), Vector::ZERO, Vector::new(0.3, 0.)],
-        ),
-        Format::CustomSubpixel(subpx) => (
-            true,
-            [
-                Vector::new(subpx[0], 0.),
-                Vector::new(subpx[1], 0.),
-                Vector::new(subpx[2], 0.),
-            ],
-        ),
-    };
-    let fill = match mask.style {
-        Style::Fill(fill) => fill,
-        _ => Fill::NonZero,
-    };
-    let w = placement.width;
-    let h = placement.height;
-    let shift = offset + mask.render_offset;
-    let data = &mask.data;
-    let style = mask.style;
-    let transform = mask.transform;
-    let mut scratch = mask.scratch.borrow_mut();
-    use super::raster::{AdaptiveStorage, Rasterizer};
-    if let Some(scratch) = scratch.as_mut() {
-        let mut ras = Rasterizer::new(&mut scratch.render);
-        let inner = &mut scratch.inner;
-        if is_subpx {
-            ras.rasterize_write(
-                shift + subpx[0],
-                w,
-                h,
-                &mut |r| {
-                    inner.apply(data.clone(), &style, transform, r);
-                },
-                fill,
-                pitch,
-                y_up,
-                &mut |row_offset, x, count, coverage| {
-                    let buf = &mut buf[row_offset..];
-                    let mut i = 0;
-                    let mut j = x * 4;
-                    while i < count {
-                        buf[j] = coverage;
-                        i += 1;
-                        j += 4;
-                    }
-                },
-            );
-            ras.rasterize_write(
-                shift + subpx[1],
-                w,
-                h,
-                &mut |r| {
-                    inner.apply(data, &style, transform, r);
-                },
-                fill,
-                pitch,
-                y_up,
-                &mut |row_offset, x, count, coverage| {
-                    let buf = &mut buf[row_offset..];
-                    let mut i = 0;
-                    let mut j = x * 4 + 1;
-                    while i < count {
-                        buf[j] = coverage;
-                        i += 1;
-                        j += 4;
-                    }
-                },
-            );
-            ras.rasterize_write(
-                shift + subpx[2],
-                w,
-                h,
-                &mut |r| {
-                    inner.apply(data, &style, transform, r);
-                },
-                fill,
-                pitch,
-                y_up,
-                &mut |row_offset, x, count, coverage| {
-                    let buf = &mut buf[row_offset..];
-                    let mut i = 0;
-                    let mut j = x * 4 + 2;
-                    while i < count {
-                        buf[j] = coverage;
-                        i += 1;
-                        j += 4;
-                    }
-                },
+    /// Renders the mask with all geometry uniformly scaled.
+    fn render_scaled(&self, scale: f32) -> (Vec<u8>, Placement) {
+        let mut scratch = self.scratch.borrow_mut();
+        let mut mask = match scratch.as_mut() {
+            Some(scratch) => Mask::with_scratch(&self.data, scratch),
+            None => Mask::new(&self.data),
+        };
+        self.inherit(&mut mask);
+        mask.style = self.style;
+        mask.transform = self.transform;
+        mask.pre_transform = self.pre_transform;
+        mask.post_transform = combine(self.post_transform, Some(Transform::scale(scale, scale)));
+        mask.offset = self.offset * scale;
+        mask.render_offset = self.render_offset * scale;
+        mask.clip = self
+            .clip
+            .map(|clip| Bounds::new(clip.min * scale, clip.max * scale));
+        if self.explicit_size {
+            mask.size(
+                (self.width as f32 * scale).ceil() as u32,
+                (self.height as f32 * scale).ceil() as u32,
             );
+        }
+        mask.render()
+    }
+
+    /// Creates a mask that renders the styled path with the same options
+    /// and placement as this mask.
+    fn styled_mask<'p, 't>(
+        &self,
+        path: &'p [Command],
+        fill: Fill,
+        offset: Vector,
+        placement: &Placement,
+        scratch: Option<&'t mut Scratch>,
+    ) -> Mask<'a, 't, &'p [Command]> {
+        let mut mask = match scratch {
+            Some(scratch) => Mask::with_scratch(path, scratch),
+            None => Mask::new(path),
+        };
+        self.inherit(&mut mask);
+        // The styled path is already transformed, so the clip path must be
+        // as well.
+        if let (Some((clip_path, _)), Some(transform)) =
+            (&mut mask.clip_path, self.full_transform())
+        {
+            for cmd in clip_path.iter_mut() {
+                *cmd = cmd.transform(&transform);
+            }
+        }
+        if let Style::Stroke(_) = self.style {
+            // The fill rules apply to the subpaths of the original path and
+            // not to those of the stroke outline.
+            mask.fill_rules = None;
+        }
+        mask.style = Style::Fill(fill);
+        mask.width = placement.width;
+        mask.height = placement.height;
+        mask.explicit_size = self.explicit_size;
+        mask.has_size = true;
+        mask.bounds_offset = offset;
+        mask.bounds_position = (placement.left, placement.top);
+        mask.bounds_fract = placement.fract;
+        mask
+    }
+
+    /// Returns the clip rectangle for a band of rows, intersected with the
+    /// clip of this mask.
+    fn band_clip(&self, top: u32, rows: u32, placement: &Placement) -> Bounds {
+        let (width, height) = (placement.width, placement.height);
+        // The clip is specified relative to the origin of the mask.
+        let (y0, y1) = if self.rows_reversed() {
+            (height - top - rows, height - top)
         } else {
-            ras.rasterize(
-                shift,
-                w,
-                h,
-                &mut |r| {
-                    inner.apply(data, &style, transform, r);
-                },
-                fill,
-                buf,
-                pitch,
-                y_up,
-            );
+            (top, top + rows)
+        };
+        let mut clip = Bounds::new(
+            Point::new(0., y0 as f32),
+            Point::new(width as f32, y1 as f32),
+        );
+        if let Some(outer) = self.clip {
+            clip.min = Point::new(clip.min.x.max(outer.min.x), clip.min.y.max(outer.min.y));
+            clip.max = Point::new(clip.max.x.min(outer.max.x), clip.max.y.min(outer.max.y));
         }
-    } else {
-        let mut storage = AdaptiveStorage::new();
-        let mut ras = Rasterizer::new(&mut storage);
-        if is_subpx {
-            ras.rasterize_write(
-                shift + subpx[0],
-                w,
-                h,
-                &mut |r| {
-                    apply(data, style, transform, r);
+        clip
+    }
+
+    /// Applies the style and transforms to the path data, returning the
+    /// resulting path and the fill rule that should be used to render it.
+    fn styled(&self) -> (Vec<Command>, Fill) {
+        let (style, pre, transform) = self.transforms();
+        let mut path = Vec::new();
+        let fill = match pre {
+            Some(pre) => apply(
+                Transformed {
+                    data: &self.data,
+                    transform: pre,
                 },
-                fill,
-                pitch,
-                y_up,
-                &mut |row_offset, x, count, coverage| {
-                    let buf = &mut buf[row_offset..];
-                    let mut i = 0;
-                    let mut j = x * 4;
-                    while i < count {
-                        buf[j] = coverage;
-                        i += 1;
-                        j += 4;
+                style,
+                transform,
+                &mut path,
+            ),
+            None => apply(&self.data, style, transform, &mut path),
+        };
+        (path, fill)
+    }
+
+    /// Copies the rendering options, excluding the style and transforms, to
+    /// another mask.
+    fn inherit<E>(&self, mask: &mut Mask<'a, '_, E>) {
+        mask.format = self.format;
+        mask.subpixel_alpha = self.subpixel_alpha;
+        mask.lcd_filter = self.lcd_filter;
+        mask.origin = self.origin;
+        mask.flip_y = self.flip_y;
+        mask.rounding = self.rounding;
+        mask.offset = self.offset;
+        mask.render_offset = self.render_offset;
+        mask.hint = self.hint;
+        mask.anti_alias = self.anti_alias;
+        mask.quality = self.quality;
+        mask.gamma = self.gamma;
+        mask.contrast = self.contrast;
+        mask.clip = self.clip;
+        mask.clip_path = self.clip_path.clone();
+        mask.fill_rules = self.fill_rules;
+        mask.composite = self.composite;
+        mask.invert = self.invert;
+        mask.trim = self.trim;
+        mask.cancel = self.cancel;
+        #[cfg(feature = "std")]
+        {
+            mask.time_limit = self.time_limit;
+        }
+    }
+
+    /// Renders the mask at the specified number of evenly spaced horizontal
+    /// subpixel offsets, returning one mask for each offset in increasing
+    /// order. The offset of variant `i` is `i / count` pixels.
+    ///
+    /// The styled and transformed outline is evaluated once and shared by
+    /// all variants. The placement is the same for each variant, except
+    /// where it is adjusted by trimming, since the mask is padded to
+    /// accommodate subpixel offsets.
+    ///
+    /// ```rust
+    /// use zeno::Mask;
+    ///
+    /// let variants = Mask::new("M0,0 4,0 4,4 0,4 Z").render_subpixel_variants(4);
+    /// assert_eq!(variants.len(), 4);
+    /// let (half, _) = &variants[2];
+    /// assert_eq!(&half[..6], &[0, 127, 255, 255, 255, 127]);
+    /// ```
+    pub fn render_subpixel_variants(&self, count: usize) -> Vec<(Vec<u8>, Placement)> {
+        let count = count.max(1);
+        let (offset, placement) = self.placement();
+        let (path, fill) = self.styled();
+        let mut scratch = self.scratch.borrow_mut();
+        (0..count)
+            .map(|i| {
+                let scratch = scratch.as_mut().map(|scratch| &mut **scratch);
+                let mut mask = self.styled_mask(&path, fill, offset, &placement, scratch);
+                mask.render_offset.x += i as f32 / count as f32;
+                mask.render()
+            })
+            .collect()
+    }
+
+    /// Rasterizes the mask and adds the signed coverage delta of each pixel
+    /// to the specified buffer rather than writing resolved coverage. See
+    /// [render_into](#method.render_into) for a description of the pitch,
+    /// which is measured in elements of the buffer.
+    ///
+    /// The prefix sum of the deltas along each row yields the accumulated
+    /// coverage of each pixel, where a fully covered pixel has a magnitude of
+    /// 2<sup>17</sup> and the sign depends on the direction of the path. Since
+    /// the deltas are additive, multiple paths may be rendered into the same
+    /// buffer and resolved once with [resolve_deltas](fn.resolve_deltas.html).
+    /// The format and fill rule of the mask are ignored; the fill rule is
+    /// applied when resolving.
+    ///
+    /// ```rust
+    /// use zeno::{resolve_deltas, Fill, Mask};
+    ///
+    /// let mut deltas = [0i32; 16 * 16];
+    /// Mask::new("M0,0 8,0 8,16 0,16 Z").size(16, 16).render_deltas_into(&mut deltas, None);
+    /// Mask::new("M8,0 16,0 16,16 8,16 Z").size(16, 16).render_deltas_into(&mut deltas, None);
+    /// let mut mask = [0u8; 16 * 16];
+    /// resolve_deltas(&deltas, 16, 16, Fill::NonZero, &mut mask);
+    /// assert!(mask.iter().all(|&c| c == 255));
+    /// ```
+    pub fn render_deltas_into(&self, deltas: &mut [i32], pitch: Option<usize>) -> Placement {
+        let (offset, placement) = self.placement();
+        let pitch = pitch.unwrap_or(placement.width as usize);
+        let _ = render(self, offset, &placement, &mut Deltas { deltas, pitch }, 0);
+        placement
+    }
+
+    /// Renders the mask into a buffer, replacing the existing coverage, and
+    /// applies supersampling and morphology.
+    fn render_replace(
+        &self,
+        offset: Vector,
+        placement: &Placement,
+        buffer: &mut [u8],
+        pitch: usize,
+        reserved: usize,
+    ) -> Result<(), RenderError> {
+        let result = if self.supersample > 1 {
+            self.render_supersampled(offset, placement, buffer, pitch)
+        } else {
+            render(
+                self,
+                offset,
+                placement,
+                &mut Buffer { buffer, pitch },
+                reserved,
+            )
+        };
+        self.morph(buffer, pitch, placement);
+        result
+    }
+
+    /// Renders the mask at a multiple of its resolution and writes the
+    /// average coverage of the samples of each pixel to the buffer.
+    fn render_supersampled(
+        &self,
+        offset: Vector,
+        placement: &Placement,
+        buffer: &mut [u8],
+        pitch: usize,
+    ) -> Result<(), RenderError> {
+        let factor = self.supersample as usize;
+        let scale = factor as f32;
+        let (width, height) = (placement.width as usize, placement.height as usize);
+        let (sample_width, sample_height) = (width * factor, height * factor);
+        let mut samples = alloc::vec![0u8; sample_width * sample_height * 2];
+        let result = {
+            let mut scratch = self.scratch.borrow_mut();
+            let mut mask = match scratch.as_mut() {
+                Some(scratch) => Mask::with_scratch(&self.data, scratch),
+                None => Mask::new(&self.data),
+            };
+            self.inherit(&mut mask);
+            // Subpixel channels are sampled from the same coverage below and
+            // are filtered once the samples are averaged.
+            mask.format = Format::Alpha16;
+            mask.subpixel_alpha = SubpixelAlpha::None;
+            mask.lcd_filter = LcdFilter::None;
+            mask.style = self.style;
+            mask.transform = self.transform;
+            mask.pre_transform = self.pre_transform;
+            mask.post_transform =
+                combine(self.post_transform, Some(Transform::scale(scale, scale)));
+            mask.offset = offset * scale;
+            mask.render_offset = self.render_offset * scale;
+            mask.clip = self
+                .clip
+                .map(|clip| Bounds::new(clip.min * scale, clip.max * scale));
+            mask.size(sample_width as u32, sample_height as u32);
+            let (offset, placement) = mask.placement();
+            let mut target = Buffer {
+                buffer: &mut samples,
+                pitch: sample_width * 2,
+            };
+            render(&mask, offset, &placement, &mut target, 0)
+        };
+        let passes = Passes {
+            shift: Vector::ZERO,
+            subpx: subpixel_offsets(self.format),
+            wide: Wide::from_format(self.format),
+            packed: self.format == Format::Packed1,
+            clip: self.clip,
+            width: placement.width,
+            height: placement.height,
+            fill: Fill::NonZero,
+            y_up: self.rows_reversed(),
+            invert: false,
+            alpha: self.subpixel_alpha,
+            lcd_filter: self.lcd_filter,
+        };
+        // Averages the samples within the square of one pixel in size at
+        // the specified position, which is fractional for subpixel channels.
+        // Positions outside of the clip region take the nearest sample
+        // within it since the coverage there is never computed.
+        let [x0, _, x1, _] = clip_rect(self.clip, placement.width, placement.height);
+        let rows = passes.rows();
+        let (columns, rows) = (
+            x0 * factor..x1 * factor,
+            rows.start * factor..rows.end * factor,
+        );
+        let average = |x: f32, y: f32| {
+            let (x, y) = (x * scale, y * scale);
+            let mut sum = 0.;
+            for j in y.floor() as isize..(y + scale).ceil() as isize {
+                let wy = (y + scale).min(j as f32 + 1.) - y.max(j as f32);
+                let j = (j.max(rows.start as isize) as usize).min(rows.end - 1);
+                let row = &samples[j * sample_width * 2..];
+                for i in x.floor() as isize..(x + scale).ceil() as isize {
+                    let wx = (x + scale).min(i as f32 + 1.) - x.max(i as f32);
+                    let i = (i.max(columns.start as isize) as usize).min(columns.end - 1);
+                    sum += wx * wy * Wide::U16.read(&row[i * 2..]) as f32;
+                }
+            }
+            (sum / (scale * scale) + 0.5) as u16
+        };
+        // Vertical channel offsets are reversed along with the rows.
+        let flip = if passes.y_up { -1. } else { 1. };
+        for y in passes.rows() {
+            let row = &mut buffer[y * pitch..];
+            for x in x0..x1 {
+                if let Some(subpx) = passes.subpx {
+                    for (channel, offset) in subpx.iter().enumerate() {
+                        let c = average(x as f32 - offset.x, y as f32 - offset.y * flip);
+                        row[x * 4 + channel] = ((c as u32 + 128) / 257) as u8;
+                    }
+                    continue;
+                }
+                let c = average(x as f32, y as f32);
+                if let Some(wide) = passes.wide {
+                    wide.fill(&mut row[x * wide.size()..(x + 1) * wide.size()], c);
+                } else if passes.packed {
+                    write_bits(row, x, 1, c >= 128 * 257);
+                } else {
+                    row[x] = ((c as u32 + 128) / 257) as u8;
+                }
+            }
+            if passes.finishes_subpixel() {
+                passes.finish_subpixel(row);
+            }
+        }
+        result
+    }
+
+    fn morph(&self, buffer: &mut [u8], pitch: usize, placement: &Placement) {
+        if self.morphology != 0. {
+            morph(
+                buffer,
+                pitch,
+                placement.width as usize,
+                placement.height as usize,
+                self.format,
+                self.morphology,
+            );
+        }
+    }
+
+    fn trimmed<S>(&self, buf: &mut S, placement: Placement) -> Placement
+    where
+        S: MaskStorage + ?Sized,
+    {
+        if self.trim == 0 || self.explicit_size {
+            return placement;
+        }
+        trim(
+            buf,
+            placement,
+            self.format,
+            self.trim,
+            self.origin == Origin::BottomLeft,
+            self.flip_y,
+        )
+    }
+
+    /// Rasterizes the mask and invokes the specified closure for each span
+    /// of pixels with non-zero coverage rather than writing to a buffer. The
+    /// arguments are the row, the first column and one past the last column
+    /// of the span, relative to the mask, and the 8-bit coverage of every
+    /// pixel in the span. Rows are numbered in the same order as the rows
+    /// of a rendered buffer.
+    ///
+    /// The format is ignored and coverage is always computed as alpha.
+    ///
+    /// ```rust
+    /// use zeno::Mask;
+    ///
+    /// let mut covered = 0;
+    /// Mask::new("M2,2 14,2 14,14 2,14 Z")
+    ///     .size(16, 16)
+    ///     .render_spans(|_, x0, x1, coverage| {
+    ///         assert_eq!(coverage, 255);
+    ///         covered += x1 - x0;
+    ///     });
+    /// assert_eq!(covered, 144);
+    /// ```
+    pub fn render_spans(&self, mut f: impl FnMut(u32, u32, u32, u8)) -> Placement {
+        let (offset, placement) = self.placement();
+        let _ = render(self, offset, &placement, &mut Spans(&mut f), 0);
+        placement
+    }
+
+    /// Renders the mask as a list of run-length encoded spans of non-zero
+    /// coverage. Spans are ordered by column within each row and rows are
+    /// ordered from the top of the mask with the top left origin or from the
+    /// bottom otherwise. Adjacent pixels with equal coverage are merged into a
+    /// single span.
+    /// See [render_spans](#method.render_spans) for the coordinate space.
+    ///
+    /// ```rust
+    /// use zeno::{Mask, Span};
+    ///
+    /// let (spans, _) = Mask::new("M2,2 14,2 14,14 2,14 Z").size(16, 16).render_rle();
+    /// assert_eq!(spans.len(), 12);
+    /// assert_eq!(spans[0], Span { y: 2, x: 2, len: 12, alpha: 255 });
+    /// ```
+    pub fn render_rle(&self) -> (Vec<Span>, Placement) {
+        let mut spans: Vec<Span> = Vec::new();
+        let placement = self.render_spans(|y, x0, x1, alpha| {
+            if let Some(last) = spans.last_mut() {
+                if last.y == y && last.x + last.len == x0 && last.alpha == alpha {
+                    last.len += x1 - x0;
+                    return;
+                }
+            }
+            spans.push(Span {
+                y,
+                x: x0,
+                len: x1 - x0,
+                alpha,
+            });
+        });
+        (spans, placement)
+    }
+
+    /// Renders the mask as a grid of square tiles of the specified size,
+    /// suitable for uploading to a tile atlas. Tiles that are entirely empty
+    /// or entirely covered are classified without storing any coverage, and
+    /// only partially covered tiles are stored. The format is ignored and
+    /// coverage is always computed as 8-bit alpha.
+    ///
+    /// ```rust
+    /// use zeno::{Mask, Tile};
+    ///
+    /// let (tiles, _) = Mask::new("M0,0 16,0 16,16 0,16 Z")
+    ///     .size(32, 20)
+    ///     .render_tiles(8);
+    /// assert_eq!((tiles.columns, tiles.rows), (4, 3));
+    /// assert_eq!(tiles.tile(0, 0), Tile::Solid);
+    /// assert_eq!(tiles.tile(3, 0), Tile::Empty);
+    /// assert_eq!(tiles.tile(1, 2), Tile::Empty);
+    /// assert!(tiles.masks.is_empty());
+    /// ```
+    pub fn render_tiles(&self, tile_size: u32) -> (Tiles, Placement) {
+        let size = tile_size.max(1);
+        let (offset, placement) = self.placement();
+        #[allow(clippy::manual_div_ceil)]
+        let (columns, rows) = (
+            (placement.width + size - 1) / size,
+            (placement.height + size - 1) / size,
+        );
+        let area = (size * size) as usize;
+        // Coverage for each touched tile, allocated on demand.
+        let mut slots = alloc::vec![u32::MAX; (columns * rows) as usize];
+        let mut data: Vec<u8> = Vec::new();
+        let _ = render(
+            self,
+            offset,
+            &placement,
+            &mut Spans(&mut |y: u32, x0: u32, x1: u32, coverage: u8| {
+                let (row, ty) = (y / size, y % size);
+                let mut x = x0;
+                while x < x1 {
+                    let column = x / size;
+                    let end = x1.min((column + 1) * size);
+                    let slot = &mut slots[(row * columns + column) as usize];
+                    if *slot == u32::MAX {
+                        *slot = (data.len() / area) as u32;
+                        data.resize(data.len() + area, 0);
                     }
+                    let start = *slot as usize * area + (ty * size + x % size) as usize;
+                    for b in &mut data[start..start + (end - x) as usize] {
+                        *b = coverage;
+                    }
+                    x = end;
+                }
+            }),
+            0,
+        );
+        let mut masks = Vec::new();
+        let mut index = Vec::with_capacity(slots.len());
+        for (i, &slot) in slots.iter().enumerate() {
+            if slot == u32::MAX {
+                index.push(Tile::Empty);
+                continue;
+            }
+            let (column, row) = (i as u32 % columns, i as u32 / columns);
+            // Tiles at the right and bottom edges may extend beyond the mask.
+            let w = size.min(placement.width - column * size) as usize;
+            let h = size.min(placement.height - row * size) as usize;
+            let tile = &data[slot as usize * area..(slot as usize + 1) * area];
+            let solid = w == size as usize && h == size as usize && tile.iter().all(|&c| c == 255);
+            if solid {
+                index.push(Tile::Solid);
+            } else if tile.iter().all(|&c| c == 0) {
+                index.push(Tile::Empty);
+            } else {
+                index.push(Tile::Mask((masks.len() / area) as u32));
+                masks.extend_from_slice(tile);
+            }
+        }
+        let tiles = Tiles {
+            tile_size: size,
+            columns,
+            rows,
+            index,
+            masks,
+        };
+        (tiles, placement)
+    }
+
+    /// Renders the mask into a two dimensional target such as a slice of
+    /// row slices. Each row must be at least as long as the width of the
+    /// mask multiplied by the number of bytes per pixel of the format.
+    ///
+    /// ```rust
+    /// use zeno::Mask;
+    ///
+    /// let mut canvas = vec![vec![0u8; 16]; 16];
+    /// let placement = Mask::new("M2,2 14,2 14,14 2,14 Z")
+    ///     .size(16, 16)
+    ///     .render_into_rows(&mut canvas[..]);
+    /// assert_eq!(canvas[8][8], 255);
+    /// ```
+    pub fn render_into_rows<R>(&self, rows: &mut R) -> Placement
+    where
+        R: RowTarget + ?Sized,
+    {
+        let (offset, placement) = self.placement();
+        let _ = render(self, offset, &placement, &mut Rows(rows), 0);
+        placement
+    }
+
+    /// Renders the mask in horizontal bands of at most the specified number
+    /// of rows, invoking the callback with the index of the first row of
+    /// each band and its pixel data. Bands are emitted from top to bottom
+    /// and the pitch of each band is the width of the mask multiplied by the
+    /// number of bytes per pixel of the format. Only a single band is
+    /// allocated and edges outside of the current band are skipped, so the
+    /// intermediate memory is bounded by the band size rather than the size
+    /// of the full mask.
+    ///
+    /// ```rust
+    /// use zeno::Mask;
+    ///
+    /// let mut mask = Mask::new("M2,2 62,8 30,62 Z");
+    /// mask.size(64, 64);
+    /// let mut banded = vec![0u8; 64 * 64];
+    /// mask.render_bands(16, |y, band| {
+    ///     let start = y as usize * 64;
+    ///     banded[start..start + band.len()].copy_from_slice(band);
+    /// });
+    /// assert_eq!(banded, mask.render().0);
+    /// ```
+    pub fn render_bands(&self, band_height: u32, mut f: impl FnMut(u32, &[u8])) -> Placement {
+        let (offset, placement) = self.placement();
+        let (width, height) = (placement.width, placement.height);
+        let band_height = band_height.clamp(1, height.max(1));
+        let pitch = self.format.row_size(width);
+        let mut buffer = alloc::vec![0u8; pitch * band_height as usize];
+        let (path, fill) = self.styled();
+        let mut scratch = self.scratch.borrow_mut();
+        let scratch = scratch.as_mut().map(|scratch| &mut **scratch);
+        let mut mask = self.styled_mask(&path, fill, offset, &placement, scratch);
+        let mut top = 0;
+        while top < height {
+            let rows = band_height.min(height - top);
+            let band = &mut buffer[..pitch * rows as usize];
+            band.fill(0);
+            mask.clip = Some(self.band_clip(top, rows, &placement));
+            mask.render_into_rows(&mut Band {
+                buffer: band,
+                pitch,
+                top: top as usize,
+            });
+            f(top, band);
+            top += rows;
+        }
+        placement
+    }
+
+    /// Renders the mask into a byte buffer, rasterizing horizontal bands of
+    /// at most the specified number of rows on multiple threads. The buffer
+    /// is expected to be cleared and the composite operation is ignored.
+    /// See [render_into](#method.render_into) for a description of the
+    /// pitch.
+    ///
+    /// Requires the `rayon` feature.
+    #[cfg(feature = "rayon")]
+    pub fn render_into_parallel(
+        &self,
+        buffer: &mut [u8],
+        pitch: Option<usize>,
+        band_height: u32,
+    ) -> Placement {
+        use rayon::prelude::*;
+        let (offset, placement) = self.placement();
+        let height = placement.height;
+        let band_height = band_height.clamp(1, height.max(1));
+        let pitch = pitch.unwrap_or(self.format.row_size(placement.width));
+        let (path, fill) = self.styled();
+        let bands: Vec<_> = buffer
+            .chunks_mut(pitch * band_height as usize)
+            .zip((0..height).step_by(band_height as usize))
+            .map(|(band, top)| {
+                let mut mask = self.styled_mask(&path, fill, offset, &placement, None);
+                let rows = band_height.min(height - top);
+                mask.clip = Some(self.band_clip(top, rows, &placement));
+                (mask, band, top)
+            })
+            .collect();
+        bands.into_par_iter().for_each(|(mask, band, top)| {
+            mask.render_into_rows(&mut Band {
+                buffer: band,
+                pitch,
+                top: top as usize,
+            });
+        });
+        placement
+    }
+
+    /// Renders the mask to a newly allocated buffer, rasterizing horizontal
+    /// bands of at most the specified number of rows on multiple threads.
+    ///
+    /// Requires the `rayon` feature.
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "rayon")]
+    /// # {
+    /// use zeno::Mask;
+    ///
+    /// let mut mask = Mask::new("M2,2 62,8 30,62 Z");
+    /// mask.size(64, 64);
+    /// assert_eq!(mask.render_parallel(16).0, mask.render().0);
+    /// # }
+    /// ```
+    #[cfg(feature = "rayon")]
+    pub fn render_parallel(&self, band_height: u32) -> (Vec<u8>, Placement) {
+        let (_, placement) = self.placement();
+        let mut buf = alloc::vec![0; self.format.buffer_size(placement.width, placement.height)];
+        let placement = self.render_into_parallel(&mut buf, None, band_height);
+        (buf, placement)
+    }
+
+    /// Returns an error if the placement cannot be rendered.
+    fn check(&self, offset: Vector, placement: &Placement) -> Result<(), RenderError> {
+        // Non-finite coordinates in the path or offset propagate to the
+        // shift that moves the content into the mask.
+        if !offset.x.is_finite() || !offset.y.is_finite() {
+            Err(RenderError::InvalidPath)
+        } else if placement.width.saturating_mul(self.supersample) > MAX_DIM
+            || placement.height.saturating_mul(self.supersample) > MAX_DIM
+        {
+            Err(RenderError::TooLarge)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn ensure_size(&mut self) {
+        if self.has_size {
+            return;
+        }
+        let (offset, placement) = self.placement();
+        self.bounds_offset = offset;
+        self.bounds_position = (placement.left, placement.top);
+        self.bounds_fract = placement.fract;
+        self.width = placement.width;
+        self.height = placement.height;
+        self.explicit_size = false;
+        self.has_size = true;
+    }
+
+    fn placement(&self) -> (Vector, Placement) {
+        let mut placement = Placement {
+            left: 0,
+            top: 0,
+            width: self.width,
+            height: self.height,
+            fract: Vector::ZERO,
+        };
+        if self.explicit_size {
+            return (self.offset, placement);
+        } else if self.has_size {
+            placement.left = self.bounds_position.0;
+            placement.top = self.bounds_position.1;
+            placement.fract = self.bounds_fract;
+            return (self.bounds_offset, placement);
+        }
+        let (style, pre, post) = self.transforms();
+        let mut bounds = match pre {
+            Some(transform) => self.bounds(
+                Transformed {
+                    data: &self.data,
+                    transform,
+                },
+                style,
+                post,
+            ),
+            None => self.bounds(&self.data, style, post),
+        };
+        let snap = self.rounding.snap(self.origin, self.offset, &bounds);
+        placement.fract = match self.rounding {
+            Rounding::Fractional => bounds_fract(self.origin, self.offset, &bounds),
+            _ => Vector::ZERO,
+        };
+        if self.hint {
+            // Leave room for edges that are snapped outward.
+            bounds.min.y -= 0.5;
+            bounds.max.y += 0.5;
+        }
+        if vertical_subpixel(self.format) {
+            bounds.min.y -= 1.;
+            bounds.max.y += 1.;
+        }
+        if self.morphology > 0. {
+            let grow = Vector::new(1., 1.) * self.morphology.ceil();
+            bounds.min = bounds.min - grow;
+            bounds.max = bounds.max + grow;
+        }
+        // The snap is applied after the offset so that the rounded corner
+        // lands exactly on a pixel boundary.
+        bounds.min = (bounds.min + self.offset + snap).floor();
+        bounds.max = (bounds.max + self.offset + snap).ceil();
+        placement.width = (bounds.width() as u32).saturating_add(2);
+        placement.height = bounds.height() as u32;
+        placement.left = bounds.min.x as i32 - 1;
+        placement.top = if self.origin == Origin::BottomLeft {
+            bounds.max.y
+        } else {
+            bounds.min.y
+        } as i32;
+        // The content is shifted so that the mask starts one pixel to the
+        // left of the bounds.
+        (
+            self.offset + snap - Vector::new(bounds.min.x - 1., bounds.min.y),
+            placement,
+        )
+    }
+
+    fn bounds(
+        &self,
+        data: impl PathData,
+        style: Style<'a>,
+        transform: Option<Transform>,
+    ) -> Bounds {
+        let mut scratch = self.scratch.borrow_mut();
+        if let Some(scratch) = scratch.as_mut() {
+            scratch.bounds(data, style, transform)
+        } else {
+            super::bounds(data, style, transform)
+        }
+    }
+
+    /// Returns true if the rows of the buffer are written in the reverse of
+    /// the order in which they are rasterized.
+    fn rows_reversed(&self) -> bool {
+        (self.origin == Origin::BottomLeft) != self.flip_y
+    }
+
+    /// Returns the combination of all transforms applied to the path.
+    fn full_transform(&self) -> Option<Transform> {
+        combine(
+            combine(self.pre_transform, self.transform),
+            self.post_transform,
+        )
+    }
+
+    /// Returns the style along with the combined transforms that are applied
+    /// before and after the style is evaluated.
+    fn transforms(&self) -> (Style<'a>, Option<Transform>, Option<Transform>) {
+        let mut style = self.style;
+        let (pre, post) = match &mut style {
+            Style::Stroke(stroke) if !stroke.scale => {
+                // Apply the primary transform before the style and then
+                // force any remaining transform to scale the stroke.
+                stroke.scale = true;
+                (
+                    combine(self.pre_transform, self.transform),
+                    self.post_transform,
+                )
+            }
+            _ => (
+                self.pre_transform,
+                combine(self.transform, self.post_transform),
+            ),
+        };
+        (style, pre, post)
+    }
+}
+
+/// Renders the coverage of the clip path of a mask in rasterization order.
+fn render_clip_mask<D: PathData>(
+    mask: &Mask<D>,
+    shift: Vector,
+    width: u32,
+    height: u32,
+) -> Option<Vec<u8>> {
+    let (path, fill) = mask.clip_path.as_ref()?;
+    let transform = mask.full_transform();
+    let mut buf = alloc::vec![0; width as usize * height as usize];
+    let mut storage = AdaptiveStorage::new();
+    let mut ras = Rasterizer::new(&mut storage);
+    ras.set_clip(mask.clip);
+    ras.rasterize(
+        shift,
+        width,
+        height,
+        &mut |r| {
+            apply(&path[..], *fill, transform, r);
+        },
+        *fill,
+        &mut buf,
+        width as usize,
+        false,
+    );
+    Some(buf)
+}
+
+/// Returns a transform that applies `a` followed by `b`.
+fn combine(a: Option<Transform>, b: Option<Transform>) -> Option<Transform> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.then(&b)),
+        (a, None) => a,
+        (None, b) => b,
+    }
+}
+
+/// Removes the rows and columns at the edges of a tightly packed mask with
+/// coverage entirely below the threshold. The rows of the buffer are
+/// bottom-up when flipped.
+/// Sets or clears the bits of a span of pixels in a row of a packed mask.
+fn write_bits(row: &mut [u8], x: usize, count: usize, on: bool) {
+    for x in x..x + count {
+        let bit = 0x80 >> (x % 8);
+        if on {
+            row[x / 8] |= bit;
+        } else {
+            row[x / 8] &= !bit;
+        }
+    }
+}
+
+/// Expands a packed mask to an 8-bit alpha mask.
+fn unpack(buffer: &[u8], pitch: usize, width: usize, height: usize) -> Vec<u8> {
+    let mut alpha = alloc::vec![0u8; width * height];
+    for (y, row) in alpha
+        .chunks_exact_mut(width.max(1))
+        .enumerate()
+        .take(height)
+    {
+        let packed = &buffer[y * pitch..];
+        for (x, value) in row.iter_mut().enumerate() {
+            if packed[x / 8] & (0x80 >> (x % 8)) != 0 {
+                *value = 255;
+            }
+        }
+    }
+    alpha
+}
+
+/// Packs an 8-bit alpha mask into a packed mask.
+fn pack(alpha: &[u8], width: usize, buffer: &mut [u8], pitch: usize) {
+    for (y, row) in alpha.chunks_exact(width.max(1)).enumerate() {
+        let packed = &mut buffer[y * pitch..];
+        for (x, value) in row.iter().enumerate() {
+            write_bits(packed, x, 1, *value >= 128);
+        }
+    }
+}
+
+/// Dilates the coverage of a mask by a positive radius or erodes it by a
+/// negative radius, treating coverage outside of the mask as empty.
+fn morph(
+    buffer: &mut [u8],
+    pitch: usize,
+    width: usize,
+    height: usize,
+    format: Format,
+    radius: f32,
+) {
+    if width == 0 || height == 0 {
+        return;
+    }
+    if format == Format::Packed1 {
+        let mut alpha = unpack(buffer, pitch, width, height);
+        morph(&mut alpha, width, width, height, Format::Alpha, radius);
+        return pack(&alpha, width, buffer, pitch);
+    }
+    let wide = Wide::from_format(format);
+    let (channels, size) = match wide {
+        Some(wide) => (1, wide.size()),
+        None => (format.bytes_per_pixel(), 1),
+    };
+    let mut plane = alloc::vec![0u16; width * height];
+    let mut temp = alloc::vec![0u16; width * height];
+    for channel in 0..channels {
+        let start = |x: usize, y: usize| y * pitch + (x * channels + channel) * size;
+        for y in 0..height {
+            for x in 0..width {
+                let pixel = &buffer[start(x, y)..];
+                plane[y * width + x] = match wide {
+                    Some(wide) => wide.read(pixel),
+                    None => pixel[0] as u16 * 257,
+                };
+            }
+        }
+        morph_lines(&plane, &mut temp, height, width, width, 1, radius);
+        morph_lines(&temp, &mut plane, width, height, 1, width, radius);
+        for y in 0..height {
+            for x in 0..width {
+                let pixel = &mut buffer[start(x, y)..];
+                let value = plane[y * width + x];
+                match wide {
+                    Some(wide) => wide.fill(&mut pixel[..size], value),
+                    None => pixel[0] = ((value as u32 + 128) / 257) as u8,
+                }
+            }
+        }
+    }
+}
+
+/// Applies a one dimensional dilation or erosion to each line of a plane of
+/// coverage values. The arguments describe the number of lines and their
+/// length along with the distance between lines and between values.
+fn morph_lines(
+    src: &[u16],
+    dst: &mut [u16],
+    lines: usize,
+    len: usize,
+    line_step: usize,
+    step: usize,
+    radius: f32,
+) {
+    let dilate = radius > 0.;
+    let radius = radius.abs();
+    let (whole, fract) = (radius.floor() as usize, radius - radius.floor());
+    for line in 0..lines {
+        let base = line * line_step;
+        let extreme = |i: usize, r: usize| {
+            let values = (i.saturating_sub(r)..(i + r + 1).min(len)).map(|j| src[base + j * step]);
+            if dilate {
+                values.max().unwrap_or(0)
+            } else if i < r || i + r >= len {
+                0
+            } else {
+                values.min().unwrap_or(0)
+            }
+        };
+        for i in 0..len {
+            let inner = extreme(i, whole) as f32;
+            let value = if fract > 0. {
+                inner + (extreme(i, whole + 1) as f32 - inner) * fract
+            } else {
+                inner
+            };
+            dst[base + i * step] = (value + 0.5) as u16;
+        }
+    }
+}
+
+fn trim<S>(
+    storage: &mut S,
+    placement: Placement,
+    format: Format,
+    threshold: u8,
+    y_up: bool,
+    flipped: bool,
+) -> Placement
+where
+    S: MaskStorage + ?Sized,
+{
+    let width = placement.width as usize;
+    let height = placement.height as usize;
+    let buf = storage.as_mut_slice();
+    if format == Format::Packed1 {
+        let mut alpha = unpack(buf, format.row_size(placement.width), width, height);
+        let placement = trim(
+            &mut alpha,
+            placement,
+            Format::Alpha,
+            threshold,
+            y_up,
+            flipped,
+        );
+        let pitch = format.row_size(placement.width);
+        storage.resize(pitch * placement.height as usize);
+        pack(
+            &alpha,
+            placement.width as usize,
+            storage.as_mut_slice(),
+            pitch,
+        );
+        return placement;
+    }
+    let bpp = format.bytes_per_pixel();
+    let pitch = width * bpp;
+    let wide = Wide::from_format(format);
+    let visible = |pixel: &[u8]| match wide {
+        Some(wide) => wide.read(pixel) >= threshold as u16 * 257,
+        None => pixel.iter().any(|b| *b >= threshold),
+    };
+    let row_visible = |y: usize| {
+        buf[y * pitch..(y + 1) * pitch]
+            .chunks_exact(bpp)
+            .any(visible)
+    };
+    let top = match (0..height).find(|&y| row_visible(y)) {
+        Some(top) => top,
+        None => {
+            storage.resize(0);
+            return Placement {
+                width: 0,
+                height: 0,
+                ..placement
+            };
+        }
+    };
+    let bottom = (top..height).rev().find(|&y| row_visible(y)).unwrap_or(top) + 1;
+    let column_visible = |x: usize| {
+        (top..bottom).any(|y| {
+            let start = y * pitch + x * bpp;
+            visible(&buf[start..start + bpp])
+        })
+    };
+    let left = (0..width).find(|&x| column_visible(x)).unwrap_or(0);
+    let right = (left..width)
+        .rev()
+        .find(|&x| column_visible(x))
+        .unwrap_or(left)
+        + 1;
+    let new_pitch = (right - left) * bpp;
+    for (i, y) in (top..bottom).enumerate() {
+        let start = y * pitch + left * bpp;
+        buf.copy_within(start..start + new_pitch, i * new_pitch);
+    }
+    storage.resize(new_pitch * (bottom - top));
+    // Number of rows removed from the top of the mask.
+    let removed = if flipped { height - bottom } else { top };
+    Placement {
+        left: placement.left + left as i32,
+        top: if y_up {
+            placement.top - removed as i32
+        } else {
+            placement.top + removed as i32
+        },
+        width: (right - left) as u32,
+        height: (bottom - top) as u32,
+        ..placement
+    }
+}
+
+/// Halves the size of a mask by averaging each two by two block of pixels.
+/// Blocks are aligned to even pixel coordinates relative to the origin so that
+/// placements remain consistent between levels. The rows of the buffer are
+/// bottom-up when flipped.
+fn downsample(
+    buf: &[u8],
+    placement: &Placement,
+    format: Format,
+    y_up: bool,
+    flipped: bool,
+) -> (Vec<u8>, Placement) {
+    let (width, height) = (placement.width as i32, placement.height as i32);
+    if width == 0 || height == 0 {
+        let placement = Placement {
+            left: placement.left.div_euclid(2),
+            top: placement.top.div_euclid(2),
+            width: 0,
+            height: 0,
+            fract: Vector::ZERO,
+        };
+        return (Vec::new(), placement);
+    }
+    if format == Format::Packed1 {
+        let alpha = unpack(
+            buf,
+            format.row_size(placement.width),
+            width as usize,
+            height as usize,
+        );
+        let (alpha, placement) = downsample(&alpha, placement, Format::Alpha, y_up, flipped);
+        let pitch = format.row_size(placement.width);
+        let mut buf = alloc::vec![0; pitch * placement.height as usize];
+        pack(&alpha, placement.width as usize, &mut buf, pitch);
+        return (buf, placement);
+    }
+    // Work with the top edge in a downward pointing coordinate system.
+    let top = if y_up { -placement.top } else { placement.top };
+    let left = placement.left.div_euclid(2);
+    let new_top = top.div_euclid(2);
+    let new_width = (placement.left + width - 1).div_euclid(2) - left + 1;
+    let new_height = (top + height - 1).div_euclid(2) - new_top + 1;
+    let wide = Wide::from_format(format);
+    // Number of values per pixel.
+    let channels = if wide.is_some() {
+        1
+    } else {
+        format.bytes_per_pixel()
+    };
+    let pitch = width as usize * format.bytes_per_pixel();
+    let new_pitch = new_width as usize * channels;
+    let mut sums = alloc::vec![0u32; new_pitch * new_height as usize];
+    for y in 0..height {
+        let row = if flipped {
+            &buf[(height - 1 - y) as usize * pitch..]
+        } else {
+            &buf[y as usize * pitch..]
+        };
+        let new_y = (top + y).div_euclid(2) - new_top;
+        let new_y = if flipped {
+            (new_height - 1 - new_y) as usize
+        } else {
+            new_y as usize
+        };
+        let sums = &mut sums[new_y * new_pitch..];
+        for x in 0..width {
+            let new_x = ((placement.left + x).div_euclid(2) - left) as usize;
+            for c in 0..channels {
+                let i = x as usize * channels + c;
+                sums[new_x * channels + c] += match wide {
+                    Some(wide) => wide.read(&row[i * wide.size()..]) as u32,
+                    None => row[i] as u32,
+                };
+            }
+        }
+    }
+    let buf = match wide {
+        Some(wide) => {
+            let mut buf = alloc::vec![0; sums.len() * wide.size()];
+            for (pixel, sum) in buf.chunks_exact_mut(wide.size()).zip(&sums) {
+                wide.fill(pixel, ((sum + 2) / 4) as u16);
+            }
+            buf
+        }
+        None => sums.iter().map(|&sum| ((sum + 2) / 4) as u8).collect(),
+    };
+    let placement = Placement {
+        left,
+        top: if y_up { -new_top } else { new_top },
+        width: new_width as u32,
+        height: new_height as u32,
+        fract: Vector::ZERO,
+    };
+    (buf, placement)
+}
+
+/// Resolves a buffer of tightly packed coverage deltas produced by
+/// [Mask::render_deltas_into](struct.Mask.html#method.render_deltas_into)
+/// into an 8-bit alpha mask using the specified fill rule. Rows are
+/// accumulated with SSE2, NEON or simd128 instructions when the target
+/// enables them.
+pub fn resolve_deltas(deltas: &[i32], width: u32, height: u32, fill: Fill, mask: &mut [u8]) {
+    let width = width as usize;
+    for (deltas, mask) in deltas
+        .chunks_exact(width)
+        .zip(mask.chunks_exact_mut(width))
+        .take(height as usize)
+    {
+        resolve_row(fill, deltas, mask);
+    }
+}
+
+/// Error returned when rendering a mask fails.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum RenderError {
+    /// Rendering would exceed the memory limit of the scratch context.
+    LimitExceeded,
+    /// Rendering was cancelled by a flag or time limit.
+    Cancelled,
+    /// The width or height of the mask, multiplied by the supersampling
+    /// factor, exceeds the maximum of 32767 pixels.
+    TooLarge,
+    /// The buffer is too small to hold the mask with the requested pitch.
+    BufferTooSmall,
+    /// The path data or offset contains coordinates that are not finite.
+    InvalidPath,
+}
+
+impl From<LimitExceeded> for RenderError {
+    fn from(_: LimitExceeded) -> Self {
+        Self::LimitExceeded
+    }
+}
+
+impl fmt::Display for RenderError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::LimitExceeded => LimitExceeded.fmt(f),
+            Self::Cancelled => write!(f, "rendering cancelled"),
+            Self::TooLarge => write!(f, "mask dimensions too large"),
+            Self::BufferTooSmall => write!(f, "buffer too small for mask"),
+            Self::InvalidPath => write!(f, "path contains non-finite coordinates"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for RenderError {}
+
+/// Statistics describing the coverage of a rendered mask.
+///
+/// For subpixel formats, each pixel is classified by the maximum coverage of
+/// its color channels.
+#[derive(Copy, Clone, Debug)]
+pub struct RenderStats {
+    /// Number of pixels with non-zero coverage.
+    pub nonzero: u32,
+    /// Number of pixels with each coverage value. For 16-bit and floating
+    /// point formats, pixels are counted by the most significant 8 bits of
+    /// their coverage.
+    pub histogram: [u32; 256],
+    /// Horizontal offset of the ink bounds from the left edge of the mask.
+    pub ink_x: u32,
+    /// Vertical offset of the ink bounds from the first row of the mask.
+    pub ink_y: u32,
+    /// Width of the smallest rectangle containing all non-zero pixels.
+    pub ink_width: u32,
+    /// Height of the smallest rectangle containing all non-zero pixels.
+    pub ink_height: u32,
+}
+
+impl RenderStats {
+    /// Returns true if no pixel has non-zero coverage.
+    ///
+    /// ```rust
+    /// use zeno::Mask;
+    ///
+    /// let (_, _, stats) = Mask::new("M20,20 24,20 24,24 Z").size(8, 8).render_with_stats();
+    /// assert!(stats.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.nonzero == 0
+    }
+
+    /// Returns true if every pixel of the mask has full coverage, at the
+    /// resolution of the histogram. Empty masks are not considered opaque.
+    ///
+    /// ```rust
+    /// use zeno::Mask;
+    ///
+    /// let (_, _, stats) = Mask::new("M0,0 8,0 8,8 0,8 Z").size(8, 8).render_with_stats();
+    /// assert!(stats.is_opaque());
+    /// ```
+    pub fn is_opaque(&self) -> bool {
+        self.nonzero != 0 && self.histogram[..255].iter().all(|&count| count == 0)
+    }
+
+    /// Returns the maximum coverage of any pixel, with the same resolution
+    /// as the histogram.
+    ///
+    /// ```rust
+    /// use zeno::Mask;
+    ///
+    /// let (_, _, stats) = Mask::new("M0,0 8,0 8,0.5 0,0.5 Z").size(8, 8).render_with_stats();
+    /// assert_eq!(stats.max_coverage(), 127);
+    /// ```
+    pub fn max_coverage(&self) -> u8 {
+        self.histogram
+            .iter()
+            .rposition(|&count| count != 0)
+            .unwrap_or(0) as u8
+    }
+
+    /// Returns the number of pixels with full coverage, at the resolution of
+    /// the histogram.
+    pub fn opaque(&self) -> u32 {
+        self.histogram[255]
+    }
+}
+
+/// Horizontal run of pixels with uniform coverage.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Span {
+    /// Row of the span.
+    pub y: u32,
+    /// First column of the span.
+    pub x: u32,
+    /// Number of pixels in the span.
+    pub len: u32,
+    /// Coverage of each pixel in the span.
+    pub alpha: u8,
+}
+
+/// Classification of a tile produced by
+/// [Mask::render_tiles](struct.Mask.html#method.render_tiles).
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Tile {
+    /// Tile with no coverage.
+    Empty,
+    /// Tile that is entirely covered.
+    Solid,
+    /// Partially covered tile with the specified index into the tile masks.
+    Mask(u32),
+}
+
+/// Grid of coverage tiles produced by
+/// [Mask::render_tiles](struct.Mask.html#method.render_tiles).
+#[derive(Clone, Default, Debug)]
+pub struct Tiles {
+    /// Width and height of each tile in pixels.
+    pub tile_size: u32,
+    /// Number of tiles in each row of the grid.
+    pub columns: u32,
+    /// Number of rows of tiles in the grid.
+    pub rows: u32,
+    /// Classification of each tile in row major order.
+    pub index: Vec<Tile>,
+    /// Tightly packed 8-bit alpha coverage of the partially covered tiles.
+    /// Each tile occupies `tile_size * tile_size` bytes and pixels beyond the
+    /// edges of the mask are zero.
+    pub masks: Vec<u8>,
+}
+
+impl Tiles {
+    /// Returns the classification of the tile at the specified column and
+    /// row.
+    pub fn tile(&self, column: u32, row: u32) -> Tile {
+        self.index[(row * self.columns + column) as usize]
+    }
+
+    /// Returns the coverage of the partially covered tile with the
+    /// specified index.
+    pub fn mask(&self, index: u32) -> &[u8] {
+        let area = (self.tile_size * self.tile_size) as usize;
+        &self.masks[index as usize * area..(index as usize + 1) * area]
+    }
+}
+
+/// Two dimensional destination for rendering a mask.
+///
+/// This is implemented for slices of mutable row slices and of row vectors,
+/// for two dimensional `ndarray` arrays when the `ndarray` feature is enabled
+/// and may be implemented for other row addressable image types.
+pub trait RowTarget {
+    /// Returns the bytes of the row at the specified index.
+    fn row(&mut self, y: usize) -> &mut [u8];
+}
+
+impl RowTarget for [&mut [u8]] {
+    fn row(&mut self, y: usize) -> &mut [u8] {
+        self[y]
+    }
+}
+
+impl RowTarget for [Vec<u8>] {
+    fn row(&mut self, y: usize) -> &mut [u8] {
+        &mut self[y]
+    }
+}
+
+/// Renders into the rows of a two dimensional array, indexed by row and
+/// then column. Panics if a row is not contiguous in memory, such as for an
+/// array in column major order.
+///
+/// ```rust
+/// use ndarray::Array2;
+/// use zeno::Mask;
+///
+/// let mut canvas = Array2::<u8>::zeros((8, 8));
+/// Mask::new("M0,0 4,0 4,8 0,8 Z")
+///     .size(8, 8)
+///     .render_into_rows(&mut canvas.view_mut());
+/// assert_eq!(canvas[[3, 2]], 255);
+/// assert_eq!(canvas[[3, 6]], 0);
+/// ```
+#[cfg(feature = "ndarray")]
+impl<S> RowTarget for ndarray::ArrayBase<S, ndarray::Ix2>
+where
+    S: ndarray::DataMut<Elem = u8>,
+{
+    fn row(&mut self, y: usize) -> &mut [u8] {
+        self.row_mut(y)
+            .into_slice()
+            .expect("rows of the array must be contiguous")
+    }
+}
+
+/// Growable byte buffer that a mask can be rendered into.
+///
+/// This is implemented for `Vec<u8>` and may be implemented for pooled or
+/// arena allocated buffers.
+pub trait MaskStorage {
+    /// Resizes the storage to the specified number of bytes.
+    fn resize(&mut self, len: usize);
+
+    /// Returns the bytes of the storage.
+    fn as_mut_slice(&mut self) -> &mut [u8];
+}
+
+impl MaskStorage for Vec<u8> {
+    fn resize(&mut self, len: usize) {
+        Vec::resize(self, len, 0);
+    }
+
+    fn as_mut_slice(&mut self) -> &mut [u8] {
+        self
+    }
+}
+
+/// Builder for rendering a mask from pre-flattened geometry.
+///
+/// The edges are directed line segments that are fed directly to the
+/// rasterizer, bypassing command parsing, styling and flattening. Each
+/// closed contour should be described by a sequence of edges that forms a
+/// closed loop; unlike paths, edges are never implicitly closed.
+///
+/// ```rust
+/// use zeno::{EdgeMask, Point};
+///
+/// let (a, b, c, d) = (
+///     Point::new(2., 2.),
+///     Point::new(14., 2.),
+///     Point::new(14., 14.),
+///     Point::new(2., 14.),
+/// );
+/// let edges = [(a, b), (b, c), (c, d), (d, a)];
+/// let (mask, placement) = EdgeMask::new(&edges).size(16, 16).render();
+/// assert_eq!(placement.width, 16);
+/// assert_eq!(mask[8 * 16 + 8], 255);
+/// ```
+pub struct EdgeMask<'a> {
+    edges: &'a [(Point, Point)],
+    fill: Fill,
+    format: Format,
+    origin: Origin,
+    offset: Vector,
+    width: u32,
+    height: u32,
+    explicit_size: bool,
+    bounds: Option<Bounds>,
+}
+
+impl<'a> EdgeMask<'a> {
+    /// Creates a new edge mask builder for the specified line segments.
+    pub fn new(edges: &'a [(Point, Point)]) -> Self {
+        Self {
+            edges,
+            fill: Fill::NonZero,
+            format: Format::Alpha,
+            origin: Origin::TopLeft,
+            offset: Vector::ZERO,
+            width: 0,
+            height: 0,
+            explicit_size: false,
+            bounds: None,
+        }
+    }
+
+    /// Sets the fill rule.
+    pub fn fill(&mut self, fill: Fill) -> &mut Self {
+        self.fill = fill;
+        self
+    }
+
+    /// Sets the desired format of the mask.
+    pub fn format(&mut self, format: Format) -> &mut Self {
+        self.format = format;
+        self
+    }
+
+    /// Sets the origin that defines the coordinate system for the mask.
+    pub fn origin(&mut self, origin: Origin) -> &mut Self {
+        self.origin = origin;
+        self
+    }
+
+    /// Sets the offset for the edges.
+    pub fn offset(&mut self, offset: impl Into<Vector>) -> &mut Self {
+        self.offset = offset.into();
+        self
+    }
+
+    /// Sets an explicit size for the mask. If left unspecified, the size will
+    /// be computed from the bounding box of the edges.
+    pub fn size(&mut self, width: u32, height: u32) -> &mut Self {
+        self.width = width;
+        self.height = height;
+        self.explicit_size = true;
+        self
+    }
+
+    /// Renders the mask into a byte buffer. See
+    /// [Mask::render_into](struct.Mask.html#method.render_into) for a
+    /// description of the pitch.
+    pub fn render_into(&self, buffer: &mut [u8], pitch: Option<usize>) -> Placement {
+        let (offset, placement) = self.placement();
+        let pitch = pitch.unwrap_or(self.format.row_size(placement.width));
+        self.render_target(offset, &placement, None, &mut Buffer { buffer, pitch });
+        placement
+    }
+
+    /// Renders the mask to a newly allocated buffer.
+    pub fn render(&self) -> (Vec<u8>, Placement) {
+        let (offset, placement) = self.placement();
+        let mut buf = alloc::vec![0; self.format.buffer_size(placement.width, placement.height)];
+        let pitch = self.format.row_size(placement.width);
+        self.render_target(
+            offset,
+            &placement,
+            None,
+            &mut Buffer {
+                buffer: &mut buf,
+                pitch,
+            },
+        );
+        (buf, placement)
+    }
+
+    /// Re-renders the region of a previously rendered mask that is covered
+    /// by the damaged bounds, leaving the rest of the buffer untouched. The
+    /// damage is specified in the same coordinate space as the edges, such
+    /// as the result of [Prepared::damage](struct.Prepared.html#method.damage).
+    /// Pixels in the damaged region are cleared before rendering and edges
+    /// that lie entirely above, below or to the right of it are skipped.
+    ///
+    /// The buffer must contain a mask rendered with the same size, format,
+    /// origin and offset, so this is typically used with an explicit size.
+    ///
+    /// ```rust
+    /// use zeno::{Fill, Prepared};
+    ///
+    /// let before = Prepared::new("M2,2 14,2 14,14 2,14 Z", Fill::NonZero, None);
+    /// let after = Prepared::new("M2,2 14,2 15,14 2,14 Z", Fill::NonZero, None);
+    /// let (mut mask, _) = before.mask().size(16, 16).render();
+    /// if let Some(damage) = after.damage(&before) {
+    ///     after.mask().size(16, 16).render_damage_into(&mut mask, None, damage);
+    /// }
+    /// assert_eq!(mask, after.mask().size(16, 16).render().0);
+    /// ```
+    pub fn render_damage_into(
+        &self,
+        buffer: &mut [u8],
+        pitch: Option<usize>,
+        damage: Bounds,
+    ) -> Placement {
+        let (offset, placement) = self.placement();
+        let bpp = self.format.bytes_per_pixel();
+        let pitch = pitch.unwrap_or(self.format.row_size(placement.width));
+        // Subpixel formats sample each channel at an offset, so the damage
+        // is padded to include the neighboring pixels.
+        let pad = match subpixel_offsets(self.format) {
+            Some(_) => Vector::new(1., 1.),
+            None => Vector::ZERO,
+        };
+        let clip = Bounds::new(damage.min + offset - pad, damage.max + offset + pad);
+        let [x0, y0, x1, y1] = clip_rect(Some(clip), placement.width, placement.height);
+        for y in y0..y1 {
+            let y = if self.origin == Origin::BottomLeft {
+                placement.height as usize - 1 - y
+            } else {
+                y
+            };
+            let row = y * pitch;
+            if self.format == Format::Packed1 {
+                write_bits(&mut buffer[row..], x0, x1 - x0, false);
+            } else {
+                for value in &mut buffer[row + x0 * bpp..row + x1 * bpp] {
+                    *value = 0;
+                }
+            }
+        }
+        self.render_target(
+            offset,
+            &placement,
+            Some(clip),
+            &mut Buffer { buffer, pitch },
+        );
+        placement
+    }
+
+    fn placement(&self) -> (Vector, Placement) {
+        if self.explicit_size {
+            let placement = Placement {
+                left: 0,
+                top: 0,
+                width: self.width,
+                height: self.height,
+                fract: Vector::ZERO,
+            };
+            return (self.offset, placement);
+        }
+        let mut bounds = self
+            .bounds
+            .unwrap_or_else(|| Bounds::from_points(self.edges.iter().flat_map(|e| [e.0, e.1])));
+        if vertical_subpixel(self.format) {
+            bounds.min.y -= 1.;
+            bounds.max.y += 1.;
+        }
+        Placement::compute(self.origin, self.offset, &bounds)
+    }
+
+    fn render_target(
+        &self,
+        offset: Vector,
+        placement: &Placement,
+        clip: Option<Bounds>,
+        target: &mut impl Target,
+    ) {
+        let passes = Passes {
+            shift: offset,
+            subpx: subpixel_offsets(self.format),
+            wide: Wide::from_format(self.format),
+            packed: self.format == Format::Packed1,
+            clip,
+            width: placement.width,
+            height: placement.height,
+            fill: self.fill,
+            y_up: self.origin == Origin::BottomLeft,
+            invert: false,
+            alpha: SubpixelAlpha::None,
+            lcd_filter: LcdFilter::None,
+        };
+        let mut storage = AdaptiveStorage::new();
+        let mut ras = Rasterizer::new(&mut storage);
+        ras.set_clip(clip);
+        let edges = self.edges;
+        target.rasterize(&passes, &mut ras, &mut |r| {
+            for (from, to) in edges {
+                r.edge(*from, *to);
+            }
+        });
+    }
+}
+
+/// Styled and transformed path that has been flattened to a list of edges.
+///
+/// Stroking, dashing, transforming and curve flattening are performed once
+/// when the path is prepared. The result can then be rendered any number of
+/// times at different offsets with only the cost of rasterization. Hinting is
+/// not supported as it depends on the final position of the path.
+///
+/// ```rust
+/// use zeno::{Prepared, Stroke};
+///
+/// let path = "M 4,4 C 10,0 14,8 8,14 Z";
+/// let prepared = Prepared::new(path, Stroke::new(2.), None);
+/// for x in 0..4 {
+///     let offset = (x as f32 * 0.25, 0.);
+///     let (mask, placement) = prepared.mask().offset(offset).render();
+///     assert_eq!(mask.len(), (placement.width * placement.height) as usize);
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct Prepared {
+    edges: Vec<(Point, Point)>,
+    fill: Fill,
+    bounds: Bounds,
+}
+
+impl Prepared {
+    /// Creates a new prepared path by applying the style and transform to
+    /// the path data and flattening the result.
+    pub fn new<'a>(
+        data: impl PathData,
+        style: impl Into<Style<'a>>,
+        transform: Option<Transform>,
+    ) -> Self {
+        let mut flatten = Flatten {
+            edges: Vec::new(),
+            start: Point::ZERO,
+            current: Point::ZERO,
+        };
+        let fill = apply(data, style, transform, &mut flatten);
+        flatten.close();
+        let edges = flatten.edges;
+        let bounds = Bounds::from_points(edges.iter().flat_map(|e| [e.0, e.1]));
+        Self {
+            edges,
+            fill,
+            bounds,
+        }
+    }
+
+    /// Returns the flattened edges of the path.
+    pub fn edges(&self) -> &[(Point, Point)] {
+        &self.edges
+    }
+
+    /// Returns the fill rule that should be used to render the edges.
+    pub fn fill(&self) -> Fill {
+        self.fill
+    }
+
+    /// Returns the bounding box of the edges.
+    pub fn bounds(&self) -> Bounds {
+        self.bounds
+    }
+
+    /// Returns the bounding box of the edges that differ between this path
+    /// and a previously prepared version of it, or `None` if the edges are
+    /// identical. Coverage can only change within this region, so it can be
+    /// passed to [EdgeMask::render_damage_into](struct.EdgeMask.html#method.render_damage_into)
+    /// to update a mask that was rendered from the previous path.
+    pub fn damage(&self, previous: &Prepared) -> Option<Bounds> {
+        if self.fill != previous.fill {
+            let points = [
+                self.bounds.min,
+                self.bounds.max,
+                previous.bounds.min,
+                previous.bounds.max,
+            ];
+            return Some(Bounds::from_points(points));
+        }
+        let key = |e: &(Point, Point)| {
+            [
+                e.0.x.to_bits(),
+                e.0.y.to_bits(),
+                e.1.x.to_bits(),
+                e.1.y.to_bits(),
+            ]
+        };
+        let mut a: Vec<_> = self.edges.iter().map(key).collect();
+        let mut b: Vec<_> = previous.edges.iter().map(key).collect();
+        a.sort_unstable();
+        b.sort_unstable();
+        let mut bounds = BoundsBuilder::new();
+        let mut add = |k: &[u32; 4]| {
+            bounds.add(Point::new(f32::from_bits(k[0]), f32::from_bits(k[1])));
+            bounds.add(Point::new(f32::from_bits(k[2]), f32::from_bits(k[3])));
+        };
+        let (mut i, mut j) = (0, 0);
+        while i < a.len() && j < b.len() {
+            match a[i].cmp(&b[j]) {
+                Ordering::Less => {
+                    add(&a[i]);
+                    i += 1;
+                }
+                Ordering::Greater => {
+                    add(&b[j]);
+                    j += 1;
+                }
+                Ordering::Equal => {
+                    i += 1;
+                    j += 1;
+                }
+            }
+        }
+        a[i..].iter().chain(&b[j..]).for_each(add);
+        if bounds.count == 0 {
+            None
+        } else {
+            Some(bounds.build())
+        }
+    }
+
+    /// Returns a mask builder for rendering the prepared path.
+    pub fn mask(&self) -> EdgeMask<'_> {
+        let mut mask = EdgeMask::new(&self.edges);
+        mask.fill = self.fill;
+        mask.bounds = Some(self.bounds);
+        mask
+    }
+}
+
+/// Path builder that flattens curves into a list of edges using the same
+/// tolerances as the rasterizer.
+struct Flatten {
+    edges: Vec<(Point, Point)>,
+    start: Point,
+    current: Point,
+}
+
+impl Flatten {
+    /// Maximum number of lines emitted for a single quadratic curve.
+    const MAX_SPLITS: usize = 1 << 16;
+
+    /// Maximum subdivision depth for cubic curves.
+    const MAX_DEPTH: u32 = 16;
+
+    fn edge(&mut self, to: Point) {
+        if to != self.current {
+            self.edges.push((self.current, to));
+        }
+        self.current = to;
+    }
+
+    /// Recursively splits a cubic curve in half until each piece is flat
+    /// enough to be replaced by a line, mirroring the rasterizer.
+    fn cubic(&mut self, from: Point, c1: Point, c2: Point, to: Point, depth: u32) {
+        let d1 = to * 2. - c2 * 3. + from;
+        let d2 = to - c1 * 3. + from * 2.;
+        let deviation = d1.x.abs().max(d1.y.abs()).max(d2.x.abs().max(d2.y.abs()));
+        if deviation > 0.5 && depth < Self::MAX_DEPTH {
+            let ab = (from + c1) * 0.5;
+            let bc = (c1 + c2) * 0.5;
+            let cd = (c2 + to) * 0.5;
+            let abc = (ab + bc) * 0.5;
+            let bcd = (bc + cd) * 0.5;
+            let mid = (abc + bcd) * 0.5;
+            self.cubic(from, ab, abc, mid, depth + 1);
+            self.cubic(mid, bcd, cd, to, depth + 1);
+        } else {
+            self.edge(to);
+        }
+    }
+}
+
+impl PathBuilder for Flatten {
+    fn current_point(&self) -> Point {
+        self.current
+    }
+
+    fn tolerance(&self) -> f32 {
+        0.25
+    }
+
+    fn move_to(&mut self, to: impl Into<Point>) -> &mut Self {
+        self.close();
+        let to = to.into();
+        self.start = to;
+        self.current = to;
+        self
+    }
+
+    fn line_to(&mut self, to: impl Into<Point>) -> &mut Self {
+        self.edge(to.into());
+        self
+    }
+
+    fn quad_to(&mut self, control: impl Into<Point>, to: impl Into<Point>) -> &mut Self {
+        let (control, to) = (control.into(), to.into());
+        let from = self.current;
+        let d = from + to - control * 2.;
+        let mut deviation = d.x.abs().max(d.y.abs());
+        let mut count = 1;
+        while deviation > 0.25 && count < Self::MAX_SPLITS {
+            deviation /= 4.;
+            count *= 2;
+        }
+        for i in 1..count {
+            let t = i as f32 / count as f32;
+            let s = 1. - t;
+            self.edge(from * (s * s) + control * (2. * s * t) + to * (t * t));
+        }
+        self.edge(to);
+        self
+    }
+
+    fn curve_to(
+        &mut self,
+        control1: impl Into<Point>,
+        control2: impl Into<Point>,
+        to: impl Into<Point>,
+    ) -> &mut Self {
+        let (c1, c2, to) = (control1.into(), control2.into(), to.into());
+        self.cubic(self.current, c1, c2, to, 0);
+        self
+    }
+
+    fn close(&mut self) -> &mut Self {
+        self.edge(self.start);
+        self
+    }
+}
+
+/// Styled and transformed path that is rendered as part of a
+/// [LayeredMask](struct.LayeredMask.html).
+#[derive(Copy, Clone, Debug)]
+pub struct Layer<'a, D> {
+    /// The path data of the layer.
+    pub path: D,
+    /// The style of the path.
+    pub style: Style<'a>,
+    /// Optional transform applied to the path.
+    pub transform: Option<Transform>,
+}
+
+impl<'a, D> Layer<'a, D> {
+    /// Creates a new layer with the specified path, style and transform.
+    pub fn new(path: D, style: impl Into<Style<'a>>, transform: Option<Transform>) -> Self {
+        Self {
+            path,
+            style: style.into(),
+            transform,
+        }
+    }
+}
+
+/// Builder for rendering multiple styled paths into a single mask.
+///
+/// Each layer is rasterized with its own style and fill rule and the
+/// resulting coverage is accumulated into the target, saturating at full
+/// coverage. When the size is computed, the mask covers the union of the
+/// bounds of all layers.
+///
+/// ```rust
+/// use zeno::{Fill, Layer, LayeredMask, Stroke};
+///
+/// let layers = [
+///     Layer::new("M0,0 8,0 8,8 0,8 Z", Fill::NonZero, None),
+///     Layer::new("M12,4 20,4", Stroke::new(2.), None),
+/// ];
+/// let (mask, placement) = LayeredMask::new(&layers).render();
+/// assert_eq!((placement.width, placement.height), (22, 8));
+/// assert_eq!(mask[4 * 22 + 4], 255);
+/// assert_eq!(mask[4 * 22 + 16], 255);
+/// ```
+pub struct LayeredMask<'a, D> {
+    layers: &'a [Layer<'a, D>],
+    format: Format,
+    origin: Origin,
+    offset: Vector,
+    width: u32,
+    height: u32,
+    explicit_size: bool,
+}
+
+impl<'a, D> LayeredMask<'a, D>
+where
+    D: PathData,
+{
+    /// Creates a new layered mask builder for the specified layers.
+    pub fn new(layers: &'a [Layer<'a, D>]) -> Self {
+        Self {
+            layers,
+            format: Format::Alpha,
+            origin: Origin::TopLeft,
+            offset: Vector::ZERO,
+            width: 0,
+            height: 0,
+            explicit_size: false,
+        }
+    }
+
+    /// Sets the desired format of the mask.
+    pub fn format(&mut self, format: Format) -> &mut Self {
+        self.format = format;
+        self
+    }
+
+    /// Sets the origin that defines the coordinate system for the mask.
+    pub fn origin(&mut self, origin: Origin) -> &mut Self {
+        self.origin = origin;
+        self
+    }
+
+    /// Sets the offset for the layers.
+    pub fn offset(&mut self, offset: impl Into<Vector>) -> &mut Self {
+        self.offset = offset.into();
+        self
+    }
+
+    /// Sets an explicit size for the mask. If left unspecified, the size will
+    /// be computed from the union of the bounding boxes of the layers.
+    pub fn size(&mut self, width: u32, height: u32) -> &mut Self {
+        self.width = width;
+        self.height = height;
+        self.explicit_size = true;
+        self
+    }
+
+    /// Renders the mask into a byte buffer, accumulating with the existing
+    /// contents. See [Mask::render_into](struct.Mask.html#method.render_into)
+    /// for a description of the pitch.
+    pub fn render_into(&self, buffer: &mut [u8], pitch: Option<usize>) -> Placement {
+        let (offset, placement) = self.placement();
+        let pitch = pitch.unwrap_or(self.format.row_size(placement.width));
+        let mut target = Blend {
+            buffer,
+            pitch,
+            op: Composite::Add,
+        };
+        self.render_target(offset, &placement, &mut target);
+        placement
+    }
+
+    /// Renders the mask to a newly allocated buffer.
+    pub fn render(&self) -> (Vec<u8>, Placement) {
+        let (offset, placement) = self.placement();
+        let mut buf = alloc::vec![0; self.format.buffer_size(placement.width, placement.height)];
+        let pitch = self.format.row_size(placement.width);
+        self.render_target(
+            offset,
+            &placement,
+            &mut Blend {
+                buffer: &mut buf,
+                pitch,
+                op: Composite::Add,
+            },
+        );
+        (buf, placement)
+    }
+
+    fn placement(&self) -> (Vector, Placement) {
+        if self.explicit_size {
+            let placement = Placement {
+                left: 0,
+                top: 0,
+                width: self.width,
+                height: self.height,
+                fract: Vector::ZERO,
+            };
+            return (self.offset, placement);
+        }
+        let mut builder = BoundsBuilder::new();
+        for layer in self.layers {
+            apply(&layer.path, layer.style, layer.transform, &mut builder);
+        }
+        let mut bounds = builder.build();
+        if vertical_subpixel(self.format) {
+            bounds.min.y -= 1.;
+            bounds.max.y += 1.;
+        }
+        Placement::compute(self.origin, self.offset, &bounds)
+    }
+
+    fn render_target(&self, offset: Vector, placement: &Placement, target: &mut impl Target) {
+        let mut storage = AdaptiveStorage::new();
+        let mut ras = Rasterizer::new(&mut storage);
+        for layer in self.layers {
+            let passes = Passes {
+                shift: offset,
+                subpx: subpixel_offsets(self.format),
+                wide: Wide::from_format(self.format),
+                packed: self.format == Format::Packed1,
+                clip: None,
+                width: placement.width,
+                height: placement.height,
+                fill: match layer.style {
+                    Style::Fill(fill) => fill,
+                    _ => Fill::NonZero,
+                },
+                y_up: self.origin == Origin::BottomLeft,
+                invert: false,
+                alpha: SubpixelAlpha::None,
+                lcd_filter: LcdFilter::None,
+            };
+            target.rasterize(&passes, &mut ras, &mut |r| {
+                apply(&layer.path, layer.style, layer.transform, r);
+            });
+        }
+    }
+}
+
+fn render<'a, 'c, D>(
+    mask: &'a Mask<'a, 'c, D>,
+    offset: Vector,
+    placement: &Placement,
+    target: &mut impl Target,
+    reserved: usize,
+) -> Result<(), RenderError>
+where
+    D: PathData,
+{
+    let (_, pre, _) = mask.transforms();
+    match pre {
+        Some(transform) => {
+            let data = Transformed {
+                data: &mask.data,
+                transform,
+            };
+            render_path(mask, data, offset, placement, target, reserved)
+        }
+        None => render_path(mask, &mask.data, offset, placement, target, reserved),
+    }
+}
+
+fn render_path<'a, 'c, D>(
+    mask: &'a Mask<'a, 'c, D>,
+    data: impl PathData,
+    offset: Vector,
+    placement: &Placement,
+    target: &mut impl Target,
+    reserved: usize,
+) -> Result<(), RenderError>
+where
+    D: PathData,
+{
+    let y_up = mask.rows_reversed();
+    let subpx = subpixel_offsets(mask.format);
+    let (fill, fill_rules) = match mask.style {
+        Style::Fill(fill) => (fill, mask.fill_rules),
+        _ => (Fill::NonZero, None),
+    };
+    let w = placement.width;
+    let h = placement.height;
+    let shift = offset + mask.render_offset;
+    let data = &data;
+    let (style, _, transform) = mask.transforms();
+    let mut scratch = mask.scratch.borrow_mut();
+    let interrupt = Interrupt::new(mask.cancel);
+    #[cfg(feature = "std")]
+    let interrupt = interrupt.with_deadline(mask.time_limit.map(|limit| Instant::now() + limit));
+    let interrupt = if interrupt.is_active() {
+        interrupt.check();
+        Some(&interrupt)
+    } else {
+        None
+    };
+    let curve = if mask.gamma != 1. || mask.contrast != 0. {
+        Some(CoverageCurve::new(mask.gamma, mask.contrast))
+    } else {
+        None
+    };
+    let passes = Passes {
+        shift,
+        subpx,
+        wide: Wide::from_format(mask.format),
+        packed: mask.format == Format::Packed1,
+        clip: mask.clip,
+        width: w,
+        height: h,
+        fill,
+        y_up,
+        invert: mask.invert,
+        alpha: mask.subpixel_alpha,
+        lcd_filter: mask.lcd_filter,
+    };
+    let clip_mask = render_clip_mask(mask, shift, w, h);
+    let hairline = match &style {
+        Style::Stroke(stroke) if !mask.hint => {
+            hairline_width(stroke, transform.as_ref()).map(|width| (stroke, width))
+        }
+        _ => None,
+    };
+    let device_commands = || {
+        data.commands().map(move |cmd| match &transform {
+            Some(transform) => cmd.transform(transform),
+            None => cmd,
+        })
+    };
+    if let Some(scratch) = scratch.as_mut() {
+        let budget = scratch.budget();
+        budget.take(reserved);
+        let mut storage = LimitedStorage {
+            storage: &mut scratch.render,
+            budget: &budget,
+        };
+        let mut ras = Rasterizer::new(&mut storage);
+        ras.set_interrupt(interrupt);
+        ras.set_aliased(!mask.anti_alias);
+        ras.set_precision(mask.quality.precision());
+        ras.set_clip(mask.clip);
+        ras.set_curve(curve.as_ref());
+        ras.set_clip_mask(clip_mask.as_deref());
+        ras.set_fill_rules(fill_rules);
+        let inner = &mut scratch.inner;
+        if mask.hint {
+            let path = &mut scratch.path;
+            path.clear();
+            let mut sink = LimitedPath {
+                path,
+                budget: &budget,
+            };
+            inner.apply(data, &style, transform, &mut sink, &budget);
+            budget.check()?;
+            hint(path, shift);
+            let path = &path[..];
+            target.rasterize(&passes, &mut ras, &mut |r| path.copy_to(r));
+        } else if let Some((stroke, width)) = hairline {
+            target.rasterize(&passes, &mut ras, &mut |r| {
+                hairline_into(device_commands(), stroke, width, r);
+            });
+        } else {
+            target.rasterize(&passes, &mut ras, &mut |r| {
+                inner.apply(data, &style, transform, r, &budget);
+            });
+        }
+        budget.check()?;
+    } else {
+        let mut storage = AdaptiveStorage::new();
+        let mut ras = Rasterizer::new(&mut storage);
+        ras.set_interrupt(interrupt);
+        ras.set_aliased(!mask.anti_alias);
+        ras.set_precision(mask.quality.precision());
+        ras.set_clip(mask.clip);
+        ras.set_curve(curve.as_ref());
+        ras.set_clip_mask(clip_mask.as_deref());
+        ras.set_fill_rules(fill_rules);
+        if mask.hint {
+            let mut path = Vec::new();
+            apply(data, style, transform, &mut path);
+            hint(&mut path, shift);
+            let path = &path[..];
+            target.rasterize(&passes, &mut ras, &mut |r| path.copy_to(r));
+        } else if let Some((stroke, width)) = hairline {
+            target.rasterize(&passes, &mut ras, &mut |r| {
+                hairline_into(device_commands(), stroke, width, r);
+            });
+        } else {
+            target.rasterize(&passes, &mut ras, &mut |r| {
+                apply(data, style, transform, r);
+            });
+        }
+    }
+    match interrupt {
+        Some(interrupt) if interrupt.is_cancelled() => Err(RenderError::Cancelled),
+        _ => Ok(()),
+    }
+}
+
+/// Returns a view of the bytes of a buffer of 32-bit pixels.
+fn as_bytes(buffer: &mut [u32]) -> &mut [u8] {
+    // Safety: u8 has no alignment requirements and every bit pattern is valid
+    // for both types.
+    unsafe { core::slice::from_raw_parts_mut(buffer.as_mut_ptr() as *mut u8, buffer.len() * 4) }
+}
+
+/// Returns the offsets of the color channels for a format.
+fn subpixel_offsets(format: Format) -> Option<[Vector; 3]> {
+    match format {
+        Format::Alpha | Format::Alpha16 | Format::AlphaF32 | Format::Packed1 => None,
+        Format::Subpixel => Some([Vector::new(-0.3, 0.), Vector::ZERO, Vector::new(0.3, 0.)]),
+        Format::CustomSubpixel(subpx) => Some([
+            Vector::new(subpx[0], 0.),
+            Vector::new(subpx[1], 0.),
+            Vector::new(subpx[2], 0.),
+        ]),
+        Format::SubpixelVRgba => Some([Vector::new(0., -0.3), Vector::ZERO, Vector::new(0., 0.3)]),
+        Format::SubpixelVBgra => Some([Vector::new(0., 0.3), Vector::ZERO, Vector::new(0., -0.3)]),
+    }
+}
+
+/// Returns true if the color channels of a format are offset vertically and
+/// the mask requires an additional row of padding above and below.
+fn vertical_subpixel(format: Format) -> bool {
+    match subpixel_offsets(format) {
+        Some(subpx) => subpx.iter().any(|offset| offset.y != 0.),
+        None => false,
+    }
+}
+
+/// Parameters for the rasterization passes required to render a mask.
+struct Passes {
+    shift: Vector,
+    subpx: Option<[Vector; 3]>,
+    wide: Option<Wide>,
+    packed: bool,
+    clip: Option<Bounds>,
+    width: u32,
+    height: u32,
+    fill: Fill,
+    y_up: bool,
+    invert: bool,
+    alpha: SubpixelAlpha,
+    lcd_filter: LcdFilter,
+}
+
+impl Passes {
+    /// Returns the range of rows in the target that are covered by the clip
+    /// region.
+    fn rows(&self) -> core::ops::Range<usize> {
+        let [_, y0, _, y1] = clip_rect(self.clip, self.width, self.height);
+        if self.y_up {
+            let h = self.height as usize;
+            h - y1..h - y0
+        } else {
+            y0..y1
+        }
+    }
+
+    /// Sets the pixels of a row within the clip region to full coverage.
+    /// This is required before rendering an inverted mask since pixels that
+    /// are not crossed by the path are never written.
+    fn fill_full(&self, row: &mut [u8]) {
+        let [x0, _, x1, _] = clip_rect(self.clip, self.width, self.height);
+        if let Some(wide) = self.wide {
+            wide.fill(&mut row[x0 * wide.size()..x1 * wide.size()], 0xFFFF);
+        } else if self.packed {
+            write_bits(row, x0, x1 - x0, true);
+        } else if self.subpx.is_some() {
+            for pixel in row[x0 * 4..x1 * 4].chunks_exact_mut(4) {
+                pixel[..3].fill(255);
+            }
+        } else {
+            row[x0..x1].fill(255);
+        }
+    }
+
+    /// Returns the filter and the order of the color channels from left to
+    /// right if the subpixel coverage must be filtered.
+    fn lcd(&self) -> Option<(LcdFilter, [usize; 3])> {
+        let subpx = self.subpx?;
+        if self.lcd_filter == LcdFilter::None || subpx.iter().any(|offset| offset.y != 0.) {
+            return None;
+        }
+        let mut order = [0, 1, 2];
+        order.sort_by(|a, b| {
+            subpx[*a]
+                .x
+                .partial_cmp(&subpx[*b].x)
+                .unwrap_or(Ordering::Equal)
+        });
+        Some((self.lcd_filter, order))
+    }
+
+    /// Returns true if the subpixel pixels must be filtered or have their
+    /// alpha channel resolved once all channels are rendered.
+    fn finishes_subpixel(&self) -> bool {
+        self.subpx.is_some() && (self.alpha != SubpixelAlpha::None || self.lcd().is_some())
+    }
+
+    /// Filters the subpixel pixels of a row within the clip region and
+    /// resolves their alpha channel from the coverage of the color channels.
+    fn finish_subpixel(&self, row: &mut [u8]) {
+        let [x0, _, x1, _] = clip_rect(self.clip, self.width, self.height);
+        let pixels = &mut row[x0 * 4..x1 * 4];
+        if let Some((filter, order)) = self.lcd() {
+            filter_lcd(filter, order, pixels);
+        }
+        if self.alpha != SubpixelAlpha::None {
+            resolve_alpha(self.alpha, pixels);
+        }
+    }
+}
+
+/// Applies an LCD filter to the color channels of a row of subpixel pixels,
+/// visiting the channels of each pixel in the given order.
+fn filter_lcd(filter: LcdFilter, order: [usize; 3], pixels: &mut [u8]) {
+    let count = pixels.len() / 4 * 3;
+    let index = |i: usize| i / 3 * 4 + order[i % 3];
+    if let Some(weights) = filter.weights() {
+        // The two preceding values are kept since they have already been
+        // replaced by their filtered values.
+        let (mut prev2, mut prev1) = (0, 0);
+        for i in 0..count {
+            let value = pixels[index(i)] as u32;
+            let next1 = if i + 1 < count {
+                pixels[index(i + 1)] as u32
+            } else {
+                0
+            };
+            let next2 = if i + 2 < count {
+                pixels[index(i + 2)] as u32
+            } else {
+                0
+            };
+            let sum = weights[0] * prev2
+                + weights[1] * prev1
+                + weights[2] * value
+                + weights[3] * next1
+                + weights[4] * next2;
+            pixels[index(i)] = (sum >> 8).min(255) as u8;
+            prev2 = prev1;
+            prev1 = value;
+        }
+    } else if filter == LcdFilter::Legacy {
+        const WEIGHTS: [[u32; 3]; 3] = [
+            [65538 * 9 / 13, 65538 / 6, 65538 / 13],
+            [65538 * 3 / 13, 65538 * 4 / 6, 65538 * 3 / 13],
+            [65538 / 13, 65538 / 6, 65538 * 9 / 13],
+        ];
+        for i in (0..count).step_by(3) {
+            let values = [
+                pixels[index(i)] as u32,
+                pixels[index(i + 1)] as u32,
+                pixels[index(i + 2)] as u32,
+            ];
+            for (j, weights) in WEIGHTS.iter().enumerate() {
+                let sum: u32 = weights.iter().zip(&values).map(|(w, v)| w * v).sum();
+                pixels[index(i + j)] = (sum >> 16).min(255) as u8;
+            }
+        }
+    }
+}
+
+/// Sets the alpha channel of each subpixel pixel to the maximum coverage of
+/// the color channels, dividing the color channels by the alpha for
+/// straight alpha.
+fn resolve_alpha(alpha: SubpixelAlpha, pixels: &mut [u8]) {
+    for pixel in pixels.chunks_exact_mut(4) {
+        let a = pixel[0].max(pixel[1]).max(pixel[2]);
+        pixel[3] = a;
+        if alpha == SubpixelAlpha::Straight && a != 0 && a != 255 {
+            let a = a as u32;
+            for c in &mut pixel[..3] {
+                *c = ((*c as u32 * 255 + a / 2) / a) as u8;
+            }
+        }
+    }
+}
+
+/// Representation of coverage values that are wider than a byte.
+#[derive(Copy, Clone, PartialEq)]
+enum Wide {
+    U16,
+    F32,
+}
+
+impl Wide {
+    fn from_format(format: Format) -> Option<Self> {
+        match format {
+            Format::Alpha16 => Some(Self::U16),
+            Format::AlphaF32 => Some(Self::F32),
+            _ => None,
+        }
+    }
+
+    /// Returns the number of bytes occupied by a single value.
+    fn size(self) -> usize {
+        match self {
+            Self::U16 => 2,
+            Self::F32 => 4,
+        }
+    }
+
+    /// Writes the 16-bit coverage to each pixel in the span.
+    fn fill(self, span: &mut [u8], coverage: u16) {
+        match self {
+            Self::U16 => {
+                let bytes = coverage.to_ne_bytes();
+                for pixel in span.chunks_exact_mut(2) {
+                    pixel.copy_from_slice(&bytes);
+                }
+            }
+            Self::F32 => {
+                let bytes = (coverage as f32 / 65535.).to_ne_bytes();
+                for pixel in span.chunks_exact_mut(4) {
+                    pixel.copy_from_slice(&bytes);
+                }
+            }
+        }
+    }
+
+    /// Reads the 16-bit coverage of the pixel at the start of the slice.
+    fn read(self, pixel: &[u8]) -> u16 {
+        match self {
+            Self::U16 => u16::from_ne_bytes([pixel[0], pixel[1]]),
+            Self::F32 => {
+                let value = f32::from_ne_bytes([pixel[0], pixel[1], pixel[2], pixel[3]]);
+                (value.clamp(0., 1.) * 65535. + 0.5) as u16
+            }
+        }
+    }
+}
+
+/// Destination of the rasterization passes.
+trait Target {
+    fn rasterize<S: RasterStorage>(
+        &mut self,
+        passes: &Passes,
+        ras: &mut Rasterizer<S>,
+        apply: &mut impl FnMut(&mut Rasterizer<S>),
+    );
+}
+
+/// Linear buffer with a fixed number of bytes between rows.
+struct Buffer<'b> {
+    buffer: &'b mut [u8],
+    pitch: usize,
+}
+
+impl Target for Buffer<'_> {
+    fn rasterize<S: RasterStorage>(
+        &mut self,
+        passes: &Passes,
+        ras: &mut Rasterizer<S>,
+        apply: &mut impl FnMut(&mut Rasterizer<S>),
+    ) {
+        let (w, h) = (passes.width, passes.height);
+        let buf = &mut *self.buffer;
+        if passes.invert {
+            for y in passes.rows() {
+                passes.fill_full(&mut buf[y * self.pitch..]);
+            }
+        }
+        ras.set_inverted(passes.invert);
+        if let Some(wide) = passes.wide {
+            let size = wide.size();
+            ras.rasterize_write16(
+                passes.shift,
+                w,
+                h,
+                apply,
+                passes.fill,
+                self.pitch,
+                passes.y_up,
+                &mut |row_offset, x, count, coverage| {
+                    let start = row_offset + x * size;
+                    wide.fill(&mut buf[start..start + count * size], coverage);
                 },
             );
+        } else if passes.packed {
             ras.rasterize_write(
-                shift + subpx[1],
+                passes.shift,
                 w,
                 h,
-                &mut |r| {
-                    apply(data, style, transform, r);
+                apply,
+                passes.fill,
+                self.pitch,
+                passes.y_up,
+                &mut |row_offset, x, count, coverage| {
+                    write_bits(&mut buf[row_offset..], x, count, coverage >= 128);
                 },
-                fill,
-                pitch,
-                y_up,
+            );
+        } else if let Some(subpx) = passes.subpx {
+            for (channel, offset) in subpx.iter().enumerate() {
+                ras.rasterize_write(
+                    passes.shift + *offset,
+                    w,
+                    h,
+                    apply,
+                    passes.fill,
+                    self.pitch,
+                    passes.y_up,
+                    &mut |row_offset, x, count, coverage| {
+                        let buf = &mut buf[row_offset..];
+                        let mut i = 0;
+                        let mut j = x * 4 + channel;
+                        while i < count {
+                            buf[j] = coverage;
+                            i += 1;
+                            j += 4;
+                        }
+                    },
+                );
+            }
+            if passes.finishes_subpixel() {
+                for y in passes.rows() {
+                    passes.finish_subpixel(&mut buf[y * self.pitch..]);
+                }
+            }
+        } else {
+            ras.rasterize(
+                passes.shift,
+                w,
+                h,
+                apply,
+                passes.fill,
+                buf,
+                self.pitch,
+                passes.y_up,
+            );
+        }
+    }
+}
+
+/// Linear buffer that gathers coverage statistics while rasterizing.
+struct StatsBuffer<'b> {
+    buffer: &'b mut [u8],
+    pitch: usize,
+    subpx: bool,
+    alpha: SubpixelAlpha,
+    lcd: Option<(LcdFilter, [usize; 3])>,
+    columns: (usize, usize),
+    nonzero: u32,
+    histogram: [u32; 256],
+    min: (usize, usize),
+    max: (usize, usize),
+}
+
+impl<'b> StatsBuffer<'b> {
+    fn new(buffer: &'b mut [u8], pitch: usize) -> Self {
+        Self {
+            buffer,
+            pitch,
+            subpx: false,
+            alpha: SubpixelAlpha::None,
+            lcd: None,
+            columns: (0, 0),
+            nonzero: 0,
+            histogram: [0; 256],
+            min: (usize::MAX, usize::MAX),
+            max: (0, 0),
+        }
+    }
+
+    fn add_ink(&mut self, x: usize, y: usize, count: usize) {
+        self.min.0 = self.min.0.min(x);
+        self.min.1 = self.min.1.min(y);
+        self.max.0 = self.max.0.max(x + count);
+        self.max.1 = self.max.1.max(y + 1);
+    }
+
+    fn finish(mut self, width: u32, height: u32) -> RenderStats {
+        let empty = self.min.0 >= self.max.0;
+        if self.subpx && !empty {
+            if let Some((filter, order)) = self.lcd {
+                if filter.weights().is_some() {
+                    // The filter spreads coverage into the adjacent pixels.
+                    self.min.0 = self.min.0.saturating_sub(1).max(self.columns.0);
+                    self.max.0 = (self.max.0 + 1).min(self.columns.1);
+                }
+                for y in self.min.1..self.max.1 {
+                    let pixels = &mut self.buffer[y * self.pitch..];
+                    filter_lcd(filter, order, &mut pixels[self.min.0 * 4..self.max.0 * 4]);
+                }
+            }
+            // Channels are rasterized in separate passes, so classify the
+            // pixels within the ink bounds once all passes are complete.
+            for y in self.min.1..self.max.1 {
+                let row = &self.buffer[y * self.pitch..];
+                for x in self.min.0..self.max.0 {
+                    let c = row[x * 4..x * 4 + 3].iter().copied().max().unwrap_or(0);
+                    if c != 0 {
+                        self.nonzero += 1;
+                        self.histogram[c as usize] += 1;
+                    }
+                }
+                if self.alpha != SubpixelAlpha::None {
+                    let pixels = &mut self.buffer[y * self.pitch..];
+                    resolve_alpha(self.alpha, &mut pixels[self.min.0 * 4..self.max.0 * 4]);
+                }
+            }
+        }
+        let covered: u32 = self.histogram[1..].iter().sum();
+        self.histogram[0] = (width * height).saturating_sub(covered);
+        let (x, y, w, h) = if empty {
+            (0, 0, 0, 0)
+        } else {
+            (
+                self.min.0,
+                self.min.1,
+                self.max.0 - self.min.0,
+                self.max.1 - self.min.1,
+            )
+        };
+        RenderStats {
+            nonzero: self.nonzero,
+            histogram: self.histogram,
+            ink_x: x as u32,
+            ink_y: y as u32,
+            ink_width: w as u32,
+            ink_height: h as u32,
+        }
+    }
+}
+
+impl Target for StatsBuffer<'_> {
+    fn rasterize<S: RasterStorage>(
+        &mut self,
+        passes: &Passes,
+        ras: &mut Rasterizer<S>,
+        apply: &mut impl FnMut(&mut Rasterizer<S>),
+    ) {
+        let (w, h) = (passes.width, passes.height);
+        let pitch = self.pitch.max(1);
+        self.subpx = passes.subpx.is_some();
+        self.alpha = passes.alpha;
+        self.lcd = passes.lcd();
+        let [x0, _, x1, _] = clip_rect(passes.clip, w, h);
+        self.columns = (x0, x1);
+        if let Some(wide) = passes.wide {
+            let size = wide.size();
+            ras.rasterize_write16(
+                passes.shift,
+                w,
+                h,
+                apply,
+                passes.fill,
+                self.pitch,
+                passes.y_up,
                 &mut |row_offset, x, count, coverage| {
-                    let buf = &mut buf[row_offset..];
-                    let mut i = 0;
-                    let mut j = x * 4 + 1;
-                    while i < count {
-                        buf[j] = coverage;
-                        i += 1;
-                        j += 4;
+                    let start = row_offset + x * size;
+                    wide.fill(&mut self.buffer[start..start + count * size], coverage);
+                    if coverage != 0 {
+                        self.add_ink(x, row_offset / pitch, count);
+                        self.nonzero += count as u32;
+                        self.histogram[(coverage >> 8) as usize] += count as u32;
                     }
                 },
             );
+            return;
+        } else if passes.packed {
             ras.rasterize_write(
-                shift + subpx[2],
+                passes.shift,
                 w,
                 h,
-                &mut |r| {
-                    apply(data, style, transform, r);
+                apply,
+                passes.fill,
+                self.pitch,
+                passes.y_up,
+                &mut |row_offset, x, count, coverage| {
+                    let on = coverage >= 128;
+                    write_bits(&mut self.buffer[row_offset..], x, count, on);
+                    if on {
+                        self.add_ink(x, row_offset / pitch, count);
+                        self.nonzero += count as u32;
+                        self.histogram[255] += count as u32;
+                    }
                 },
-                fill,
-                pitch,
-                y_up,
+            );
+            return;
+        }
+        let (stride, offsets) = match passes.subpx {
+            Some(subpx) => (4, subpx),
+            None => (1, [Vector::ZERO; 3]),
+        };
+        for (channel, offset) in offsets.iter().take(stride.min(3)).enumerate() {
+            ras.rasterize_write(
+                passes.shift + *offset,
+                w,
+                h,
+                apply,
+                passes.fill,
+                self.pitch,
+                passes.y_up,
                 &mut |row_offset, x, count, coverage| {
-                    let buf = &mut buf[row_offset..];
-                    let mut i = 0;
-                    let mut j = x * 4 + 2;
-                    while i < count {
+                    let buf = &mut self.buffer[row_offset..];
+                    let mut j = x * stride + channel;
+                    for _ in 0..count {
                         buf[j] = coverage;
-                        i += 1;
-                        j += 4;
+                        j += stride;
                     }
+                    if coverage != 0 {
+                        self.add_ink(x, row_offset / pitch, count);
+                        if stride == 1 {
+                            self.nonzero += count as u32;
+                            self.histogram[coverage as usize] += count as u32;
+                        }
+                    }
+                },
+            );
+        }
+    }
+}
+
+/// Buffer of signed coverage deltas.
+struct Deltas<'b> {
+    deltas: &'b mut [i32],
+    pitch: usize,
+}
+
+impl Target for Deltas<'_> {
+    fn rasterize<S: RasterStorage>(
+        &mut self,
+        passes: &Passes,
+        ras: &mut Rasterizer<S>,
+        apply: &mut impl FnMut(&mut Rasterizer<S>),
+    ) {
+        ras.rasterize_deltas(
+            passes.shift,
+            passes.width,
+            passes.height,
+            apply,
+            self.deltas,
+            self.pitch,
+            passes.y_up,
+        );
+    }
+}
+
+/// Linear buffer that combines coverage with its existing contents.
+struct Blend<'b> {
+    buffer: &'b mut [u8],
+    pitch: usize,
+    op: Composite,
+}
+
+impl Target for Blend<'_> {
+    fn rasterize<S: RasterStorage>(
+        &mut self,
+        passes: &Passes,
+        ras: &mut Rasterizer<S>,
+        apply: &mut impl FnMut(&mut Rasterizer<S>),
+    ) {
+        let (w, h) = (passes.width, passes.height);
+        ras.set_inverted(passes.invert);
+        let mut blender = Blender {
+            buffer: &mut *self.buffer,
+            pitch: self.pitch,
+            height: h as usize,
+            y_up: passes.y_up,
+            layout: Layout::Byte(1, 0),
+            op: self.op,
+            invert: passes.invert,
+            region: clip_rect(passes.clip, w, h),
+            cursor: (0, 0),
+            shader: None,
+            colors: Vec::new(),
+        };
+        if let Some(wide) = passes.wide {
+            blender.begin(Layout::Wide(wide));
+            ras.rasterize_write16(
+                passes.shift,
+                w,
+                h,
+                apply,
+                passes.fill,
+                self.pitch,
+                passes.y_up,
+                &mut |row_offset, x, count, coverage| {
+                    blender.span(row_offset, x, count, coverage as u32);
+                },
+            );
+            blender.finish();
+            return;
+        }
+        let (stride, offsets) = match passes.subpx {
+            Some(subpx) => (4, subpx),
+            None => (1, [Vector::ZERO; 3]),
+        };
+        for (channel, offset) in offsets.iter().take(stride.min(3)).enumerate() {
+            blender.begin(if passes.packed {
+                Layout::Bit
+            } else {
+                Layout::Byte(stride, channel)
+            });
+            ras.rasterize_write(
+                passes.shift + *offset,
+                w,
+                h,
+                apply,
+                passes.fill,
+                self.pitch,
+                passes.y_up,
+                &mut |row_offset, x, count, coverage| {
+                    blender.span(row_offset, x, count, coverage as u32);
                 },
             );
+            blender.finish();
+        }
+    }
+}
+
+/// Arrangement of the values written by a single rasterization pass.
+#[derive(Copy, Clone)]
+enum Layout {
+    /// Single byte values with the specified stride and channel offset.
+    Byte(usize, usize),
+    /// Wide values occupying a full pixel.
+    Wide(Wide),
+    /// Single bit values packed eight to a byte.
+    Bit,
+    /// Premultiplied RGBA color composited over a full pixel.
+    Paint([u32; 4]),
+    /// RGBA colors produced by the shader composited over full pixels.
+    Shade,
+}
+
+/// Function that produces the non-premultiplied RGBA colors for a span of
+/// pixels starting at the specified column and row.
+type Shader<'s> = dyn FnMut(u32, u32, &mut [[u8; 4]]) + 's;
+
+/// State for combining the spans of a rasterization pass with a buffer.
+struct Blender<'b> {
+    buffer: &'b mut [u8],
+    pitch: usize,
+    height: usize,
+    y_up: bool,
+    layout: Layout,
+    op: Composite,
+    invert: bool,
+    region: [usize; 4],
+    /// Row and column following the last span, in rasterization order.
+    cursor: (usize, usize),
+    shader: Option<&'b mut Shader<'b>>,
+    colors: Vec<[u8; 4]>,
+}
+
+impl Blender<'_> {
+    fn begin(&mut self, layout: Layout) {
+        self.layout = layout;
+        self.cursor = (self.region[1], self.region[0]);
+    }
+
+    fn span(&mut self, row_offset: usize, x: usize, count: usize, coverage: u32) {
+        let row = row_offset / self.pitch.max(1);
+        let y = if self.y_up {
+            self.height - 1 - row
         } else {
-            ras.rasterize(
-                shift,
+            row
+        };
+        if self.blends_gaps() {
+            self.blend_to(y, x);
+        }
+        self.blend_span(row_offset, x, count, coverage);
+        self.cursor = (y, x + count);
+    }
+
+    fn finish(&mut self) {
+        if self.blends_gaps() {
+            self.blend_to(self.region[3], self.region[0]);
+        }
+    }
+
+    /// Returns true if pixels that are not crossed by the path modify the
+    /// buffer. Without inversion, they have no coverage and only clear the
+    /// buffer when multiplying. With inversion, they have full coverage and
+    /// leave the buffer unchanged only when multiplying.
+    fn blends_gaps(&self) -> bool {
+        (self.op == Composite::Multiply) != self.invert
+    }
+
+    /// Blends the pixels between the cursor and the specified position with
+    /// the coverage of pixels that are not crossed by the path.
+    fn blend_to(&mut self, y: usize, x: usize) {
+        let gap = match (self.invert, self.layout) {
+            (false, _) => 0,
+            (true, Layout::Byte(..))
+            | (true, Layout::Bit)
+            | (true, Layout::Paint(_))
+            | (true, Layout::Shade) => 255,
+            (true, Layout::Wide(_)) => 65535,
+        };
+        let [x0, _, x1, _] = self.region;
+        while self.cursor < (y, x) {
+            let (cy, cx) = self.cursor;
+            let end = if cy < y { x1 } else { x };
+            let row_offset = if self.y_up {
+                self.pitch * (self.height - 1 - cy)
+            } else {
+                self.pitch * cy
+            };
+            self.blend_span(row_offset, cx, end - cx, gap);
+            self.cursor = if cy < y { (cy + 1, x0) } else { (cy, end) };
+        }
+    }
+
+    fn blend_span(&mut self, row_offset: usize, x: usize, count: usize, coverage: u32) {
+        if let Layout::Shade = self.layout {
+            return self.shade(row_offset, x, count, coverage);
+        }
+        for x in x..x + count {
+            self.blend(row_offset, x, coverage);
+        }
+    }
+
+    fn blend(&mut self, row_offset: usize, x: usize, coverage: u32) {
+        let (dst, one) = match self.layout {
+            Layout::Byte(stride, channel) => {
+                (self.buffer[row_offset + x * stride + channel] as u32, 255)
+            }
+            Layout::Wide(wide) => (
+                wide.read(&self.buffer[row_offset + x * wide.size()..]) as u32,
+                65535,
+            ),
+            Layout::Bit => {
+                // Both the existing and rendered coverage are reduced to a
+                // single bit before blending.
+                let bit = self.buffer[row_offset + x / 8] & (0x80 >> (x % 8)) != 0;
+                let coverage = if coverage >= 128 { 255 } else { 0 };
+                let value = self.op.blend(if bit { 255 } else { 0 }, coverage, 255);
+                return write_bits(&mut self.buffer[row_offset..], x, 1, value >= 128);
+            }
+            Layout::Paint(color) => {
+                let start = row_offset + x * 4;
+                return source_over(&mut self.buffer[start..start + 4], color, coverage);
+            }
+            Layout::Shade => unreachable!("shaded spans are composited directly"),
+        };
+        self.store(row_offset, x, self.op.blend(dst, coverage, one));
+    }
+
+    fn store(&mut self, row_offset: usize, x: usize, value: u32) {
+        match self.layout {
+            Layout::Byte(stride, channel) => {
+                self.buffer[row_offset + x * stride + channel] = value as u8;
+            }
+            Layout::Wide(wide) => {
+                let start = row_offset + x * wide.size();
+                wide.fill(&mut self.buffer[start..start + wide.size()], value as u16);
+            }
+            Layout::Bit | Layout::Paint(_) | Layout::Shade => {
+                unreachable!("packed and painted pixels are composited directly")
+            }
+        }
+    }
+
+    /// Composites the colors produced by the shader over a span of pixels.
+    fn shade(&mut self, row_offset: usize, x: usize, count: usize, coverage: u32) {
+        let shader = match &mut self.shader {
+            Some(shader) if coverage != 0 && count != 0 => shader,
+            _ => return,
+        };
+        self.colors.clear();
+        self.colors.resize(count, [0; 4]);
+        let y = row_offset / self.pitch.max(1);
+        shader(x as u32, y as u32, &mut self.colors);
+        let start = row_offset + x * 4;
+        let pixels = self.buffer[start..start + count * 4].chunks_exact_mut(4);
+        for (pixel, color) in pixels.zip(&self.colors) {
+            let a = color[3] as u32;
+            let premultiply = |c: u8| (c as u32 * a + 127) / 255;
+            let color = [
+                premultiply(color[0]),
+                premultiply(color[1]),
+                premultiply(color[2]),
+                a,
+            ];
+            source_over(pixel, color, coverage);
+        }
+    }
+}
+
+/// Composites a premultiplied color, scaled by coverage, over a premultiplied
+/// RGBA pixel.
+fn source_over(pixel: &mut [u8], color: [u32; 4], coverage: u32) {
+    if coverage == 0 {
+        return;
+    }
+    let alpha = (color[3] * coverage + 127) / 255;
+    for (dst, src) in pixel.iter_mut().zip(&color) {
+        let src = (src * coverage + 127) / 255;
+        *dst = (src + (*dst as u32 * (255 - alpha) + 127) / 255) as u8;
+    }
+}
+
+/// Buffer of premultiplied RGBA pixels that a solid color or the colors
+/// produced by a shader are composited into.
+struct Paint<'b> {
+    buffer: &'b mut [u8],
+    pitch: usize,
+    color: [u32; 4],
+    shader: Option<&'b mut Shader<'b>>,
+}
+
+impl Target for Paint<'_> {
+    fn rasterize<S: RasterStorage>(
+        &mut self,
+        passes: &Passes,
+        ras: &mut Rasterizer<S>,
+        apply: &mut impl FnMut(&mut Rasterizer<S>),
+    ) {
+        let (w, h) = (passes.width, passes.height);
+        ras.set_inverted(passes.invert);
+        let layout = match self.shader {
+            Some(_) => Layout::Shade,
+            None => Layout::Paint(self.color),
+        };
+        // Source-over leaves pixels without coverage unchanged, so gaps are
+        // only blended when inverted.
+        let mut blender = Blender {
+            buffer: &mut *self.buffer,
+            pitch: self.pitch,
+            height: h as usize,
+            y_up: passes.y_up,
+            layout,
+            op: Composite::Add,
+            invert: passes.invert,
+            region: clip_rect(passes.clip, w, h),
+            cursor: (0, 0),
+            shader: match &mut self.shader {
+                Some(shader) => Some(&mut **shader),
+                None => None,
+            },
+            colors: Vec::new(),
+        };
+        blender.begin(layout);
+        ras.rasterize_write(
+            passes.shift,
+            w,
+            h,
+            apply,
+            passes.fill,
+            self.pitch,
+            passes.y_up,
+            &mut |row_offset, x, count, coverage| {
+                blender.span(row_offset, x, count, coverage as u32);
+            },
+        );
+        blender.finish();
+    }
+}
+
+/// Target that emits spans of coverage to a closure.
+struct Spans<'f, F>(&'f mut F);
+
+impl<F: FnMut(u32, u32, u32, u8)> Target for Spans<'_, F> {
+    fn rasterize<S: RasterStorage>(
+        &mut self,
+        passes: &Passes,
+        ras: &mut Rasterizer<S>,
+        apply: &mut impl FnMut(&mut Rasterizer<S>),
+    ) {
+        let f = &mut *self.0;
+        // With a pitch of one, the row offset is simply the row index.
+        ras.rasterize_write(
+            passes.shift,
+            passes.width,
+            passes.height,
+            apply,
+            passes.fill,
+            1,
+            passes.y_up,
+            &mut |y, x, count, coverage| {
+                if coverage != 0 {
+                    f(y as u32, x as u32, (x + count) as u32, coverage);
+                }
+            },
+        );
+    }
+}
+
+/// Target addressed by rows.
+struct Rows<'r, R: ?Sized>(&'r mut R);
+
+/// Horizontal band of a larger mask.
+struct Band<'b> {
+    buffer: &'b mut [u8],
+    pitch: usize,
+    top: usize,
+}
+
+impl RowTarget for Band<'_> {
+    fn row(&mut self, y: usize) -> &mut [u8] {
+        &mut self.buffer[(y - self.top) * self.pitch..]
+    }
+}
+
+impl<R: RowTarget + ?Sized> Target for Rows<'_, R> {
+    fn rasterize<S: RasterStorage>(
+        &mut self,
+        passes: &Passes,
+        ras: &mut Rasterizer<S>,
+        apply: &mut impl FnMut(&mut Rasterizer<S>),
+    ) {
+        let (w, h) = (passes.width, passes.height);
+        let rows = &mut *self.0;
+        if passes.invert {
+            for y in passes.rows() {
+                passes.fill_full(rows.row(y));
+            }
+        }
+        ras.set_inverted(passes.invert);
+        if let Some(wide) = passes.wide {
+            let size = wide.size();
+            ras.rasterize_write16(
+                passes.shift,
                 w,
                 h,
-                &mut |r| {
-                    apply(data, style, transform, r);
+                apply,
+                passes.fill,
+                1,
+                passes.y_up,
+                &mut |y, x, count, coverage| {
+                    wide.fill(&mut rows.row(y)[x * size..(x + count) * size], coverage);
+                },
+            );
+            return;
+        } else if passes.packed {
+            ras.rasterize_write(
+                passes.shift,
+                w,
+                h,
+                apply,
+                passes.fill,
+                1,
+                passes.y_up,
+                &mut |y, x, count, coverage| {
+                    write_bits(rows.row(y), x, count, coverage >= 128);
+                },
+            );
+            return;
+        }
+        let (stride, offsets) = match passes.subpx {
+            Some(subpx) => (4, subpx),
+            None => (1, [Vector::ZERO; 3]),
+        };
+        for (channel, offset) in offsets.iter().take(stride.min(3)).enumerate() {
+            // With a pitch of one, the row offset is simply the row index.
+            ras.rasterize_write(
+                passes.shift + *offset,
+                w,
+                h,
+                apply,
+                passes.fill,
+                1,
+                passes.y_up,
+                &mut |y, x, count, coverage| {
+                    let row = rows.row(y);
+                    let mut j = x * stride + channel;
+                    for _ in 0..count {
+                        row[j] = coverage;
+                        j += stride;
+                    }
                 },
-                fill,
-                buf,
-                pitch,
-                y_up,
             );
         }
+        if passes.finishes_subpixel() {
+            for y in passes.rows() {
+                passes.finish_subpixel(rows.row(y));
+            }
+        }
     }
 }
@@ -0,0 +1,138 @@
+//! Offsetting of path center lines.
+
+use super::command::Command;
+use super::decoration::flatten;
+use super::geometry::{normal, Point, Vector};
+use super::path_builder::{arc, ArcSize, ArcSweep, PathBuilder};
+use super::style::{Join, MiterFallback};
+#[cfg(not(feature = "std"))]
+use super::F32Ext;
+
+use core::borrow::Borrow;
+
+/// Emits the flattened path offset along the normal of each subpath by the
+/// specified distance. Corners on the outer side of a turn are connected
+/// with the join of the offset and corners on the inner side are trimmed at
+/// the intersection of the offset edges.
+pub(super) fn offset_into<I>(
+    commands: I,
+    offset: &Offset,
+    flatness: f32,
+    sink: &mut impl PathBuilder,
+) where
+    I: Iterator + Clone,
+    I::Item: Borrow<Command>,
+{
+    for (mut points, closed) in flatten(commands, flatness) {
+        if closed && points.len() > 2 && points.first() == points.last() {
+            points.pop();
+        }
+        let len = points.len();
+        if len < 2 {
+            continue;
+        }
+        let edge = |i: usize| {
+            let (a, b) = (points[i % len], points[(i + 1) % len]);
+            let n = normal(a, b) * offset.distance;
+            (a + n, b + n)
+        };
+        let mut start = true;
+        if closed {
+            let first = offset.corner(sink, edge(len - 1), edge(0), &mut start);
+            for i in 1..len {
+                offset.corner(sink, edge(i - 1), edge(i), &mut start);
+            }
+            if !sink.current_point().nearly_eq(first) {
+                sink.line_to(first);
+            }
+            sink.close();
+        } else {
+            sink.move_to(edge(0).0);
+            start = false;
+            for i in 1..len - 1 {
+                offset.corner(sink, edge(i - 1), edge(i), &mut start);
+            }
+            sink.line_to(edge(len - 2).1);
+        }
+    }
+}
+
+/// Distance and corner style of an offset path.
+pub(super) struct Offset {
+    pub distance: f32,
+    pub join: Join,
+    pub miter_limit: f32,
+    pub miter_fallback: MiterFallback,
+}
+
+impl Offset {
+    /// Emits the corner between two consecutive offset edges and returns the
+    /// first point of the corner. The first point begins a new subpath if
+    /// `start` is true.
+    fn corner(
+        &self,
+        sink: &mut impl PathBuilder,
+        from: (Point, Point),
+        to: (Point, Point),
+        start: &mut bool,
+    ) -> Point {
+        let (d1, d2) = (from.1 - from.0, to.1 - to.0);
+        let cross = d1.cross(d2);
+        let (a, b) = (from.1, to.0);
+        if a.nearly_eq(b) || cross.abs() <= 1e-6 * d1.length() * d2.length() {
+            point_to(sink, a, start);
+            return a;
+        }
+        let t = (to.0 - from.0).cross(d2) / cross;
+        let u = (to.0 - from.0).cross(d1) / cross;
+        let tip = from.0 + d1 * t;
+        if cross * self.distance < 0. {
+            // Trim both edges at their intersection when it lies within
+            // them.
+            if t > 0. && u < 1. {
+                point_to(sink, tip, start);
+                return tip;
+            }
+            point_to(sink, a, start);
+            sink.line_to(b);
+            return a;
+        }
+        let n1 = Vector::new(d1.y, -d1.x).normalize();
+        let n2 = Vector::new(d2.y, -d2.x).normalize();
+        let sin_half = ((1. + n1.dot(n2)) * 0.5).max(0.).sqrt();
+        let round = match self.join {
+            Join::Bevel => false,
+            Join::Round => true,
+            _ if sin_half * self.miter_limit >= 1. => {
+                point_to(sink, tip, start);
+                return tip;
+            }
+            Join::Miter => self.miter_fallback == MiterFallback::Round,
+            _ => false,
+        };
+        point_to(sink, a, start);
+        if round {
+            let r = self.distance.abs();
+            let sweep = if cross > 0. {
+                ArcSweep::Positive
+            } else {
+                ArcSweep::Negative
+            };
+            arc(sink, a, r, r, 0., ArcSize::Small, sweep, b);
+        } else {
+            sink.line_to(b);
+        }
+        a
+    }
+}
+
+/// Begins a new subpath at the point if `start` is true or adds a line to
+/// the point otherwise.
+fn point_to(sink: &mut impl PathBuilder, point: Point, start: &mut bool) {
+    if *start {
+        sink.move_to(point);
+        *start = false;
+    } else {
+        sink.line_to(point);
+    }
+}
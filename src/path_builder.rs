@@ -7,7 +7,7 @@ use super::F32Ext;
 use crate::lib::Vec;
 
 /// Describes the size of an arc.
-#[derive(Copy, Clone, PartialEq)]
+#[derive(Copy, Clone, PartialEq, Debug)]
 pub enum ArcSize {
     /// An arc of <= 180 degrees will be drawn.
     Small,
@@ -16,7 +16,7 @@ pub enum ArcSize {
 }
 
 /// Describes the sweep direction for an arc.
-#[derive(Copy, Clone, PartialEq)]
+#[derive(Copy, Clone, PartialEq, Debug)]
 pub enum ArcSweep {
     /// The arc is drawn in a positive angle direction.
     Positive,
@@ -29,6 +29,15 @@ pub trait PathBuilder: Sized {
     /// Returns the current point of the path.
     fn current_point(&self) -> Point;
 
+    /// Returns the maximum distance, in the coordinate space of the builder,
+    /// between a curve and its approximation when conic sections and arcs
+    /// are converted to other curves. Builders that transform or rasterize the
+    /// path override this so that the error is maintained at the final
+    /// scale. The default is 0.1.
+    fn tolerance(&self) -> f32 {
+        DEFAULT_TOLERANCE
+    }
+
     /// Moves to the specified point, beginning a new subpath.
     fn move_to(&mut self, to: impl Into<Point>) -> &mut Self;
 
@@ -87,10 +96,34 @@ pub trait PathBuilder: Sized {
         self.quad_to(control.into() + r, to.into() + r)
     }
 
+    /// Adds a conic section from the current point through the specified
+    /// control point to the final point with the given weight. This will
+    /// begin a new subpath if the path is empty or the previous subpath was
+    /// closed.
+    ///
+    /// The default implementation approximates the conic with a sequence of
+    /// quadratic curves.
+    fn conic_to(
+        &mut self,
+        control: impl Into<Point>,
+        to: impl Into<Point>,
+        weight: f32,
+    ) -> &mut Self {
+        let from = self.current_point();
+        let tolerance = self.tolerance();
+        for (c, p) in ConicQuads::new(from, control.into(), to.into(), weight, tolerance) {
+            self.quad_to(c, p);
+        }
+        self
+    }
+
     /// Adds an arc with the specified x- and y-radius, rotation angle, arc size,
     /// and arc sweep from the current point to the specified end point. The center
     /// point of the arc will be computed from the parameters. This will begin a
     /// new subpath if the path is empty or the previous subpath was closed.
+    ///
+    /// The default implementation approximates the arc with a sequence of
+    /// cubic bezier curves that stay within the tolerance of the builder.
     fn arc_to(
         &mut self,
         rx: f32,
@@ -208,6 +241,8 @@ impl PathBuilder for Vec<Command> {
                 Command::MoveTo(p)
                 | Command::LineTo(p)
                 | Command::QuadTo(_, p)
+                | Command::ConicTo(_, p, _)
+                | Command::ArcTo(.., p)
                 | Command::CurveTo(_, _, p) => *p,
                 Command::Close => {
                     for cmd in self.iter().rev().skip(1) {
@@ -237,6 +272,16 @@ impl PathBuilder for Vec<Command> {
         self
     }
 
+    fn conic_to(
+        &mut self,
+        control: impl Into<Point>,
+        to: impl Into<Point>,
+        weight: f32,
+    ) -> &mut Self {
+        self.push(Command::ConicTo(control.into(), to.into(), weight));
+        self
+    }
+
     fn curve_to(
         &mut self,
         control1: impl Into<Point>,
@@ -257,6 +302,266 @@ impl PathBuilder for Vec<Command> {
     }
 }
 
+impl<T: PathBuilder> PathBuilder for &mut T {
+    fn current_point(&self) -> Point {
+        (**self).current_point()
+    }
+
+    fn tolerance(&self) -> f32 {
+        (**self).tolerance()
+    }
+
+    fn move_to(&mut self, to: impl Into<Point>) -> &mut Self {
+        (**self).move_to(to);
+        self
+    }
+
+    fn line_to(&mut self, to: impl Into<Point>) -> &mut Self {
+        (**self).line_to(to);
+        self
+    }
+
+    fn quad_to(&mut self, control: impl Into<Point>, to: impl Into<Point>) -> &mut Self {
+        (**self).quad_to(control, to);
+        self
+    }
+
+    fn conic_to(
+        &mut self,
+        control: impl Into<Point>,
+        to: impl Into<Point>,
+        weight: f32,
+    ) -> &mut Self {
+        (**self).conic_to(control, to, weight);
+        self
+    }
+
+    fn arc_to(
+        &mut self,
+        rx: f32,
+        ry: f32,
+        angle: Angle,
+        size: ArcSize,
+        sweep: ArcSweep,
+        to: impl Into<Point>,
+    ) -> &mut Self {
+        (**self).arc_to(rx, ry, angle, size, sweep, to);
+        self
+    }
+
+    fn curve_to(
+        &mut self,
+        control1: impl Into<Point>,
+        control2: impl Into<Point>,
+        to: impl Into<Point>,
+    ) -> &mut Self {
+        (**self).curve_to(control1, control2, to);
+        self
+    }
+
+    fn close(&mut self) -> &mut Self {
+        (**self).close();
+        self
+    }
+}
+
+/// Path builder that forwards every command to two inner builders.
+///
+/// The current point is taken from the first builder. Adapters may be nested
+/// to forward commands to any number of builders and mutable references to
+/// builders are themselves builders.
+///
+/// ```rust
+/// use zeno::{apply, Command, Stroke, Tee};
+///
+/// let mut a: Vec<Command> = Vec::new();
+/// let mut b: Vec<Command> = Vec::new();
+/// apply("M0,0 10,10", Stroke::new(2.), None, &mut Tee::new(&mut a, &mut b));
+/// assert!(!a.is_empty());
+/// assert_eq!(a, b);
+/// ```
+#[derive(Copy, Clone, Default, Debug)]
+pub struct Tee<A, B> {
+    /// First builder.
+    pub first: A,
+    /// Second builder.
+    pub second: B,
+}
+
+impl<A, B> Tee<A, B> {
+    /// Creates a new adapter that forwards to the specified builders.
+    pub fn new(first: A, second: B) -> Self {
+        Self { first, second }
+    }
+
+    /// Consumes the adapter and returns the inner builders.
+    pub fn into_inner(self) -> (A, B) {
+        (self.first, self.second)
+    }
+}
+
+impl<A: PathBuilder, B: PathBuilder> PathBuilder for Tee<A, B> {
+    fn current_point(&self) -> Point {
+        self.first.current_point()
+    }
+
+    fn tolerance(&self) -> f32 {
+        self.first.tolerance().min(self.second.tolerance())
+    }
+
+    fn move_to(&mut self, to: impl Into<Point>) -> &mut Self {
+        let to = to.into();
+        self.first.move_to(to);
+        self.second.move_to(to);
+        self
+    }
+
+    fn line_to(&mut self, to: impl Into<Point>) -> &mut Self {
+        let to = to.into();
+        self.first.line_to(to);
+        self.second.line_to(to);
+        self
+    }
+
+    fn quad_to(&mut self, control: impl Into<Point>, to: impl Into<Point>) -> &mut Self {
+        let (control, to) = (control.into(), to.into());
+        self.first.quad_to(control, to);
+        self.second.quad_to(control, to);
+        self
+    }
+
+    fn conic_to(
+        &mut self,
+        control: impl Into<Point>,
+        to: impl Into<Point>,
+        weight: f32,
+    ) -> &mut Self {
+        let (control, to) = (control.into(), to.into());
+        self.first.conic_to(control, to, weight);
+        self.second.conic_to(control, to, weight);
+        self
+    }
+
+    fn arc_to(
+        &mut self,
+        rx: f32,
+        ry: f32,
+        angle: Angle,
+        size: ArcSize,
+        sweep: ArcSweep,
+        to: impl Into<Point>,
+    ) -> &mut Self {
+        let to = to.into();
+        self.first.arc_to(rx, ry, angle, size, sweep, to);
+        self.second.arc_to(rx, ry, angle, size, sweep, to);
+        self
+    }
+
+    fn curve_to(
+        &mut self,
+        control1: impl Into<Point>,
+        control2: impl Into<Point>,
+        to: impl Into<Point>,
+    ) -> &mut Self {
+        let (control1, control2, to) = (control1.into(), control2.into(), to.into());
+        self.first.curve_to(control1, control2, to);
+        self.second.curve_to(control1, control2, to);
+        self
+    }
+
+    fn close(&mut self) -> &mut Self {
+        self.first.close();
+        self.second.close();
+        self
+    }
+}
+
+/// Path builder that appends to a command buffer and retains elliptical arcs
+/// as [arc commands](enum.Command.html#variant.ArcTo).
+///
+/// Building into a `Vec<Command>` directly approximates arcs with cubic bezier
+/// curves. This adapter is the builder counterpart of
+/// [retain_arcs](struct.SvgCommands.html#method.retain_arcs).
+///
+/// ```rust
+/// use zeno::{Angle, ArcSize, ArcSweep, Command, PathBuilder, PathData, RetainArcs};
+///
+/// let mut path: Vec<Command> = Vec::new();
+/// RetainArcs(&mut path).move_to([1, 2]).rel_arc_to(
+///     8.0,
+///     4.0,
+///     Angle::from_degrees(30.0),
+///     ArcSize::Small,
+///     ArcSweep::Positive,
+///     [10, 4],
+/// );
+/// assert!(matches!(path[1], Command::ArcTo(..)));
+/// assert!((&path)
+///     .commands()
+///     .eq("M1,2 a8,4,30,0,1,10,4".commands().retain_arcs(true)));
+/// ```
+pub struct RetainArcs<'a>(pub &'a mut Vec<Command>);
+
+impl<'a> PathBuilder for RetainArcs<'a> {
+    fn current_point(&self) -> Point {
+        self.0.current_point()
+    }
+
+    fn move_to(&mut self, to: impl Into<Point>) -> &mut Self {
+        self.0.move_to(to);
+        self
+    }
+
+    fn line_to(&mut self, to: impl Into<Point>) -> &mut Self {
+        self.0.line_to(to);
+        self
+    }
+
+    fn quad_to(&mut self, control: impl Into<Point>, to: impl Into<Point>) -> &mut Self {
+        self.0.quad_to(control, to);
+        self
+    }
+
+    fn conic_to(
+        &mut self,
+        control: impl Into<Point>,
+        to: impl Into<Point>,
+        weight: f32,
+    ) -> &mut Self {
+        self.0.conic_to(control, to, weight);
+        self
+    }
+
+    fn arc_to(
+        &mut self,
+        rx: f32,
+        ry: f32,
+        angle: Angle,
+        size: ArcSize,
+        sweep: ArcSweep,
+        to: impl Into<Point>,
+    ) -> &mut Self {
+        self.0
+            .push(Command::ArcTo(rx, ry, angle, size, sweep, to.into()));
+        self
+    }
+
+    fn curve_to(
+        &mut self,
+        control1: impl Into<Point>,
+        control2: impl Into<Point>,
+        to: impl Into<Point>,
+    ) -> &mut Self {
+        self.0.curve_to(control1, control2, to);
+        self
+    }
+
+    fn close(&mut self) -> &mut Self {
+        self.0.close();
+        self
+    }
+}
+
 pub struct TransformSink<'a, S> {
     pub sink: &'a mut S,
     pub transform: Transform,
@@ -267,6 +572,15 @@ impl<'a, S: PathBuilder> PathBuilder for TransformSink<'a, S> {
         self.sink.current_point()
     }
 
+    fn tolerance(&self) -> f32 {
+        let scale = self.transform.max_scale();
+        if scale.is_finite() && scale > 0. {
+            self.sink.tolerance() / scale
+        } else {
+            self.sink.tolerance()
+        }
+    }
+
     fn move_to(&mut self, to: impl Into<Point>) -> &mut Self {
         self.sink.move_to(self.transform.transform_point(to.into()));
         self
@@ -285,6 +599,41 @@ impl<'a, S: PathBuilder> PathBuilder for TransformSink<'a, S> {
         self
     }
 
+    fn conic_to(
+        &mut self,
+        control: impl Into<Point>,
+        to: impl Into<Point>,
+        weight: f32,
+    ) -> &mut Self {
+        self.sink.conic_to(
+            self.transform.transform_point(control.into()),
+            self.transform.transform_point(to.into()),
+            weight,
+        );
+        self
+    }
+
+    fn arc_to(
+        &mut self,
+        rx: f32,
+        ry: f32,
+        angle: Angle,
+        size: ArcSize,
+        sweep: ArcSweep,
+        to: impl Into<Point>,
+    ) -> &mut Self {
+        let (rx, ry, angle, sweep) = transform_arc(rx, ry, angle, sweep, &self.transform);
+        self.sink.arc_to(
+            rx,
+            ry,
+            angle,
+            size,
+            sweep,
+            self.transform.transform_point(to.into()),
+        );
+        self
+    }
+
     fn curve_to(
         &mut self,
         control1: impl Into<Point>,
@@ -325,8 +674,13 @@ impl PathBuilder for BoundsBuilder {
     }
 
     fn quad_to(&mut self, control: impl Into<Point>, to: impl Into<Point>) -> &mut Self {
-        self.add(control.into());
+        let c = control.into();
         let p = to.into();
+        if self.exact {
+            self.add_quad_extrema(self.current, c, p);
+        } else {
+            self.add(c);
+        }
         self.add(p);
         self.current = p;
         self
@@ -338,9 +692,15 @@ impl PathBuilder for BoundsBuilder {
         control2: impl Into<Point>,
         to: impl Into<Point>,
     ) -> &mut Self {
-        self.add(control1.into());
-        self.add(control2.into());
+        let c1 = control1.into();
+        let c2 = control2.into();
         let p = to.into();
+        if self.exact {
+            self.add_curve_extrema(self.current, c1, c2, p);
+        } else {
+            self.add(c1);
+            self.add(c2);
+        }
         self.add(p);
         self.current = p;
         self
@@ -378,7 +738,7 @@ impl Arc {
         let to = to.into();
         let (px, py) = (from[0], from[1]);
         const TAU: f32 = 3.141579 * 2.;
-        let (sinphi, cosphi) = angle.sin_cos();
+        let (sinphi, cosphi) = F32Ext::sin_cos(angle);
         let pxp = cosphi * (px - to[0]) / 2. + sinphi * (py - to[1]) / 2.;
         let pyp = -sinphi * (px - to[0]) / 2. + cosphi * (py - to[1]) / 2.;
         if pxp == 0. && pyp == 0. {
@@ -386,7 +746,8 @@ impl Arc {
         }
         let mut rx = rx.abs();
         let mut ry = ry.abs();
-        let lambda = pxp.powi(2) / rx.powi(2) + pyp.powi(2) / ry.powi(2);
+        let lambda =
+            F32Ext::powi(pxp, 2) / F32Ext::powi(rx, 2) + F32Ext::powi(pyp, 2) / F32Ext::powi(ry, 2);
         if lambda > 1. {
             let s = lambda.sqrt();
             rx *= s;
@@ -403,7 +764,7 @@ impl Arc {
                 } else if dot < -1. {
                     dot = -1.
                 }
-                sign * dot.acos()
+                sign * F32Ext::acos(dot)
             }
             let rxsq = rx * rx;
             let rysq = ry * ry;
@@ -444,7 +805,7 @@ impl Arc {
         } else if ang2 == -1.5707963267948966 {
             -0.551915024494
         } else {
-            4. / 3. * (ang2 / 4.).tan()
+            4. / 3. * F32Ext::tan(ang2 / 4.)
         };
         Self {
             count: segments as usize,
@@ -457,6 +818,18 @@ impl Arc {
             a,
         }
     }
+
+    /// Subdivides the arc further until each cubic curve is within the
+    /// specified distance of the true arc.
+    pub fn with_tolerance(mut self, tolerance: f32) -> Self {
+        let split = arc_split(self.ang2, self.radii.0.max(self.radii.1), tolerance);
+        if self.count != 0 && split > 1. {
+            self.count *= split as usize;
+            self.ang2 /= split;
+            self.a = 4. / 3. * F32Ext::tan(self.ang2 / 4.);
+        }
+        self
+    }
 }
 
 impl Iterator for Arc {
@@ -467,8 +840,8 @@ impl Iterator for Arc {
             return None;
         }
         self.count -= 1;
-        let (y1, x1) = self.ang1.sin_cos();
-        let (y2, x2) = (self.ang1 + self.ang2).sin_cos();
+        let (y1, x1) = F32Ext::sin_cos(self.ang1);
+        let (y2, x2) = F32Ext::sin_cos(self.ang1 + self.ang2);
         let a = self.a;
         let (cx, cy) = self.center;
         let (rx, ry) = self.radii;
@@ -492,8 +865,125 @@ impl Iterator for Arc {
         self.ang1 += self.ang2;
         Some(Command::CurveTo(c1, c2, p))
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.count, Some(self.count))
+    }
 }
 
+impl ExactSizeIterator for Arc {}
+
+/// Returns the radii, rotation angle and sweep direction of an arc after
+/// applying the specified transformation matrix.
+pub(super) fn transform_arc(
+    rx: f32,
+    ry: f32,
+    angle: Angle,
+    sweep: ArcSweep,
+    transform: &Transform,
+) -> (f32, f32, Angle, ArcSweep) {
+    let t = transform;
+    // The arc lies on the image of the unit circle under the matrix
+    // rotate(angle) * scale(rx, ry). Apply the transform to that matrix and
+    // decompose the result into rotate(phi) * scale(sx, sy) * rotate(theta).
+    let (sin, cos) = F32Ext::sin_cos(angle.to_radians());
+    let (m00, m01) = (cos * rx, -sin * ry);
+    let (m10, m11) = (sin * rx, cos * ry);
+    let a = t.xx * m00 + t.yx * m10;
+    let b = t.xx * m01 + t.yx * m11;
+    let c = t.xy * m00 + t.yy * m10;
+    let d = t.xy * m01 + t.yy * m11;
+    let e = (a + d) * 0.5;
+    let f = (a - d) * 0.5;
+    let g = (c + b) * 0.5;
+    let h = (c - b) * 0.5;
+    let q = (e * e + h * h).sqrt();
+    let r = (f * f + g * g).sqrt();
+    let phi = (F32Ext::atan2(g, f) + F32Ext::atan2(h, e)) * 0.5;
+    let sweep = if t.determinant() < 0. {
+        match sweep {
+            ArcSweep::Positive => ArcSweep::Negative,
+            ArcSweep::Negative => ArcSweep::Positive,
+        }
+    } else {
+        sweep
+    };
+    (q + r, (q - r).abs(), Angle::from_radians(phi), sweep)
+}
+
+/// Default tolerance of path builders.
+pub(super) const DEFAULT_TOLERANCE: f32 = 0.1;
+
+/// Maximum number of times a conic section is split in half when it is
+/// approximated with quadratic curves. Each split reduces the error by a
+/// factor of four, so this only limits the output for degenerate input.
+const MAX_CONIC_DEPTH: usize = 10;
+
+/// Iterator over the control and final points of a sequence of quadratic
+/// curves that approximate a conic section to within a tolerance.
+#[derive(Copy, Clone, Default)]
+pub(super) struct ConicQuads {
+    /// Pending pieces of the conic with their remaining subdivision depth.
+    stack: [(Point, Point, Point, f32, usize); MAX_CONIC_DEPTH + 1],
+    len: usize,
+}
+
+impl ConicQuads {
+    pub fn new(from: Point, control: Point, to: Point, weight: f32, tolerance: f32) -> Self {
+        let mut quads = Self::default();
+        if !(weight > 0. && weight.is_finite()) {
+            quads.stack[0] = (from, (from + to) * 0.5, to, 1., 0);
+            quads.len = 1;
+            return quads;
+        }
+        // Estimate the number of subdivisions required.
+        let a = weight - 1.;
+        let k = a / (4. * (2. + a));
+        let mut error = ((from - control * 2. + to) * k).length();
+        let mut depth = 0;
+        while depth < MAX_CONIC_DEPTH && error > tolerance {
+            error *= 0.25;
+            depth += 1;
+        }
+        quads.stack[0] = (from, control, to, weight, depth);
+        quads.len = 1;
+        quads
+    }
+}
+
+impl Iterator for ConicQuads {
+    type Item = (Point, Point);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            self.len = self.len.checked_sub(1)?;
+            let (from, control, to, weight, depth) = self.stack[self.len];
+            if depth == 0 {
+                return Some((control, to));
+            }
+            let scale = 1. / (1. + weight);
+            let mid = (from + control * (2. * weight) + to) * (scale * 0.5);
+            let c0 = (from + control * weight) * scale;
+            let c1 = (control * weight + to) * scale;
+            let weight = (0.5 + weight * 0.5).sqrt();
+            // The second half is pushed first so that it is emitted last.
+            self.stack[self.len] = (mid, c1, to, weight, depth - 1);
+            self.stack[self.len + 1] = (from, c0, mid, weight, depth - 1);
+            self.len += 2;
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let count = self.stack[..self.len]
+            .iter()
+            .map(|piece| 1 << piece.4)
+            .sum();
+        (count, Some(count))
+    }
+}
+
+impl ExactSizeIterator for ConicQuads {}
+
 pub fn arc(
     sink: &mut impl PathBuilder,
     from: Point,
@@ -507,7 +997,7 @@ pub fn arc(
     let p = from;
     let (px, py) = (p.x, p.y);
     const TAU: f32 = core::f32::consts::PI * 2.;
-    let (sinphi, cosphi) = angle.sin_cos();
+    let (sinphi, cosphi) = F32Ext::sin_cos(angle);
     let pxp = cosphi * (px - to.x) / 2. + sinphi * (py - to.y) / 2.;
     let pyp = -sinphi * (px - to.x) / 2. + cosphi * (py - to.y) / 2.;
     if pxp == 0. && pyp == 0. {
@@ -515,7 +1005,8 @@ pub fn arc(
     }
     let mut rx = rx.abs();
     let mut ry = ry.abs();
-    let lambda = pxp.powi(2) / rx.powi(2) + pyp.powi(2) / ry.powi(2);
+    let lambda =
+        F32Ext::powi(pxp, 2) / F32Ext::powi(rx, 2) + F32Ext::powi(pyp, 2) / F32Ext::powi(ry, 2);
     if lambda > 1. {
         let s = lambda.sqrt();
         rx *= s;
@@ -532,7 +1023,7 @@ pub fn arc(
             } else if dot < -1. {
                 dot = -1.
             }
-            sign * dot.acos()
+            sign * F32Ext::acos(dot)
         }
         let rxsq = rx * rx;
         let rysq = ry * ry;
@@ -566,18 +1057,19 @@ pub fn arc(
     if (1. - ratio).abs() < 0.0000001 {
         ratio = 1.
     }
-    let segments = ratio.ceil().max(1.);
+    let mut segments = ratio.ceil().max(1.);
+    segments *= arc_split(ang2 / segments, rx.max(ry), sink.tolerance());
     ang2 /= segments;
     let a = if ang2 == 1.5707963267948966 {
         0.551915024494
     } else if ang2 == -1.5707963267948966 {
         -0.551915024494
     } else {
-        4. / 3. * (ang2 / 4.).tan()
+        4. / 3. * F32Ext::tan(ang2 / 4.)
     };
     for _ in 0..segments as usize {
-        let (y1, x1) = ang1.sin_cos();
-        let (y2, x2) = (ang1 + ang2).sin_cos();
+        let (y1, x1) = F32Ext::sin_cos(ang1);
+        let (y2, x2) = F32Ext::sin_cos(ang1 + ang2);
         let c1 = Point::new((x1 - y1 * a) * rx, (y1 + x1 * a) * ry);
         let c1 = Point::new(
             cx + (cosphi * c1.x - sinphi * c1.y),
@@ -597,3 +1089,21 @@ pub fn arc(
         ang1 += ang2;
     }
 }
+
+/// Maximum factor by which a single cubic arc segment is subdivided.
+const MAX_ARC_SPLIT: f32 = 16.;
+
+/// Returns the number of pieces that a cubic approximation of an arc segment
+/// spanning the specified angle must be split into to stay within the
+/// tolerance.
+fn arc_split(angle: f32, radius: f32, tolerance: f32) -> f32 {
+    // A cubic approximating a quarter circle deviates from it by about
+    // 0.027% of the radius and the error shrinks with the sixth power of the
+    // angle.
+    let error = 0.000273 * radius * F32Ext::powi(angle / core::f32::consts::FRAC_PI_2, 6);
+    if tolerance > 0. && error > tolerance {
+        F32Ext::ceil(F32Ext::powf(error / tolerance, 1. / 6.)).min(MAX_ARC_SPLIT)
+    } else {
+        1.
+    }
+}
@@ -1,22 +1,28 @@
 //! Path data.
 
-use super::command::{Command, PointsCommands, Verb};
+use super::command::{Command, PointsCommands, TransformCommands, Verb};
 use super::geometry::{Point, Transform};
 use super::path_builder::PathBuilder;
-use super::segment::segments;
+use super::segment::{segments, Measure};
 use super::svg_parser::SvgCommands;
 
 #[cfg(feature = "eval")]
-use super::stroke::stroke_into;
+use super::stroke::{clamp_width, stroke_bounds, stroke_into};
 
 #[cfg(feature = "eval")]
 use super::style::*;
 
 #[cfg(feature = "eval")]
-use super::geometry::{Bounds, BoundsBuilder};
+use super::geometry::{Bounds, BoundsBuilder, StyledBounds, Vector};
 
 #[cfg(feature = "eval")]
-use super::path_builder::TransformSink;
+use super::path_builder::{Tee, TransformSink};
+
+#[cfg(feature = "eval")]
+use super::segment::Curve;
+
+#[cfg(all(feature = "eval", not(feature = "std")))]
+use super::F32Ext;
 
 use crate::lib::Vec;
 
@@ -33,7 +39,9 @@ use crate::lib::Vec;
 ///
 /// Implementations of this trait are provided for SVG path data (in the form
 /// of strings), slices/vectors of commands, and the common point and
-/// verb list structure (as the tuple `(&[Point], &[Verb])`).
+/// verb list structure (as the tuple `(&[Point], &[Verb])`, or
+/// `(&[Point], &[Verb], &[f32])` when the path contains conics or arcs and
+/// the third slice holds their scalar parameters).
 ///
 /// As such, these paths are all equivalent:
 ///
@@ -97,6 +105,8 @@ pub trait PathData {
                 MoveTo(p) => sink.move_to(p),
                 LineTo(p) => sink.line_to(p),
                 QuadTo(c, p) => sink.quad_to(c, p),
+                ConicTo(c, p, w) => sink.conic_to(c, p, w),
+                ArcTo(rx, ry, a, size, sweep, p) => sink.arc_to(rx, ry, a, size, sweep, p),
                 CurveTo(c1, c2, p) => sink.curve_to(c1, c2, p),
                 Close => sink.close(),
             };
@@ -106,15 +116,21 @@ pub trait PathData {
 
 /// Computes the total length of the path.
 pub fn length(data: impl PathData, transform: Option<Transform>) -> f32 {
+    length_with(data, transform, Measure::Fast)
+}
+
+/// Computes the total length of the path using the specified method for
+/// measuring curves.
+pub fn length_with(data: impl PathData, transform: Option<Transform>, measure: Measure) -> f32 {
     let data = data.commands();
     let mut length = 0.;
     if let Some(transform) = transform {
         for s in segments(data.map(|cmd| cmd.transform(&transform)), false) {
-            length += s.length();
+            length += s.length_by(measure);
         }
     } else {
         for s in segments(data, false) {
-            length += s.length();
+            length += s.length_by(measure);
         }
     }
     length
@@ -133,6 +149,227 @@ pub fn bounds<'a>(
     bounds.build()
 }
 
+/// Computes both a conservative and an exact bounding box of the path in a
+/// single call.
+///
+/// The conservative box is derived from the control points of the path,
+/// expanded by the maximum extent of any stroke, and does not require
+/// evaluation of the style. The exact box is the tight bounding box of the
+/// fully styled and transformed path. For solid strokes with the standard
+/// joins and caps, the exact box is computed from the center line without
+/// evaluating the stroke outline, which makes it suitable for culling and
+/// dirty rectangle tracking.
+///
+/// ```
+/// use zeno::{bounds, styled_bounds, Cap, Join, Stroke};
+///
+/// let path = "M0,0 L10,10 L20,0";
+/// let mut stroke = Stroke::new(2.);
+/// stroke.join(Join::Miter).cap(Cap::Square);
+/// let fast = styled_bounds(path, stroke, None).exact;
+/// let outline = bounds(path, stroke, None);
+/// assert!((fast.min - outline.min).length() < 1e-4);
+/// assert!((fast.max - outline.max).length() < 1e-4);
+/// ```
+#[cfg(feature = "eval")]
+pub fn styled_bounds<'a>(
+    data: impl PathData,
+    style: impl Into<Style<'a>>,
+    transform: Option<Transform>,
+) -> StyledBounds {
+    let style = style.into();
+    let mut exact = BoundsBuilder::exact();
+    if !stroke_bounds_from_center(&data, &style, transform, &mut exact) {
+        apply(&data, style, transform, &mut exact);
+    }
+    StyledBounds {
+        conservative: conservative_bounds(&data, &style, transform),
+        exact: exact.build(),
+    }
+}
+
+/// Adds the exact bounds of a stroke to the builder without evaluating the
+/// stroke outline. Returns false, leaving the builder untouched, if the style
+/// is not a stroke or has features that cannot be bounded from the center
+/// line, such as dashes, variable widths, custom joins or caps, markers and
+/// decorations.
+#[cfg(feature = "eval")]
+pub(super) fn stroke_bounds_from_center(
+    data: impl PathData,
+    style: &Style,
+    transform: Option<Transform>,
+    b: &mut BoundsBuilder,
+) -> bool {
+    let stroke = match style {
+        Style::Stroke(stroke) if is_simple_stroke(stroke) => stroke,
+        _ => return false,
+    };
+    let stroke = clamp_width(stroke, transform.as_ref());
+    match transform {
+        Some(transform) if stroke.scale => {
+            // A uniform scale with rotation or reflection maps the outline of
+            // the stroke to the outline of a scaled stroke of the transformed
+            // path.
+            let (a, c) = (
+                Vector::new(transform.xx, transform.xy),
+                Vector::new(transform.yx, transform.yy),
+            );
+            let (la, lb) = (a.length(), c.length());
+            let uniform = (la - lb).abs() <= la.max(lb) * 1e-5 && a.dot(c).abs() <= la * lb * 1e-5;
+            if !uniform {
+                return false;
+            }
+            let commands = data.commands().map(|cmd| cmd.transform(&transform));
+            stroke_bounds(commands, &stroke, la, b);
+        }
+        Some(transform) => {
+            let commands = data.commands().map(|cmd| cmd.transform(&transform));
+            stroke_bounds(commands, &stroke, 1., b);
+        }
+        None => stroke_bounds(data.commands(), &stroke, 1., b),
+    }
+    true
+}
+
+/// Returns true if the bounds of the stroke can be computed from the center
+/// line of the path.
+#[cfg(feature = "eval")]
+fn is_simple_stroke(stroke: &Stroke) -> bool {
+    stroke.widths.is_empty()
+        && stroke.dashes.is_empty()
+        && stroke.dash_pattern.is_none()
+        && stroke.join_builder.is_none()
+        && stroke.start_cap_path.is_none()
+        && stroke.end_cap_path.is_none()
+        && stroke.alignment == Alignment::Center
+        && stroke.join != Join::Arcs
+        && stroke.start_marker.is_none()
+        && stroke.mid_marker.is_none()
+        && stroke.end_marker.is_none()
+        && stroke.decoration.is_none()
+        && stroke.parallel.is_empty()
+}
+
+/// Computes a bounding box that contains the styled path without evaluating
+/// the style.
+#[cfg(feature = "eval")]
+pub(super) fn conservative_bounds(
+    data: impl PathData,
+    style: &Style,
+    transform: Option<Transform>,
+) -> Bounds {
+    let mut b = BoundsBuilder::new();
+    let stroke = match style {
+        Style::Stroke(stroke) => clamp_width(stroke, transform.as_ref()),
+        Style::Fill(_) => {
+            copy_transformed(&data, transform, &mut b);
+            return b.build();
+        }
+    };
+    let transform_after = if stroke.scale { transform } else { None };
+    if transform_after.is_some() {
+        data.copy_to(&mut b);
+    } else {
+        copy_transformed(&data, transform, &mut b);
+    }
+    if b.count == 0 {
+        return Bounds::default();
+    }
+    let mut bounds = b.build();
+    let extent = stroke_extent(&stroke);
+    bounds.min = bounds.min - Vector::new(extent, extent);
+    bounds.max = bounds.max + Vector::new(extent, extent);
+    match transform_after {
+        Some(transform) => bounds.transform(&transform),
+        None => bounds,
+    }
+}
+
+/// Returns the maximum distance from the center line that can be covered by
+/// the stroke.
+#[cfg(feature = "eval")]
+fn stroke_extent(stroke: &Stroke) -> f32 {
+    let mut width = stroke.widths.iter().fold(stroke.width, |a, &b| a.max(b));
+    if !stroke.parallel.is_empty() {
+        width = stroke.parallel.iter().map(|w| w.max(0.)).sum();
+    }
+    let mut radius = width.max(0.01) * 0.5;
+    if stroke.alignment != Alignment::Center {
+        radius *= 2.;
+    }
+    let mut factor: f32 = 1.;
+    if matches!(stroke.join, Join::Miter | Join::MiterClip | Join::Arcs) {
+        factor = factor.max(stroke.miter_limit);
+    }
+    if stroke.start_cap == Cap::Square
+        || stroke.end_cap == Cap::Square
+        || stroke.dash_cap == Some(Cap::Square)
+    {
+        factor = factor.max(core::f32::consts::SQRT_2);
+    }
+    let mut extent = radius * factor;
+    if let Some(builder) = stroke.join_builder {
+        extent = extent.max(builder.extent(radius * 2.));
+    }
+    for path in [stroke.start_cap_path, stroke.end_cap_path]
+        .iter()
+        .flatten()
+    {
+        extent = extent.max(path_reach(path) * radius);
+    }
+    // Markers are sized relative to the base width of the stroke.
+    let marker_radius = stroke.width.max(0.01) * 0.5;
+    for marker in [stroke.start_marker, stroke.mid_marker, stroke.end_marker]
+        .iter()
+        .flatten()
+    {
+        extent = extent.max(path_reach(marker.path) * marker_radius * marker.scale.abs());
+    }
+    if let Some(decoration) = stroke.decoration {
+        let offset = decoration.path.iter().fold(0f32, |offset, cmd| {
+            let y = match *cmd {
+                Command::MoveTo(p) | Command::LineTo(p) => p.y.abs(),
+                Command::QuadTo(c, p) | Command::ConicTo(c, p, _) => c.y.abs().max(p.y.abs()),
+                Command::CurveTo(c1, c2, p) => c1.y.abs().max(c2.y.abs()).max(p.y.abs()),
+                Command::ArcTo(..) => path_reach(core::slice::from_ref(cmd)),
+                Command::Close => 0.,
+            };
+            offset.max(y)
+        });
+        extent += offset * decoration.amplitude.abs();
+    }
+    extent
+}
+
+/// Returns the maximum distance from the origin of any point of the path.
+#[cfg(feature = "eval")]
+fn path_reach(path: &[Command]) -> f32 {
+    let mut reach: f32 = 0.;
+    for cmd in path.iter() {
+        reach = reach.max(match *cmd {
+            Command::MoveTo(p) | Command::LineTo(p) => p.length(),
+            Command::QuadTo(c, p) | Command::ConicTo(c, p, _) => c.length().max(p.length()),
+            Command::CurveTo(c1, c2, p) => c1.length().max(c2.length()).max(p.length()),
+            Command::ArcTo(rx, ry, _, _, _, p) => p.length() + 2. * rx.abs().max(ry.abs()),
+            Command::Close => 0.,
+        });
+    }
+    reach
+}
+
+#[cfg(feature = "eval")]
+fn copy_transformed(
+    data: impl PathData,
+    transform: Option<Transform>,
+    sink: &mut impl PathBuilder,
+) {
+    if let Some(transform) = transform {
+        data.copy_to(&mut TransformSink { sink, transform });
+    } else {
+        data.copy_to(sink);
+    }
+}
+
 /// Applies the style and transform to the path and emits the result to the
 /// specified sink.
 #[cfg(feature = "eval")]
@@ -155,25 +392,302 @@ pub fn apply<'a>(
             }
         }
         Style::Stroke(stroke) => {
+            let stroke = clamp_width(&stroke, transform.as_ref());
             if let Some(transform) = transform {
                 if stroke.scale {
+                    let tolerance = stroke_tolerance(&transform);
                     let mut transform_sink = TransformSink { sink, transform };
-                    stroke_into(data.commands(), &stroke, &mut transform_sink);
+                    stroke_into(data.commands(), &stroke, &mut transform_sink, tolerance);
                 } else {
                     stroke_into(
                         data.commands().map(|cmd| cmd.transform(&transform)),
                         &stroke,
                         sink,
+                        1.,
                     );
                 }
             } else {
-                stroke_into(data.commands(), &stroke, sink);
+                stroke_into(data.commands(), &stroke, sink, 1.);
             }
             Fill::NonZero
         }
     }
 }
 
+/// Returns the factor that maps stroker tolerances to device space for a
+/// stroke that is evaluated before the specified transform.
+#[cfg(feature = "eval")]
+pub(super) fn stroke_tolerance(transform: &Transform) -> f32 {
+    let scale = transform.max_scale();
+    if scale.is_finite() && scale > 0. {
+        1. / scale
+    } else {
+        1.
+    }
+}
+
+/// Bounds and length of the geometry emitted by
+/// [apply_with_info](fn.apply_with_info.html).
+#[cfg(feature = "eval")]
+#[derive(Copy, Clone, Debug)]
+pub struct ApplyInfo {
+    /// Fill rule that should be used to render the emitted geometry.
+    pub fill: Fill,
+    /// Tight bounding box of the emitted geometry.
+    pub bounds: Bounds,
+    /// Total length of the emitted geometry, including closing segments.
+    pub length: f32,
+}
+
+/// Applies the style and transform to the path, emits the result to the
+/// specified sink and returns the bounds and length of the emitted geometry.
+///
+/// This is equivalent to following [apply](fn.apply.html) with separate
+/// passes to compute the bounds and length of the result, but performs only
+/// a single traversal.
+///
+/// ```rust
+/// use zeno::{apply_with_info, Fill};
+///
+/// let mut path = Vec::new();
+/// let info = apply_with_info("M0,0 10,0 10,10 0,10 Z", Fill::NonZero, None, &mut path);
+/// assert_eq!(info.length, 40.);
+/// assert_eq!(info.bounds.width(), 10.);
+/// assert_eq!(path.len(), 5);
+/// ```
+#[cfg(feature = "eval")]
+pub fn apply_with_info<'a>(
+    data: impl PathData,
+    style: impl Into<Style<'a>>,
+    transform: Option<Transform>,
+    sink: &mut impl PathBuilder,
+) -> ApplyInfo {
+    let mut tee = Tee::new(
+        sink,
+        Metrics {
+            bounds: BoundsBuilder::exact(),
+            length: 0.,
+            start: Point::ZERO,
+        },
+    );
+    let fill = apply(data, style, transform, &mut tee);
+    let metrics = tee.second;
+    ApplyInfo {
+        fill,
+        bounds: metrics.bounds.build(),
+        length: metrics.length,
+    }
+}
+
+/// Accumulates the bounds and length of a path.
+#[cfg(feature = "eval")]
+struct Metrics {
+    bounds: BoundsBuilder,
+    length: f32,
+    start: Point,
+}
+
+#[cfg(feature = "eval")]
+impl PathBuilder for Metrics {
+    fn current_point(&self) -> Point {
+        self.bounds.current
+    }
+
+    fn move_to(&mut self, to: impl Into<Point>) -> &mut Self {
+        let to = to.into();
+        self.start = to;
+        self.bounds.move_to(to);
+        self
+    }
+
+    fn line_to(&mut self, to: impl Into<Point>) -> &mut Self {
+        let to = to.into();
+        self.length += (to - self.bounds.current).length();
+        self.bounds.line_to(to);
+        self
+    }
+
+    fn quad_to(&mut self, control: impl Into<Point>, to: impl Into<Point>) -> &mut Self {
+        let (control, to) = (control.into(), to.into());
+        self.length += Curve::from_quadratic(self.bounds.current, control, to).length();
+        self.bounds.quad_to(control, to);
+        self
+    }
+
+    fn curve_to(
+        &mut self,
+        control1: impl Into<Point>,
+        control2: impl Into<Point>,
+        to: impl Into<Point>,
+    ) -> &mut Self {
+        let (control1, control2, to) = (control1.into(), control2.into(), to.into());
+        self.length += Curve::new(self.bounds.current, control1, control2, to).length();
+        self.bounds.curve_to(control1, control2, to);
+        self
+    }
+
+    fn close(&mut self) -> &mut Self {
+        let start = self.start;
+        self.line_to(start)
+    }
+}
+
+/// Estimated cost of rendering a styled path.
+#[cfg(feature = "eval")]
+#[derive(Copy, Clone, Default, Debug)]
+pub struct Complexity {
+    /// Number of line segments produced when flattening the path for
+    /// rasterization, including those that implicitly close subpaths.
+    pub segments: usize,
+    /// Approximate number of coverage cells touched by the segments. This
+    /// dominates the cost of building the raster and tends to scale with the
+    /// perimeter of the shape.
+    pub cells: usize,
+    /// Number of pixels in the bounding box of the path. This bounds the cost
+    /// of sweeping the raster into a mask.
+    pub area: usize,
+}
+
+/// Estimates the cost of rendering the path with the specified style and
+/// transform.
+///
+/// The style is evaluated but the result is not flattened or rasterized;
+/// instead, curves are measured using the same subdivision criteria as the
+/// rasterizer. This is useful for choosing between rendering strategies on a
+/// per shape basis.
+///
+/// ```rust
+/// use zeno::{complexity, Fill, Stroke};
+///
+/// let fill = complexity("M0,0 100,0 100,100 Z", Fill::NonZero, None);
+/// assert_eq!(fill.segments, 3);
+/// assert_eq!(fill.area, 10000);
+/// let stroke = complexity("M0,0 Q100,0 100,100", Stroke::new(4.), None);
+/// assert!(stroke.segments > fill.segments);
+/// ```
+#[cfg(feature = "eval")]
+pub fn complexity<'a>(
+    data: impl PathData,
+    style: impl Into<Style<'a>>,
+    transform: Option<Transform>,
+) -> Complexity {
+    let mut estimator = Estimator {
+        bounds: BoundsBuilder::new(),
+        start: Point::ZERO,
+        closed: true,
+        segments: 0,
+        cells: 0.,
+    };
+    apply(data, style, transform, &mut estimator);
+    estimator.close();
+    let bounds = estimator.bounds.build();
+    Complexity {
+        segments: estimator.segments,
+        cells: estimator.cells as usize,
+        area: (bounds.width().ceil() * bounds.height().ceil()) as usize,
+    }
+}
+
+/// Counts the segments and cells generated by the rasterizer for a path.
+#[cfg(feature = "eval")]
+struct Estimator {
+    bounds: BoundsBuilder,
+    start: Point,
+    closed: bool,
+    segments: usize,
+    cells: f32,
+}
+
+#[cfg(feature = "eval")]
+impl Estimator {
+    /// Maximum subdivision depth of the rasterizer for curves.
+    const MAX_SPLITS: usize = 1 << 16;
+
+    fn add_lines(&mut self, from: Point, to: Point, count: usize) {
+        let d = to - from;
+        self.segments += count;
+        self.cells += d.x.abs() + d.y.abs() + count as f32;
+    }
+}
+
+#[cfg(feature = "eval")]
+impl PathBuilder for Estimator {
+    fn current_point(&self) -> Point {
+        self.bounds.current
+    }
+
+    fn move_to(&mut self, to: impl Into<Point>) -> &mut Self {
+        self.close();
+        let to = to.into();
+        self.start = to;
+        self.closed = false;
+        self.bounds.move_to(to);
+        self
+    }
+
+    fn line_to(&mut self, to: impl Into<Point>) -> &mut Self {
+        let to = to.into();
+        self.add_lines(self.bounds.current, to, 1);
+        self.closed = false;
+        self.bounds.line_to(to);
+        self
+    }
+
+    fn quad_to(&mut self, control: impl Into<Point>, to: impl Into<Point>) -> &mut Self {
+        let (control, to) = (control.into(), to.into());
+        let from = self.bounds.current;
+        let d = from + to - control * 2.;
+        let mut deviation = d.x.abs().max(d.y.abs());
+        let mut count = 1;
+        while deviation > 0.25 && count < Self::MAX_SPLITS {
+            deviation /= 4.;
+            count *= 2;
+        }
+        // Measure the control polygon as an upper bound on the length.
+        self.add_lines(from, control, 0);
+        self.add_lines(control, to, count);
+        self.closed = false;
+        self.bounds.quad_to(control, to);
+        self
+    }
+
+    fn curve_to(
+        &mut self,
+        control1: impl Into<Point>,
+        control2: impl Into<Point>,
+        to: impl Into<Point>,
+    ) -> &mut Self {
+        let (c1, c2, to) = (control1.into(), control2.into(), to.into());
+        let from = self.bounds.current;
+        let d1 = to * 2. - c2 * 3. + from;
+        let d2 = to - c1 * 3. + from * 2.;
+        let mut deviation = d1.x.abs().max(d1.y.abs()).max(d2.x.abs().max(d2.y.abs()));
+        let mut count = 1;
+        while deviation > 0.5 && count < Self::MAX_SPLITS {
+            deviation /= 4.;
+            count *= 2;
+        }
+        self.add_lines(from, c1, 0);
+        self.add_lines(c1, c2, 0);
+        self.add_lines(c2, to, count);
+        self.closed = false;
+        self.bounds.curve_to(c1, c2, to);
+        self
+    }
+
+    fn close(&mut self) -> &mut Self {
+        if !self.closed {
+            let (from, to) = (self.bounds.current, self.start);
+            if from != to {
+                self.add_lines(from, to, 1);
+            }
+            self.bounds.line_to(to);
+            self.closed = true;
+        }
+        self
+    }
+}
+
 impl<T> PathData for &'_ T
 where
     T: PathData,
@@ -190,6 +704,36 @@ where
     }
 }
 
+/// Path data with a transformation matrix applied to each command.
+#[cfg(feature = "eval")]
+pub(super) struct Transformed<D> {
+    pub data: D,
+    pub transform: Transform,
+}
+
+#[cfg(feature = "eval")]
+impl<D> PathData for Transformed<D>
+where
+    D: PathData,
+{
+    type Commands = TransformCommands<D::Commands>;
+
+    fn commands(&self) -> Self::Commands {
+        TransformCommands {
+            data: self.data.commands(),
+            transform: self.transform,
+        }
+    }
+
+    #[inline(always)]
+    fn copy_to(&self, sink: &mut impl PathBuilder) {
+        self.data.copy_to(&mut TransformSink {
+            sink,
+            transform: self.transform,
+        });
+    }
+}
+
 impl<'a> PathData for &'a str {
     type Commands = SvgCommands<'a>;
 
@@ -202,7 +746,20 @@ impl<'a> PathData for (&'a [Point], &'a [Verb]) {
     type Commands = PointsCommands<'a>;
 
     fn commands(&self) -> Self::Commands {
-        PointsCommands::new(self.0, self.1)
+        PointsCommands::new(self.0, self.1, &[])
+    }
+
+    #[inline(always)]
+    fn copy_to(&self, sink: &mut impl PathBuilder) {
+        self.commands().copy_to(sink);
+    }
+}
+
+impl<'a> PathData for (&'a [Point], &'a [Verb], &'a [f32]) {
+    type Commands = PointsCommands<'a>;
+
+    fn commands(&self) -> Self::Commands {
+        PointsCommands::new(self.0, self.1, self.2)
     }
 
     #[inline(always)]
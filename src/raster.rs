@@ -1,14 +1,18 @@
 //! Path rasterizer.
 
-use super::geometry::{Point, Vector};
+use super::geometry::{Bounds, Point, Vector};
 use super::path_builder::PathBuilder;
+use super::scratch::Budget;
 use super::style::Fill;
 
+use super::F32Ext;
 use crate::lib::Vec;
+use core::cell::Cell as StdCell;
 use core::fmt;
+use core::sync::atomic::{AtomicBool, Ordering};
 
 #[inline(always)]
-fn coverage(fill: Fill, mut coverage: i32) -> u8 {
+pub(super) fn coverage(fill: Fill, mut coverage: i32) -> u8 {
     coverage >>= PIXEL_BITS * 2 + 1 - 8;
     if fill == Fill::EvenOdd {
         coverage &= 511;
@@ -26,8 +30,241 @@ fn coverage(fill: Fill, mut coverage: i32) -> u8 {
     coverage as u8
 }
 
+fn coverage16(fill: Fill, mut coverage: i32) -> u16 {
+    coverage >>= PIXEL_BITS * 2 + 1 - 16;
+    if fill == Fill::EvenOdd {
+        coverage &= 0x1FFFF;
+        if coverage >= 0x10000 {
+            coverage = 0x1FFFFi32.wrapping_sub(coverage);
+        }
+    } else {
+        if coverage < 0 {
+            coverage = !coverage;
+        }
+        if coverage >= 0x10000 {
+            coverage = 0xFFFF;
+        }
+    }
+    coverage as u16
+}
+
+/// Returns the fill rule of the subpaths that are accumulated separately
+/// from those with the specified rule.
+fn secondary_fill(fill: Fill) -> Fill {
+    match fill {
+        Fill::NonZero => Fill::EvenOdd,
+        Fill::EvenOdd => Fill::NonZero,
+    }
+}
+
+/// Computes the coverage of a pixel as the union of the primary and
+/// secondary accumulated coverage, optionally remapping it with a curve,
+/// snapping it to fully opaque or fully transparent, multiplying it by the
+/// coverage of a clip mask and inverting it.
+#[inline(always)]
+fn pixel_coverage(
+    fill: Fill,
+    value: i32,
+    secondary: i32,
+    aliased: bool,
+    curve: Option<&CoverageCurve>,
+    clip: u8,
+    inverted: bool,
+) -> u8 {
+    let mut c = coverage(fill, value);
+    if secondary != 0 {
+        let s = coverage(secondary_fill(fill), secondary) as u32;
+        c = (c as u32 + s - (c as u32 * s + 127) / 255) as u8;
+    }
+    if let Some(curve) = curve {
+        c = curve.table[c as usize];
+    }
+    if aliased {
+        c = if c >= 128 { 255 } else { 0 };
+    }
+    if clip != 255 {
+        c = ((c as u32 * clip as u32 + 127) / 255) as u8;
+    }
+    if inverted {
+        255 - c
+    } else {
+        c
+    }
+}
+
+/// Computes the 16-bit coverage of a pixel as the union of the primary and
+/// secondary accumulated coverage, optionally remapping it with a curve,
+/// snapping it to fully opaque or fully transparent, multiplying it by the
+/// coverage of a clip mask and inverting it.
+#[inline(always)]
+fn pixel_coverage16(
+    fill: Fill,
+    value: i32,
+    secondary: i32,
+    aliased: bool,
+    curve: Option<&CoverageCurve>,
+    clip: u8,
+    inverted: bool,
+) -> u16 {
+    let mut c = coverage16(fill, value);
+    if secondary != 0 {
+        let s = coverage16(secondary_fill(fill), secondary) as u32;
+        c = (c as u32 + s - (c as u32 * s + 0x7FFF) / 0xFFFF) as u16;
+    }
+    if let Some(curve) = curve {
+        if c != 0 && c != 0xFFFF {
+            c = (curve.eval(c as f32 / 65535.) * 65535. + 0.5) as u16;
+        }
+    }
+    if aliased {
+        c = if c >= 0x8000 { 0xFFFF } else { 0 };
+    }
+    if clip != 255 {
+        c = ((c as u32 * clip as u32 + 127) / 255) as u16;
+    }
+    if inverted {
+        0xFFFF - c
+    } else {
+        c
+    }
+}
+
+/// Curve that remaps coverage values for gamma and contrast correction.
+pub struct CoverageCurve {
+    gamma: f32,
+    contrast: f32,
+    table: [u8; 256],
+}
+
+impl CoverageCurve {
+    /// Creates a curve that raises coverage to the power of the reciprocal
+    /// of gamma and then blends toward a smoothstep by the contrast.
+    pub fn new(gamma: f32, contrast: f32) -> Self {
+        let mut curve = Self {
+            gamma,
+            contrast,
+            table: [0; 256],
+        };
+        for i in 0..256 {
+            curve.table[i] = (curve.eval(i as f32 / 255.) * 255. + 0.5) as u8;
+        }
+        curve
+    }
+
+    fn eval(&self, x: f32) -> f32 {
+        let y = F32Ext::powf(x, 1. / self.gamma).clamp(0., 1.);
+        let s = y * y * (3. - 2. * y);
+        y + (s - y) * self.contrast
+    }
+}
+
+/// Returns the minimum column, minimum row, maximum column and maximum row of
+/// the pixels within the clip for a target of the specified size.
+pub(super) fn clip_rect(clip: Option<Bounds>, width: u32, height: u32) -> [usize; 4] {
+    let (w, h) = (width as i32, height as i32);
+    match clip {
+        Some(clip) => {
+            let x0 = (clip.min.x.floor() as i32).max(0).min(w);
+            let y0 = (clip.min.y.floor() as i32).max(0).min(h);
+            let x1 = (clip.max.x.ceil() as i32).max(x0).min(w);
+            let y1 = (clip.max.y.ceil() as i32).max(y0).min(h);
+            [x0 as usize, y0 as usize, x1 as usize, y1 as usize]
+        }
+        None => [0, 0, width as usize, height as usize],
+    }
+}
+
+/// Cooperative cancellation state for a rasterization.
+pub struct Interrupt<'a> {
+    flag: Option<&'a AtomicBool>,
+    #[cfg(feature = "std")]
+    deadline: Option<std::time::Instant>,
+    count: StdCell<u32>,
+    cancelled: StdCell<bool>,
+}
+
+impl<'a> Interrupt<'a> {
+    pub fn new(flag: Option<&'a AtomicBool>) -> Self {
+        Self {
+            flag,
+            #[cfg(feature = "std")]
+            deadline: None,
+            count: StdCell::new(0),
+            cancelled: StdCell::new(false),
+        }
+    }
+
+    #[cfg(feature = "std")]
+    pub fn with_deadline(mut self, deadline: Option<std::time::Instant>) -> Self {
+        self.deadline = deadline;
+        self
+    }
+
+    /// Returns true if the interrupt can ever be triggered.
+    pub fn is_active(&self) -> bool {
+        #[cfg(feature = "std")]
+        if self.deadline.is_some() {
+            return true;
+        }
+        self.flag.is_some()
+    }
+
+    /// Returns true if the operation has been cancelled. The flag and
+    /// deadline are only polled periodically.
+    #[inline(always)]
+    pub fn poll(&self) -> bool {
+        if self.cancelled.get() {
+            return true;
+        }
+        let count = self.count.get().wrapping_add(1);
+        self.count.set(count);
+        if count & (INTERRUPT_INTERVAL - 1) == 0 {
+            self.check();
+        }
+        self.cancelled.get()
+    }
+
+    /// Returns true if cancellation has been observed.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.get()
+    }
+
+    /// Returns true if the operation has been cancelled, polling the flag
+    /// and deadline immediately.
+    pub fn check(&self) -> bool {
+        let mut cancelled = self.cancelled.get();
+        if let Some(flag) = self.flag {
+            cancelled |= flag.load(Ordering::Relaxed);
+        }
+        #[cfg(feature = "std")]
+        if let Some(deadline) = self.deadline {
+            cancelled |= std::time::Instant::now() >= deadline;
+        }
+        self.cancelled.set(cancelled);
+        cancelled
+    }
+}
+
+/// Number of polls between checks of the flag and deadline. Must be a power
+/// of two.
+const INTERRUPT_INTERVAL: u32 = 256;
+
 pub struct Rasterizer<'a, S: RasterStorage> {
     storage: &'a mut S,
+    interrupt: Option<&'a Interrupt<'a>>,
+    aliased: bool,
+    inverted: bool,
+    curve: Option<&'a CoverageCurve>,
+    clip_mask: Option<&'a [u8]>,
+    fill_rules: Option<&'a [Fill]>,
+    fill: Fill,
+    group: Option<Fill>,
+    secondary: HeapStorage,
+    subpath: usize,
+    skip: bool,
+    clip: Option<Bounds>,
+    snap: i32,
+    flatness: i32,
     xmin: i32,
     xmax: i32,
     ymin: i32,
@@ -50,6 +287,20 @@ impl<'a, S: RasterStorage> Rasterizer<'a, S> {
     pub fn new(storage: &'a mut S) -> Self {
         Self {
             storage,
+            interrupt: None,
+            aliased: false,
+            inverted: false,
+            curve: None,
+            clip_mask: None,
+            fill_rules: None,
+            fill: Fill::NonZero,
+            group: None,
+            secondary: HeapStorage::default(),
+            subpath: 0,
+            skip: false,
+            clip: None,
+            snap: 0,
+            flatness: 0,
             xmin: 0,
             xmax: 0,
             ymin: 0,
@@ -69,50 +320,211 @@ impl<'a, S: RasterStorage> Rasterizer<'a, S> {
         }
     }
 
-    pub fn rasterize(
-        &mut self,
-        shift: Vector,
-        width: u32,
-        height: u32,
-        apply: &mut impl FnMut(&mut Self),
-        fill: Fill,
-        buffer: &mut [u8],
-        pitch: usize,
-        y_up: bool,
-    ) {
-        let w = width as i32;
+    /// Sets the interrupt that is polled while building cells and sweeping
+    /// scanlines. Once triggered, the remainder of the path is ignored.
+    pub fn set_interrupt(&mut self, interrupt: Option<&'a Interrupt<'a>>) {
+        self.interrupt = interrupt;
+    }
+
+    /// Sets whether resolved coverage is snapped to fully opaque or fully
+    /// transparent. Pixels that are at least half covered become opaque.
+    pub fn set_aliased(&mut self, aliased: bool) {
+        self.aliased = aliased;
+    }
+
+    /// Sets whether resolved coverage is inverted after any snapping. Pixels
+    /// that are not crossed by the path are never written, so the target
+    /// must be filled with full coverage beforehand.
+    pub fn set_inverted(&mut self, inverted: bool) {
+        self.inverted = inverted;
+    }
+
+    /// Sets 8-bit coverage that multiplies the resolved coverage of each
+    /// pixel. The mask has one value per pixel of the target, with rows in
+    /// rasterization order regardless of the vertical orientation.
+    pub fn set_clip_mask(&mut self, mask: Option<&'a [u8]>) {
+        self.clip_mask = mask;
+    }
+
+    /// Sets the fill rules for the subpaths of the path, keyed by the order
+    /// in which they are started. Subpaths beyond the end of the slice use
+    /// the fill rule passed when rasterizing. Subpaths with each rule are
+    /// accumulated separately and the resolved coverage is the union of
+    /// both. Ignored when rasterizing deltas.
+    pub fn set_fill_rules(&mut self, rules: Option<&'a [Fill]>) {
+        self.fill_rules = rules;
+    }
+
+    /// Sets a curve that remaps resolved coverage before any snapping.
+    pub fn set_curve(&mut self, curve: Option<&'a CoverageCurve>) {
+        self.curve = curve;
+    }
+
+    /// Sets a rectangle, in pixels relative to the target, that restricts
+    /// the cells that are accumulated and the pixels that are written.
+    pub fn set_clip(&mut self, clip: Option<Bounds>) {
+        self.clip = clip;
+    }
+
+    /// Sets the number of fractional bits per axis used to position edges,
+    /// up to the full precision of eight bits. Lower precision snaps edge
+    /// coordinates to a coarser grid and flattens curves with a
+    /// proportionally coarser tolerance.
+    pub fn set_precision(&mut self, bits: i32) {
+        self.snap = PIXEL_BITS - bits.clamp(1, PIXEL_BITS);
+        self.flatness = self.snap / 3;
+    }
+
+    /// Converts a point to fixed point, applying the shift and snapping it
+    /// to the precision of the rasterizer.
+    #[inline(always)]
+    fn fixed(&self, p: Point) -> FixedPoint {
+        let p = FixedPoint::from_point(p + self.shift);
+        if self.snap == 0 {
+            return p;
+        }
+        let half = 1 << (self.snap - 1);
+        let mask = !((1 << self.snap) - 1);
+        FixedPoint {
+            x: (p.x + half) & mask,
+            y: (p.y + half) & mask,
+        }
+    }
+
+    /// Adds a single directed line segment. Unlike the path builder methods,
+    /// this never implicitly closes the current subpath.
+    #[inline(always)]
+    pub fn edge(&mut self, from: Point, to: Point) {
+        self.move_to(self.fixed(from));
+        self.line_to(self.fixed(to));
+        self.current = to;
+    }
+
+    #[inline(always)]
+    fn interrupted(&self) -> bool {
+        match self.interrupt {
+            Some(interrupt) => interrupt.poll(),
+            None => false,
+        }
+    }
+
+    /// Resets the rasterizer and accumulates the cells for the path emitted
+    /// by the specified closure.
+    fn build(&mut self, shift: Vector, width: u32, height: u32, apply: &mut impl FnMut(&mut Self)) {
         let h = height as i32;
-        self.storage
-            .reset(FixedPoint { x: 0, y: 0 }, FixedPoint { x: w, y: h });
+        let [x0, y0, x1, y1] = clip_rect(self.clip, width, height);
+        let min = FixedPoint::new(x0 as i32, y0 as i32);
+        let max = FixedPoint::new(x1 as i32, y1 as i32);
+        if self.in_secondary() {
+            self.secondary.reset(min, max);
+        } else {
+            self.storage.reset(min, max);
+        }
         self.shift = shift;
         self.start = FixedPoint::default();
         self.closed = true;
         self.current = Point::ZERO;
-        self.xmin = 0;
-        self.ymin = 0;
-        self.xmax = w;
-        self.ymax = h;
+        self.xmin = min.x;
+        self.ymin = min.y;
+        self.xmax = max.x;
+        self.ymax = max.y;
         self.height = h;
         self.x = 0;
         self.y = 0;
         self.px = 0;
         self.py = 0;
         self.invalid = true;
+        self.subpath = 0;
+        self.skip = false;
         apply(self);
         if !self.closed {
             self.line_to(self.start);
         }
         if !self.invalid {
+            self.store();
+        }
+    }
+
+    /// Returns true if cells are being accumulated for the subpaths with
+    /// the fill rule that differs from the primary one.
+    #[inline(always)]
+    fn in_secondary(&self) -> bool {
+        match self.group {
+            Some(group) => group != self.fill,
+            None => false,
+        }
+    }
+
+    /// Adds the current cell to the storage for the group being built.
+    #[inline(always)]
+    fn store(&mut self) {
+        if self.in_secondary() {
+            self.secondary.set(self.x, self.y, self.area, self.cover);
+        } else {
             self.storage.set(self.x, self.y, self.area, self.cover);
         }
+    }
+
+    /// Starts a new subpath, determining whether it belongs to the group
+    /// being built.
+    #[inline(always)]
+    fn begin_subpath(&mut self) {
+        if let (Some(rules), Some(group)) = (self.fill_rules, self.group) {
+            let rule = rules.get(self.subpath).copied().unwrap_or(self.fill);
+            self.skip = rule != group;
+        }
+        self.subpath += 1;
+    }
+
+    pub fn rasterize(
+        &mut self,
+        shift: Vector,
+        width: u32,
+        height: u32,
+        apply: &mut impl FnMut(&mut Self),
+        fill: Fill,
+        buffer: &mut [u8],
+        pitch: usize,
+        y_up: bool,
+    ) {
+        if self.clip_mask.is_some() || self.fill_rules.is_some() {
+            // Coverage varies within spans, so write through the sweep.
+            self.rasterize_write(
+                shift,
+                width,
+                height,
+                apply,
+                fill,
+                pitch,
+                y_up,
+                &mut |row_offset, x, count, c| {
+                    let start = row_offset + x;
+                    buffer[start..start + count].fill(c);
+                },
+            );
+            return;
+        }
+        self.build(shift, width, height, apply);
+        if self.storage.exhausted() {
+            return;
+        }
         let indices = self.storage.indices();
         let cells = self.storage.cells();
         let min = FixedPoint::new(self.xmin, self.ymin);
         let max = FixedPoint::new(self.xmax, self.ymax);
         let height = height as usize;
+        let interrupt = self.interrupt;
+        let aliased = self.aliased;
+        let curve = self.curve;
+        let inverted = self.inverted;
         for (i, &index) in indices.iter().enumerate() {
+            if let Some(interrupt) = interrupt {
+                if interrupt.poll() {
+                    break;
+                }
+            }
             if index != -1 {
-                let y = ((i as i32) - min.y) as usize;
+                let y = (i as i32 + min.y) as usize;
                 let row_offset = if y_up {
                     (pitch * (height - 1 - y)) as usize
                 } else {
@@ -127,7 +539,7 @@ impl<'a, S: RasterStorage> Rasterizer<'a, S> {
                     let cell = &cells[index as usize];
                     if cover != 0 && cell.x > x {
                         let count = (cell.x - x) as usize;
-                        let c = coverage(fill, cover);
+                        let c = pixel_coverage(fill, cover, 0, aliased, curve, 255, inverted);
                         let xi = x as usize;
                         for b in &mut row[xi..xi + count] {
                             *b = c;
@@ -137,7 +549,7 @@ impl<'a, S: RasterStorage> Rasterizer<'a, S> {
                     area = cover.wrapping_sub(cell.area);
                     if area != 0 && cell.x >= min.x {
                         let count = 1;
-                        let c = coverage(fill, area);
+                        let c = pixel_coverage(fill, area, 0, aliased, curve, 255, inverted);
                         let xi = cell.x as usize;
                         for b in &mut row[xi..xi + count] {
                             *b = c;
@@ -151,7 +563,7 @@ impl<'a, S: RasterStorage> Rasterizer<'a, S> {
                 }
                 if cover != 0 {
                     let count = (max.x - x) as usize;
-                    let c = coverage(fill, cover);
+                    let c = pixel_coverage(fill, cover, 0, aliased, curve, 255, inverted);
                     let xi = x as usize;
                     for b in &mut row[xi..xi + count] {
                         *b = c;
@@ -172,76 +584,237 @@ impl<'a, S: RasterStorage> Rasterizer<'a, S> {
         y_up: bool,
         write: &mut impl FnMut(usize, usize, usize, u8),
     ) {
-        let w = width as i32;
-        let h = height as i32;
-        self.storage
-            .reset(FixedPoint { x: 0, y: 0 }, FixedPoint { x: w, y: h });
-        self.shift = shift;
-        self.start = FixedPoint::default();
-        self.closed = true;
-        self.current = Point::ZERO;
-        self.xmin = 0;
-        self.ymin = 0;
-        self.xmax = w;
-        self.ymax = h;
-        self.height = h;
-        self.x = 0;
-        self.y = 0;
-        self.px = 0;
-        self.py = 0;
-        self.invalid = true;
-        apply(self);
-        if !self.closed {
-            self.line_to(self.start);
+        let aliased = self.aliased;
+        let curve = self.curve;
+        let inverted = self.inverted;
+        self.sweep(
+            shift,
+            width,
+            height,
+            apply,
+            fill,
+            pitch,
+            y_up,
+            |c, s, clip| pixel_coverage(fill, c, s, aliased, curve, clip, inverted),
+            write,
+        );
+    }
+
+    /// Same as [rasterize_write](#method.rasterize_write), but resolves
+    /// coverage with 16 bits of precision.
+    #[allow(clippy::too_many_arguments)]
+    pub fn rasterize_write16(
+        &mut self,
+        shift: Vector,
+        width: u32,
+        height: u32,
+        apply: &mut impl FnMut(&mut Self),
+        fill: Fill,
+        pitch: usize,
+        y_up: bool,
+        write: &mut impl FnMut(usize, usize, usize, u16),
+    ) {
+        let aliased = self.aliased;
+        let curve = self.curve;
+        let inverted = self.inverted;
+        self.sweep(
+            shift,
+            width,
+            height,
+            apply,
+            fill,
+            pitch,
+            y_up,
+            |c, s, clip| pixel_coverage16(fill, c, s, aliased, curve, clip, inverted),
+            write,
+        );
+    }
+
+    /// Rasterizes the path and invokes the write function for each span of
+    /// pixels with the coverage produced by the resolve function from the
+    /// accumulated coverage of the primary and secondary groups of subpaths
+    /// and the clip mask.
+    #[allow(clippy::too_many_arguments)]
+    fn sweep<T: Copy>(
+        &mut self,
+        shift: Vector,
+        width: u32,
+        height: u32,
+        apply: &mut impl FnMut(&mut Self),
+        fill: Fill,
+        pitch: usize,
+        y_up: bool,
+        resolve: impl Fn(i32, i32, u8) -> T,
+        write: &mut impl FnMut(usize, usize, usize, T),
+    ) {
+        let grouped = self.fill_rules.is_some();
+        self.fill = fill;
+        if grouped {
+            self.group = Some(secondary_fill(fill));
+            self.build(shift, width, height, apply);
+            self.group = Some(fill);
         }
-        if !self.invalid {
-            self.storage.set(self.x, self.y, self.area, self.cover);
+        self.build(shift, width, height, apply);
+        self.group = None;
+        if self.storage.exhausted() {
+            return;
+        }
+        let indices = self.storage.indices();
+        let cells = self.storage.cells();
+        let (secondary_indices, secondary_cells) = if grouped {
+            (self.secondary.indices(), self.secondary.cells())
+        } else {
+            (&[][..], &[][..])
+        };
+        let min = FixedPoint::new(self.xmin, self.ymin);
+        let max = FixedPoint::new(self.xmax, self.ymax);
+        let width = width as usize;
+        let height = height as usize;
+        let interrupt = self.interrupt;
+        let clip_mask = self.clip_mask;
+        // Splits spans into runs of equal clip coverage, skipping pixels
+        // that are fully clipped.
+        let mut emit = |y: usize, row_offset: usize, x: usize, count: usize, value: (i32, i32)| {
+            let mask = match clip_mask {
+                Some(mask) => &mask[y * width + x..y * width + x + count],
+                None => return write(row_offset, x, count, resolve(value.0, value.1, 255)),
+            };
+            let mut start = 0;
+            while start < count {
+                let clip = mask[start];
+                let mut end = start + 1;
+                while end < count && mask[end] == clip {
+                    end += 1;
+                }
+                if clip != 0 {
+                    let c = resolve(value.0, value.1, clip);
+                    write(row_offset, x + start, end - start, c);
+                }
+                start = end;
+            }
+        };
+        for (i, &index) in indices.iter().enumerate() {
+            if let Some(interrupt) = interrupt {
+                if interrupt.poll() {
+                    break;
+                }
+            }
+            let mut secondary = secondary_indices.get(i).copied().unwrap_or(-1);
+            if index == -1 && secondary == -1 {
+                continue;
+            }
+            let y = (i as i32 + min.y) as usize;
+            let row_offset = if y_up {
+                (pitch * (height - 1 - y)) as usize
+            } else {
+                (pitch * y) as usize
+            };
+            let mut x = min.x;
+            let mut cover = (0, 0);
+            let mut index = index;
+            // Merges the cells of both groups in order of increasing x.
+            loop {
+                let next = if index != -1 {
+                    cells[index as usize].x
+                } else {
+                    i32::MAX
+                };
+                let next_secondary = if secondary != -1 {
+                    secondary_cells[secondary as usize].x
+                } else {
+                    i32::MAX
+                };
+                let cell_x = next.min(next_secondary);
+                if cell_x == i32::MAX {
+                    break;
+                }
+                if cover != (0, 0) && cell_x > x {
+                    let count = (cell_x - x) as usize;
+                    emit(y, row_offset, x as usize, count, cover);
+                }
+                let mut area = cover;
+                if next == cell_x {
+                    let cell = &cells[index as usize];
+                    cover.0 = cover.0.wrapping_add(cell.cover.wrapping_mul(ONE_PIXEL * 2));
+                    area.0 = cover.0.wrapping_sub(cell.area);
+                    index = cell.next;
+                }
+                if next_secondary == cell_x {
+                    let cell = &secondary_cells[secondary as usize];
+                    cover.1 = cover.1.wrapping_add(cell.cover.wrapping_mul(ONE_PIXEL * 2));
+                    area.1 = cover.1.wrapping_sub(cell.area);
+                    secondary = cell.next;
+                }
+                if area != (0, 0) && cell_x >= min.x {
+                    emit(y, row_offset, cell_x as usize, 1, area);
+                }
+                x = cell_x + 1;
+            }
+            if cover != (0, 0) {
+                let count = (max.x - x) as usize;
+                emit(y, row_offset, x as usize, count, cover);
+            }
+        }
+    }
+
+    /// Rasterizes the path and adds the signed coverage delta of each pixel
+    /// to the buffer. The prefix sum of a row yields the accumulated coverage
+    /// of each pixel.
+    #[allow(clippy::too_many_arguments)]
+    pub fn rasterize_deltas(
+        &mut self,
+        shift: Vector,
+        width: u32,
+        height: u32,
+        apply: &mut impl FnMut(&mut Self),
+        buffer: &mut [i32],
+        pitch: usize,
+        y_up: bool,
+    ) {
+        self.build(shift, width, height, apply);
+        if self.storage.exhausted() {
+            return;
         }
         let indices = self.storage.indices();
         let cells = self.storage.cells();
         let min = FixedPoint::new(self.xmin, self.ymin);
         let max = FixedPoint::new(self.xmax, self.ymax);
         let height = height as usize;
+        let interrupt = self.interrupt;
         for (i, &index) in indices.iter().enumerate() {
+            if let Some(interrupt) = interrupt {
+                if interrupt.poll() {
+                    break;
+                }
+            }
             if index != -1 {
-                let y = ((i as i32) - min.y) as usize;
+                let y = (i as i32 + min.y) as usize;
                 let row_offset = if y_up {
-                    (pitch * (height - 1 - y)) as usize
+                    pitch * (height - 1 - y)
                 } else {
-                    (pitch * y) as usize
+                    pitch * y
                 };
-                let mut x = min.x;
-                let mut cover = 0;
-                let mut area;
+                let row = &mut buffer[row_offset..];
                 let mut index = index;
                 loop {
                     let cell = &cells[index as usize];
-                    if cover != 0 && cell.x > x {
-                        let count = (cell.x - x) as usize;
-                        let c = coverage(fill, cover);
-                        let xi = x as usize;
-                        write(row_offset, xi, count, c);
-                    }
-                    cover = cover.wrapping_add(cell.cover.wrapping_mul(ONE_PIXEL * 2));
-                    area = cover.wrapping_sub(cell.area);
-                    if area != 0 && cell.x >= min.x {
-                        let count = 1;
-                        let c = coverage(fill, area);
-                        let xi = cell.x as usize;
-                        write(row_offset, xi, count, c);
+                    let cover = cell.cover.wrapping_mul(ONE_PIXEL * 2);
+                    if cell.x < min.x {
+                        // Cells to the left only contribute their cover.
+                        let x = min.x as usize;
+                        row[x] = row[x].wrapping_add(cover);
+                    } else if cell.x < max.x {
+                        let x = cell.x as usize;
+                        row[x] = row[x].wrapping_add(cover.wrapping_sub(cell.area));
+                        if cell.x + 1 < max.x {
+                            row[x + 1] = row[x + 1].wrapping_add(cell.area);
+                        }
                     }
-                    x = cell.x + 1;
                     index = cell.next;
                     if index == -1 {
                         break;
                     }
                 }
-                if cover != 0 {
-                    let count = (max.x - x) as usize;
-                    let c = coverage(fill, cover);
-                    let xi = x as usize;
-                    write(row_offset, xi, count, c);
-                }
             }
         }
     }
@@ -249,7 +822,7 @@ impl<'a, S: RasterStorage> Rasterizer<'a, S> {
     #[inline(always)]
     fn set_cell(&mut self, x: i32, y: i32) {
         if !self.invalid && (self.area != 0 || self.cover != 0) {
-            self.storage.set(self.x, self.y, self.area, self.cover);
+            self.store();
         }
         self.area = 0;
         self.cover = 0;
@@ -273,7 +846,10 @@ impl<'a, S: RasterStorage> Rasterizer<'a, S> {
         let to_y = to.y;
         let mut ey1 = trunc(self.py);
         let ey2 = trunc(to_y);
-        if (ey1 >= self.ymax && ey2 >= self.ymax) || (ey1 < self.ymin && ey2 < self.ymin) {
+        if (ey1 >= self.ymax && ey2 >= self.ymax)
+            || (ey1 < self.ymin && ey2 < self.ymin)
+            || (trunc(self.px) >= self.xmax && trunc(to_x) >= self.xmax)
+        {
             self.px = to_x;
             self.py = to_y;
             return;
@@ -392,6 +968,9 @@ impl<'a, S: RasterStorage> Rasterizer<'a, S> {
             || (trunc(arc[0].y) < self.ymin
                 && trunc(arc[1].y) < self.ymin
                 && trunc(arc[2].y) < self.ymin)
+            || (trunc(arc[0].x) >= self.xmax
+                && trunc(arc[1].x) >= self.xmax
+                && trunc(arc[2].x) >= self.xmax)
         {
             self.px = arc[0].x;
             self.py = arc[0].y;
@@ -403,7 +982,7 @@ impl<'a, S: RasterStorage> Rasterizer<'a, S> {
             dx = dy;
         }
         let mut draw = 1;
-        while dx > ONE_PIXEL / 4 {
+        while dx > (ONE_PIXEL / 4) << self.flatness {
             dx >>= 2;
             draw <<= 1;
         }
@@ -447,17 +1026,22 @@ impl<'a, S: RasterStorage> Rasterizer<'a, S> {
                 && trunc(arc[1].y) < self.ymin
                 && trunc(arc[2].y) < self.ymin
                 && trunc(arc[3].y) < self.ymin)
+            || (trunc(arc[0].x) >= self.xmax
+                && trunc(arc[1].x) >= self.xmax
+                && trunc(arc[2].x) >= self.xmax
+                && trunc(arc[3].x) >= self.xmax)
         {
             self.px = arc[0].x;
             self.py = arc[0].y;
             return;
         }
+        let tolerance = (ONE_PIXEL / 2) << self.flatness;
         let mut a = 0;
         loop {
-            if (2 * arc[a].x - 3 * arc[a + 1].x + arc[a + 3].x).abs() > ONE_PIXEL / 2
-                || (2 * arc[a].y - 3 * arc[a + 1].y + arc[a + 3].y).abs() > ONE_PIXEL / 2
-                || (arc[a].x - 3 * arc[a + 2].x + 2 * arc[a + 3].x).abs() > ONE_PIXEL / 2
-                || (arc[a].y - 3 * arc[a + 2].y + 2 * arc[a + 3].y).abs() > ONE_PIXEL / 2
+            if (2 * arc[a].x - 3 * arc[a + 1].x + arc[a + 3].x).abs() > tolerance
+                || (2 * arc[a].y - 3 * arc[a + 1].y + arc[a + 3].y).abs() > tolerance
+                || (arc[a].x - 3 * arc[a + 2].x + 2 * arc[a + 3].x).abs() > tolerance
+                || (arc[a].y - 3 * arc[a + 2].y + 2 * arc[a + 3].y).abs() > tolerance
             {
                 let buf = &mut arc[a..];
                 // if buf.len() < 7 {
@@ -484,41 +1068,63 @@ impl<'a, S: RasterStorage> Rasterizer<'a, S> {
 
 impl<'a, S: RasterStorage> PathBuilder for Rasterizer<'a, S> {
     fn current_point(&self) -> Point {
-        self.current + self.shift
+        self.current
+    }
+
+    fn tolerance(&self) -> f32 {
+        // Matches the flatness used to subdivide quadratic curves.
+        0.25 * (1 << self.flatness) as f32
     }
 
     #[inline(always)]
     fn move_to(&mut self, to: impl Into<Point>) -> &mut Self {
+        if self.interrupted() {
+            return self;
+        }
         if !self.closed {
             self.line_to(self.start);
         }
+        self.begin_subpath();
         let to = to.into();
-        let p = FixedPoint::from_point(to + self.shift);
+        self.current = to;
+        if self.skip {
+            self.closed = true;
+            return self;
+        }
+        let p = self.fixed(to);
         self.move_to(p);
         self.closed = false;
         self.start = p;
-        self.current = to;
         self
     }
 
     #[inline(always)]
     fn line_to(&mut self, to: impl Into<Point>) -> &mut Self {
+        if self.interrupted() {
+            return self;
+        }
         let to = to.into();
         self.current = to;
+        if self.skip {
+            return self;
+        }
         self.closed = false;
-        self.line_to(FixedPoint::from_point(to + self.shift));
+        self.line_to(self.fixed(to));
         self
     }
 
     #[inline(always)]
     fn quad_to(&mut self, control: impl Into<Point>, to: impl Into<Point>) -> &mut Self {
+        if self.interrupted() {
+            return self;
+        }
         let to = to.into();
         self.current = to;
+        if self.skip {
+            return self;
+        }
         self.closed = false;
-        self.quad_to(
-            FixedPoint::from_point(control.into() + self.shift),
-            FixedPoint::from_point(to + self.shift),
-        );
+        self.quad_to(self.fixed(control.into()), self.fixed(to));
         self
     }
 
@@ -529,19 +1135,28 @@ impl<'a, S: RasterStorage> PathBuilder for Rasterizer<'a, S> {
         control2: impl Into<Point>,
         to: impl Into<Point>,
     ) -> &mut Self {
+        if self.interrupted() {
+            return self;
+        }
         let to = to.into();
         self.current = to;
+        if self.skip {
+            return self;
+        }
         self.closed = false;
         self.curve_to(
-            FixedPoint::from_point(control1.into() + self.shift),
-            FixedPoint::from_point(control2.into() + self.shift),
-            FixedPoint::from_point(to + self.shift),
+            self.fixed(control1.into()),
+            self.fixed(control2.into()),
+            self.fixed(to),
         );
         self
     }
 
     #[inline(always)]
     fn close(&mut self) -> &mut Self {
+        if self.skip {
+            return self;
+        }
         self.line_to(self.start);
         self.closed = true;
         self
@@ -561,6 +1176,12 @@ pub trait RasterStorage {
     fn cells(&self) -> &[Cell];
     fn indices(&self) -> &[i32];
     fn set(&mut self, x: i32, y: i32, area: i32, cover: i32);
+
+    /// Returns true if cells were dropped because the storage could not
+    /// grow, in which case nothing should be written from it.
+    fn exhausted(&self) -> bool {
+        false
+    }
 }
 
 #[derive(Default)]
@@ -621,6 +1242,54 @@ impl RasterStorage for HeapStorage {
     }
 }
 
+impl HeapStorage {
+    /// Returns the number of bytes held by the storage.
+    pub fn capacity(&self) -> usize {
+        self.cells.capacity() * core::mem::size_of::<Cell>()
+            + self.indices.capacity() * core::mem::size_of::<i32>()
+    }
+}
+
+/// Heap storage that grows only within a budget.
+pub struct LimitedStorage<'a> {
+    pub storage: &'a mut HeapStorage,
+    pub budget: &'a Budget,
+}
+
+impl RasterStorage for LimitedStorage<'_> {
+    fn reset(&mut self, min: FixedPoint, max: FixedPoint) {
+        let storage = &mut *self.storage;
+        storage.min = min;
+        storage.max = max;
+        storage.cells.clear();
+        storage.indices.clear();
+        let height = (max.y - min.y) as usize;
+        if self.budget.reserve(&mut storage.indices, height) {
+            storage.indices.resize(height, -1);
+        }
+    }
+
+    fn cells(&self) -> &[Cell] {
+        &self.storage.cells
+    }
+
+    fn indices(&self) -> &[i32] {
+        &self.storage.indices
+    }
+
+    #[inline(always)]
+    fn set(&mut self, x: i32, y: i32, area: i32, cover: i32) {
+        if !self.budget.reserve(&mut self.storage.cells, 1) {
+            return;
+        }
+        self.storage.set(x, y, area, cover);
+    }
+
+    fn exhausted(&self) -> bool {
+        self.budget.exceeded()
+    }
+}
+
 const MAX_CELLS: usize = 1024;
 const MAX_BAND: usize = 512;
 
@@ -734,7 +1403,8 @@ impl RasterStorage for AdaptiveStorage {
     }
 }
 
-const _MAX_DIM: u32 = core::i16::MAX as u32;
+/// Maximum width or height of a mask in pixels.
+pub(super) const MAX_DIM: u32 = core::i16::MAX as u32;
 
 fn split_quad(base: &mut [FixedPoint]) {
     let mut a;
@@ -812,7 +1482,7 @@ fn to_fixed(v: f32) -> i32 {
     unsafe { (v * 256.).to_int_unchecked() }
 }
 
-const PIXEL_BITS: i32 = 8;
+pub(super) const PIXEL_BITS: i32 = 8;
 const ONE_PIXEL: i32 = 1 << PIXEL_BITS;
 
 #[inline(always)]
@@ -0,0 +1,350 @@
+//! Retained scene.
+
+use super::command::Command;
+use super::geometry::{Bounds, Origin, Placement, Point, Transform, Vector};
+use super::hit_test::{lookup, HitTest};
+use super::mask::Mask;
+use super::path_data::PathData;
+use super::raster::MAX_DIM;
+use super::scratch::Scratch;
+use super::style::Style;
+#[cfg(not(feature = "std"))]
+use super::F32Ext;
+
+use crate::lib::Vec;
+use alloc::collections::BTreeMap;
+
+/// Retained collection of styled paths that supports incremental updates.
+///
+/// Each item is identified by a caller provided id and is painted in
+/// insertion order. Changes to items are tracked so that only the modified
+/// items are rasterized by [render](#method.render), and the union of the
+/// areas affected by those changes is reported as a dirty region. Hit testing
+/// culls by bounds and uses the cached masks of rendered items, falling back
+/// to an exact test for items with pending changes.
+///
+/// This type is only available with the `scene` feature.
+///
+/// ```rust
+/// use zeno::{Fill, Scene, Transform};
+///
+/// let mut scene = Scene::new();
+/// scene.insert(1, "M0,0 10,0 10,10 0,10 Z", Fill::NonZero, None);
+/// scene.insert(2, "M5,5 15,5 15,15 5,15 Z", Fill::NonZero, None);
+/// scene.render();
+/// assert_eq!(scene.hit_test([7, 7]), Some(2));
+/// assert_eq!(scene.hit_test([2, 2]), Some(1));
+///
+/// scene.set_transform(2, Some(Transform::translation(20., 0.)));
+/// let dirty = scene.render().unwrap();
+/// assert!(dirty.contains([7., 7.]) && dirty.contains([27., 7.]));
+/// assert_eq!(scene.hit_test([7, 7]), Some(1));
+/// ```
+pub struct Scene<'a> {
+    items: Vec<Item<'a>>,
+    ids: BTreeMap<u64, usize>,
+    dirty: Option<Bounds>,
+    viewport: Option<Bounds>,
+    scratch: Scratch,
+}
+
+struct Item<'a> {
+    id: u64,
+    path: Vec<Command>,
+    style: Style<'a>,
+    transform: Option<Transform>,
+    placement: Placement,
+    mask: Vec<u8>,
+    dirty: bool,
+}
+
+impl<'a> Scene<'a> {
+    /// Creates a new empty scene.
+    pub fn new() -> Self {
+        Self {
+            items: Vec::new(),
+            ids: BTreeMap::new(),
+            dirty: None,
+            viewport: None,
+            scratch: Scratch::new(),
+        }
+    }
+
+    /// Returns the number of items in the scene.
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Returns true if the scene contains no items.
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Returns true if the scene contains an item with the specified id.
+    pub fn contains(&self, id: u64) -> bool {
+        self.ids.contains_key(&id)
+    }
+
+    /// Inserts an item into the scene. If an item with the same id already
+    /// exists, it is replaced and retains its paint order. Otherwise, the
+    /// item is painted above all existing items.
+    pub fn insert(
+        &mut self,
+        id: u64,
+        data: impl PathData,
+        style: impl Into<Style<'a>>,
+        transform: Option<Transform>,
+    ) {
+        let mut path = Vec::new();
+        data.copy_to(&mut path);
+        let item = Item {
+            id,
+            path,
+            style: style.into(),
+            transform,
+            placement: Placement::default(),
+            mask: Vec::new(),
+            dirty: true,
+        };
+        match self.ids.get(&id) {
+            Some(&index) => {
+                let old = self.items[index].placement;
+                self.items[index] = item;
+                self.invalidate(&old);
+                self.update(index);
+            }
+            None => {
+                self.ids.insert(id, self.items.len());
+                self.items.push(item);
+                self.update(self.items.len() - 1);
+            }
+        }
+    }
+
+    /// Removes the item with the specified id. Returns true if the item was
+    /// present.
+    pub fn remove(&mut self, id: u64) -> bool {
+        let index = match self.ids.remove(&id) {
+            Some(index) => index,
+            None => return false,
+        };
+        let item = self.items.remove(index);
+        self.invalidate(&item.placement);
+        for other in self.ids.values_mut() {
+            if *other > index {
+                *other -= 1;
+            }
+        }
+        true
+    }
+
+    /// Sets the transform of the item with the specified id. Returns true if
+    /// the item was present.
+    pub fn set_transform(&mut self, id: u64, transform: Option<Transform>) -> bool {
+        self.modify(id, |item| item.transform = transform)
+    }
+
+    /// Sets the style of the item with the specified id. Returns true if the
+    /// item was present.
+    pub fn set_style(&mut self, id: u64, style: impl Into<Style<'a>>) -> bool {
+        let style = style.into();
+        self.modify(id, |item| item.style = style)
+    }
+
+    /// Returns the region of the scene that is rasterized.
+    pub fn viewport(&self) -> Option<Bounds> {
+        self.viewport
+    }
+
+    /// Sets the region of the scene that is rasterized. The placements and
+    /// masks of items are clipped to the viewport and hit testing is limited
+    /// to it. The default is `None`, in which case the full extent of each
+    /// item is rasterized.
+    ///
+    /// ```rust
+    /// use zeno::{Bounds, Fill, Scene};
+    ///
+    /// let mut scene = Scene::new();
+    /// scene.set_viewport(Some(Bounds::new([0., 0.].into(), [100., 50.].into())));
+    /// let huge = "M-1000000,-1000000 1000000,-1000000 1000000,1000000 -1000000,1000000 Z";
+    /// scene.insert(1, huge, Fill::NonZero, None);
+    /// scene.render();
+    /// let (mask, placement) = scene.mask(1).unwrap();
+    /// assert_eq!((placement.width, placement.height), (100, 50));
+    /// assert!(mask.len() == 100 * 50 && mask.iter().all(|&c| c == 255));
+    /// assert_eq!(scene.hit_test([200, 10]), None);
+    /// ```
+    pub fn set_viewport(&mut self, viewport: Option<Bounds>) {
+        self.viewport = viewport;
+        for index in 0..self.items.len() {
+            let old = self.items[index].placement;
+            self.invalidate(&old);
+            self.update(index);
+        }
+    }
+
+    /// Returns the region affected by changes since the last call to
+    /// [render](#method.render).
+    pub fn dirty_region(&self) -> Option<Bounds> {
+        self.dirty
+    }
+
+    /// Rasterizes all items with pending changes and returns the region that
+    /// was affected by the changes.
+    ///
+    /// Items that are wider or taller than 32767 pixels after clipping to the
+    /// [viewport](#method.set_viewport) are not rasterized. They keep empty
+    /// masks and are hit tested exactly.
+    ///
+    /// ```rust
+    /// use zeno::{Fill, Scene};
+    ///
+    /// let mut scene = Scene::new();
+    /// scene.insert(1, "M0,0 1000000,0 1000000,1000000 0,1000000 Z", Fill::NonZero, None);
+    /// scene.render();
+    /// assert!(scene.mask(1).unwrap().0.is_empty());
+    /// assert_eq!(scene.hit_test([500000, 500000]), Some(1));
+    /// ```
+    pub fn render(&mut self) -> Option<Bounds> {
+        let scratch = &mut self.scratch;
+        for item in self.items.iter_mut().filter(|item| item.dirty) {
+            let placement = item.placement;
+            let len = match (placement.width as usize).checked_mul(placement.height as usize) {
+                Some(len) if placement.width <= MAX_DIM && placement.height <= MAX_DIM => len,
+                _ => continue,
+            };
+            item.mask.clear();
+            item.mask.resize(len, 0);
+            Mask::with_scratch(&item.path[..], scratch)
+                .style(item.style)
+                .transform(item.transform)
+                .offset(Vector::new(-placement.left as f32, -placement.top as f32))
+                .size(placement.width, placement.height)
+                .render_into(&mut item.mask, None);
+            item.dirty = false;
+        }
+        self.dirty.take()
+    }
+
+    /// Returns the rendered alpha mask and placement for the item with the
+    /// specified id. The mask is empty if the item has pending changes.
+    pub fn mask(&self, id: u64) -> Option<(&[u8], Placement)> {
+        let item = &self.items[*self.ids.get(&id)?];
+        if item.dirty {
+            Some((&[], item.placement))
+        } else {
+            Some((&item.mask, item.placement))
+        }
+    }
+
+    /// Returns an iterator over the ids, masks and placements of all items in
+    /// paint order. Masks are empty for items with pending changes.
+    pub fn masks(&self) -> impl Iterator<Item = (u64, &[u8], Placement)> + '_ {
+        self.items.iter().map(|item| {
+            let mask: &[u8] = if item.dirty { &[] } else { &item.mask };
+            (item.id, mask, item.placement)
+        })
+    }
+
+    /// Returns the id of the topmost item that paints the specified point.
+    pub fn hit_test(&mut self, point: impl Into<Point>) -> Option<u64> {
+        let point = point.into();
+        let scratch = &mut self.scratch;
+        self.items.iter().rev().find_map(|item| {
+            if !placement_bounds(&item.placement).contains(point) {
+                return None;
+            }
+            let hit = if item.dirty {
+                HitTest::with_scratch(&item.path[..], scratch)
+                    .style(item.style)
+                    .transform(item.transform)
+                    .test(point)
+            } else {
+                lookup(&item.mask, &item.placement, point) != 0
+            };
+            if hit {
+                Some(item.id)
+            } else {
+                None
+            }
+        })
+    }
+
+    fn modify(&mut self, id: u64, f: impl FnOnce(&mut Item<'a>)) -> bool {
+        let index = match self.ids.get(&id) {
+            Some(&index) => index,
+            None => return false,
+        };
+        let old = self.items[index].placement;
+        f(&mut self.items[index]);
+        self.invalidate(&old);
+        self.update(index);
+        true
+    }
+
+    /// Recomputes the placement of an item and marks it for rendering.
+    fn update(&mut self, index: usize) {
+        let item = &mut self.items[index];
+        let bounds = self
+            .scratch
+            .bounds(&item.path[..], item.style, item.transform);
+        let (_, mut placement) = Placement::compute(Origin::TopLeft, Vector::ZERO, &bounds);
+        if let Some(viewport) = &self.viewport {
+            placement = clip_placement(placement, viewport);
+        }
+        item.placement = placement;
+        item.dirty = true;
+        self.invalidate(&placement);
+    }
+
+    fn invalidate(&mut self, placement: &Placement) {
+        if placement.width == 0 || placement.height == 0 {
+            return;
+        }
+        let bounds = placement_bounds(placement);
+        self.dirty = Some(match self.dirty {
+            Some(dirty) => Bounds::new(
+                Point::new(dirty.min.x.min(bounds.min.x), dirty.min.y.min(bounds.min.y)),
+                Point::new(dirty.max.x.max(bounds.max.x), dirty.max.y.max(bounds.max.y)),
+            ),
+            None => bounds,
+        });
+    }
+}
+
+impl Default for Scene<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Returns the part of a placement that lies within the viewport.
+fn clip_placement(placement: Placement, viewport: &Bounds) -> Placement {
+    let left = (placement.left as i64).max(viewport.min.x.floor() as i64);
+    let top = (placement.top as i64).max(viewport.min.y.floor() as i64);
+    let right = (placement.left as i64 + placement.width as i64).min(viewport.max.x.ceil() as i64);
+    let bottom = (placement.top as i64 + placement.height as i64).min(viewport.max.y.ceil() as i64);
+    if right <= left || bottom <= top {
+        return Placement {
+            width: 0,
+            height: 0,
+            ..placement
+        };
+    }
+    Placement {
+        left: left as i32,
+        top: top as i32,
+        width: (right - left) as u32,
+        height: (bottom - top) as u32,
+        ..placement
+    }
+}
+
+/// Returns the area covered by a placement.
+fn placement_bounds(placement: &Placement) -> Bounds {
+    let min = Point::new(placement.left as f32, placement.top as f32);
+    Bounds::new(
+        min,
+        min + Vector::new(placement.width as f32, placement.height as f32),
+    )
+}
@@ -1,21 +1,29 @@
 //! Context for reusing dynamic memory allocations.
 
-use super::geometry::{Bounds, BoundsBuilder, Transform};
+use super::command::Command;
+use super::geometry::{Bounds, BoundsBuilder, Point, StyledBounds, Transform};
 use super::path_builder::{PathBuilder, TransformSink};
-use super::path_data::PathData;
+use super::path_data::{
+    conservative_bounds, stroke_bounds_from_center, stroke_tolerance, PathData,
+};
 use super::raster::HeapStorage;
 use super::segment::Segment;
-use super::stroke::stroke_with_storage;
+use super::stroke::{clamp_width, stroke_with_storage, StrokerStorage};
 use super::style::{Fill, Style};
 
 use crate::lib::Vec;
 use core::borrow::Borrow;
+use core::cell::Cell;
+use core::fmt;
+use core::mem::size_of;
 
 /// Scratch memory for reusable heap allocations.
 #[derive(Default)]
 pub struct Scratch {
     pub(super) inner: Inner,
     pub(super) render: HeapStorage,
+    pub(super) path: Vec<Command>,
+    pub(super) limit: Option<usize>,
 }
 
 impl Scratch {
@@ -24,6 +32,53 @@ impl Scratch {
         Self::default()
     }
 
+    /// Creates a new scratch memory context that will never hold more than
+    /// the specified number of bytes.
+    pub fn with_limit(limit: usize) -> Self {
+        Self {
+            limit: Some(limit),
+            ..Self::default()
+        }
+    }
+
+    /// Sets the maximum number of bytes that may be held by the context.
+    ///
+    /// Operations that would grow the context beyond the limit fail rather
+    /// than allocate. Once the limit is reached, the context stops accepting
+    /// data and nothing is rasterized from the incomplete path. Fallible
+    /// operations such as
+    /// [Mask::try_render](struct.Mask.html#method.try_render) report this
+    /// with an error while the infallible variants produce an empty result.
+    /// Memory that is already held is not released.
+    ///
+    /// ```rust
+    /// use zeno::{Mask, RenderError, Scratch};
+    ///
+    /// let mut scratch = Scratch::new();
+    /// scratch.set_limit(Some(1024));
+    /// let mut buf = vec![0u8; 100 * 100];
+    /// let result = Mask::with_scratch("M0,0 L100,0 L100,100 L0,100 Z", &mut scratch)
+    ///     .size(100, 100)
+    ///     .try_render_into(&mut buf, None);
+    /// assert_eq!(result.unwrap_err(), RenderError::LimitExceeded);
+    /// assert!(buf.iter().all(|&c| c == 0));
+    /// ```
+    pub fn set_limit(&mut self, limit: Option<usize>) {
+        self.limit = limit;
+    }
+
+    /// Returns the maximum number of bytes that may be held by the context.
+    pub fn limit(&self) -> Option<usize> {
+        self.limit
+    }
+
+    /// Returns the number of bytes currently held by the context.
+    pub fn usage(&self) -> usize {
+        self.inner.segments.capacity() * size_of::<Segment>()
+            + self.render.capacity()
+            + self.path.capacity() * size_of::<Command>()
+    }
+
     /// Applies the style and transform to the path and emits the result to the specified sink.
     pub fn apply<'a>(
         &mut self,
@@ -32,7 +87,30 @@ impl Scratch {
         transform: Option<Transform>,
         sink: &mut impl PathBuilder,
     ) -> Fill {
-        self.inner.apply(data, &style.into(), transform, sink)
+        let budget = self.budget();
+        self.inner
+            .apply(data, &style.into(), transform, sink, &budget)
+    }
+
+    /// Applies the style and transform to the path and emits the result to
+    /// the specified sink. Returns an error if the operation would exceed the
+    /// memory limit of the context.
+    pub fn try_apply<'a>(
+        &mut self,
+        data: impl PathData,
+        style: impl Into<Style<'a>>,
+        transform: Option<Transform>,
+        sink: &mut impl PathBuilder,
+    ) -> Result<Fill, LimitExceeded> {
+        let budget = self.budget();
+        let fill = self
+            .inner
+            .apply(data, &style.into(), transform, sink, &budget);
+        budget.check().map(|_| fill)
+    }
+
+    pub(super) fn budget(&self) -> Budget {
+        Budget::new(self.limit, self.usage())
     }
 
     /// Computes the bounding box of the path.
@@ -47,6 +125,24 @@ impl Scratch {
         self.apply(data, style, transform, &mut bounds);
         bounds.build()
     }
+
+    /// Computes both a conservative and an exact bounding box of the path.
+    pub fn styled_bounds<'a>(
+        &mut self,
+        data: impl PathData,
+        style: impl Into<Style<'a>>,
+        transform: Option<Transform>,
+    ) -> StyledBounds {
+        let style = style.into();
+        let mut exact = BoundsBuilder::exact();
+        if !stroke_bounds_from_center(&data, &style, transform, &mut exact) {
+            self.apply(&data, style, transform, &mut exact);
+        }
+        StyledBounds {
+            conservative: conservative_bounds(&data, &style, transform),
+            exact: exact.build(),
+        }
+    }
 }
 
 #[derive(Default)]
@@ -61,7 +157,12 @@ impl Inner {
         style: &Style,
         transform: Option<Transform>,
         sink: &mut impl PathBuilder,
+        budget: &Budget,
     ) -> Fill {
+        let segments = &mut LimitedSegments {
+            segments: &mut self.segments,
+            budget,
+        };
         match style {
             Style::Fill(fill) => {
                 if let Some(transform) = transform {
@@ -74,14 +175,17 @@ impl Inner {
                 }
             }
             Style::Stroke(stroke) => {
+                let stroke = clamp_width(stroke, transform.as_ref());
                 if let Some(transform) = transform {
                     if stroke.scale {
+                        let tolerance = stroke_tolerance(&transform);
                         let mut transform_sink = TransformSink { sink, transform };
                         stroke_with_storage(
                             data.commands(),
                             &stroke,
                             &mut transform_sink,
-                            &mut self.segments,
+                            segments,
+                            tolerance,
                         );
                     } else {
                         stroke_with_storage(
@@ -89,14 +193,177 @@ impl Inner {
                                 .map(|cmd| cmd.borrow().transform(&transform)),
                             &stroke,
                             sink,
-                            &mut self.segments,
+                            segments,
+                            1.,
                         );
                     }
                 } else {
-                    stroke_with_storage(data.commands(), &stroke, sink, &mut self.segments);
+                    stroke_with_storage(data.commands(), &stroke, sink, segments, 1.);
                 }
                 Fill::NonZero
             }
         }
     }
 }
+
+/// Error returned when an operation would exceed the memory limit of a
+/// [Scratch](struct.Scratch.html) context.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct LimitExceeded;
+
+impl fmt::Display for LimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "scratch memory limit exceeded")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for LimitExceeded {}
+
+/// Remaining allowance of bytes for a single operation on a scratch context.
+pub(super) struct Budget {
+    remaining: Cell<usize>,
+    exceeded: Cell<bool>,
+}
+
+impl Budget {
+    pub fn new(limit: Option<usize>, usage: usize) -> Self {
+        let remaining = match limit {
+            Some(limit) => limit.saturating_sub(usage),
+            None => usize::MAX,
+        };
+        Self {
+            remaining: Cell::new(remaining),
+            exceeded: Cell::new(false),
+        }
+    }
+
+    pub fn check(&self) -> Result<(), LimitExceeded> {
+        if self.exceeded() {
+            Err(LimitExceeded)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Returns true if an allocation was refused, after which nothing more
+    /// is accepted.
+    pub fn exceeded(&self) -> bool {
+        self.exceeded.get()
+    }
+
+    /// Takes the specified number of bytes from the budget.
+    pub fn take(&self, bytes: usize) -> bool {
+        if self.exceeded.get() {
+            return false;
+        }
+        match self.remaining.get().checked_sub(bytes) {
+            Some(remaining) => {
+                self.remaining.set(remaining);
+                true
+            }
+            None => {
+                self.exceeded.set(true);
+                false
+            }
+        }
+    }
+
+    /// Ensures that the vector has room for the additional elements,
+    /// growing it only if the growth fits within the budget.
+    pub fn reserve<T>(&self, vec: &mut Vec<T>, additional: usize) -> bool {
+        let len = vec.len();
+        let capacity = vec.capacity();
+        if capacity - len >= additional {
+            return !self.exceeded.get();
+        }
+        let new_capacity = (len + additional).max(capacity * 2).max(4);
+        if !self.take((new_capacity - capacity) * size_of::<T>()) {
+            return false;
+        }
+        vec.reserve_exact(new_capacity - len);
+        true
+    }
+}
+
+/// Stroker storage that grows only within a budget.
+struct LimitedSegments<'a> {
+    segments: &'a mut Vec<Segment>,
+    budget: &'a Budget,
+}
+
+impl StrokerStorage for LimitedSegments<'_> {
+    fn clear(&mut self) {
+        self.segments.clear();
+    }
+
+    #[inline(always)]
+    fn push(&mut self, segment: &Segment) {
+        if self.budget.reserve(self.segments, 1) {
+            self.segments.push(*segment);
+        }
+    }
+
+    fn get(&self) -> &[Segment] {
+        self.segments
+    }
+}
+
+/// Path builder that records commands only within a budget.
+pub(super) struct LimitedPath<'a> {
+    pub path: &'a mut Vec<Command>,
+    pub budget: &'a Budget,
+}
+
+impl LimitedPath<'_> {
+    fn push(&mut self, cmd: Command) -> &mut Self {
+        if self.budget.reserve(self.path, 1) {
+            self.path.push(cmd);
+        }
+        self
+    }
+}
+
+impl PathBuilder for LimitedPath<'_> {
+    fn current_point(&self) -> Point {
+        self.path.current_point()
+    }
+
+    fn move_to(&mut self, to: impl Into<Point>) -> &mut Self {
+        self.push(Command::MoveTo(to.into()))
+    }
+
+    fn line_to(&mut self, to: impl Into<Point>) -> &mut Self {
+        self.push(Command::LineTo(to.into()))
+    }
+
+    fn quad_to(&mut self, control: impl Into<Point>, to: impl Into<Point>) -> &mut Self {
+        self.push(Command::QuadTo(control.into(), to.into()))
+    }
+
+    fn conic_to(
+        &mut self,
+        control: impl Into<Point>,
+        to: impl Into<Point>,
+        weight: f32,
+    ) -> &mut Self {
+        self.push(Command::ConicTo(control.into(), to.into(), weight))
+    }
+
+    fn curve_to(
+        &mut self,
+        control1: impl Into<Point>,
+        control2: impl Into<Point>,
+        to: impl Into<Point>,
+    ) -> &mut Self {
+        self.push(Command::CurveTo(
+            control1.into(),
+            control2.into(),
+            to.into(),
+        ))
+    }
+
+    fn close(&mut self) -> &mut Self {
+        self.push(Command::Close)
+    }
+}
@@ -2,10 +2,24 @@
 
 use super::command::Command;
 use super::geometry::*;
+use super::path_builder::{Arc, ConicQuads, DEFAULT_TOLERANCE};
 use super::F32Ext;
 
+use crate::lib::Vec;
 use core::borrow::Borrow;
 
+/// Method used for measuring the arc length of curves.
+#[derive(Copy, Clone, PartialEq, Default, Debug)]
+pub enum Measure {
+    /// Fast approximation based on the lengths of chords. This is the
+    /// default.
+    #[default]
+    Fast,
+    /// Accurate measurement using adaptive Gauss-Legendre quadrature with
+    /// the specified maximum error.
+    Exact(f32),
+}
+
 /// Represents the time parameter for a specific distance along
 /// a segment.
 #[derive(Copy, Clone, Debug)]
@@ -156,6 +170,52 @@ impl Curve {
         SegmentTime { distance, time }
     }
 
+    /// Returns the length of the curve computed by adaptive quadrature with
+    /// the specified maximum error.
+    pub fn length_exact(&self, tolerance: f32) -> f32 {
+        self.arc_length(0., 1., tolerance)
+    }
+
+    /// Returns the time parameter for the specified distance along the curve
+    /// computed by adaptive quadrature with the specified maximum error.
+    pub fn time_exact(&self, distance: f32, tolerance: f32) -> SegmentTime {
+        if distance <= 0. {
+            return SegmentTime {
+                distance: 0.,
+                time: 0.,
+            };
+        }
+        let length = self.length_exact(tolerance);
+        if distance >= length {
+            return SegmentTime {
+                distance: length,
+                time: 1.,
+            };
+        }
+        // Newton's method, safeguarded by bisection.
+        let (mut lo, mut hi) = (0., 1.);
+        let mut t = distance / length;
+        for _ in 0..16 {
+            let err = self.arc_length(0., t, tolerance) - distance;
+            if err.abs() <= tolerance {
+                break;
+            }
+            if err > 0. {
+                hi = t;
+            } else {
+                lo = t;
+            }
+            let speed = self.derivative(t).length();
+            let next = if speed > 0. { t - err / speed } else { -1. };
+            t = if next > lo && next < hi {
+                next
+            } else {
+                (lo + hi) * 0.5
+            };
+        }
+        SegmentTime { distance, time: t }
+    }
+
     /// Returns true if the curve can be represented as a line within some
     /// tolerance.
     pub fn is_line(&self, tolerance: f32) -> bool {
@@ -175,6 +235,71 @@ impl Curve {
             + (self.d * (t * t * t))
     }
 
+    /// Appends points along the curve, excluding the start point, such that
+    /// the lines connecting them deviate from the curve by roughly no more
+    /// than the specified flatness.
+    pub(super) fn flatten(&self, flatness: f32, points: &mut Vec<Point>) {
+        let d1 = self.d * 2. - self.c * 3. + self.a;
+        let d2 = self.d - self.b * 3. + self.a * 2.;
+        let mut deviation = d1.x.abs().max(d1.y.abs()).max(d2.x.abs().max(d2.y.abs()));
+        let mut count = 1;
+        while deviation > flatness && count < 1 << 16 {
+            deviation /= 4.;
+            count *= 2;
+        }
+        let step = 1. / count as f32;
+        for i in 1..count {
+            points.push(self.evaluate(i as f32 * step));
+        }
+        points.push(self.d);
+    }
+
+    /// Evaluates the derivative of the curve at the specified time.
+    pub fn derivative(&self, time: f32) -> Vector {
+        let t = time;
+        let t0 = 1. - t;
+        ((self.b - self.a) * (t0 * t0)
+            + (self.c - self.b) * (2. * t0 * t)
+            + (self.d - self.c) * (t * t))
+            * 3.
+    }
+
+    fn arc_length(&self, t0: f32, t1: f32, tolerance: f32) -> f32 {
+        let whole = self.gauss_length(t0, t1);
+        self.adaptive_length(t0, t1, whole, tolerance.max(1e-6), 12)
+    }
+
+    fn adaptive_length(&self, t0: f32, t1: f32, whole: f32, tolerance: f32, depth: u8) -> f32 {
+        let mid = (t0 + t1) * 0.5;
+        let left = self.gauss_length(t0, mid);
+        let right = self.gauss_length(mid, t1);
+        let sum = left + right;
+        if depth == 0 || (sum - whole).abs() <= tolerance {
+            sum
+        } else {
+            self.adaptive_length(t0, mid, left, tolerance * 0.5, depth - 1)
+                + self.adaptive_length(mid, t1, right, tolerance * 0.5, depth - 1)
+        }
+    }
+
+    /// Five point Gauss-Legendre quadrature of the speed of the curve.
+    fn gauss_length(&self, t0: f32, t1: f32) -> f32 {
+        const NODES: [(f32, f32); 5] = [
+            (0., 0.568_888_9),
+            (-0.538_469_3, 0.478_628_7),
+            (0.538_469_3, 0.478_628_7),
+            (-0.906_179_8, 0.236_926_9),
+            (0.906_179_8, 0.236_926_9),
+        ];
+        let half = (t1 - t0) * 0.5;
+        let mid = (t1 + t0) * 0.5;
+        let mut sum = 0.;
+        for &(x, w) in &NODES {
+            sum += w * self.derivative(mid + half * x).length();
+        }
+        sum * half
+    }
+
     fn to_segment(&self, id: SegmentId) -> Option<Segment> {
         if self.is_line(MERGE_EPSILON) {
             if self.a.nearly_eq_by(self.d, MERGE_EPSILON) {
@@ -187,7 +312,11 @@ impl Curve {
         }
     }
 
-    fn split_at_max_curvature(&self, splits: &mut [Curve; 4]) -> usize {
+    fn split_at_max_curvature(
+        &self,
+        splits: &mut [Curve; 4],
+        ranges: &mut [(f32, f32); 4],
+    ) -> usize {
         let mut tmp = [0f32; 3];
         let count1 = self.max_curvature(&mut tmp);
         let mut count = 0;
@@ -200,15 +329,18 @@ impl Curve {
         }
         if count == 0 {
             splits[0] = *self;
+            ranges[0] = (0., 1.);
         } else {
             let mut i = 0;
             let mut last_t = 0.;
             for &t in &ts[..count] {
                 splits[i] = self.slice(last_t, t);
+                ranges[i] = (last_t, t);
                 i += 1;
                 last_t = t;
             }
             splits[i] = self.slice(last_t, 1.);
+            ranges[i] = (last_t, 1.);
         }
         count + 1
     }
@@ -268,11 +400,11 @@ impl Curve {
         let r2_sub_q3 = r * r - q3;
         let adiv3 = a / 3.;
         if r2_sub_q3 < 0. {
-            let theta = satf32(r / q3.sqrt()).acos();
+            let theta = F32Ext::acos(satf32(r / q3.sqrt()));
             let neg2_root_q = -2. * q.sqrt();
-            ts[0] = satf32(neg2_root_q * (theta / 3.).cos() - adiv3);
-            ts[1] = satf32(neg2_root_q * ((theta + 2. * PI) / 3.).cos() - adiv3);
-            ts[2] = satf32(neg2_root_q * ((theta - 2. * PI) / 3.).cos() - adiv3);
+            ts[0] = satf32(neg2_root_q * F32Ext::cos(theta / 3.) - adiv3);
+            ts[1] = satf32(neg2_root_q * F32Ext::cos((theta + 2. * PI) / 3.) - adiv3);
+            ts[2] = satf32(neg2_root_q * F32Ext::cos((theta - 2. * PI) / 3.) - adiv3);
             ts.sort_unstable_by(|x, y| x.partial_cmp(y).unwrap_or(core::cmp::Ordering::Less));
             let mut count = 3;
             if ts[0] == ts[1] {
@@ -285,7 +417,7 @@ impl Curve {
             count
         } else {
             let mut a = r.abs() + r2_sub_q3.sqrt();
-            a = a.powf(0.3333333);
+            a = F32Ext::powf(a, 0.3333333);
             if r > 0. {
                 a = -a;
             }
@@ -304,16 +436,15 @@ impl Curve {
             || (self.d.y - 3. * self.b.y + 2. * self.a.y).abs() > tolerance
     }
 
-    fn needs_split(&self) -> bool {
+    /// Returns true if the angle between adjacent edges of the control
+    /// polygon exceeds the angle with the specified cosine.
+    fn needs_split(&self, flat_enough: f32) -> bool {
         if self.b.nearly_eq_by(self.c, MERGE_EPSILON) {
             return true;
         }
         let normal_ab = normal(self.a, self.b);
         let normal_bc = normal(self.b, self.c);
-        fn too_curvy(n0: Vector, n1: Vector) -> bool {
-            const FLAT_ENOUGH: f32 = 1.41421356237 / 2. + 1. / 10.;
-            n0.dot(n1) <= FLAT_ENOUGH
-        }
+        let too_curvy = |n0: Vector, n1: Vector| n0.dot(n1) <= flat_enough;
         too_curvy(normal_ab, normal_bc) || too_curvy(normal_bc, normal(self.c, self.d))
     }
 }
@@ -373,6 +504,22 @@ impl Segment {
         }
     }
 
+    pub fn length_by(&self, measure: Measure) -> f32 {
+        match (self, measure) {
+            (Self::Curve(_, curve), Measure::Exact(tolerance)) => curve.length_exact(tolerance),
+            _ => self.length(),
+        }
+    }
+
+    pub fn time_by(&self, distance: f32, measure: Measure) -> SegmentTime {
+        match (self, measure) {
+            (Self::Curve(_, curve), Measure::Exact(tolerance)) => {
+                curve.time_exact(distance, tolerance)
+            }
+            _ => self.time(distance, 1.),
+        }
+    }
+
     pub fn point_normal(&self, time: f32) -> (Point, Vector) {
         match self {
             Self::Line(_, line) => {
@@ -403,6 +550,10 @@ impl Default for Segment {
 // and numeric stability.
 const MERGE_EPSILON: f32 = 0.01;
 
+/// Cosine of the maximum angle between adjacent edges of the control polygon
+/// of a curve before it is subdivided for stroking.
+const FLAT_ENOUGH: f32 = 1.41421356237 / 2. + 1. / 10.;
+
 /// Creates a segment iterator from a command iterator, optionally producing
 /// simplified curves.
 pub fn segments<I>(commands: I, simplify_curves: bool) -> Segments<I>
@@ -413,6 +564,17 @@ where
     Segments::new(simplify_curves, commands)
 }
 
+/// Source of a segment in terms of the on-curve points of the original
+/// path. The segment covers the range from `start` to `end` of the command
+/// connecting the points at indices `from` and `to`.
+#[derive(Copy, Clone, Default, Debug)]
+pub(super) struct SegmentSource {
+    pub from: usize,
+    pub to: usize,
+    pub start: f32,
+    pub end: f32,
+}
+
 /// Iterator over path segments.
 #[derive(Clone)]
 pub struct Segments<I> {
@@ -421,12 +583,24 @@ pub struct Segments<I> {
     prev: Vector,
     close: bool,
     split: bool,
-    splits: [Curve; 16],
+    splits: [Curve; 32],
+    split_ranges: [(f32, f32); 32],
     split_count: usize,
     split_index: usize,
+    conic: ConicQuads,
+    arc: Arc,
     last_was_end: bool,
     id: u8,
     count: u32,
+    tolerance: f32,
+    flat_enough: f32,
+    split_depth: u8,
+    index: usize,
+    prev_index: usize,
+    start_index: usize,
+    pieces: usize,
+    piece: (f32, f32),
+    source: SegmentSource,
 }
 
 impl<I> Segments<I>
@@ -441,57 +615,163 @@ where
             prev: Vector::ZERO,
             close: false,
             split,
-            splits: [Curve::default(); 16],
+            splits: [Curve::default(); 32],
+            split_ranges: [(0., 1.); 32],
             split_count: 0,
             split_index: 0,
+            conic: ConicQuads::default(),
+            arc: Arc::default(),
             last_was_end: true,
             id: 0,
             count: 0,
+            tolerance: 1.,
+            flat_enough: FLAT_ENOUGH,
+            split_depth: 1,
+            index: usize::MAX,
+            prev_index: usize::MAX,
+            start_index: usize::MAX,
+            pieces: 0,
+            piece: (0., 1.),
+            source: SegmentSource::default(),
         }
     }
 
+    /// Sets the factor applied to the epsilons used for merging points and
+    /// approximating conics. This is the reciprocal of the scale of any
+    /// transform applied to the segments so that tolerances are maintained
+    /// in device space.
+    pub(super) fn with_tolerance(mut self, tolerance: f32) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Sets the factor applied to the maximum angle spanned by the control
+    /// polygon of a curve before it is subdivided. The error of the
+    /// approximation grows with the square of the angle so the angle is
+    /// scaled by the square root of the factor.
+    pub(super) fn with_flatness(mut self, factor: f32) -> Self {
+        if factor != 1. {
+            let angle = (F32Ext::acos(FLAT_ENOUGH) * factor.sqrt()).min(1.4);
+            self.flat_enough = F32Ext::cos(angle);
+            self.split_depth = if factor < 1. { 2 } else { 1 };
+        }
+        self
+    }
+
+    /// Returns the tolerance factor for the segments.
+    pub(super) fn tolerance(&self) -> f32 {
+        self.tolerance
+    }
+
+    /// Returns the source of the most recent segment produced by the
+    /// iterator. Only available when curves are simplified.
+    pub(super) fn source(&self) -> SegmentSource {
+        self.source
+    }
+
+    /// Sets the source of the next segment to the specified range of the
+    /// current command.
+    fn set_source(&mut self, range: (f32, f32)) {
+        let (start, end) = self.piece;
+        let len = end - start;
+        self.source = SegmentSource {
+            from: self.prev_index,
+            to: self.index,
+            start: start + len * range.0,
+            end: start + len * range.1,
+        };
+    }
+
     fn split_curve(&mut self, id: SegmentId, c: &Curve) -> Option<Segment> {
-        if c.is_line(MERGE_EPSILON) {
-            if c.a.nearly_eq_by(c.d, MERGE_EPSILON) {
+        let epsilon = MERGE_EPSILON * self.tolerance;
+        if c.is_line(epsilon) {
+            if c.a.nearly_eq_by(c.d, epsilon) {
                 return None;
             }
             return Some(Segment::Line(id, Line::new(c.a, c.d)));
         }
         let mut splits = [Curve::default(); 4];
-        let count = c.split_at_max_curvature(&mut splits);
+        let mut ranges = [(0., 1.); 4];
+        let count = c.split_at_max_curvature(&mut splits, &mut ranges);
         let mut i = 0;
         for j in 0..count {
             let curve = splits[j];
-            if curve.needs_split() {
+            let (t0, t1) = ranges[j];
+            if curve.needs_split(self.flat_enough) {
+                let t = (t0 + t1) * 0.5;
                 let (a, b) = curve.split(0.5);
-                if a.needs_split() {
-                    let (c, d) = a.split(0.5);
-                    self.splits[i] = c;
-                    self.splits[i + 1] = d;
-                    i += 2;
-                } else {
-                    self.splits[i] = a;
-                    i += 1;
-                }
-                if b.needs_split() {
-                    let (c, d) = b.split(0.5);
-                    self.splits[i] = c;
-                    self.splits[i + 1] = d;
-                    i += 2;
-                } else {
-                    self.splits[i] = b;
-                    i += 1;
-                }
+                i = self.push_halves(i, &a, (t0, t), self.split_depth);
+                i = self.push_halves(i, &b, (t, t1), self.split_depth);
             } else {
                 self.splits[i] = curve;
+                self.split_ranges[i] = (t0, t1);
                 i += 1;
             }
         }
         self.split_count = i;
         self.split_index = 1;
+        self.set_source(self.split_ranges[0]);
         return self.splits[0].to_segment(id);
     }
 
+    /// Stores the curve at the specified index, split in half up to the
+    /// specified depth if necessary, and returns the next index.
+    fn push_halves(&mut self, i: usize, curve: &Curve, range: (f32, f32), depth: u8) -> usize {
+        if depth > 0 && curve.needs_split(self.flat_enough) {
+            let t = (range.0 + range.1) * 0.5;
+            let (a, b) = curve.split(0.5);
+            let i = self.push_halves(i, &a, (range.0, t), depth - 1);
+            self.push_halves(i, &b, (t, range.1), depth - 1)
+        } else {
+            self.splits[i] = *curve;
+            self.split_ranges[i] = range;
+            i + 1
+        }
+    }
+
+    /// Returns the next command, replacing conic sections with a sequence of
+    /// quadratic curves and arcs with a sequence of cubic curves.
+    fn next_command(&mut self) -> Option<Command> {
+        if let Some(cmd) = self.arc.next() {
+            let n = self.pieces as f32;
+            let k = (self.pieces - self.arc.len()) as f32;
+            self.piece = ((k - 1.) / n, k / n);
+            return Some(cmd);
+        }
+        if let Some((c, p)) = self.conic.next() {
+            let n = self.pieces as f32;
+            let k = (self.pieces - self.conic.len()) as f32;
+            self.piece = ((k - 1.) / n, k / n);
+            return Some(Command::QuadTo(c, p));
+        }
+        let cmd = *self.commands.next()?.borrow();
+        self.piece = (0., 1.);
+        if cmd != Command::Close {
+            self.prev_index = self.index;
+            self.index = self.index.wrapping_add(1);
+        }
+        if let Command::ConicTo(c, p, w) = cmd {
+            let tolerance = DEFAULT_TOLERANCE * self.tolerance;
+            self.conic = ConicQuads::new(self.prev, c, p, w, tolerance);
+            self.pieces = self.conic.len();
+            self.piece = (0., 1. / self.pieces as f32);
+            let (c, p) = self.conic.next().unwrap_or((p, p));
+            return Some(Command::QuadTo(c, p));
+        }
+        if let Command::ArcTo(rx, ry, a, size, sweep, p) = cmd {
+            let tolerance = DEFAULT_TOLERANCE * self.tolerance;
+            self.arc = Arc::new(self.prev, rx, ry, a.to_radians(), size, sweep, p)
+                .with_tolerance(tolerance);
+            self.pieces = self.arc.len();
+            if self.pieces > 1 {
+                self.piece = (0., 1. / self.pieces as f32);
+            }
+            // Degenerate arcs are replaced with a line to the end point.
+            return Some(self.arc.next().unwrap_or(Command::LineTo(p)));
+        }
+        Some(cmd)
+    }
+
     fn inc_id(&mut self) {
         if self.id == 254 {
             self.id = 0;
@@ -519,6 +799,7 @@ where
             loop {
                 if self.split_index < self.split_count {
                     let curve = self.splits[self.split_index];
+                    self.set_source(self.split_ranges[self.split_index]);
                     self.split_index += 1;
                     if let Some(segment) = curve.to_segment(self.id) {
                         self.count += 1;
@@ -531,18 +812,20 @@ where
                 self.inc_id();
                 let id = self.id;
                 let from = self.prev;
-                match *self.commands.next()?.borrow() {
+                match self.next_command()? {
                     MoveTo(to) => {
                         self.start = to;
                         self.prev = to;
                         self.count = 0;
+                        self.start_index = self.index;
                         if !self.last_was_end {
                             self.last_was_end = true;
                             return Some(Segment::End(false));
                         }
                     }
                     LineTo(to) => {
-                        if !from.nearly_eq_by(to, MERGE_EPSILON) {
+                        if !from.nearly_eq_by(to, MERGE_EPSILON * self.tolerance) {
+                            self.set_source((0., 1.));
                             self.count += 1;
                             self.prev = to;
                             self.last_was_end = false;
@@ -567,10 +850,20 @@ where
                             return Some(segment);
                         }
                     }
+                    // Conics and arcs are expanded into curves by next_command.
+                    ConicTo(..) | ArcTo(..) => {}
                     Close => {
                         self.prev = self.start;
-                        if self.count == 0 || !from.nearly_eq_by(self.start, MERGE_EPSILON) {
+                        if self.count == 0
+                            || !from.nearly_eq_by(self.start, MERGE_EPSILON * self.tolerance)
+                        {
                             self.close = true;
+                            self.source = SegmentSource {
+                                from: self.index,
+                                to: self.start_index,
+                                start: 0.,
+                                end: 1.,
+                            };
                             return Some(Segment::Line(id, Line::new(from, self.start)));
                         } else {
                             self.count = 0;
@@ -585,7 +878,7 @@ where
             self.inc_id();
             loop {
                 let from = self.prev;
-                match *self.commands.next()?.borrow() {
+                match self.next_command()? {
                     MoveTo(to) => {
                         self.start = to;
                         self.prev = to;
@@ -596,7 +889,7 @@ where
                         }
                     }
                     LineTo(to) => {
-                        if !from.nearly_eq_by(to, MERGE_EPSILON) {
+                        if !from.nearly_eq_by(to, MERGE_EPSILON * self.tolerance) {
                             self.count += 1;
                             self.prev = to;
                             self.last_was_end = false;
@@ -617,9 +910,13 @@ where
                         self.last_was_end = false;
                         return Some(segment);
                     }
+                    // Conics and arcs are expanded into curves by next_command.
+                    ConicTo(..) | ArcTo(..) => {}
                     Close => {
                         self.prev = self.start;
-                        if self.count == 0 || !from.nearly_eq_by(self.start, 0.01) {
+                        if self.count == 0
+                            || !from.nearly_eq_by(self.start, MERGE_EPSILON * self.tolerance)
+                        {
                             self.close = true;
                             return Some(Segment::Line(id, Line::new(from, self.start)));
                         } else {
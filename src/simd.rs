@@ -0,0 +1,240 @@
+//! Vectorized resolution of coverage delta rows.
+//!
+//! This accelerates [resolve_deltas](../fn.resolve_deltas.html) with SSE2,
+//! NEON or simd128 when the target enables them at compile time. Regular
+//! rasterization writes spans of constant coverage directly and does not use
+//! this module.
+
+use super::raster::{coverage, PIXEL_BITS};
+use super::style::Fill;
+
+/// Number of bits that accumulated coverage is shifted right to produce an
+/// 8-bit value.
+#[allow(dead_code)]
+const SHIFT: i32 = PIXEL_BITS * 2 + 1 - 8;
+
+/// Accumulates a row of signed coverage deltas and converts the running sum
+/// at each pixel to 8-bit coverage.
+pub(super) fn resolve_row(fill: Fill, deltas: &[i32], mask: &mut [u8]) {
+    let len = deltas.len().min(mask.len());
+    let (deltas, mask) = (&deltas[..len], &mut mask[..len]);
+    let (done, mut acc) = resolve_simd(fill, deltas, mask);
+    for (delta, c) in deltas[done..].iter().zip(&mut mask[done..]) {
+        acc = acc.wrapping_add(*delta);
+        *c = coverage(fill, acc);
+    }
+}
+
+/// Resolves as many leading pixels of the row as possible in groups of
+/// eight. Returns the number of pixels written and the accumulated sum.
+#[cfg(all(
+    any(target_arch = "x86", target_arch = "x86_64"),
+    target_feature = "sse2"
+))]
+fn resolve_simd(fill: Fill, deltas: &[i32], mask: &mut [u8]) -> (usize, i32) {
+    #[cfg(target_arch = "x86")]
+    use core::arch::x86::*;
+    #[cfg(target_arch = "x86_64")]
+    use core::arch::x86_64::*;
+
+    #[inline(always)]
+    unsafe fn prefix(mut v: __m128i, acc: &mut __m128i) -> __m128i {
+        v = _mm_add_epi32(v, _mm_slli_si128(v, 4));
+        v = _mm_add_epi32(v, _mm_slli_si128(v, 8));
+        v = _mm_add_epi32(v, *acc);
+        *acc = _mm_shuffle_epi32(v, 0xFF);
+        v
+    }
+
+    #[inline(always)]
+    unsafe fn resolve(v: __m128i, even_odd: bool) -> __m128i {
+        let v = _mm_srai_epi32(v, SHIFT);
+        if even_odd {
+            // Values in [256, 512) are reflected by flipping the low 9 bits.
+            let bits = _mm_set1_epi32(511);
+            let v = _mm_and_si128(v, bits);
+            let reflect = _mm_srai_epi32(_mm_slli_epi32(v, 23), 31);
+            _mm_xor_si128(v, _mm_and_si128(reflect, bits))
+        } else {
+            // Negative values are complemented; saturation when packing
+            // clamps the result to 255.
+            _mm_xor_si128(v, _mm_srai_epi32(v, 31))
+        }
+    }
+
+    let count = deltas.len() / 8 * 8;
+    let even_odd = fill == Fill::EvenOdd;
+    // SAFETY: SSE2 is enabled at compile time and every load and store is
+    // within the first `count` elements of the slices.
+    unsafe {
+        let mut acc = _mm_setzero_si128();
+        let mut i = 0;
+        while i < count {
+            let src = deltas.as_ptr().add(i) as *const __m128i;
+            let a = resolve(prefix(_mm_loadu_si128(src), &mut acc), even_odd);
+            let b = resolve(prefix(_mm_loadu_si128(src.add(1)), &mut acc), even_odd);
+            let packed = _mm_packus_epi16(_mm_packs_epi32(a, b), _mm_setzero_si128());
+            _mm_storel_epi64(mask.as_mut_ptr().add(i) as *mut __m128i, packed);
+            i += 8;
+        }
+        (count, _mm_cvtsi128_si32(acc))
+    }
+}
+
+/// Resolves as many leading pixels of the row as possible in groups of
+/// eight. Returns the number of pixels written and the accumulated sum.
+#[cfg(all(target_arch = "aarch64", target_feature = "neon"))]
+fn resolve_simd(fill: Fill, deltas: &[i32], mask: &mut [u8]) -> (usize, i32) {
+    use core::arch::aarch64::*;
+
+    #[inline(always)]
+    unsafe fn prefix(mut v: int32x4_t, acc: &mut int32x4_t) -> int32x4_t {
+        let zero = vdupq_n_s32(0);
+        v = vaddq_s32(v, vextq_s32(zero, v, 3));
+        v = vaddq_s32(v, vextq_s32(zero, v, 2));
+        v = vaddq_s32(v, *acc);
+        *acc = vdupq_laneq_s32(v, 3);
+        v
+    }
+
+    #[inline(always)]
+    unsafe fn resolve(v: int32x4_t, even_odd: bool) -> uint16x4_t {
+        let v = vshrq_n_s32(v, SHIFT);
+        let v = if even_odd {
+            // Values in [256, 512) are reflected by flipping the low 9 bits.
+            let bits = vdupq_n_s32(511);
+            let v = vandq_s32(v, bits);
+            let reflect = vshrq_n_s32(vshlq_n_s32(v, 23), 31);
+            veorq_s32(v, vandq_s32(reflect, bits))
+        } else {
+            // Negative values are complemented; saturation when narrowing
+            // clamps the result to 255.
+            veorq_s32(v, vshrq_n_s32(v, 31))
+        };
+        vqmovun_s32(v)
+    }
+
+    let count = deltas.len() / 8 * 8;
+    let even_odd = fill == Fill::EvenOdd;
+    // SAFETY: NEON is enabled at compile time and every load and store is
+    // within the first `count` elements of the slices.
+    unsafe {
+        let mut acc = vdupq_n_s32(0);
+        let mut i = 0;
+        while i < count {
+            let src = deltas.as_ptr().add(i);
+            let a = resolve(prefix(vld1q_s32(src), &mut acc), even_odd);
+            let b = resolve(prefix(vld1q_s32(src.add(4)), &mut acc), even_odd);
+            vst1_u8(mask.as_mut_ptr().add(i), vqmovn_u16(vcombine_u16(a, b)));
+            i += 8;
+        }
+        (count, vgetq_lane_s32(acc, 0))
+    }
+}
+
+/// Resolves as many leading pixels of the row as possible in groups of
+/// eight. Returns the number of pixels written and the accumulated sum.
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+fn resolve_simd(fill: Fill, deltas: &[i32], mask: &mut [u8]) -> (usize, i32) {
+    use core::arch::wasm32::*;
+
+    #[inline(always)]
+    fn prefix(mut v: v128, acc: &mut v128) -> v128 {
+        let zero = i32x4_splat(0);
+        v = i32x4_add(v, i32x4_shuffle::<0, 4, 5, 6>(zero, v));
+        v = i32x4_add(v, i32x4_shuffle::<0, 1, 4, 5>(zero, v));
+        v = i32x4_add(v, *acc);
+        *acc = i32x4_shuffle::<3, 3, 3, 3>(v, v);
+        v
+    }
+
+    #[inline(always)]
+    fn resolve(v: v128, even_odd: bool) -> v128 {
+        let v = i32x4_shr(v, SHIFT as u32);
+        if even_odd {
+            // Values in [256, 512) are reflected by flipping the low 9 bits.
+            let bits = i32x4_splat(511);
+            let v = v128_and(v, bits);
+            let reflect = i32x4_shr(i32x4_shl(v, 23), 31);
+            v128_xor(v, v128_and(reflect, bits))
+        } else {
+            // Negative values are complemented; saturation when narrowing
+            // clamps the result to 255.
+            v128_xor(v, i32x4_shr(v, 31))
+        }
+    }
+
+    let count = deltas.len() / 8 * 8;
+    let even_odd = fill == Fill::EvenOdd;
+    let mut acc = i32x4_splat(0);
+    let mut i = 0;
+    while i < count {
+        // SAFETY: every load and store is within the first `count` elements
+        // of the slices.
+        unsafe {
+            let src = deltas.as_ptr().add(i) as *const v128;
+            let a = resolve(prefix(v128_load(src), &mut acc), even_odd);
+            let b = resolve(prefix(v128_load(src.add(1)), &mut acc), even_odd);
+            let words = i16x8_narrow_i32x4(a, b);
+            let bytes = u8x16_narrow_i16x8(words, words);
+            v128_store64_lane::<0>(bytes, mask.as_mut_ptr().add(i) as *mut u64);
+        }
+        i += 8;
+    }
+    (count, i32x4_extract_lane::<0>(acc))
+}
+
+/// Scalar fallback for targets without a vectorized implementation.
+#[cfg(not(any(
+    all(
+        any(target_arch = "x86", target_arch = "x86_64"),
+        target_feature = "sse2"
+    ),
+    all(target_arch = "aarch64", target_feature = "neon"),
+    all(target_arch = "wasm32", target_feature = "simd128")
+)))]
+fn resolve_simd(_fill: Fill, _deltas: &[i32], _mask: &mut [u8]) -> (usize, i32) {
+    (0, 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lib::Vec;
+
+    #[test]
+    fn simd_matches_scalar() {
+        let mut seed = 0x2545f491u32;
+        let mut random = move || {
+            seed ^= seed << 13;
+            seed ^= seed >> 17;
+            seed ^= seed << 5;
+            seed
+        };
+        for fill in [Fill::NonZero, Fill::EvenOdd] {
+            for len in 0..67 {
+                // Mix pixel sized deltas with arbitrary ones that overflow
+                // the accumulator.
+                let deltas: Vec<i32> = (0..len)
+                    .map(|_| match random() % 4 {
+                        0 => 0,
+                        1 => (random() % (1 << 19)) as i32 - (1 << 18),
+                        2 => (random() % 5) as i32 * (1 << 17) - (1 << 18),
+                        _ => random() as i32,
+                    })
+                    .collect();
+                let mut simd = alloc::vec![0u8; len];
+                resolve_row(fill, &deltas, &mut simd);
+                let mut acc = 0i32;
+                let scalar: Vec<u8> = deltas
+                    .iter()
+                    .map(|delta| {
+                        acc = acc.wrapping_add(*delta);
+                        coverage(fill, acc)
+                    })
+                    .collect();
+                assert_eq!(simd, scalar, "{:?} {}", fill, len);
+            }
+        }
+    }
+}
@@ -0,0 +1,205 @@
+//! Coordinate snapping and quantization.
+
+use super::command::Command;
+use super::geometry::{Point, Transform, Vector};
+use super::path_builder::PathBuilder;
+use super::path_data::PathData;
+#[cfg(not(feature = "std"))]
+use super::F32Ext;
+
+/// Describes the grid used for snapping path coordinates.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Snap {
+    /// Coordinates are snapped to pixel boundaries. This produces crisp edges
+    /// for fills.
+    Pixel,
+    /// Coordinates are snapped to pixel centers. This produces crisp edges
+    /// for strokes with odd integer widths.
+    HalfPixel,
+}
+
+impl Snap {
+    /// Returns the nearest grid point to the specified point.
+    pub fn point(self, point: impl Into<Point>) -> Point {
+        let p = point.into();
+        match self {
+            Self::Pixel => Point::new(p.x.round(), p.y.round()),
+            Self::HalfPixel => Point::new((p.x - 0.5).round() + 0.5, (p.y - 0.5).round() + 0.5),
+        }
+    }
+}
+
+/// Snaps the on-curve points of the path to the specified grid after applying
+/// the transform and emits the result to the sink.
+///
+/// Control points are moved by the same distance as their adjacent on-curve
+/// points to preserve the shape of curves.
+pub fn snap(
+    data: impl PathData,
+    transform: Option<Transform>,
+    snap: Snap,
+    sink: &mut impl PathBuilder,
+) {
+    let mut start_delta = Vector::ZERO;
+    let mut prev_delta = Vector::ZERO;
+    for cmd in data.commands() {
+        use Command::*;
+        let cmd = match transform {
+            Some(transform) => cmd.transform(&transform),
+            None => cmd,
+        };
+        match cmd {
+            MoveTo(p) => {
+                let to = snap.point(p);
+                start_delta = to - p;
+                prev_delta = start_delta;
+                sink.move_to(to);
+            }
+            LineTo(p) => {
+                let to = snap.point(p);
+                prev_delta = to - p;
+                sink.line_to(to);
+            }
+            QuadTo(c, p) => {
+                let to = snap.point(p);
+                let delta = to - p;
+                sink.quad_to(c + (prev_delta + delta) * 0.5, to);
+                prev_delta = delta;
+            }
+            ConicTo(c, p, w) => {
+                let to = snap.point(p);
+                let delta = to - p;
+                sink.conic_to(c + (prev_delta + delta) * 0.5, to, w);
+                prev_delta = delta;
+            }
+            ArcTo(rx, ry, a, size, sweep, p) => {
+                let to = snap.point(p);
+                prev_delta = to - p;
+                sink.arc_to(rx, ry, a, size, sweep, to);
+            }
+            CurveTo(c1, c2, p) => {
+                let to = snap.point(p);
+                let delta = to - p;
+                sink.curve_to(c1 + prev_delta, c2 + delta, to);
+                prev_delta = delta;
+            }
+            Close => {
+                prev_delta = start_delta;
+                sink.close();
+            }
+        }
+    }
+}
+
+/// Rounds all coordinates of the path to the nearest multiple of the
+/// specified precision after applying the transform and emits the result to
+/// the sink.
+///
+/// Segments that become degenerate after rounding are collapsed: zero length
+/// segments are removed, curves with control points that coincide with
+/// their end points are emitted as lines and empty subpaths are dropped. A
+/// precision less than or equal to zero disables rounding.
+pub fn quantize(
+    data: impl PathData,
+    transform: Option<Transform>,
+    precision: f32,
+    sink: &mut impl PathBuilder,
+) {
+    let round = |p: Point| {
+        if precision > 0. {
+            Point::new(
+                (p.x / precision).round() * precision,
+                (p.y / precision).round() * precision,
+            )
+        } else {
+            p
+        }
+    };
+    let mut start = Point::ZERO;
+    let mut prev = Point::ZERO;
+    // A move is only emitted once the subpath contains a segment.
+    let mut pending_move = false;
+    for cmd in data.commands() {
+        use Command::*;
+        let cmd = match transform {
+            Some(transform) => cmd.transform(&transform),
+            None => cmd,
+        };
+        match cmd {
+            MoveTo(p) => {
+                start = round(p);
+                prev = start;
+                pending_move = true;
+            }
+            LineTo(p) => {
+                let to = round(p);
+                if to != prev {
+                    flush_move(sink, &mut pending_move, start);
+                    sink.line_to(to);
+                    prev = to;
+                }
+            }
+            QuadTo(c, p) => {
+                let (c, to) = (round(c), round(p));
+                if c == prev || c == to {
+                    if to != prev {
+                        flush_move(sink, &mut pending_move, start);
+                        sink.line_to(to);
+                    }
+                } else {
+                    flush_move(sink, &mut pending_move, start);
+                    sink.quad_to(c, to);
+                }
+                prev = to;
+            }
+            ConicTo(c, p, w) => {
+                let (c, to) = (round(c), round(p));
+                if c == prev || c == to {
+                    if to != prev {
+                        flush_move(sink, &mut pending_move, start);
+                        sink.line_to(to);
+                    }
+                } else {
+                    flush_move(sink, &mut pending_move, start);
+                    sink.conic_to(c, to, w);
+                }
+                prev = to;
+            }
+            ArcTo(rx, ry, a, size, sweep, p) => {
+                let to = round(p);
+                if to != prev {
+                    flush_move(sink, &mut pending_move, start);
+                    sink.arc_to(rx, ry, a, size, sweep, to);
+                    prev = to;
+                }
+            }
+            CurveTo(c1, c2, p) => {
+                let (c1, c2, to) = (round(c1), round(c2), round(p));
+                if (c1 == prev || c1 == to) && (c2 == prev || c2 == to) {
+                    if to != prev {
+                        flush_move(sink, &mut pending_move, start);
+                        sink.line_to(to);
+                    }
+                } else {
+                    flush_move(sink, &mut pending_move, start);
+                    sink.curve_to(c1, c2, to);
+                }
+                prev = to;
+            }
+            Close => {
+                if !pending_move {
+                    sink.close();
+                }
+                prev = start;
+            }
+        }
+    }
+}
+
+#[inline(always)]
+fn flush_move(sink: &mut impl PathBuilder, pending: &mut bool, start: Point) {
+    if *pending {
+        sink.move_to(start);
+        *pending = false;
+    }
+}
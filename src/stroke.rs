@@ -1,27 +1,69 @@
 //! Stroking and dashing of paths.
 
 use super::command::Command;
+use super::decoration::decorate_into;
 use super::geometry::*;
+use super::offset::{offset_into, Offset};
 use super::path_builder::*;
 use super::segment::*;
 use super::style::*;
+use super::traversal::{MarkerPosition, Vertices};
+use super::union::union_into;
+use super::F32Ext;
 
-use crate::lib::Vec;
+use crate::lib::{Box, Vec};
 use core::borrow::Borrow;
 
-pub fn stroke_into<'a, I>(commands: I, style: &Stroke<'a>, sink: &mut impl PathBuilder)
-where
+/// Strokes the commands into the sink. The tolerance is a factor applied to
+/// the internal epsilons and should be the reciprocal of the scale of any
+/// transform that is applied to the output.
+pub fn stroke_into<'a, I>(
+    commands: I,
+    style: &Stroke<'a>,
+    sink: &mut impl PathBuilder,
+    tolerance: f32,
+) where
     I: Iterator + Clone,
     I::Item: Borrow<Command>,
 {
-    let mut stroker = Stroker::new(segments(commands, true), sink, style);
-    let (dashes, dash_offset, empty_gaps) = validate_dashes(style.dashes, style.offset);
+    if style.remove_overlaps {
+        let mut outline = Vec::new();
+        let mut style = *style;
+        style.remove_overlaps = false;
+        stroke_into(commands, &style, &mut outline, tolerance);
+        let flatness = 0.25 * tolerance * stroke_quality(&style);
+        union_into(&outline, Fill::NonZero, flatness, sink);
+        return;
+    }
+    if let Some((path, decorated)) = decorate(commands.clone(), style, tolerance) {
+        stroke_into(path.iter(), &decorated, sink, tolerance);
+        add_markers(commands, style, sink);
+        return;
+    }
+    if !style.parallel.is_empty() {
+        stroke_parallel(commands, style, sink, tolerance);
+        return;
+    }
+    let mut buf = Vec::new();
+    let (dashes, offset) = resolve_dashes(commands.clone(), style, &mut buf);
+    let quality = stroke_quality(style);
+    let mut stroker = Stroker::new(
+        segments(commands.clone(), true)
+            .with_tolerance(tolerance * quality)
+            .with_flatness(quality),
+        sink,
+        style,
+    );
+    let (dashes, dash_offset, empty_gaps) = validate_dashes(dashes, offset);
     let mut segment_buf = SmallBuf::new();
-    if dashes.len() > 0 {
+    if stroker.dash_pattern.is_some() {
+        stroker.dash(&mut segment_buf, &[], style.offset.max(0.), true);
+    } else if dashes.len() > 0 {
         stroker.dash(&mut segment_buf, dashes, dash_offset, empty_gaps);
     } else {
         stroker.stroke(&mut segment_buf);
     }
+    add_markers(commands, style, sink);
 }
 
 pub fn stroke_with_storage<'a, I>(
@@ -29,57 +71,848 @@ pub fn stroke_with_storage<'a, I>(
     style: &Stroke<'a>,
     sink: &mut impl PathBuilder,
     storage: &mut impl StrokerStorage,
+    tolerance: f32,
 ) where
     I: Iterator + Clone,
     I::Item: Borrow<Command>,
 {
-    let mut stroker = Stroker::new(segments(commands, true), sink, style);
-    let (dashes, dash_offset, empty_gaps) = validate_dashes(style.dashes, style.offset);
-    if dashes.len() > 0 {
+    if style.remove_overlaps {
+        // The outline is buffered in any case so the storage is not used.
+        stroke_into(commands, style, sink, tolerance);
+        return;
+    }
+    if let Some((path, decorated)) = decorate(commands.clone(), style, tolerance) {
+        stroke_with_storage(path.iter(), &decorated, sink, storage, tolerance);
+        add_markers(commands, style, sink);
+        return;
+    }
+    if !style.parallel.is_empty() {
+        stroke_parallel(commands, style, sink, tolerance);
+        return;
+    }
+    let mut buf = Vec::new();
+    let (dashes, offset) = resolve_dashes(commands.clone(), style, &mut buf);
+    let quality = stroke_quality(style);
+    let mut stroker = Stroker::new(
+        segments(commands.clone(), true)
+            .with_tolerance(tolerance * quality)
+            .with_flatness(quality),
+        sink,
+        style,
+    );
+    let (dashes, dash_offset, empty_gaps) = validate_dashes(dashes, offset);
+    if stroker.dash_pattern.is_some() {
+        stroker.dash(storage, &[], style.offset.max(0.), true);
+    } else if dashes.len() > 0 {
         stroker.dash(storage, dashes, dash_offset, empty_gaps);
     } else {
         stroker.stroke(storage);
     }
+    add_markers(commands, style, sink);
+}
+
+/// Returns the factor applied to the tolerances of the stroke.
+fn stroke_quality(style: &Stroke) -> f32 {
+    if style.tolerance.is_finite() && style.tolerance > 0. {
+        style.tolerance
+    } else {
+        1.
+    }
+}
+
+/// Returns the path with each subpath replaced by the decoration of the
+/// stroke along with the style for stroking the result, or `None` if the
+/// stroke does not have a valid decoration.
+fn decorate<'a, I>(
+    commands: I,
+    style: &Stroke<'a>,
+    tolerance: f32,
+) -> Option<(Vec<Command>, Stroke<'a>)>
+where
+    I: Iterator + Clone,
+    I::Item: Borrow<Command>,
+{
+    let decoration = style
+        .decoration
+        .filter(|d| d.period > 0. && d.period.is_finite() && d.amplitude.is_finite())?;
+    let mut path = Vec::new();
+    let flatness = 0.25 * tolerance * stroke_quality(style);
+    decorate_into(commands, &decoration, flatness, &mut path);
+    // Markers are placed along the original path.
+    let mut decorated = *style;
+    decorated.decoration = None;
+    decorated.start_marker = None;
+    decorated.mid_marker = None;
+    decorated.end_marker = None;
+    Some((path, decorated))
+}
+
+/// Strokes each of the parallel lines of the stroke along a copy of the
+/// path offset to its center.
+fn stroke_parallel<I>(commands: I, style: &Stroke, sink: &mut impl PathBuilder, tolerance: f32)
+where
+    I: Iterator + Clone,
+    I::Item: Borrow<Command>,
+{
+    let mut line = *style;
+    line.parallel = &[];
+    line.start_marker = None;
+    line.mid_marker = None;
+    line.end_marker = None;
+    let flatness = 0.25 * tolerance * stroke_quality(style);
+    // Distance of the current line or gap from the path along the normal.
+    let mut distance = style.parallel.iter().map(|w| w.max(0.)).sum::<f32>() * 0.5;
+    let mut path = Vec::new();
+    for (i, width) in style.parallel.iter().enumerate() {
+        let width = width.max(0.);
+        if i & 1 == 0 && width > 0. {
+            let offset = Offset {
+                distance: distance - width * 0.5,
+                join: style.join,
+                miter_limit: style.miter_limit,
+                miter_fallback: style.miter_fallback,
+            };
+            path.clear();
+            offset_into(commands.clone(), &offset, flatness, &mut path);
+            line.width = width;
+            stroke_into(path.iter(), &line, sink, tolerance);
+        }
+        distance -= width;
+    }
+    add_markers(commands, style, sink);
+}
+
+/// Adds the markers of the stroke at the vertices of the path.
+fn add_markers<I>(commands: I, style: &Stroke, sink: &mut impl PathBuilder)
+where
+    I: Iterator + Clone,
+    I::Item: Borrow<Command>,
+{
+    if style.start_marker.is_none() && style.mid_marker.is_none() && style.end_marker.is_none() {
+        return;
+    }
+    let radius = style.width.max(0.01) * 0.5;
+    let mut buf = Vec::new();
+    for vertex in Vertices::from_commands(commands).markers() {
+        let marker = match vertex.position {
+            MarkerPosition::Start => style.start_marker,
+            MarkerPosition::Middle => style.mid_marker,
+            MarkerPosition::End => style.end_marker,
+        };
+        let marker = match marker {
+            Some(marker) => marker,
+            None => continue,
+        };
+        let angle = match marker.orient {
+            MarkerOrient::Auto => vertex.angle(),
+            MarkerOrient::AutoStartReverse => vertex.start_reverse_angle(),
+            MarkerOrient::Angle(angle) => angle,
+        };
+        let scale = radius * marker.scale;
+        let transform = Transform::scale(scale, scale)
+            .then_rotate(angle)
+            .then_translate(vertex.point.x, vertex.point.y);
+        add_marker(marker.path, &transform, &mut buf, sink);
+    }
+}
+
+/// Adds a marker path with the winding direction of its subpaths reversed
+/// if necessary to match the outline of the stroke, which has a positive
+/// signed area.
+fn add_marker(
+    path: &[Command],
+    transform: &Transform,
+    buf: &mut Vec<Segment>,
+    sink: &mut impl PathBuilder,
+) {
+    buf.clear();
+    buf.extend(segments(
+        path.iter().map(|cmd| cmd.transform(transform)),
+        false,
+    ));
+    let subpaths = || {
+        buf.split(|segment| matches!(segment, Segment::End(..)))
+            .filter(|segments| !segments.is_empty())
+    };
+    let area: f32 = subpaths().map(signed_area).sum();
+    for segments in subpaths() {
+        if area < 0. {
+            sink.move_to(end_point(&segments[segments.len() - 1]).unwrap_or_default());
+            for segment in segments.iter().rev() {
+                emit_segment(sink, &segment.reverse());
+            }
+        } else {
+            sink.move_to(start_point(&segments[0]).unwrap_or_default());
+            for segment in segments {
+                emit_segment(sink, segment);
+            }
+        }
+        sink.close();
+    }
+}
+
+/// Emits a segment to a sink, continuing from the current point.
+fn emit_segment(sink: &mut impl PathBuilder, segment: &Segment) {
+    match segment {
+        Segment::Line(_, line) => {
+            sink.line_to(line.b);
+        }
+        Segment::Curve(_, curve) => {
+            sink.curve_to(curve.b, curve.c, curve.d);
+        }
+        Segment::End(..) => {}
+    }
+}
+
+/// Adds points to the builder that bound the stroke of the path without
+/// evaluating its outline. The width of the stroke is multiplied by the
+/// scale. Dashes are ignored and the stroke must not have widths, custom
+/// joins or caps, or other features that extend beyond its joins and caps.
+pub(super) fn stroke_bounds<I>(commands: I, style: &Stroke, scale: f32, bounds: &mut BoundsBuilder)
+where
+    I: Iterator + Clone,
+    I::Item: Borrow<Command>,
+{
+    let radius = style.width.max(0.01) * 0.5 * scale;
+    let mut subpath: Vec<Segment> = Vec::new();
+    let mut start = None;
+    let mut source = segments(commands, false);
+    loop {
+        let (closed, done) = match source.next() {
+            Some(Segment::End(closed)) => (closed, false),
+            Some(segment) => {
+                if start.is_none() {
+                    start = start_point(&segment);
+                }
+                if start_tangent(&segment) != Vector::ZERO {
+                    subpath.push(segment);
+                }
+                continue;
+            }
+            None => (false, true),
+        };
+        if let Some(point) = start.take() {
+            bound_subpath(&subpath, point, closed, style, radius, bounds);
+        }
+        subpath.clear();
+        if done {
+            break;
+        }
+    }
+}
+
+/// Adds points that bound the stroke of a subpath with degenerate segments
+/// removed.
+fn bound_subpath(
+    segments: &[Segment],
+    start: Point,
+    closed: bool,
+    style: &Stroke,
+    radius: f32,
+    bounds: &mut BoundsBuilder,
+) {
+    let (first, last) = match (segments.first(), segments.last()) {
+        (Some(first), Some(last)) => (first, last),
+        _ => {
+            // Zero length subpaths are drawn as dots with the caps.
+            let (x, y) = (Vector::new(radius, 0.), Vector::new(0., radius));
+            match (style.start_cap, style.end_cap) {
+                (Cap::Butt, Cap::Butt) => {}
+                (Cap::Square, _) | (_, Cap::Square) => {
+                    for p in [start - x - y, start + x + y] {
+                        bounds.add(p);
+                    }
+                }
+                _ => {
+                    for p in [start - x, start + x, start - y, start + y] {
+                        bounds.add(p);
+                    }
+                }
+            }
+            return;
+        }
+    };
+    let closed = match style.closure {
+        Closure::Explicit => closed,
+        Closure::Never => false,
+        Closure::Coincident => {
+            closed
+                || match (start_point(first), end_point(last)) {
+                    (Some(start), Some(end)) => start.nearly_eq(end),
+                    _ => false,
+                }
+        }
+    };
+    let side = |dir: Vector| Vector::new(dir.y, -dir.x) * radius;
+    for segment in segments {
+        match segment {
+            Segment::Line(_, line) => {
+                let n = side(start_tangent(segment));
+                for p in [line.a + n, line.a - n, line.b + n, line.b - n] {
+                    bounds.add(p);
+                }
+            }
+            Segment::Curve(_, curve) => {
+                let (n0, n1) = (side(start_tangent(segment)), side(end_tangent(segment)));
+                for p in [curve.a + n0, curve.a - n0, curve.d + n1, curve.d - n1] {
+                    bounds.add(p);
+                }
+                // The offset curves share the extrema of the center line
+                // where the normal is parallel to an axis.
+                let (a, b, c, d) = (curve.a, curve.b, curve.c, curve.d);
+                let axes = [
+                    (cubic_extrema(a.x, b.x, c.x, d.x), Vector::new(radius, 0.)),
+                    (cubic_extrema(a.y, b.y, c.y, d.y), Vector::new(0., radius)),
+                ];
+                for (times, offset) in axes {
+                    for &t in times.iter().filter(|&&t| t > 0. && t < 1.) {
+                        let p = curve.evaluate(t);
+                        bounds.add(p + offset);
+                        bounds.add(p - offset);
+                    }
+                }
+            }
+            Segment::End(..) => {}
+        }
+    }
+    for pair in segments.windows(2) {
+        let pivot = end_point(&pair[0]).unwrap_or_default();
+        bound_join(
+            pivot,
+            end_tangent(&pair[0]),
+            start_tangent(&pair[1]),
+            style,
+            radius,
+            bounds,
+        );
+    }
+    if closed {
+        let pivot = end_point(last).unwrap_or_default();
+        bound_join(
+            pivot,
+            end_tangent(last),
+            start_tangent(first),
+            style,
+            radius,
+            bounds,
+        );
+    } else {
+        let (start, end) = (start_point(first), end_point(last));
+        let start_dir = start_tangent(first) * -1.;
+        bound_cap(
+            start.unwrap_or_default(),
+            start_dir,
+            style.start_cap,
+            radius,
+            bounds,
+        );
+        bound_cap(
+            end.unwrap_or_default(),
+            end_tangent(last),
+            style.end_cap,
+            radius,
+            bounds,
+        );
+    }
+}
+
+/// Adds points that bound the join between two directions on the outer side
+/// of the turn.
+fn bound_join(
+    pivot: Point,
+    from: Vector,
+    to: Vector,
+    style: &Stroke,
+    radius: f32,
+    bounds: &mut BoundsBuilder,
+) {
+    let cross = from.cross(to);
+    if cross == 0. {
+        return;
+    }
+    let sign = if cross > 0. { 1. } else { -1. };
+    let (n0, n1) = (
+        Vector::new(from.y, -from.x) * sign,
+        Vector::new(to.y, -to.x) * sign,
+    );
+    let inv_miter_limit = if style.miter_limit >= 1. {
+        1. / style.miter_limit
+    } else {
+        1.
+    };
+    let dot = n0.dot(n1);
+    let sin_half = ((1. + dot) * 0.5).max(0.).sqrt();
+    let dir = (n0 + n1).normalize();
+    match style.join {
+        Join::Bevel => {}
+        Join::Round => bound_arc(pivot, n0, n1, radius, bounds),
+        Join::Miter => {
+            if dot < 0. || sin_half < inv_miter_limit {
+                if style.miter_fallback == MiterFallback::Round {
+                    bound_arc(pivot, n0, n1, radius, bounds);
+                }
+            } else {
+                bounds.add(pivot + dir * (radius / sin_half));
+            }
+        }
+        Join::MiterClip | Join::Arcs => {
+            if (n0 + n1).length() < 1e-6 {
+                return;
+            }
+            if sin_half >= inv_miter_limit {
+                bounds.add(pivot + dir * (radius / sin_half));
+                return;
+            }
+            let limit = radius / inv_miter_limit;
+            for normal in [n0, n1] {
+                let point = pivot + normal * radius;
+                let mut edge = Vector::new(normal.y, -normal.x);
+                if edge.dot(dir) < 0. {
+                    edge = edge * -1.;
+                }
+                let t = (limit - (point - pivot).dot(dir)) / edge.dot(dir);
+                bounds.add(point + edge * t);
+            }
+        }
+    }
+}
+
+/// Adds points that bound a cap with the specified outward direction.
+fn bound_cap(point: Point, dir: Vector, cap: Cap, radius: f32, bounds: &mut BoundsBuilder) {
+    let n = Vector::new(dir.y, -dir.x) * radius;
+    match cap {
+        Cap::Butt => {}
+        Cap::Square => {
+            let d = dir * radius;
+            bounds.add(point + n + d);
+            bounds.add(point - n + d);
+        }
+        Cap::Round => bound_arc(point, n * (1. / radius), n * (-1. / radius), radius, bounds),
+    }
+}
+
+/// Adds points that bound a circular arc around the center from one unit
+/// normal to another in the direction of the shorter turn, or through the
+/// left side when they are opposite.
+fn bound_arc(center: Point, from: Vector, to: Vector, radius: f32, bounds: &mut BoundsBuilder) {
+    let cross = from.cross(to);
+    let mid = if cross == 0. && from.dot(to) < 0. {
+        Vector::new(from.y, -from.x) * -1.
+    } else {
+        from + to
+    };
+    let axes = [
+        Vector::new(1., 0.),
+        Vector::new(-1., 0.),
+        Vector::new(0., 1.),
+        Vector::new(0., -1.),
+    ];
+    for axis in axes {
+        let within = if cross == 0. {
+            axis.dot(mid) >= 0.
+        } else {
+            from.cross(axis) * cross >= 0. && axis.cross(to) * cross >= 0. && axis.dot(mid) > 0.
+        };
+        if within {
+            bounds.add(center + axis * radius);
+        }
+    }
+}
+
+/// Returns the stroke with its width increased to satisfy the minimum width
+/// in device space when it is evaluated before the transform.
+pub(super) fn clamp_width<'a>(style: &Stroke<'a>, transform: Option<&Transform>) -> Stroke<'a> {
+    let mut style = *style;
+    if style.min_width > 0. {
+        let scale = match transform {
+            Some(transform) if style.scale => transform.min_scale(),
+            _ => 1.,
+        };
+        if scale > 0. && scale.is_finite() {
+            style.width = style.width.max(style.min_width / scale);
+        }
+    }
+    style
+}
+
+/// Returns the width in device space of a stroke that is thin enough to be
+/// rendered as a hairline, or `None` if the stroke requires a full outline.
+pub(super) fn hairline_width(style: &Stroke, transform: Option<&Transform>) -> Option<f32> {
+    if !style.dashes.is_empty()
+        || style.dash_pattern.is_some()
+        || !style.widths.is_empty()
+        || style.join_builder.is_some()
+        || !matches!(style.join, Join::Bevel | Join::Miter | Join::Round)
+        || style.start_cap_path.is_some()
+        || style.end_cap_path.is_some()
+        || style.alignment != Alignment::Center
+        || style.remove_overlaps
+        || style.start_marker.is_some()
+        || style.mid_marker.is_some()
+        || style.end_marker.is_some()
+        || style.decoration.is_some()
+        || !style.parallel.is_empty()
+    {
+        return None;
+    }
+    let style = &clamp_width(style, transform);
+    let (max_scale, scale) = match transform {
+        Some(transform) if style.scale => {
+            (transform.max_scale(), transform.determinant().abs().sqrt())
+        }
+        _ => (1., 1.),
+    };
+    if style.width * max_scale <= 1. {
+        Some(style.width.max(0.01) * scale)
+    } else {
+        None
+    }
 }
 
-pub struct Stroker<'a, I, S> {
+/// Strokes commands that are already in device space with a hairline of the
+/// specified width. Each line of the flattened path is emitted as a thin
+/// quad and the gaps between them are filled with the joins and caps of the
+/// style, skipping construction of the offset curves.
+pub(super) fn hairline_into<I>(commands: I, style: &Stroke, width: f32, sink: &mut impl PathBuilder)
+where
+    I: Iterator + Clone,
+    I::Item: Borrow<Command>,
+{
+    let radius = width * 0.5;
+    let flatness = 0.5 * stroke_quality(style);
+    let mut points = Vec::new();
+    let mut closed = false;
+    for segment in segments(commands, false) {
+        match segment {
+            Segment::Line(_, line) => {
+                if points.is_empty() {
+                    points.push(line.a);
+                }
+                points.push(line.b);
+            }
+            Segment::Curve(_, c) => {
+                if points.is_empty() {
+                    points.push(c.a);
+                }
+                c.flatten(flatness, &mut points);
+            }
+            Segment::End(is_closed) => {
+                closed = is_closed;
+            }
+        }
+        if let Segment::End(..) = segment {
+            let closed = match style.closure {
+                Closure::Explicit => closed,
+                Closure::Never => false,
+                Closure::Coincident => {
+                    closed || (points.len() > 1 && points[0].nearly_eq(points[points.len() - 1]))
+                }
+            };
+            hairline_subpath(&mut points, style, closed, radius, sink);
+            points.clear();
+        }
+    }
+    hairline_subpath(&mut points, style, closed, radius, sink);
+}
+
+fn hairline_subpath(
+    points: &mut Vec<Point>,
+    style: &Stroke,
+    closed: bool,
+    radius: f32,
+    sink: &mut impl PathBuilder,
+) {
+    points.dedup_by(|a, b| a.nearly_eq(*b));
+    if points.is_empty() {
+        return;
+    }
+    let first = points[0];
+    if points.len() == 1 {
+        let caps = [style.start_cap, style.end_cap];
+        let (x, y) = (Vector::new(radius, 0.), Vector::new(0., radius));
+        if caps.contains(&Cap::Round) {
+            sink.move_to(first - y);
+            hairline_arc(sink, first, y * -1., x, y);
+            hairline_arc(sink, first, y, x * -1., y * -1.);
+            sink.close();
+        } else if caps.contains(&Cap::Square) {
+            sink.move_to(first - x - y);
+            sink.line_to(first + x - y);
+            sink.line_to(first + x + y);
+            sink.line_to(first - x + y);
+            sink.close();
+        }
+        return;
+    }
+    if closed && !points[points.len() - 1].nearly_eq(first) {
+        points.push(first);
+    }
+    let len = points.len();
+    let dir = |i: usize| (points[i + 1] - points[i]).normalize();
+    for i in 0..len - 1 {
+        let d = dir(i);
+        let mut a = points[i];
+        let mut b = points[i + 1];
+        let n = Vector::new(d.y, -d.x) * radius;
+        if !closed {
+            if i == 0 {
+                match style.start_cap {
+                    Cap::Butt => {}
+                    Cap::Square => a = a - d * radius,
+                    Cap::Round => {
+                        sink.move_to(a - n);
+                        hairline_arc(sink, a, n * -1., d * -radius, n);
+                        sink.close();
+                    }
+                }
+            }
+            if i == len - 2 {
+                match style.end_cap {
+                    Cap::Butt => {}
+                    Cap::Square => b = b + d * radius,
+                    Cap::Round => {
+                        sink.move_to(b + n);
+                        hairline_arc(sink, b, n, d * radius, n * -1.);
+                        sink.close();
+                    }
+                }
+            }
+        }
+        sink.move_to(a + n);
+        sink.line_to(b + n);
+        sink.line_to(b - n);
+        sink.line_to(a - n);
+        sink.close();
+    }
+    // Fill the wedge on the outer side of each join.
+    let inv_miter_limit = if style.miter_limit >= 1. {
+        1. / style.miter_limit
+    } else {
+        1.
+    };
+    let joins = if closed { 1..len } else { 1..len - 1 };
+    for i in joins {
+        let (d0, d1) = (dir(i - 1), dir(i % (len - 1)));
+        let side = if d0.cross(d1) > 0. { radius } else { -radius };
+        let p = points[i];
+        let from = Vector::new(d0.y, -d0.x) * side;
+        let to = Vector::new(d1.y, -d1.x) * side;
+        let dot = d0.dot(d1);
+        let sin_half = ((1. + dot) * 0.5).sqrt();
+        let join = match style.join {
+            Join::Miter if dot < 0. || sin_half < inv_miter_limit => match style.miter_fallback {
+                MiterFallback::Bevel => Join::Bevel,
+                MiterFallback::Round => Join::Round,
+            },
+            join => join,
+        };
+        sink.move_to(p);
+        sink.line_to(p + from);
+        match join {
+            Join::Miter => {
+                sink.line_to(p + (from + to) * (1. / (1. + dot)));
+            }
+            Join::Round => {
+                let mid = from + to;
+                let mid = if mid.length() > radius * 1e-3 {
+                    mid.normalize() * radius
+                } else {
+                    d0 * radius
+                };
+                hairline_arc(sink, p, from, mid, to);
+            }
+            _ => {}
+        }
+        sink.line_to(p + to);
+        sink.close();
+    }
+}
+
+/// Adds a circular arc around the center from the current point at the
+/// offset `from` through `mid` to `to`. Each half of the arc may span at
+/// most a quarter turn.
+fn hairline_arc(sink: &mut impl PathBuilder, center: Point, from: Vector, mid: Vector, to: Vector) {
+    let r2 = mid.dot(mid);
+    for (a, b) in [(from, mid), (mid, to)] {
+        let cos = a.dot(b) / r2;
+        let weight = ((1. + cos) * 0.5).sqrt();
+        sink.conic_to(center + (a + b) * (1. / (1. + cos)), center + b, weight);
+    }
+}
+
+pub struct Stroker<'a, 's, I, S> {
     source: Segments<I>,
     sink: &'a mut S,
     radius: f32,
     radius_abs: f32,
+    base_radius: f32,
+    widths: &'s [f32],
+    radii: Vec<(f32, f32)>,
+    alignment: Alignment,
+    sides: (f32, f32),
     join: Join,
+    join_builder: Option<&'s dyn JoinBuilder>,
     inv_miter_limit: f32,
+    miter_fallback: MiterFallback,
     start_cap: Cap,
     end_cap: Cap,
+    start_cap_path: Option<&'s [Command]>,
+    end_cap_path: Option<&'s [Command]>,
+    dash_cap: Option<Cap>,
+    closure: Closure,
+    dash_mode: DashMode,
+    dash_reset: bool,
+    dash_pattern: Option<&'s dyn DashPattern>,
+    epsilon: f32,
 }
 
-impl<'a, I, S> Stroker<'a, I, S>
+impl<'a, 's, I, S> Stroker<'a, 's, I, S>
 where
     I: Iterator + Clone,
     I::Item: Borrow<Command>,
     S: PathBuilder,
 {
-    pub(super) fn new(source: Segments<I>, sink: &'a mut S, style: &Stroke) -> Self {
+    pub(super) fn new(source: Segments<I>, sink: &'a mut S, style: &Stroke<'s>) -> Self {
         let radius = style.width.max(0.01) * 0.5;
+        let tolerance = source.tolerance();
         Self {
             source,
             sink,
             radius,
             radius_abs: radius.abs(),
+            base_radius: radius,
+            widths: style.widths,
+            radii: Vec::new(),
+            alignment: style.alignment,
+            sides: (1., 1.),
             join: style.join,
+            join_builder: style.join_builder,
             inv_miter_limit: if style.miter_limit >= 1. {
                 1. / style.miter_limit
             } else {
                 1.
             },
+            miter_fallback: style.miter_fallback,
             start_cap: style.start_cap,
             end_cap: style.end_cap,
+            start_cap_path: style.start_cap_path,
+            end_cap_path: style.end_cap_path,
+            dash_cap: style.dash_cap,
+            closure: style.closure,
+            dash_mode: style.dash_mode,
+            dash_reset: style.dash_reset,
+            dash_pattern: style
+                .dash_pattern
+                .filter(|pattern| pattern.dashes().next().is_some()),
+            epsilon: tolerance,
+        }
+    }
+
+    fn is_closed(&self, segments: &[Segment], closed: bool) -> bool {
+        match self.closure {
+            Closure::Explicit => closed,
+            Closure::Never => false,
+            Closure::Coincident => {
+                closed
+                    || match (segments.first(), segments.last()) {
+                        (Some(first), Some(last)) => match (start_point(first), end_point(last)) {
+                            (Some(start), Some(end)) => {
+                                start.nearly_eq_by(end, 0.01 * self.epsilon)
+                            }
+                            _ => false,
+                        },
+                        _ => false,
+                    }
+            }
         }
     }
 
+    /// Collects the next subpath into the buffer along with the radii at the
+    /// ends of each segment when the stroke has per-point widths.
+    fn collect(&mut self, segment_buf: &mut impl StrokerStorage) -> (bool, bool) {
+        if self.widths.is_empty() {
+            return segment_buf.collect(&mut self.source);
+        }
+        segment_buf.clear();
+        self.radii.clear();
+        loop {
+            match self.source.next() {
+                Some(Segment::End(closed)) => return (closed, false),
+                Some(segment) => {
+                    segment_buf.push(&segment);
+                    let source = self.source.source();
+                    let (from, to) = (self.point_radius(source.from), self.point_radius(source.to));
+                    let lerp = |t: f32| from + (to - from) * t;
+                    self.radii.push((lerp(source.start), lerp(source.end)));
+                }
+                None => return (false, true),
+            }
+        }
+    }
+
+    /// Returns the radius of the stroke at the on-curve point with the
+    /// specified index.
+    fn point_radius(&self, index: usize) -> f32 {
+        match self.widths.get(index) {
+            Some(width) => width.max(0.01) * 0.5,
+            None => self.base_radius,
+        }
+    }
+
+    /// Returns the radii at the start and end of the segment with the
+    /// specified signed index.
+    fn segment_radii(&self, index: isize) -> (f32, f32) {
+        let len = self.radii.len() as isize;
+        if len == 0 {
+            return (self.base_radius, self.base_radius);
+        }
+        if index < 0 {
+            self.radii[(len + index) as usize]
+        } else {
+            self.radii[index as usize]
+        }
+    }
+
+    /// Returns the radii at the specified times along the segment with the
+    /// specified signed index.
+    fn slice_radii(&self, index: isize, t0: f32, t1: f32) -> (f32, f32) {
+        let (r0, r1) = self.segment_radii(index);
+        (r0 + (r1 - r0) * t0, r0 + (r1 - r0) * t1)
+    }
+
+    /// Returns the radii for offsetting the segment in the specified
+    /// direction, accounting for the alignment of the stroke.
+    fn pass_radii(&self, radii: (f32, f32), forward: bool) -> (f32, f32) {
+        if forward {
+            (radii.0 * self.sides.0, radii.1 * self.sides.0)
+        } else {
+            (radii.1 * self.sides.1, radii.0 * self.sides.1)
+        }
+    }
+
+    /// Returns the factors applied to the radius for the forward and
+    /// backward offsets of a subpath. The forward offset lies outside the
+    /// subpath when the signed area is positive.
+    fn alignment_sides(&self, segments: &[Segment], is_closed: bool) -> (f32, f32) {
+        if !is_closed || self.alignment == Alignment::Center {
+            return (1., 1.);
+        }
+        let outside = signed_area(segments) > 0.;
+        match (self.alignment, outside) {
+            (Alignment::Outside, true) | (Alignment::Inside, false) => (2., 0.),
+            _ => (0., 2.),
+        }
+    }
+
+    fn set_radius(&mut self, radius: f32) {
+        self.radius = radius;
+        self.radius_abs = radius.abs();
+    }
+
     fn stroke(&mut self, segment_buf: &mut impl StrokerStorage) {
         loop {
-            let (closed, done) = segment_buf.collect(&mut self.source);
+            let (closed, done) = self.collect(segment_buf);
+            let closed = self.is_closed(segment_buf.get(), closed);
             self.stroke_segments(segment_buf.get(), closed);
             if done {
                 break;
@@ -103,6 +936,7 @@ where
                 Segment::End(..) => Point::ZERO,
             };
             let n = Vector::new(0., 1.);
+            self.set_radius(self.segment_radii(0).0);
             let nr = n * self.radius;
             let start = from + nr;
             let rstart = from - nr;
@@ -111,26 +945,30 @@ where
             self.add_start_cap(rstart, start, n * -1.);
             return;
         }
-        let radius = self.radius;
+        self.sides = self.alignment_sides(segments, is_closed);
         let mut last_dir = Vector::ZERO;
+        let mut last_curvature = 0.;
         let mut first_point = Point::ZERO;
         let mut last_point = Point::ZERO;
         let mut pivot = Point::ZERO;
         let mut last_id = 0xFF;
         if is_closed {
-            let segment = segments[len - 1].offset(radius);
+            let radii = self.pass_radii(self.segment_radii(-1), true);
+            let segment = segments[len - 1].offset(radii, self.epsilon);
             let end_point = segment.end;
             let out_dir = segment.end_normal;
             pivot = segment.end_pivot;
             last_dir = out_dir;
+            last_curvature = segment.end_curvature;
             last_point = end_point;
             first_point = end_point;
             self.sink.move_to(last_point);
         }
         // Forward for the outer stroke.
         let mut is_first = !is_closed;
-        for segment in segments {
-            let segment = segment.offset(radius);
+        for (i, segment) in segments.iter().enumerate() {
+            let radii = self.pass_radii(self.segment_radii(i as isize), true);
+            let segment = segment.offset(radii, self.epsilon);
             let id = segment.id;
             let start = segment.start;
             if is_first {
@@ -138,44 +976,77 @@ where
                 first_point = start;
                 is_first = false;
             } else {
-                self.add_join(last_point, start, pivot, last_dir, segment.start_normal);
+                self.set_radius(radii.0);
+                let curvature = (last_curvature, segment.start_curvature);
+                self.add_join(
+                    last_point,
+                    start,
+                    pivot,
+                    last_dir,
+                    segment.start_normal,
+                    curvature,
+                );
             }
             last_id = id;
             last_dir = segment.end_normal;
+            last_curvature = segment.end_curvature;
             pivot = segment.end_pivot;
             last_point = self.emit(&segment.segment);
         }
         // Now backward for the inner stroke.
         is_first = true;
-        for segment in segments.iter().rev() {
-            let segment = segment.reverse().offset(radius);
+        for (i, segment) in segments.iter().enumerate().rev() {
+            let radii = self.pass_radii(self.segment_radii(i as isize), false);
+            let segment = segment.reverse().offset(radii, self.epsilon);
             let id = segment.id;
             let start = segment.start;
+            self.set_radius(radii.0);
             if is_first {
                 if is_closed {
-                    let init = segments[0].reverse().offset(self.radius);
+                    let radii = self.pass_radii(self.segment_radii(0), false);
+                    self.set_radius(radii.1);
+                    let init = segments[0].reverse().offset(radii, self.epsilon);
                     last_point = init.end;
                     last_dir = init.end_normal;
+                    last_curvature = init.end_curvature;
                     pivot = init.end_pivot;
                     self.sink.line_to(init.end);
-                    self.add_join(last_point, start, pivot, last_dir, segment.start_normal);
+                    let curvature = (last_curvature, segment.start_curvature);
+                    self.add_join(
+                        last_point,
+                        start,
+                        pivot,
+                        last_dir,
+                        segment.start_normal,
+                        curvature,
+                    );
                 } else {
                     self.add_end_cap(last_point, start, last_dir);
                 }
                 is_first = false;
             } else {
                 if id != last_id {
-                    self.add_join(last_point, start, pivot, last_dir, segment.start_normal);
+                    let curvature = (last_curvature, segment.start_curvature);
+                    self.add_join(
+                        last_point,
+                        start,
+                        pivot,
+                        last_dir,
+                        segment.start_normal,
+                        curvature,
+                    );
                 } else {
                     self.add_split_join(last_point, start, pivot, last_dir, segment.start_normal);
                 }
             }
             last_id = id;
             last_dir = segment.end_normal;
+            last_curvature = segment.end_curvature;
             pivot = segment.end_pivot;
             last_point = self.emit(&segment.segment);
         }
         if !is_closed {
+            self.set_radius(self.segment_radii(0).0);
             self.add_start_cap(last_point, first_point, last_dir);
         }
         self.sink.close();
@@ -190,37 +1061,100 @@ where
     ) {
         let mut dasher = Dasher::default();
         dasher.empty_gaps = empty_gaps;
+        let mut dash_buf = Vec::new();
+        // Accumulated length of previous subpaths as a fraction of the
+        // pattern length when the dash phase continues across subpaths.
+        let mut phase = 0.;
+        // Position along the pattern when a procedural pattern continues
+        // across subpaths.
+        let mut position = offset;
         let mut done = false;
         while !done {
-            let (is_closed, is_done) = segment_buf.collect(&mut self.source);
+            let (is_closed, is_done) = self.collect(segment_buf);
             done = is_done;
             let segments = segment_buf.get();
             if segments.is_empty() {
                 continue;
             }
+            let is_closed = self.is_closed(segments, is_closed);
+            self.sides = self.alignment_sides(segments, is_closed);
+            if let Some(pattern) = self.dash_pattern {
+                if self.dash_reset || dasher.pattern.is_none() {
+                    dasher.pattern = Some(PatternDashes::new(pattern));
+                    dasher.init(is_closed, dashes, offset);
+                } else {
+                    dasher.resume(is_closed, dashes, position);
+                }
+                position += segments.iter().map(|s| s.length()).sum::<f32>();
+                self.dash_subpath(&mut dasher, segments, dashes, is_closed);
+                continue;
+            }
+            let (dashes, offset) = match self.dash_mode {
+                DashMode::Fit => fit_dashes(segments, dashes, offset, &mut dash_buf),
+                DashMode::Corners => corner_dashes(segments, is_closed, dashes, &mut dash_buf),
+                _ => (dashes, offset),
+            };
+            let offset = if self.dash_reset || self.dash_mode == DashMode::Corners {
+                offset
+            } else {
+                let period = dash_period(dashes);
+                let length = segments.iter().map(|s| s.length()).sum::<f32>();
+                let offset = (offset + phase * period) % period;
+                phase += length / period;
+                phase -= phase.floor();
+                offset
+            };
             dasher.init(is_closed, dashes, offset);
-            loop {
-                match dasher.next(segments, dashes) {
-                    DashOp::Done => break,
-                    DashOp::Continue => {}
-                    DashOp::Emit => {
-                        let (start, end) = dasher.range;
-                        let (t0, t1) = dasher.trange;
-                        self.dash_segments(segments, start, end, t0, t1);
-                    }
-                    DashOp::Stroke => {
-                        self.stroke_segments(segments, true);
-                        break;
-                    }
+            self.dash_subpath(&mut dasher, segments, dashes, is_closed);
+        }
+    }
+
+    fn dash_subpath(
+        &mut self,
+        dasher: &mut Dasher,
+        segments: &[Segment],
+        dashes: &[f32],
+        is_closed: bool,
+    ) {
+        loop {
+            match dasher.next(segments, dashes) {
+                DashOp::Done => break,
+                DashOp::Continue => {}
+                DashOp::Emit => {
+                    let (start, end) = dasher.range;
+                    let (t0, t1) = dasher.trange;
+                    self.dash_segments(segments, is_closed, start, end, t0, t1);
+                }
+                DashOp::Stroke => {
+                    self.stroke_segments(segments, true);
+                    break;
                 }
             }
         }
     }
 
-    fn dash_segments(&mut self, segments: &[Segment], start: isize, end: isize, t0: f32, t1: f32) {
-        let radius = self.radius;
+    fn dash_segments(
+        &mut self,
+        segments: &[Segment],
+        is_closed: bool,
+        start: isize,
+        end: isize,
+        t0: f32,
+        t1: f32,
+    ) {
+        // Dash ends that do not coincide with the ends of an open subpath.
+        let last = segments.len() as isize - 1;
+        let inner = (
+            is_closed || start != 0 || t0 > 0.,
+            is_closed || end != last || t1 < 1.,
+        );
         if t0 == t1 && start == end {
-            if self.start_cap == Cap::Butt && self.end_cap == Cap::Butt {
+            let cap = |inner, cap| match self.dash_cap {
+                Some(dash_cap) if inner => dash_cap,
+                _ => cap,
+            };
+            if cap(inner.0, self.start_cap) == Cap::Butt && cap(inner.1, self.end_cap) == Cap::Butt
+            {
                 return;
             }
             let (t0, t1) = if t0 >= 1. {
@@ -228,16 +1162,22 @@ where
             } else {
                 (t0, t0 + 0.001)
             };
-            let segment = get_signed(segments, start).slice(t0, t1).offset(radius);
+            let radius = self.slice_radii(start, t0, t1).0;
+            self.set_radius(radius);
+            let radii = self.pass_radii((radius, radius), true);
+            let segment = get_signed(segments, start)
+                .slice(t0, t1)
+                .offset(radii, self.epsilon);
             let start = segment.start;
             let rstart = segment.start - (segment.start_normal * (2. * radius));
             self.sink.move_to(start);
-            self.add_end_cap(start, rstart, segment.start_normal);
-            self.add_start_cap(rstart, start, segment.start_normal * -1.);
+            self.add_dash_end_cap(start, rstart, segment.start_normal, inner.1);
+            self.add_dash_start_cap(rstart, start, segment.start_normal * -1., inner.0);
             self.sink.close();
             return;
         }
         let mut last_dir = Vector::ZERO;
+        let mut last_curvature = 0.;
         let mut first_point = Point::ZERO;
         let mut last_point = Point::ZERO;
         let mut pivot = Point::ZERO;
@@ -249,7 +1189,10 @@ where
             if t0 >= 1. {
                 continue;
             }
-            let segment = get_signed(segments, i).slice(t0, t1).offset(radius);
+            let radii = self.pass_radii(self.slice_radii(i, t0, t1), true);
+            let segment = get_signed(segments, i)
+                .slice(t0, t1)
+                .offset(radii, self.epsilon);
             let id = segment.id;
             let start = segment.start;
             if is_first {
@@ -257,8 +1200,17 @@ where
                 first_point = start;
                 is_first = false;
             } else {
+                self.set_radius(radii.0);
                 if id != last_id {
-                    self.add_join(last_point, start, pivot, last_dir, segment.start_normal);
+                    let curvature = (last_curvature, segment.start_curvature);
+                    self.add_join(
+                        last_point,
+                        start,
+                        pivot,
+                        last_dir,
+                        segment.start_normal,
+                        curvature,
+                    );
                 } else {
                     self.add_split_join(last_point, start, pivot, last_dir, segment.start_normal);
                 }
@@ -266,6 +1218,7 @@ where
             last_id = id;
             pivot = segment.end_pivot;
             last_dir = segment.end_normal;
+            last_curvature = segment.end_curvature;
             last_point = self.emit(&segment.segment);
         }
         is_first = true;
@@ -276,18 +1229,30 @@ where
             if t0 >= 1. {
                 continue;
             }
+            let radii = self.slice_radii(i, t0, t1);
+            let pass_radii = self.pass_radii(radii, false);
             let segment = get_signed(segments, i)
                 .slice(t0, t1)
                 .reverse()
-                .offset(radius);
+                .offset(pass_radii, self.epsilon);
             let id = segment.id;
             let start = segment.start;
             if is_first {
-                self.add_end_cap(last_point, start, last_dir);
+                self.set_radius(radii.1);
+                self.add_dash_end_cap(last_point, start, last_dir, inner.1);
                 is_first = false;
             } else {
+                self.set_radius(pass_radii.0);
                 if id != last_id {
-                    self.add_join(last_point, start, pivot, last_dir, segment.start_normal);
+                    let curvature = (last_curvature, segment.start_curvature);
+                    self.add_join(
+                        last_point,
+                        start,
+                        pivot,
+                        last_dir,
+                        segment.start_normal,
+                        curvature,
+                    );
                 } else {
                     self.add_split_join(last_point, start, pivot, last_dir, segment.start_normal);
                 }
@@ -295,9 +1260,11 @@ where
             last_id = id;
             pivot = segment.end_pivot;
             last_dir = segment.end_normal;
+            last_curvature = segment.end_curvature;
             last_point = self.emit(&segment.segment);
         }
-        self.add_start_cap(last_point, first_point, last_dir);
+        self.set_radius(self.slice_radii(start, t0, 1.).0);
+        self.add_dash_start_cap(last_point, first_point, last_dir, inner.0);
         self.sink.close();
     }
 
@@ -323,6 +1290,7 @@ where
         pivot: Point,
         from_normal: Vector,
         to_normal: Vector,
+        curvature: (f32, f32),
     ) -> Point {
         if from.nearly_eq(to) {
             return from;
@@ -332,15 +1300,32 @@ where
             self.sink.line_to(to);
             return to;
         }
+        if let Some(builder) = self.join_builder {
+            let join = JoinGeometry {
+                position: pivot,
+                from_tangent: Vector::new(-from_normal.y, from_normal.x),
+                to_tangent: Vector::new(-to_normal.y, to_normal.x),
+                width: self.radius * 2.,
+                from,
+                to,
+            };
+            let sink = &mut *self.sink;
+            builder.build(&join, &mut |cmd| match cmd {
+                Command::MoveTo(_) | Command::Close => {}
+                _ => emit_command(sink, &cmd),
+            });
+            if !self.sink.current_point().nearly_eq(to) {
+                self.sink.line_to(to);
+            }
+            return to;
+        }
         match self.join {
             Join::Bevel => {
                 self.sink.line_to(to);
                 return to;
             }
             Join::Round => {
-                let r = self.radius_abs;
-                let (size, sweep) = (ArcSize::Small, ArcSweep::Positive);
-                arc(self.sink, from, r, r, 0., size, sweep, to);
+                self.add_round_join(from, to);
                 return to;
             }
             Join::Miter => {
@@ -348,7 +1333,12 @@ where
                 let dot = from_normal.dot(to_normal);
                 let sin_half = ((1. + dot) * 0.5).sqrt();
                 if dot < 0.0 || sin_half < inv_limit {
-                    self.sink.line_to(to);
+                    match self.miter_fallback {
+                        MiterFallback::Bevel => {
+                            self.sink.line_to(to);
+                        }
+                        MiterFallback::Round => self.add_round_join(from, to),
+                    }
                     return to;
                 } else {
                     let mid = (from_normal + to_normal).normalize() * (self.radius / sin_half);
@@ -358,6 +1348,136 @@ where
                     return to;
                 }
             }
+            Join::MiterClip => self.add_miter_clip(from, to, pivot, from_normal, to_normal),
+            Join::Arcs => {
+                let from_edge = JoinEdge::new(from, pivot, from_normal, curvature.0);
+                let to_edge = JoinEdge::new(to, pivot, to_normal, curvature.1);
+                if !self.add_arcs(from, to, pivot, from_normal, to_normal, from_edge, to_edge) {
+                    self.add_miter_clip(from, to, pivot, from_normal, to_normal);
+                }
+                to
+            }
+        }
+    }
+
+    fn add_round_join(&mut self, from: Point, to: Point) {
+        let r = self.radius_abs;
+        let (size, sweep) = (ArcSize::Small, ArcSweep::Positive);
+        arc(self.sink, from, r, r, 0., size, sweep, to);
+    }
+
+    /// Adds a miter join that is clipped at the miter limit.
+    fn add_miter_clip(
+        &mut self,
+        from: Point,
+        to: Point,
+        pivot: Point,
+        from_normal: Vector,
+        to_normal: Vector,
+    ) -> Point {
+        {
+            {
+                let bisector = from_normal + to_normal;
+                if bisector.length() < 1e-6 {
+                    self.sink.line_to(to);
+                    return to;
+                }
+                let sin_half = ((1. + from_normal.dot(to_normal)) * 0.5).sqrt();
+                let dir = bisector.normalize();
+                if sin_half >= self.inv_miter_limit {
+                    self.sink.line_to(pivot + dir * (self.radius / sin_half));
+                } else {
+                    // Clip the miter with a line perpendicular to the
+                    // bisector at the limit distance from the pivot.
+                    let limit = self.radius / self.inv_miter_limit;
+                    for (point, normal) in [(from, from_normal), (to, to_normal)] {
+                        let mut edge = Vector::new(normal.y, -normal.x);
+                        if edge.dot(dir) < 0. {
+                            edge = edge * -1.;
+                        }
+                        let t = (limit - (point - pivot).dot(dir)) / edge.dot(dir);
+                        self.sink.line_to(point + edge * t);
+                    }
+                }
+                self.sink.line_to(to);
+                to
+            }
+        }
+    }
+
+    /// Adds a join formed by extending the outer edges of the segments until
+    /// they meet, clipping at the miter limit. Returns false if the edges do
+    /// not meet on the outer side of the join.
+    #[allow(clippy::too_many_arguments)]
+    fn add_arcs(
+        &mut self,
+        from: Point,
+        to: Point,
+        pivot: Point,
+        from_normal: Vector,
+        to_normal: Vector,
+        from_edge: JoinEdge,
+        to_edge: JoinEdge,
+    ) -> bool {
+        let bisector = from_normal + to_normal;
+        if bisector.length() < 1e-6 {
+            return false;
+        }
+        let dir = bisector.normalize();
+        let (points, count) = from_edge.intersect(&to_edge);
+        let mut tip = None;
+        let mut best = f32::MAX;
+        for &point in &points[..count] {
+            if (point - pivot).dot(dir) <= 0. {
+                continue;
+            }
+            let (t0, t1) = (from_edge.travel(from, point), to_edge.travel(point, to));
+            if t0.max(t1) < f32::MAX && t0 < best {
+                best = t0;
+                tip = Some(point);
+            }
+        }
+        let tip = match tip {
+            Some(tip) => tip,
+            _ => return false,
+        };
+        let limit = self.radius / self.inv_miter_limit;
+        if (tip - pivot).dot(dir) <= limit {
+            from_edge.emit(self.sink, from, tip);
+            to_edge.emit(self.sink, tip, to);
+            return true;
+        }
+        // Clip both edges with a line perpendicular to the bisector at the
+        // limit distance from the pivot.
+        let clip = JoinEdge::Line(pivot + dir * limit, Vector::new(dir.y, -dir.x));
+        let nearest = |edge: &JoinEdge, from: Point, forward: bool| {
+            let (points, count) = edge.intersect(&clip);
+            let mut nearest = None;
+            let mut best = f32::MAX;
+            for &point in &points[..count] {
+                let t = if forward {
+                    edge.travel(from, point)
+                } else {
+                    edge.travel(point, from)
+                };
+                if t < best {
+                    best = t;
+                    nearest = Some(point);
+                }
+            }
+            nearest
+        };
+        match (
+            nearest(&from_edge, from, true),
+            nearest(&to_edge, to, false),
+        ) {
+            (Some(a), Some(b)) => {
+                from_edge.emit(self.sink, from, a);
+                self.sink.line_to(b);
+                to_edge.emit(self.sink, b, to);
+                true
+            }
+            _ => false,
         }
     }
 
@@ -402,12 +1522,55 @@ where
         }
     }
 
+    /// Adds a cap from a custom path in a coordinate system where the x-axis
+    /// is the outward tangent and the y-axis runs from the start of the cap
+    /// to the end, scaled by the radius.
+    fn add_cap_path(&mut self, from: Point, to: Point, dir: Vector, path: &[Command]) {
+        let r = self.radius_abs;
+        let center = (from + to) * 0.5;
+        let x_axis = Vector::new(-dir.y, dir.x) * r;
+        let y_axis = dir * -r;
+        let transform = Transform::new(x_axis.x, x_axis.y, y_axis.x, y_axis.y, center.x, center.y);
+        for cmd in path {
+            match *cmd {
+                Command::MoveTo(p) => {
+                    self.sink.line_to(transform.transform_point(p));
+                }
+                Command::Close => {}
+                cmd => emit_command(self.sink, &cmd.transform(&transform)),
+            }
+        }
+        if !self.sink.current_point().nearly_eq(to) {
+            self.sink.line_to(to);
+        }
+    }
+
     fn add_start_cap(&mut self, from: Point, to: Point, dir: Vector) {
-        self.add_cap(from, to, dir, self.start_cap);
+        match self.start_cap_path {
+            Some(path) => self.add_cap_path(from, to, dir, path),
+            None => self.add_cap(from, to, dir, self.start_cap),
+        }
     }
 
     fn add_end_cap(&mut self, from: Point, to: Point, dir: Vector) {
-        self.add_cap(from, to, dir, self.end_cap);
+        match self.end_cap_path {
+            Some(path) => self.add_cap_path(from, to, dir, path),
+            None => self.add_cap(from, to, dir, self.end_cap),
+        }
+    }
+
+    fn add_dash_start_cap(&mut self, from: Point, to: Point, dir: Vector, inner: bool) {
+        match self.dash_cap {
+            Some(cap) if inner => self.add_cap(from, to, dir, cap),
+            _ => self.add_start_cap(from, to, dir),
+        }
+    }
+
+    fn add_dash_end_cap(&mut self, from: Point, to: Point, dir: Vector, inner: bool) {
+        match self.dash_cap {
+            Some(cap) if inner => self.add_cap(from, to, dir, cap),
+            _ => self.add_end_cap(from, to, dir),
+        }
     }
 }
 
@@ -418,8 +1581,57 @@ enum DashOp {
     Stroke,
 }
 
-#[derive(Copy, Clone, Default)]
-struct Dasher {
+/// Dash lengths produced by a procedural pattern.
+struct PatternDashes<'d> {
+    pattern: &'d dyn DashPattern,
+    iter: Box<dyn Iterator<Item = f32> + 'd>,
+    peeked: Option<f32>,
+    /// Sum of the lengths returned so far.
+    consumed: f32,
+    /// Number of lengths returned so far.
+    count: usize,
+}
+
+impl<'d> PatternDashes<'d> {
+    fn new(pattern: &'d dyn DashPattern) -> Self {
+        Self {
+            pattern,
+            iter: pattern.dashes(),
+            peeked: None,
+            consumed: 0.,
+            count: 0,
+        }
+    }
+
+    fn peek(&mut self) -> f32 {
+        if let Some(dash) = self.peeked {
+            return dash;
+        }
+        let dash = match self.iter.next() {
+            Some(dash) => dash,
+            None => {
+                // Repeat the pattern.
+                self.iter = self.pattern.dashes();
+                self.iter.next().unwrap_or(0.)
+            }
+        };
+        let dash = dash.max(0.);
+        self.peeked = Some(dash);
+        dash
+    }
+
+    fn next(&mut self) -> f32 {
+        let dash = self.peek();
+        self.peeked = None;
+        self.consumed += dash;
+        self.count += 1;
+        dash
+    }
+}
+
+#[derive(Default)]
+struct Dasher<'d> {
+    pattern: Option<PatternDashes<'d>>,
     done: bool,
     is_closed: bool,
     empty_gaps: bool,
@@ -436,8 +1648,8 @@ struct Dasher {
     trange: (f32, f32),
 }
 
-impl Dasher {
-    fn init(&mut self, is_closed: bool, dashes: &[f32], offset: f32) {
+impl Dasher<'_> {
+    fn reset(&mut self, is_closed: bool) {
         self.done = false;
         self.is_closed = is_closed;
         self.on = true;
@@ -447,6 +1659,10 @@ impl Dasher {
         self.index = 0;
         self.is_first = true;
         self.first_on = true;
+    }
+
+    fn init(&mut self, is_closed: bool, dashes: &[f32], offset: f32) {
+        self.reset(is_closed);
         let mut first_dash = self.next_dash(dashes);
         if offset > 0. {
             let mut accum = first_dash;
@@ -460,8 +1676,37 @@ impl Dasher {
         self.first_dash = first_dash;
     }
 
+    /// Initializes the dasher for a subpath that begins at the specified
+    /// position along the procedural pattern, continuing with the remainder
+    /// of the last dash.
+    fn resume(&mut self, is_closed: bool, dashes: &[f32], position: f32) {
+        self.reset(is_closed);
+        let (remaining, last_on) = match &self.pattern {
+            Some(pattern) => (pattern.consumed - position, pattern.count & 1 == 1),
+            None => (0., false),
+        };
+        if remaining > 0. {
+            self.on = last_on;
+            self.first_dash = remaining;
+        } else {
+            self.on = !last_on;
+            self.first_dash = self.next_dash(dashes);
+        }
+        self.first_on = self.on;
+    }
+
     #[inline(always)]
     fn next_dash(&mut self, dashes: &[f32]) -> f32 {
+        if let Some(pattern) = &mut self.pattern {
+            let mut dash = pattern.next();
+            if self.on && self.empty_gaps {
+                while pattern.peek() == 0. {
+                    pattern.next();
+                    dash += pattern.next();
+                }
+            }
+            return dash;
+        }
         let len = dashes.len();
         let mut dash = dashes[self.index % len];
         if self.on && self.empty_gaps {
@@ -592,7 +1837,142 @@ impl Dasher {
     }
 }
 
-fn validate_dashes(dashes: &[f32], offset: f32) -> (&[f32], f32, bool) {
+/// Returns the dash array and offset of the style in absolute lengths,
+/// converting percentages of the path length if necessary.
+fn resolve_dashes<'d, I>(commands: I, style: &Stroke<'d>, buf: &'d mut Vec<f32>) -> (&'d [f32], f32)
+where
+    I: Iterator + Clone,
+    I::Item: Borrow<Command>,
+{
+    if style.dash_mode != DashMode::Percent || style.dashes.is_empty() {
+        return (style.dashes, style.offset);
+    }
+    let scale = segments(commands, true).map(|s| s.length()).sum::<f32>() * 0.01;
+    buf.extend(style.dashes.iter().map(|dash| dash * scale));
+    (buf, style.offset * scale)
+}
+
+/// Returns the dash array and offset scaled so that a whole number of
+/// repetitions of the pattern fits the length of the subpath.
+fn fit_dashes<'d>(
+    segments: &[Segment],
+    dashes: &[f32],
+    offset: f32,
+    buf: &'d mut Vec<f32>,
+) -> (&'d [f32], f32) {
+    let length = segments.iter().map(|s| s.length()).sum::<f32>();
+    let period = dash_period(dashes);
+    let count = (length / period).round().max(1.);
+    let scale = length / (count * period);
+    buf.clear();
+    buf.extend(dashes.iter().map(|dash| dash * scale));
+    (buf, offset * scale)
+}
+
+/// Returns a dash array for the subpath with the pattern scaled along each
+/// edge between corners so that every corner falls at the center of a dash
+/// and the ends of an open subpath at the start and end of a dash.
+fn corner_dashes<'d>(
+    segments: &[Segment],
+    is_closed: bool,
+    dashes: &[f32],
+    buf: &'d mut Vec<f32>,
+) -> (&'d [f32], f32) {
+    let len = segments.len();
+    let is_corner = |i: usize| {
+        let (a, b) = (get_signed(segments, i as isize - 1), segments[i]);
+        segment_id(&a) != segment_id(&b) && end_tangent(&a).dot(start_tangent(&b)) < 0.99
+    };
+    let first = (0..len).find(|&i| (i > 0 || is_closed) && is_corner(i));
+    let start = match first {
+        Some(first) if is_closed => first,
+        None if is_closed => return fit_dashes(segments, dashes, 0., buf),
+        _ => 0,
+    };
+    let count = if dashes.len() & 1 != 0 {
+        dashes.len() * 2
+    } else {
+        dashes.len()
+    };
+    let period = dash_period(dashes);
+    let head = dashes[0];
+    // Appends a length, extending the last element if it has the same
+    // on/off state.
+    let push = |buf: &mut Vec<f32>, length: f32, on: bool| {
+        if (buf.len() & 1 == 1) == on {
+            if let Some(last) = buf.last_mut() {
+                *last += length;
+            }
+        } else {
+            buf.push(length);
+        }
+    };
+    buf.clear();
+    let mut total = 0.;
+    let mut edge = 0.;
+    let mut tail = 0.;
+    let mut start_corner = is_closed;
+    for k in 0..len {
+        let index = (start + k) % len;
+        edge += segments[index].length();
+        let is_last = k == len - 1;
+        if !is_last && !is_corner((index + 1) % len) {
+            continue;
+        }
+        let end_corner = is_closed || !is_last;
+        // Length of the partial dashes at ends that are not corners.
+        let extra = (!start_corner as u8 + !end_corner as u8) as f32 * head * 0.5;
+        let min_repeats = if start_corner && end_corner { 1. } else { 0. };
+        let repeats = ((edge - extra) / period).round().max(min_repeats);
+        if repeats == 0. {
+            push(buf, edge, true);
+        } else {
+            let scale = edge / (repeats * period + extra);
+            let factor = |corner| if corner { 0.5 } else { 1. };
+            push(buf, head * scale * factor(start_corner), true);
+            for r in 0..repeats as usize {
+                for i in 1..count {
+                    push(buf, dashes[i % dashes.len()] * scale, i & 1 == 0);
+                }
+                if r + 1 < repeats as usize {
+                    push(buf, head * scale, true);
+                }
+            }
+            tail = head * scale * factor(end_corner);
+            push(buf, tail, true);
+        }
+        total += edge;
+        edge = 0.;
+        start_corner = true;
+    }
+    if !is_closed {
+        // Overshoot the end of the subpath to avoid starting a new dash.
+        if let Some(last) = buf.last_mut() {
+            *last += 1.;
+        }
+        return (buf, 0.);
+    }
+    // Move the half dash that ends at the first corner to the start so the
+    // array begins with the dash that spans the corner.
+    buf.pop();
+    buf[0] += tail;
+    let before: f32 = segments[..start].iter().map(|s| s.length()).sum();
+    let offset = tail - before;
+    (buf, if offset < 0. { offset + total } else { offset })
+}
+
+/// Returns the length of one repetition of the dash pattern. Arrays with an
+/// odd number of elements are repeated to form an even number.
+fn dash_period(dashes: &[f32]) -> f32 {
+    let period: f32 = dashes.iter().sum();
+    if dashes.len() & 1 != 0 {
+        period * 2.
+    } else {
+        period
+    }
+}
+
+pub(super) fn validate_dashes(dashes: &[f32], offset: f32) -> (&[f32], f32, bool) {
     let len = dashes.len();
     if len > 0 {
         // Generate a full stroke under any of the following conditions:
@@ -639,6 +2019,51 @@ fn validate_dashes(dashes: &[f32], offset: f32) -> (&[f32], f32, bool) {
     return (&[], 0., false);
 }
 
+/// Returns twice the signed area enclosed by the control polygons of the
+/// segments.
+fn signed_area(segments: &[Segment]) -> f32 {
+    let cross = |a: Point, b: Point| a.x * b.y - b.x * a.y;
+    let mut area = 0.;
+    for segment in segments {
+        area += match segment {
+            Segment::Line(_, line) => cross(line.a, line.b),
+            Segment::Curve(_, c) => cross(c.a, c.b) + cross(c.b, c.c) + cross(c.c, c.d),
+            Segment::End(..) => 0.,
+        };
+    }
+    if let (Some(first), Some(last)) = (segments.first(), segments.last()) {
+        if let (Some(start), Some(end)) = (start_point(first), end_point(last)) {
+            area += cross(end, start);
+        }
+    }
+    area
+}
+
+#[inline(always)]
+fn segment_id(segment: &Segment) -> SegmentId {
+    match segment {
+        Segment::Line(id, _) | Segment::Curve(id, _) => *id,
+        Segment::End(..) => 0,
+    }
+}
+
+fn start_tangent(segment: &Segment) -> Vector {
+    match segment {
+        Segment::Line(_, line) => (line.b - line.a).normalize(),
+        Segment::Curve(_, c) => [c.b, c.c, c.d]
+            .iter()
+            .map(|&p| p - c.a)
+            .find(|v| v.length_squared() > 0.)
+            .unwrap_or(Vector::ZERO)
+            .normalize(),
+        Segment::End(..) => Vector::ZERO,
+    }
+}
+
+fn end_tangent(segment: &Segment) -> Vector {
+    start_tangent(&segment.reverse()) * -1.
+}
+
 #[inline(always)]
 fn get_signed(segments: &[Segment], index: isize) -> Segment {
     let index = if index < 0 {
@@ -649,13 +2074,164 @@ fn get_signed(segments: &[Segment], index: isize) -> Segment {
     segments[index]
 }
 
+/// Emits a drawing command to a sink, continuing from the current point.
+fn emit_command(sink: &mut impl PathBuilder, cmd: &Command) {
+    match *cmd {
+        Command::MoveTo(p) => {
+            sink.move_to(p);
+        }
+        Command::LineTo(p) => {
+            sink.line_to(p);
+        }
+        Command::CurveTo(c1, c2, p) => {
+            sink.curve_to(c1, c2, p);
+        }
+        Command::QuadTo(c, p) => {
+            sink.quad_to(c, p);
+        }
+        Command::ConicTo(c, p, w) => {
+            sink.conic_to(c, p, w);
+        }
+        Command::ArcTo(rx, ry, angle, size, sweep, p) => {
+            sink.arc_to(rx, ry, angle, size, sweep, p);
+        }
+        Command::Close => {
+            sink.close();
+        }
+    }
+}
+
+/// Returns the curvature at an end point of a cubic curve from the first
+/// difference of the control points and the second difference. The center of
+/// curvature lies along the normal of the segment when positive.
+fn curvature(d1: Vector, d2: Vector) -> f32 {
+    let length = d1.length();
+    if length < 1e-6 {
+        return 0.;
+    }
+    // The normals point to the right of the direction of travel.
+    -(2. / 3.) * d1.cross(d2) / (length * length * length)
+}
+
+/// Outer edge of a stroke extended beyond a join.
+#[derive(Copy, Clone)]
+enum JoinEdge {
+    /// Line through a point in the direction of travel.
+    Line(Point, Vector),
+    /// Circle with a center and radius, and the direction of travel as the
+    /// sign of the rate of change of the angle.
+    Circle(Point, f32, f32),
+}
+
+impl JoinEdge {
+    /// Creates the edge passing through the specified point on the outer side
+    /// of a segment with the curvature of the segment at the pivot.
+    fn new(point: Point, pivot: Point, normal: Vector, curvature: f32) -> Self {
+        let dir = Vector::new(-normal.y, normal.x);
+        if curvature.abs() < 1e-6 {
+            return Self::Line(point, dir);
+        }
+        let center = pivot + normal * (1. / curvature);
+        let radius = (point - center).length();
+        if !(1e-6..=1e6).contains(&radius) {
+            return Self::Line(point, dir);
+        }
+        Self::Circle(center, radius, (point - center).cross(dir).signum())
+    }
+
+    /// Returns the intersections of two edges.
+    fn intersect(&self, other: &Self) -> ([Point; 2], usize) {
+        let mut points = [Point::ZERO; 2];
+        match (*self, *other) {
+            (Self::Line(p0, d0), Self::Line(p1, d1)) => {
+                let det = d0.cross(d1);
+                if det.abs() < 1e-9 {
+                    return (points, 0);
+                }
+                points[0] = p0 + d0 * ((p1 - p0).cross(d1) / det);
+                (points, 1)
+            }
+            (Self::Line(p, d), Self::Circle(center, radius, _))
+            | (Self::Circle(center, radius, _), Self::Line(p, d)) => {
+                let d = d.normalize();
+                let t = (center - p).dot(d);
+                let closest = p + d * t;
+                let h = radius * radius - (center - closest).length_squared();
+                if h < 0. {
+                    return (points, 0);
+                }
+                let h = h.sqrt();
+                points[0] = closest - d * h;
+                points[1] = closest + d * h;
+                (points, 2)
+            }
+            (Self::Circle(c0, r0, _), Self::Circle(c1, r1, _)) => {
+                let v = c1 - c0;
+                let dist = v.length();
+                if dist < 1e-6 || dist > r0 + r1 || dist < (r0 - r1).abs() {
+                    return (points, 0);
+                }
+                let a = (r0 * r0 - r1 * r1 + dist * dist) / (2. * dist);
+                let h = (r0 * r0 - a * a).max(0.).sqrt();
+                let (u, n) = (v * (1. / dist), Vector::new(-v.y, v.x) * (1. / dist));
+                let mid = c0 + u * a;
+                points[0] = mid + n * h;
+                points[1] = mid - n * h;
+                (points, 2)
+            }
+        }
+    }
+
+    /// Returns the distance, or angle for circles, traveled along the edge
+    /// from one point to another, or the maximum value if the second point is
+    /// behind the first or more than half of a circle away.
+    fn travel(&self, from: Point, to: Point) -> f32 {
+        match *self {
+            Self::Line(_, d) => {
+                let t = (to - from).dot(d);
+                if t < -1e-4 {
+                    f32::MAX
+                } else {
+                    t.max(0.)
+                }
+            }
+            Self::Circle(center, _, sign) => {
+                let (a, b) = (from - center, to - center);
+                let angle = F32Ext::atan2(a.cross(b) * sign, a.dot(b));
+                if angle < -1e-4 {
+                    f32::MAX
+                } else {
+                    angle.max(0.)
+                }
+            }
+        }
+    }
+
+    /// Emits the edge from one point to another.
+    fn emit(&self, sink: &mut impl PathBuilder, from: Point, to: Point) {
+        match *self {
+            Self::Line(..) => {
+                sink.line_to(to);
+            }
+            Self::Circle(_, radius, sign) => {
+                let sweep = if sign > 0. {
+                    ArcSweep::Positive
+                } else {
+                    ArcSweep::Negative
+                };
+                arc(sink, from, radius, radius, 0., ArcSize::Small, sweep, to);
+            }
+        }
+    }
+}
+
 fn is_clockwise(a: Vector, b: Vector) -> bool {
     a.x * b.y > a.y * b.x
 }
 
 impl Segment {
-    fn offset(&self, radius: f32) -> OffsetSegment {
-        OffsetSegment::new(self, radius)
+    fn offset(&self, radii: (f32, f32), epsilon: f32) -> OffsetSegment {
+        OffsetSegment::new(self, radii, epsilon)
     }
 }
 
@@ -668,16 +2244,18 @@ pub struct OffsetSegment {
     pub start_normal: Vector,
     pub end_normal: Vector,
     pub end_pivot: Point,
+    pub start_curvature: f32,
+    pub end_curvature: f32,
 }
 
 impl OffsetSegment {
-    fn new(segment: &Segment, radius: f32) -> Self {
+    fn new(segment: &Segment, radii: (f32, f32), epsilon: f32) -> Self {
+        let (r0, r1) = radii;
         match segment {
             Segment::Line(id, Line { a, b }) => {
                 let n = normal(*a, *b);
-                let nr = n * radius;
-                let start = *a + nr;
-                let end = *b + nr;
+                let start = *a + n * r0;
+                let end = *b + n * r1;
                 Self {
                     segment: Segment::Line(*id, Line { a: start, b: end }),
                     id: *id,
@@ -686,13 +2264,14 @@ impl OffsetSegment {
                     start_normal: n,
                     end_normal: n,
                     end_pivot: *b,
+                    start_curvature: 0.,
+                    end_curvature: 0.,
                 }
             }
             Segment::Curve(id, c) => {
-                const EPS: f32 = 0.5;
-                //const EPS: f32 = CURVE_EPSILON;
-                let normal_ab = if c.a.nearly_eq_by(c.b, EPS) {
-                    if c.a.nearly_eq_by(c.c, EPS) {
+                let eps = 0.5 * epsilon;
+                let normal_ab = if c.a.nearly_eq_by(c.b, eps) {
+                    if c.a.nearly_eq_by(c.c, eps) {
                         normal(c.a, c.d)
                     } else {
                         normal(c.a, c.c)
@@ -700,8 +2279,8 @@ impl OffsetSegment {
                 } else {
                     normal(c.a, c.b)
                 };
-                let normal_bc = if c.b.nearly_eq_by(c.c, EPS) {
-                    if c.b.nearly_eq_by(c.d, EPS) {
+                let normal_bc = if c.b.nearly_eq_by(c.c, eps) {
+                    if c.b.nearly_eq_by(c.d, eps) {
                         normal(c.a, c.d)
                     } else {
                         normal(c.b, c.d)
@@ -709,8 +2288,8 @@ impl OffsetSegment {
                 } else {
                     normal(c.b, c.c)
                 };
-                let normal_cd = if c.c.nearly_eq_by(c.d, EPS) {
-                    if c.b.nearly_eq_by(c.d, EPS) {
+                let normal_cd = if c.c.nearly_eq_by(c.d, eps) {
+                    if c.b.nearly_eq_by(c.d, eps) {
                         normal(c.a, c.d)
                     } else {
                         normal(c.b, c.d)
@@ -721,11 +2300,13 @@ impl OffsetSegment {
                 let mut normal_b = normal_ab + normal_bc;
                 let mut normal_c = normal_cd + normal_bc;
                 let dot = normal_ab.dot(normal_bc);
-                normal_b = normal_b.normalize() * (radius / ((1. + dot) * 0.5).sqrt());
+                let radius_b = r0 + (r1 - r0) * (1. / 3.);
+                normal_b = normal_b.normalize() * (radius_b / ((1. + dot) * 0.5).sqrt());
                 let dot = normal_cd.dot(normal_bc);
-                normal_c = normal_c.normalize() * (radius / ((1. + dot) * 0.5).sqrt());
-                let start = c.a + normal_ab * radius;
-                let end = c.d + normal_cd * radius;
+                let radius_c = r0 + (r1 - r0) * (2. / 3.);
+                normal_c = normal_c.normalize() * (radius_c / ((1. + dot) * 0.5).sqrt());
+                let start = c.a + normal_ab * r0;
+                let end = c.d + normal_cd * r1;
                 Self {
                     segment: Segment::Curve(
                         *id,
@@ -737,6 +2318,8 @@ impl OffsetSegment {
                     start_normal: normal_ab,
                     end_normal: normal_cd,
                     end_pivot: c.d,
+                    start_curvature: curvature(c.b - c.a, c.a - c.b * 2. + c.c),
+                    end_curvature: curvature(c.d - c.c, c.b - c.c * 2. + c.d),
                 }
             }
             Segment::End(..) => Self {
@@ -747,11 +2330,29 @@ impl OffsetSegment {
                 start_normal: Vector::ZERO,
                 end_normal: Vector::ZERO,
                 end_pivot: Point::ZERO,
+                start_curvature: 0.,
+                end_curvature: 0.,
             },
         }
     }
 }
 
+fn start_point(segment: &Segment) -> Option<Point> {
+    match segment {
+        Segment::Line(_, line) => Some(line.a),
+        Segment::Curve(_, curve) => Some(curve.a),
+        Segment::End(..) => None,
+    }
+}
+
+fn end_point(segment: &Segment) -> Option<Point> {
+    match segment {
+        Segment::Line(_, line) => Some(line.b),
+        Segment::Curve(_, curve) => Some(curve.d),
+        Segment::End(..) => None,
+    }
+}
+
 pub trait StrokerStorage {
     fn clear(&mut self);
     fn push(&mut self, segment: &Segment);
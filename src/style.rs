@@ -1,5 +1,10 @@
 //! Path styles.
 
+use super::command::Command;
+use super::geometry::{Angle, Point, Vector};
+use crate::lib::Box;
+use core::fmt;
+
 /// Describes the visual style of a fill.
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
 pub enum Fill {
@@ -18,6 +23,110 @@ pub enum Join {
     Miter,
     /// An arc between the segments.
     Round,
+    /// The segments are extended to their natural intersection point and
+    /// miters exceeding the limit are clipped at the limit rather than
+    /// replaced with a bevel.
+    ///
+    /// ```rust
+    /// use zeno::{Join, Mask, Stroke};
+    ///
+    /// let area = |join, limit| {
+    ///     let (mask, _) = Mask::new("M0,0 L20,40 L40,0")
+    ///         .style(Stroke::new(4.).join(join).miter_limit(limit))
+    ///         .render();
+    ///     mask.iter().map(|&c| c as u32).sum::<u32>()
+    /// };
+    /// let clipped = area(Join::MiterClip, 1.5);
+    /// assert!(clipped > area(Join::Bevel, 1.5));
+    /// assert!(clipped < area(Join::MiterClip, 10.));
+    /// ```
+    MiterClip,
+    /// The outer edges of the segments are extended with circular arcs that
+    /// match their curvature at the join until they meet. Joins that exceed
+    /// the miter limit, or where the arcs do not meet, are clipped as for
+    /// the clipped miter.
+    ///
+    /// ```rust
+    /// use zeno::{bounds, Join, Stroke};
+    ///
+    /// // Two circular arcs meeting at a point. The extended edges meet
+    /// // short of the tip of a miter.
+    /// let lens = "M0,-16 A20,20 0 0,1 0,16 A20,20 0 0,1 0,-16 Z";
+    /// let arcs = bounds(lens, Stroke::new(4.).join(Join::Arcs), None);
+    /// let miter = bounds(lens, Stroke::new(4.).join(Join::Miter), None);
+    /// assert!((arcs.max.y - 340f32.sqrt()).abs() < 0.05);
+    /// assert!(arcs.max.y < miter.max.y);
+    /// ```
+    Arcs,
+}
+
+/// Defines the join that replaces a miter join exceeding the miter limit.
+#[derive(Copy, Clone, PartialEq, Eq, Default, Debug)]
+pub enum MiterFallback {
+    /// A straight line connecting the segments. This is the default.
+    #[default]
+    Bevel,
+    /// An arc between the segments.
+    Round,
+}
+
+/// Geometry of a join between two segments of a stroke on the outer side of
+/// the turn.
+#[derive(Copy, Clone, Debug)]
+pub struct JoinGeometry {
+    /// Point on the path where the segments meet.
+    pub position: Point,
+    /// Unit tangent at the end of the incoming segment.
+    pub from_tangent: Vector,
+    /// Unit tangent at the start of the outgoing segment.
+    pub to_tangent: Vector,
+    /// Width of the stroke at the join.
+    pub width: f32,
+    /// Point on the outer edge of the incoming segment where the join begins.
+    pub from: Point,
+    /// Point on the outer edge of the outgoing segment where the join ends.
+    pub to: Point,
+}
+
+/// Builder for custom joins between the segments of a stroke. Builders are
+/// shared between threads when rendering in parallel.
+///
+/// ```rust
+/// use zeno::{bounds, Command, JoinBuilder, JoinGeometry, Stroke};
+///
+/// /// Join with a spike that extends to the full width of the stroke.
+/// struct Spike;
+///
+/// impl JoinBuilder for Spike {
+///     fn build(&self, join: &JoinGeometry, sink: &mut dyn FnMut(Command)) {
+///         let dir = ((join.from + join.to) * 0.5 - join.position).normalize();
+///         sink(Command::LineTo(join.position + dir * join.width));
+///         sink(Command::LineTo(join.to));
+///     }
+///
+///     fn extent(&self, width: f32) -> f32 {
+///         width
+///     }
+/// }
+///
+/// let b = bounds("M0,0 L20,20 L40,0", Stroke::new(4.).join_builder(&Spike), None);
+/// assert!((b.max.y - 24.).abs() < 1e-4);
+/// ```
+pub trait JoinBuilder: Sync {
+    /// Emits the outer edge of a join. The current point is the start of
+    /// the join and the edge should end at the end of the join. Move and
+    /// close commands are ignored.
+    fn build(&self, join: &JoinGeometry, sink: &mut dyn FnMut(Command));
+
+    /// Returns the maximum distance from the position of a join to any point
+    /// emitted for a stroke of the specified width.
+    fn extent(&self, width: f32) -> f32;
+}
+
+impl fmt::Debug for dyn JoinBuilder + '_ {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "JoinBuilder")
+    }
 }
 
 /// Defines the shape to be drawn at the beginning or end of a stroke.
@@ -31,38 +140,377 @@ pub enum Cap {
     Round,
 }
 
+/// Defines which subpaths are treated as closed when stroking.
+#[derive(Copy, Clone, PartialEq, Eq, Default, Debug)]
+pub enum Closure {
+    /// Only subpaths that end with an explicit close command are closed.
+    #[default]
+    Explicit,
+    /// Close commands are ignored and every subpath is capped as if it
+    /// were open.
+    Never,
+    /// Subpaths that end with an explicit close command are closed, as are
+    /// open subpaths with an end point that coincides with the start point.
+    Coincident,
+}
+
+/// Defines the position of a stroke relative to a closed subpath.
+#[derive(Copy, Clone, PartialEq, Eq, Default, Debug)]
+pub enum Alignment {
+    /// The stroke is centered on the path. This is the default.
+    #[default]
+    Center,
+    /// The stroke lies entirely inside the subpath.
+    Inside,
+    /// The stroke lies entirely outside the subpath.
+    Outside,
+}
+
+/// Defines how the dash array and offset of a stroke are interpreted.
+#[derive(Copy, Clone, PartialEq, Eq, Default, Debug)]
+pub enum DashMode {
+    /// Dash lengths and offset are absolute lengths.
+    #[default]
+    Length,
+    /// Dash lengths and offset are percentages of the total length of the
+    /// path where a value of 100 spans the entire path.
+    Percent,
+    /// Dash lengths and offset are absolute lengths that are scaled for
+    /// each subpath so that a whole number of repetitions of the pattern
+    /// fits its length. This avoids a partial dash at the start point of
+    /// closed subpaths.
+    ///
+    /// ```rust
+    /// use zeno::{DashMode, Mask, Stroke};
+    ///
+    /// // Roughly 10 unit dashes around a circle without a seam.
+    /// let dashes = [10.0, 10.0];
+    /// Mask::new("M50,10 A40,40 0 1,1 50,90 A40,40 0 1,1 50,10 Z")
+    ///     .style(Stroke::new(4.0).dash(&dashes, 0.0).dash_mode(DashMode::Fit))
+    ///     .render();
+    /// ```
+    Fit,
+    /// Dash lengths are absolute lengths that are scaled along each edge
+    /// between corners so that corners fall at the center of a dash and the
+    /// ends of open subpaths at the ends of a dash. The offset is ignored.
+    /// Closed subpaths without corners are dashed as for the fit mode.
+    ///
+    /// ```rust
+    /// use zeno::{DashMode, Mask, Stroke};
+    ///
+    /// // Every corner of the square is covered by a dash.
+    /// let dashes = [6.0, 6.0];
+    /// let (mask, placement) = Mask::new("M2,2 L30,2 L30,30 L2,30 Z")
+    ///     .style(Stroke::new(2.0).dash(&dashes, 3.0).dash_mode(DashMode::Corners))
+    ///     .render();
+    /// for &(x, y) in &[(1, 1), (30, 1), (1, 30), (30, 30)] {
+    ///     let x = (x - placement.left) as usize;
+    ///     let y = (y - placement.top) as usize;
+    ///     assert_eq!(mask[y * placement.width as usize + x], 255);
+    /// }
+    /// ```
+    Corners,
+}
+
+/// Source of dash lengths that are generated on demand rather than stored
+/// in an array. Patterns are shared between threads when rendering in
+/// parallel.
+///
+/// The trait is implemented for cloneable iterators that yield lengths.
+///
+/// ```rust
+/// use zeno::{Mask, Stroke};
+///
+/// // Dashes that grow longer along the path with fixed gaps.
+/// let pattern = (1..).map(|i| if i % 2 == 1 { i as f32 } else { 2. });
+/// Mask::new("M0,0 L1000,0")
+///     .style(Stroke::new(2.).dash_pattern(&pattern, 0.))
+///     .render();
+///
+/// // A repeating pattern matches the equivalent dash array.
+/// let pattern = core::iter::repeat(6f32);
+/// let path = "M2,2 L30,2 L30,30 L2,30 Z";
+/// let (a, _) = Mask::new(path).style(Stroke::new(2.).dash_pattern(&pattern, 3.)).render();
+/// let (b, _) = Mask::new(path).style(Stroke::new(2.).dash(&[6., 6.], 3.)).render();
+/// assert_eq!(a, b);
+/// ```
+pub trait DashPattern: Sync {
+    /// Returns an iterator over the dash lengths in alternating on/off
+    /// order. The pattern is restarted when the iterator ends and negative
+    /// lengths are treated as zero. The pattern must eventually yield a
+    /// positive length.
+    fn dashes(&self) -> Box<dyn Iterator<Item = f32> + '_>;
+}
+
+impl<I> DashPattern for I
+where
+    I: Iterator<Item = f32> + Clone + Sync,
+{
+    fn dashes(&self) -> Box<dyn Iterator<Item = f32> + '_> {
+        Box::new(self.clone())
+    }
+}
+
+impl fmt::Debug for dyn DashPattern + '_ {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "DashPattern")
+    }
+}
+
+/// Defines the rotation of a marker placed at a vertex of a stroke.
+#[derive(Copy, Clone, PartialEq, Default, Debug)]
+pub enum MarkerOrient {
+    /// The x-axis of the marker bisects the incoming and outgoing directions
+    /// of the path at the vertex. This is the default.
+    #[default]
+    Auto,
+    /// Same as auto except that markers at the start of a subpath are
+    /// rotated by 180 degrees so that they point away from the path.
+    AutoStartReverse,
+    /// The x-axis of the marker is rotated by a fixed angle.
+    Angle(Angle),
+}
+
+/// Describes a path that is drawn at vertices of a stroke.
+///
+/// The path is specified in a coordinate system with the origin at the
+/// vertex, the x-axis oriented as determined by the orientation and one unit
+/// equal to half the stroke width multiplied by the scale. This matches the
+/// coordinate system of [cap paths](struct.Stroke.html#method.cap_path) so
+/// the same path can serve as either. The path is filled with the stroke
+/// and the winding direction of its subpaths is adjusted to match.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct MarkerStyle<'a> {
+    /// Path of the marker.
+    pub path: &'a [Command],
+    /// Factor applied to the size of the marker.
+    pub scale: f32,
+    /// Rotation of the marker.
+    pub orient: MarkerOrient,
+}
+
+impl<'a> MarkerStyle<'a> {
+    /// Creates a new marker style for the specified path with a scale of 1
+    /// and automatic orientation.
+    pub fn new(path: &'a [Command]) -> Self {
+        Self {
+            path,
+            scale: 1.,
+            orient: MarkerOrient::Auto,
+        }
+    }
+
+    /// Sets the factor applied to the size of the marker.
+    pub fn scale(&mut self, scale: f32) -> &mut Self {
+        self.scale = scale;
+        self
+    }
+
+    /// Sets the rotation of the marker.
+    pub fn orient(&mut self, orient: MarkerOrient) -> &mut Self {
+        self.orient = orient;
+        self
+    }
+}
+
+const ZIGZAG: [Command; 4] = [
+    Command::MoveTo(Point::new(0., 0.)),
+    Command::LineTo(Point::new(0.25, 1.)),
+    Command::LineTo(Point::new(0.75, -1.)),
+    Command::LineTo(Point::new(1., 0.)),
+];
+
+const WAVE: [Command; 3] = [
+    Command::MoveTo(Point::new(0., 0.)),
+    Command::CurveTo(
+        Point::new(0.2122, 1.3333),
+        Point::new(0.2878, 1.3333),
+        Point::new(0.5, 0.),
+    ),
+    Command::CurveTo(
+        Point::new(0.7122, -1.3333),
+        Point::new(0.7878, -1.3333),
+        Point::new(1., 0.),
+    ),
+];
+
+const TICKS: [Command; 4] = [
+    Command::MoveTo(Point::new(0., 0.)),
+    Command::LineTo(Point::new(1., 0.)),
+    Command::MoveTo(Point::new(0.5, -1.)),
+    Command::LineTo(Point::new(0.5, 1.)),
+];
+
+const RAILS: [Command; 6] = [
+    Command::MoveTo(Point::new(0., -1.)),
+    Command::LineTo(Point::new(1., -1.)),
+    Command::MoveTo(Point::new(0., 1.)),
+    Command::LineTo(Point::new(1., 1.)),
+    Command::MoveTo(Point::new(0.5, -1.)),
+    Command::LineTo(Point::new(0.5, 1.)),
+];
+
+/// Describes a template path that is repeated along the center line of a
+/// stroke and replaces it before the stroke is applied.
+///
+/// The template is specified in a coordinate system where the x-axis runs
+/// along the path with one unit equal to the period and the y-axis runs
+/// along the normal of the path with one unit equal to the amplitude.
+/// A single repetition spans x coordinates from 0 to 1. Subpaths of the
+/// template that start at x = 0 and end at x = 1 with the same y coordinate
+/// are connected across repetitions while others are repeated as separate
+/// subpaths. The period is adjusted for each subpath so that a whole number
+/// of repetitions fits its length.
+///
+/// ```rust
+/// use zeno::{bounds, Decoration, Stroke};
+///
+/// let mut stroke = Stroke::new(1.);
+/// stroke.decoration(Decoration::zigzag(10., 4.));
+/// let b = bounds("M0,0 L100,0", stroke, None);
+/// assert!(b.min.y < -4. && b.max.y > 4.);
+/// ```
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Decoration<'a> {
+    /// Template path for a single repetition.
+    pub path: &'a [Command],
+    /// Length of the path covered by a single repetition.
+    pub period: f32,
+    /// Distance from the path of a template point with a y coordinate of 1.
+    pub amplitude: f32,
+}
+
+impl<'a> Decoration<'a> {
+    /// Creates a new decoration from a template path, period and amplitude.
+    pub fn new(path: &'a [Command], period: f32, amplitude: f32) -> Self {
+        Self {
+            path,
+            period,
+            amplitude,
+        }
+    }
+}
+
+impl Decoration<'static> {
+    /// Creates a decoration of straight lines alternating between the sides
+    /// of the path.
+    pub fn zigzag(period: f32, amplitude: f32) -> Self {
+        Self::new(&ZIGZAG, period, amplitude)
+    }
+
+    /// Creates a decoration of a smooth wave approximating a sine curve.
+    pub fn wave(period: f32, amplitude: f32) -> Self {
+        Self::new(&WAVE, period, amplitude)
+    }
+
+    /// Creates a decoration that follows the path with perpendicular ticks
+    /// at the center of each repetition.
+    pub fn ticks(period: f32, amplitude: f32) -> Self {
+        Self::new(&TICKS, period, amplitude)
+    }
+
+    /// Creates a decoration of two parallel lines on either side of the path
+    /// connected by ties at the center of each repetition.
+    pub fn rails(period: f32, amplitude: f32) -> Self {
+        Self::new(&RAILS, period, amplitude)
+    }
+}
+
 /// Describes the visual style of a stroke.
 #[derive(Copy, Clone, Debug)]
 pub struct Stroke<'a> {
     /// Width of the stroke.
     pub width: f32,
+    /// Minimum width of the stroke in device space.
+    pub min_width: f32,
+    /// Widths of the stroke at each on-curve point of the path.
+    pub widths: &'a [f32],
     /// Style for connecting segments of the stroke.
     pub join: Join,
+    /// Custom builder for connecting segments of the stroke, which overrides
+    /// the join style.
+    pub join_builder: Option<&'a dyn JoinBuilder>,
     /// Limit for miter joins.
     pub miter_limit: f32,
+    /// Join that replaces miter joins exceeding the limit.
+    pub miter_fallback: MiterFallback,
     /// Style for capping the beginning of an open subpath.
     pub start_cap: Cap,
     /// Style for capping the end of an open subpath.
     pub end_cap: Cap,
+    /// Custom path for capping the beginning of an open subpath, which
+    /// overrides the start cap style.
+    pub start_cap_path: Option<&'a [Command]>,
+    /// Custom path for capping the end of an open subpath, which overrides
+    /// the end cap style.
+    pub end_cap_path: Option<&'a [Command]>,
+    /// Style for capping the ends of dashes that do not coincide with the
+    /// ends of an open subpath, which overrides the start and end caps.
+    pub dash_cap: Option<Cap>,
     /// Lengths of dashes in alternating on/off order.
     pub dashes: &'a [f32],
+    /// Procedural source of dash lengths, which overrides the dash array.
+    pub dash_pattern: Option<&'a dyn DashPattern>,
     /// Offset of the first dash.
     pub offset: f32,
+    /// Determines how the dash array and offset are interpreted.
+    pub dash_mode: DashMode,
+    /// True if the dash pattern restarts at the offset for each subpath.
+    pub dash_reset: bool,
     /// True if the stroke width should be affected by the scale of a transform.
     pub scale: bool,
+    /// Determines which subpaths are joined at their start point.
+    pub closure: Closure,
+    /// Position of the stroke relative to closed subpaths.
+    pub alignment: Alignment,
+    /// Factor applied to the tolerances used when evaluating the stroke.
+    pub tolerance: f32,
+    /// True if overlapping regions are removed from the outline of the
+    /// stroke.
+    pub remove_overlaps: bool,
+    /// Marker drawn at the first vertex of each subpath.
+    pub start_marker: Option<MarkerStyle<'a>>,
+    /// Marker drawn at each intermediate vertex of a subpath.
+    pub mid_marker: Option<MarkerStyle<'a>>,
+    /// Marker drawn at the final vertex of each subpath.
+    pub end_marker: Option<MarkerStyle<'a>>,
+    /// Template that replaces the center line of the stroke.
+    pub decoration: Option<Decoration<'a>>,
+    /// Widths of parallel lines and the gaps between them in alternating
+    /// order, which override the width of the stroke.
+    pub parallel: &'a [f32],
 }
 
 impl Default for Stroke<'_> {
     fn default() -> Self {
         Self {
             width: 1.,
+            min_width: 0.,
+            widths: &[],
             join: Join::Miter,
+            join_builder: None,
             miter_limit: 4.,
+            miter_fallback: MiterFallback::Bevel,
             start_cap: Cap::Butt,
             end_cap: Cap::Butt,
+            start_cap_path: None,
+            end_cap_path: None,
+            dash_cap: None,
             dashes: &[],
+            dash_pattern: None,
             offset: 0.,
+            dash_mode: DashMode::Length,
+            dash_reset: true,
             scale: true,
+            closure: Closure::Explicit,
+            alignment: Alignment::Center,
+            tolerance: 1.,
+            remove_overlaps: false,
+            start_marker: None,
+            mid_marker: None,
+            end_marker: None,
+            decoration: None,
+            parallel: &[],
         }
     }
 }
@@ -75,12 +523,127 @@ impl<'a> Stroke<'a> {
         s
     }
 
+    /// Creates a new stroke style with the specified width, returning an
+    /// error if the width is negative or not finite.
+    ///
+    /// ```rust
+    /// use zeno::{Stroke, StrokeError};
+    ///
+    /// assert!(Stroke::try_new(2.).is_ok());
+    /// assert_eq!(Stroke::try_new(-1.).unwrap_err(), StrokeError::NegativeWidth);
+    /// ```
+    pub fn try_new(width: f32) -> Result<Self, StrokeError> {
+        let s = Self::new(width);
+        s.validate()?;
+        Ok(s)
+    }
+
+    /// Checks the parameters of the stroke and returns an error describing
+    /// the first one that would produce an invalid outline. Evaluating an
+    /// invalid stroke does not fail, but the result is unspecified.
+    ///
+    /// ```rust
+    /// use zeno::{Stroke, StrokeError};
+    ///
+    /// let dashes = [0., 0.];
+    /// let stroke = *Stroke::new(2.).dash(&dashes, 0.);
+    /// assert_eq!(stroke.validate(), Err(StrokeError::InvalidDashes));
+    /// let stroke = *Stroke::new(2.).miter_limit(f32::NAN);
+    /// assert_eq!(stroke.validate(), Err(StrokeError::NotFinite("miter_limit")));
+    /// ```
+    pub fn validate(&self) -> Result<(), StrokeError> {
+        let finite = |name: &'static str, values: &[f32]| {
+            if values.iter().all(|v| v.is_finite()) {
+                Ok(())
+            } else {
+                Err(StrokeError::NotFinite(name))
+            }
+        };
+        finite("width", &[self.width])?;
+        finite("min_width", &[self.min_width])?;
+        finite("widths", self.widths)?;
+        finite("miter_limit", &[self.miter_limit])?;
+        finite("dashes", self.dashes)?;
+        finite("offset", &[self.offset])?;
+        finite("tolerance", &[self.tolerance])?;
+        finite("parallel", self.parallel)?;
+        for marker in [self.start_marker, self.mid_marker, self.end_marker]
+            .iter()
+            .flatten()
+        {
+            finite("marker scale", &[marker.scale])?;
+        }
+        if let Some(decoration) = self.decoration {
+            finite("decoration", &[decoration.period, decoration.amplitude])?;
+            if decoration.period <= 0. {
+                return Err(StrokeError::InvalidDecoration);
+            }
+        }
+        let widths = [self.width, self.min_width];
+        if widths
+            .iter()
+            .chain(self.widths)
+            .chain(self.parallel)
+            .any(|&w| w < 0.)
+        {
+            return Err(StrokeError::NegativeWidth);
+        }
+        if !self.dashes.is_empty()
+            && (self.dashes.iter().any(|&d| d < 0.) || self.dashes.iter().all(|&d| d == 0.))
+        {
+            return Err(StrokeError::InvalidDashes);
+        }
+        if self.tolerance <= 0. {
+            return Err(StrokeError::InvalidTolerance);
+        }
+        Ok(())
+    }
+
     /// Sets the width of the stroke. The default is 1.
     pub fn width(&mut self, width: f32) -> &mut Self {
         self.width = width;
         self
     }
 
+    /// Sets the minimum width of the stroke in device space. When a
+    /// transform scales the stroke below this width, the width is increased
+    /// so that the stroke remains visible. For transforms that scale
+    /// unevenly, the smallest scale factor is used. This applies to the base
+    /// width of the stroke. The default is 0.
+    ///
+    /// ```rust
+    /// use zeno::{bounds, Stroke, Transform};
+    ///
+    /// let zoom = Some(Transform::scale(0.1, 0.1));
+    /// let b = bounds("M0,0 L100,0", Stroke::new(2.).min_width(1.), zoom);
+    /// assert!((b.height() - 1.).abs() < 1e-5);
+    /// ```
+    pub fn min_width(&mut self, width: f32) -> &mut Self {
+        self.min_width = width;
+        self
+    }
+
+    /// Sets the widths of the stroke at each on-curve point of the path,
+    /// which are interpolated along the segments between them. This is
+    /// useful for rendering pressure data captured from stylus input.
+    ///
+    /// Every command other than close ends at an on-curve point, so the
+    /// slice is indexed by command with close commands skipped. Points
+    /// beyond the end of the slice use the width of the stroke.
+    ///
+    /// ```rust
+    /// use zeno::{bounds, Stroke};
+    ///
+    /// // Taper from a width of 2 to 10.
+    /// let widths = [2., 10.];
+    /// let b = bounds("M0,0 L100,0", Stroke::new(1.).widths(&widths), None);
+    /// assert_eq!((b.min.y, b.max.y), (-5., 5.));
+    /// ```
+    pub fn widths(&mut self, widths: &'a [f32]) -> &mut Self {
+        self.widths = widths;
+        self
+    }
+
     /// Sets the join style that determines how individual segments of the path
     /// will be connected. The default is miter.
     pub fn join(&mut self, join: Join) -> &mut Self {
@@ -88,13 +651,42 @@ impl<'a> Stroke<'a> {
         self
     }
 
-    /// Sets the limit for miter joins beyond which a bevel will be generated.
-    /// The default is 4.
+    /// Sets a custom builder for connecting the segments of the path, which
+    /// overrides the join style. See [JoinBuilder](trait.JoinBuilder.html)
+    /// for an example.
+    pub fn join_builder(&mut self, builder: &'a dyn JoinBuilder) -> &mut Self {
+        self.join_builder = Some(builder);
+        self
+    }
+
+    /// Sets the limit for miter joins beyond which the fallback join will be
+    /// generated, or the miter will be clipped for the clipped join. The
+    /// default is 4.
     pub fn miter_limit(&mut self, limit: f32) -> &mut Self {
         self.miter_limit = limit;
         self
     }
 
+    /// Sets the join that replaces miter joins exceeding the miter limit.
+    /// This does not affect clipped miter or arcs joins. The default is
+    /// bevel.
+    ///
+    /// ```rust
+    /// use zeno::{Join, Mask, MiterFallback, Stroke};
+    ///
+    /// let area = |fallback| {
+    ///     let (mask, _) = Mask::new("M0,0 L20,40 L40,0")
+    ///         .style(Stroke::new(4.).join(Join::Miter).miter_fallback(fallback))
+    ///         .render();
+    ///     mask.iter().map(|&c| c as u32).sum::<u32>()
+    /// };
+    /// assert!(area(MiterFallback::Round) > area(MiterFallback::Bevel));
+    /// ```
+    pub fn miter_fallback(&mut self, fallback: MiterFallback) -> &mut Self {
+        self.miter_fallback = fallback;
+        self
+    }
+
     /// Sets the cap style that will be generated at the start and end of the
     /// stroke. Note that this will override the individual start and end cap
     /// options. The default is butt.
@@ -111,6 +703,70 @@ impl<'a> Stroke<'a> {
         self
     }
 
+    /// Sets a custom path that will be generated at the start and end of the
+    /// stroke, overriding the cap styles.
+    ///
+    /// The path is specified in a coordinate system with the origin at the
+    /// end of the stroke, the x-axis pointing away from the stroke along the
+    /// tangent and one unit equal to half the stroke width. The edges of the
+    /// stroke are at (0, -1) and (0, 1) and the path should proceed from the
+    /// former to the latter. Initial move and close commands are replaced
+    /// with lines.
+    ///
+    /// ```rust
+    /// use zeno::{bounds, Command, Stroke};
+    ///
+    /// // Arrowhead twice the width of the stroke.
+    /// let arrow = [
+    ///     Command::MoveTo((0., -1.).into()),
+    ///     Command::LineTo((0., -2.).into()),
+    ///     Command::LineTo((3., 0.).into()),
+    ///     Command::LineTo((0., 2.).into()),
+    ///     Command::LineTo((0., 1.).into()),
+    /// ];
+    /// let mut stroke = Stroke::new(2.);
+    /// stroke.cap_paths(None, Some(&arrow));
+    /// let b = bounds("M0,0 L10,0", stroke, None);
+    /// assert_eq!((b.min.x, b.max.x, b.min.y, b.max.y), (0., 13., -2., 2.));
+    /// ```
+    pub fn cap_path(&mut self, path: &'a [Command]) -> &mut Self {
+        self.start_cap_path = Some(path);
+        self.end_cap_path = Some(path);
+        self
+    }
+
+    /// Sets custom paths for the start and end of the stroke. See
+    /// [cap_path](#method.cap_path) for a description of the coordinate
+    /// system.
+    pub fn cap_paths(
+        &mut self,
+        start: Option<&'a [Command]>,
+        end: Option<&'a [Command]>,
+    ) -> &mut Self {
+        self.start_cap_path = start;
+        self.end_cap_path = end;
+        self
+    }
+
+    /// Sets the cap style for the ends of dashes. The start and end caps
+    /// still apply where a dash begins or ends at the start or end of an
+    /// open subpath. The default uses the start and end caps for all
+    /// dashes.
+    ///
+    /// ```rust
+    /// use zeno::{bounds, Cap, Stroke};
+    ///
+    /// let dashes = [4., 4.];
+    /// let mut stroke = Stroke::new(2.);
+    /// stroke.dash(&dashes, 0.).dash_cap(Cap::Round);
+    /// let b = bounds("M0,0 L10,0", stroke, None);
+    /// assert_eq!((b.min.x, b.max.x), (0., 10.));
+    /// ```
+    pub fn dash_cap(&mut self, cap: Cap) -> &mut Self {
+        self.dash_cap = Some(cap);
+        self
+    }
+
     /// Sets the dash array and offset of the stroke. The default is an empty
     /// array, meaning that the stroke will be drawn as a continuous line.
     pub fn dash(&mut self, dashes: &'a [f32], offset: f32) -> &mut Self {
@@ -119,15 +775,281 @@ impl<'a> Stroke<'a> {
         self
     }
 
+    /// Sets a procedural source of dash lengths and the offset of the
+    /// stroke, overriding the dash array. This avoids materializing a large
+    /// array for patterns that do not repeat. The dash mode is ignored and
+    /// negative offsets are treated as zero. See
+    /// [DashPattern](trait.DashPattern.html) for an example.
+    pub fn dash_pattern(&mut self, pattern: &'a dyn DashPattern, offset: f32) -> &mut Self {
+        self.dash_pattern = Some(pattern);
+        self.offset = offset;
+        self
+    }
+
+    /// Sets the mode that determines how the dash array and offset are
+    /// interpreted. The default is length.
+    ///
+    /// ```rust
+    /// use zeno::{DashMode, Mask, Stroke};
+    ///
+    /// // Twelve evenly spaced dashes around a circle.
+    /// let dashes = [100.0 / 24.0; 2];
+    /// Mask::new("M50,10 A40,40 0 1,1 50,90 A40,40 0 1,1 50,10 Z")
+    ///     .style(Stroke::new(4.0).dash(&dashes, 0.0).dash_mode(DashMode::Percent))
+    ///     .render();
+    /// ```
+    pub fn dash_mode(&mut self, mode: DashMode) -> &mut Self {
+        self.dash_mode = mode;
+        self
+    }
+
+    /// Sets whether the dash pattern restarts at the offset for each
+    /// subpath or continues from where the previous subpath ended. The
+    /// default is true.
+    ///
+    /// ```rust
+    /// use zeno::{bounds, Stroke};
+    ///
+    /// // The second subpath falls within a gap when the phase continues.
+    /// let dashes = [6., 6.];
+    /// let path = "M0,0 L6,0 M0,10 L6,10";
+    /// let mut stroke = Stroke::new(2.);
+    /// stroke.dash(&dashes, 0.);
+    /// assert_eq!(bounds(path, stroke, None).max.y, 11.);
+    /// stroke.dash_reset(false);
+    /// assert_eq!(bounds(path, stroke, None).max.y, 1.);
+    /// ```
+    pub fn dash_reset(&mut self, reset: bool) -> &mut Self {
+        self.dash_reset = reset;
+        self
+    }
+
     /// Sets whether or not scaling is applied to the stroke. The default is true.
     pub fn scale(&mut self, scale: bool) -> &mut Self {
         self.scale = scale;
         self
     }
+
+    /// Sets whether the stroke is applied in device space after any
+    /// transform so that the width is unaffected by zooming. This is the
+    /// inverse of [scale](#method.scale) and matches the SVG
+    /// `vector-effect="non-scaling-stroke"` property. The default is false.
+    ///
+    /// ```rust
+    /// use zeno::{bounds, Stroke, Transform};
+    ///
+    /// let zoom = Some(Transform::scale(4., 4.));
+    /// let b = bounds("M0,0 L10,0", *Stroke::new(2.).non_scaling(true), zoom);
+    /// assert_eq!((b.min.y, b.max.y, b.max.x), (-1., 1., 40.));
+    /// ```
+    pub fn non_scaling(&mut self, non_scaling: bool) -> &mut Self {
+        self.scale = !non_scaling;
+        self
+    }
+
+    /// Sets the rule that determines which subpaths are joined at their
+    /// start point rather than capped. The default is explicit.
+    pub fn closure(&mut self, closure: Closure) -> &mut Self {
+        self.closure = closure;
+        self
+    }
+
+    /// Sets the position of the stroke relative to closed subpaths. Inside
+    /// and outside strokes cover the full width on one side of the path,
+    /// as determined by the winding direction of each subpath. Open
+    /// subpaths are always centered. The default is center.
+    ///
+    /// ```rust
+    /// use zeno::{bounds, Alignment, Stroke};
+    ///
+    /// let square = "M10,10 L90,10 L90,90 L10,90 Z";
+    /// let b = bounds(square, *Stroke::new(4.).alignment(Alignment::Outside), None);
+    /// assert_eq!((b.min.x, b.max.x), (6., 94.));
+    /// let b = bounds(square, *Stroke::new(4.).alignment(Alignment::Inside), None);
+    /// assert_eq!((b.min.x, b.max.x), (10., 90.));
+    /// ```
+    pub fn alignment(&mut self, alignment: Alignment) -> &mut Self {
+        self.alignment = alignment;
+        self
+    }
+
+    /// Sets the factor applied to the tolerances used when approximating
+    /// the outline of the stroke. Values greater than 1 produce fewer
+    /// segments for faster rendering at the cost of accuracy while values
+    /// less than 1 produce a more accurate outline. Values that are not
+    /// positive are treated as 1. The default is 1.
+    ///
+    /// ```rust
+    /// use zeno::{apply, Command, Stroke};
+    ///
+    /// let path = "M0,0 C100,0 100,100 0,100";
+    /// let segments = |tolerance| {
+    ///     let mut outline: Vec<Command> = Vec::new();
+    ///     apply(path, *Stroke::new(8.).tolerance(tolerance), None, &mut outline);
+    ///     outline.len()
+    /// };
+    /// assert!(segments(0.1) > segments(1.));
+    /// assert!(segments(4.) < segments(1.));
+    /// ```
+    pub fn tolerance(&mut self, tolerance: f32) -> &mut Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Sets whether overlapping regions are removed from the outline of the
+    /// stroke. The outline is flattened and replaced with the boundary of
+    /// the area it covers so that it can be filled with either fill rule and
+    /// covers every point only once, which is useful when the result of
+    /// [apply](fn.apply.html) is filled with partial alpha. The result
+    /// consists only of line segments, even where the stroke is curved.
+    ///
+    /// This is considerably more expensive than the default, and the cost
+    /// grows with the number of places where the outline crosses itself.
+    /// Outlines that flatten to more than 65536 line segments are emitted
+    /// with their overlaps intact. The default is false.
+    ///
+    /// ```rust
+    /// use zeno::{apply, Command, Fill, Mask, Stroke};
+    ///
+    /// // The stroke overlaps itself where the path crosses.
+    /// let path = "M0,10 L20,10 M10,0 L10,20";
+    /// let mut outline: Vec<Command> = Vec::new();
+    /// apply(path, *Stroke::new(4.).remove_overlaps(true), None, &mut outline);
+    /// let (even_odd, _) = Mask::new(&outline[..]).style(Fill::EvenOdd).render();
+    /// let (non_zero, _) = Mask::new(path).style(Stroke::new(4.)).render();
+    /// assert_eq!(even_odd, non_zero);
+    /// ```
+    pub fn remove_overlaps(&mut self, remove: bool) -> &mut Self {
+        self.remove_overlaps = remove;
+        self
+    }
+
+    /// Sets a marker that will be drawn at every vertex of the path.
+    pub fn marker(&mut self, marker: MarkerStyle<'a>) -> &mut Self {
+        self.start_marker = Some(marker);
+        self.mid_marker = Some(marker);
+        self.end_marker = Some(marker);
+        self
+    }
+
+    /// Sets the markers that will be drawn at the first, intermediate and
+    /// final vertices of each subpath. Markers are drawn at the vertices of
+    /// the path regardless of dashing. For closed subpaths, the start and
+    /// end markers are both drawn at the start point.
+    ///
+    /// ```rust
+    /// use zeno::{bounds, Command, MarkerOrient, MarkerStyle, Stroke};
+    ///
+    /// // Arrowhead with the tip at the vertex.
+    /// let arrow = [
+    ///     Command::MoveTo((0., 0.).into()),
+    ///     Command::LineTo((-3., -2.).into()),
+    ///     Command::LineTo((-3., 2.).into()),
+    ///     Command::Close,
+    /// ];
+    /// let mut head = MarkerStyle::new(&arrow);
+    /// head.orient(MarkerOrient::AutoStartReverse);
+    /// let mut stroke = Stroke::new(2.);
+    /// stroke.markers(Some(head), None, Some(head));
+    /// // The arrowheads point outward without extending the stroke.
+    /// let b = bounds("M0,0 L10,0", stroke, None);
+    /// assert_eq!((b.min.x, b.max.x), (0., 10.));
+    /// assert_eq!((b.min.y.round(), b.max.y.round()), (-2., 2.));
+    /// ```
+    pub fn markers(
+        &mut self,
+        start: Option<MarkerStyle<'a>>,
+        mid: Option<MarkerStyle<'a>>,
+        end: Option<MarkerStyle<'a>>,
+    ) -> &mut Self {
+        self.start_marker = start;
+        self.mid_marker = mid;
+        self.end_marker = end;
+        self
+    }
+
+    /// Sets a template that replaces the center line of the stroke. The
+    /// decorated path is then stroked, dashed and capped as usual while
+    /// markers remain at the vertices of the original path.
+    ///
+    /// ```rust
+    /// use zeno::{Decoration, Mask, Stroke};
+    ///
+    /// // Railroad symbol with dashed rails.
+    /// let dashes = [8., 2.];
+    /// Mask::new("M10,50 C40,0 60,100 90,50")
+    ///     .style(Stroke::new(1.).decoration(Decoration::rails(6., 3.)).dash(&dashes, 0.))
+    ///     .render();
+    /// ```
+    pub fn decoration(&mut self, decoration: Decoration<'a>) -> &mut Self {
+        self.decoration = Some(decoration);
+        self
+    }
+
+    /// Sets the widths of parallel lines that replace the stroke and the
+    /// gaps between them in alternating order, starting with a line on the
+    /// left side of the path. The lines are centered on the path and each
+    /// line is stroked along a copy of the path offset by its distance from
+    /// the center with the joins, caps and dashes of the stroke. Negative
+    /// values are treated as zero.
+    ///
+    /// ```rust
+    /// use zeno::{bounds, Mask, Stroke};
+    ///
+    /// // Double rule with 1 unit lines separated by 2 units.
+    /// let rule = [1., 2., 1.];
+    /// let b = bounds("M0,0 L100,0", *Stroke::new(1.).parallel(&rule), None);
+    /// assert_eq!((b.min.y, b.max.y), (-2., 2.));
+    ///
+    /// // The center of the path is not covered.
+    /// let (mask, placement) = Mask::new("M0,10 L20,10")
+    ///     .style(Stroke::new(1.).parallel(&rule))
+    ///     .render();
+    /// let row = |y: i32| (y - placement.top) as usize * placement.width as usize;
+    /// assert_eq!(mask[row(8) + 10], 255);
+    /// assert_eq!(mask[row(10) + 10], 0);
+    /// assert_eq!(mask[row(11) + 10], 255);
+    /// ```
+    pub fn parallel(&mut self, widths: &'a [f32]) -> &mut Self {
+        self.parallel = widths;
+        self
+    }
 }
 
+/// Error returned when validating a [Stroke](struct.Stroke.html).
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum StrokeError {
+    /// A parameter with the specified name is NaN or infinite.
+    NotFinite(&'static str),
+    /// The width of the stroke, one of its variable or parallel widths, or
+    /// its minimum width is negative.
+    NegativeWidth,
+    /// The dash array contains a negative length or has no positive length.
+    InvalidDashes,
+    /// The tolerance factor is not positive.
+    InvalidTolerance,
+    /// The period of the decoration is not positive.
+    InvalidDecoration,
+}
+
+impl fmt::Display for StrokeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::NotFinite(name) => write!(f, "stroke {} is not finite", name),
+            Self::NegativeWidth => write!(f, "stroke width is negative"),
+            Self::InvalidDashes => write!(f, "dash array has negative or no positive lengths"),
+            Self::InvalidTolerance => write!(f, "stroke tolerance is not positive"),
+            Self::InvalidDecoration => write!(f, "decoration period is not positive"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for StrokeError {}
+
 /// Represents the style of a path for rendering or hit testing.
 #[derive(Copy, Clone, Debug)]
+#[allow(clippy::large_enum_variant)]
 pub enum Style<'a> {
     Fill(Fill),
     Stroke(Stroke<'a>),
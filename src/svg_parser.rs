@@ -1,7 +1,7 @@
 //! SVG path data parser.
 
 use super::command::Command;
-use super::geometry::Vector;
+use super::geometry::{Angle, Vector};
 use super::path_builder::{Arc, ArcSize, ArcSweep};
 
 #[derive(Copy, Clone)]
@@ -11,19 +11,36 @@ enum State {
     Continue(u8),
 }
 
+/// Iterator over the commands of SVG path data.
+///
+/// Elliptical arcs are approximated with cubic bezier curves by default. Use
+/// [retain_arcs](struct.SvgCommands.html#method.retain_arcs) to receive them
+/// as [arc commands](enum.Command.html#variant.ArcTo) instead.
+///
+/// ```rust
+/// use zeno::{Command, PathData};
+///
+/// let path = "M0,0 A10,10 0 0 1 20,0";
+/// assert!(path.commands().all(|cmd| !matches!(cmd, Command::ArcTo(..))));
+/// assert!(matches!(
+///     path.commands().retain_arcs(true).nth(1),
+///     Some(Command::ArcTo(..))
+/// ));
+/// ```
 #[derive(Clone)]
 pub struct SvgCommands<'a> {
     buf: &'a [u8],
     cur: u8,
-    pub pos: usize,
+    pub(crate) pos: usize,
     cmd_pos: usize,
-    pub error: bool,
-    pub done: bool,
+    pub(crate) error: bool,
+    pub(crate) done: bool,
     start_point: Vector,
     cur_point: Vector,
     last_control: Vector,
     last_cmd: u8,
     state: State,
+    arcs: bool,
     arc: Arc,
 }
 
@@ -49,10 +66,18 @@ impl<'a> SvgCommands<'a> {
             last_control: Vector::ZERO,
             last_cmd: 0,
             state: State::Initial,
+            arcs: false,
             arc: Arc::default(),
         }
     }
 
+    /// Sets whether elliptical arcs are emitted as arc commands rather than
+    /// approximated with cubic bezier curves.
+    pub fn retain_arcs(mut self, retain: bool) -> Self {
+        self.arcs = retain;
+        self
+    }
+
     fn parse(&mut self) -> Option<Command> {
         use Command::*;
         let mut cmd = self.cur;
@@ -190,15 +215,35 @@ impl<'a> SvgCommands<'a> {
                         b'A' => {
                             let from = self.cur_point;
                             let (rx, ry, a, size, sweep, to) = self.arc_arguments(false)?;
-                            self.arc = Arc::new(from, rx, ry, a.to_radians(), size, sweep, to);
                             self.skip_comma_whitespace();
+                            if self.arcs {
+                                return Some(ArcTo(
+                                    rx,
+                                    ry,
+                                    Angle::from_degrees(a),
+                                    size,
+                                    sweep,
+                                    to,
+                                ));
+                            }
+                            self.arc = Arc::new(from, rx, ry, a.to_radians(), size, sweep, to);
                             continue;
                         }
                         b'a' => {
                             let from = self.cur_point;
                             let (rx, ry, a, size, sweep, to) = self.arc_arguments(true)?;
-                            self.arc = Arc::new(from, rx, ry, a.to_radians(), size, sweep, to);
                             self.skip_comma_whitespace();
+                            if self.arcs {
+                                return Some(ArcTo(
+                                    rx,
+                                    ry,
+                                    Angle::from_degrees(a),
+                                    size,
+                                    sweep,
+                                    to,
+                                ));
+                            }
+                            self.arc = Arc::new(from, rx, ry, a.to_radians(), size, sweep, to);
                             continue;
                         }
                         _ => {
@@ -379,8 +424,18 @@ impl<'a> SvgCommands<'a> {
                         if let Some(rx) = self.coord() {
                             let from = self.cur_point;
                             let (ry, a, size, sweep, to) = self.arc_rest_arguments(false)?;
-                            self.arc = Arc::new(from, rx, ry, a.to_radians(), size, sweep, to);
                             self.skip_comma_whitespace();
+                            if self.arcs {
+                                return Some(ArcTo(
+                                    rx,
+                                    ry,
+                                    Angle::from_degrees(a),
+                                    size,
+                                    sweep,
+                                    to,
+                                ));
+                            }
+                            self.arc = Arc::new(from, rx, ry, a.to_radians(), size, sweep, to);
                         } else {
                             self.state = State::Next;
                         }
@@ -389,8 +444,18 @@ impl<'a> SvgCommands<'a> {
                         if let Some(rx) = self.coord() {
                             let from = self.cur_point;
                             let (ry, a, size, sweep, to) = self.arc_rest_arguments(true)?;
-                            self.arc = Arc::new(from, rx, ry, a.to_radians(), size, sweep, to);
                             self.skip_comma_whitespace();
+                            if self.arcs {
+                                return Some(ArcTo(
+                                    rx,
+                                    ry,
+                                    Angle::from_degrees(a),
+                                    size,
+                                    sweep,
+                                    to,
+                                ));
+                            }
+                            self.arc = Arc::new(from, rx, ry, a.to_radians(), size, sweep, to);
                         } else {
                             self.state = State::Next;
                         }
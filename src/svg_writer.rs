@@ -0,0 +1,266 @@
+//! SVG path data serialization.
+
+use super::geometry::{Angle, Point};
+use super::path_builder::{ArcSize, ArcSweep, PathBuilder};
+
+use core::fmt::{self, Write};
+
+/// Path builder that incrementally serializes commands as SVG path data.
+///
+/// Commands are written to the underlying writer as they are received so
+/// that large paths, such as the output of [apply](fn.apply.html), can be
+/// exported without storing the commands. Conic curves have no SVG
+/// equivalent and are written as quadratic curves.
+///
+/// ```rust
+/// use zeno::{apply, PathBuilder, Stroke, SvgWriter};
+///
+/// let mut svg = SvgWriter::new(String::new());
+/// svg.precision(Some(2));
+/// apply("M0,0 10,0", Stroke::new(2.), None, &mut svg);
+/// assert_eq!(svg.finish().unwrap(), "M0,-1 L10,-1 L10,1 L0,1 L0,-1 Z");
+///
+/// let mut svg = SvgWriter::new(String::new());
+/// svg.move_to([0, 0]).line_to([10, 10]).close().rel_line_to([5, 5]);
+/// assert_eq!(svg.finish().unwrap(), "M0,0 L10,10 Z L5,5");
+/// ```
+pub struct SvgWriter<W> {
+    writer: W,
+    precision: Option<usize>,
+    start: Point,
+    current: Point,
+    empty: bool,
+    error: Option<fmt::Error>,
+}
+
+impl<W: Write> SvgWriter<W> {
+    /// Creates a new SVG writer that emits to the specified writer.
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            precision: None,
+            start: Point::ZERO,
+            current: Point::ZERO,
+            empty: true,
+            error: None,
+        }
+    }
+
+    /// Sets the maximum number of fractional digits used for coordinates.
+    /// Trailing zeros are omitted. If unspecified, the shortest
+    /// representation that round trips is used.
+    pub fn precision(&mut self, digits: Option<usize>) -> &mut Self {
+        self.precision = digits;
+        self
+    }
+
+    /// Returns the underlying writer or the first error that occurred while
+    /// writing.
+    pub fn finish(self) -> Result<W, fmt::Error> {
+        match self.error {
+            Some(error) => Err(error),
+            None => Ok(self.writer),
+        }
+    }
+
+    fn verb(&mut self, verb: char) {
+        if !self.empty {
+            self.write(' ', None);
+        }
+        self.empty = false;
+        self.write(verb, None);
+    }
+
+    fn pair(&mut self, separator: Option<char>, point: Point) {
+        self.write(separator, Some(point.x));
+        self.write(',', Some(point.y));
+    }
+
+    fn write(&mut self, separator: impl Into<Option<char>>, value: Option<f32>) {
+        if self.error.is_some() {
+            return;
+        }
+        let mut buf = NumberBuf::default();
+        let mut result = Ok(());
+        if let Some(separator) = separator.into() {
+            result = self.writer.write_char(separator);
+        }
+        if let Some(value) = value {
+            result = result.and_then(|_| self.writer.write_str(buf.format(value, self.precision)));
+        }
+        if let Err(error) = result {
+            self.error = Some(error);
+        }
+    }
+}
+
+impl<W: Write> PathBuilder for SvgWriter<W> {
+    fn current_point(&self) -> Point {
+        self.current
+    }
+
+    fn move_to(&mut self, to: impl Into<Point>) -> &mut Self {
+        let to = to.into();
+        self.verb('M');
+        self.pair(None, to);
+        self.start = to;
+        self.current = to;
+        self
+    }
+
+    fn line_to(&mut self, to: impl Into<Point>) -> &mut Self {
+        let to = to.into();
+        self.verb('L');
+        self.pair(None, to);
+        self.current = to;
+        self
+    }
+
+    fn quad_to(&mut self, control: impl Into<Point>, to: impl Into<Point>) -> &mut Self {
+        let to = to.into();
+        self.verb('Q');
+        self.pair(None, control.into());
+        self.pair(Some(' '), to);
+        self.current = to;
+        self
+    }
+
+    fn curve_to(
+        &mut self,
+        control1: impl Into<Point>,
+        control2: impl Into<Point>,
+        to: impl Into<Point>,
+    ) -> &mut Self {
+        let to = to.into();
+        self.verb('C');
+        self.pair(None, control1.into());
+        self.pair(Some(' '), control2.into());
+        self.pair(Some(' '), to);
+        self.current = to;
+        self
+    }
+
+    fn arc_to(
+        &mut self,
+        rx: f32,
+        ry: f32,
+        angle: Angle,
+        size: ArcSize,
+        sweep: ArcSweep,
+        to: impl Into<Point>,
+    ) -> &mut Self {
+        let to = to.into();
+        self.verb('A');
+        self.pair(None, Point::new(rx, ry));
+        self.write(' ', Some(angle.to_degrees()));
+        self.write(' ', None);
+        self.write(if size == ArcSize::Large { '1' } else { '0' }, None);
+        self.write(' ', None);
+        self.write(
+            if sweep == ArcSweep::Positive {
+                '1'
+            } else {
+                '0'
+            },
+            None,
+        );
+        self.pair(Some(' '), to);
+        self.current = to;
+        self
+    }
+
+    fn close(&mut self) -> &mut Self {
+        self.verb('Z');
+        self.current = self.start;
+        self
+    }
+}
+
+/// Adapter that allows an [SvgWriter](struct.SvgWriter.html) to emit to an
+/// implementation of `std::io::Write`.
+#[cfg(feature = "std")]
+pub struct IoWriter<W> {
+    writer: W,
+    error: Option<std::io::Error>,
+}
+
+#[cfg(feature = "std")]
+impl<W: std::io::Write> IoWriter<W> {
+    /// Creates a new adapter for the specified writer.
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            error: None,
+        }
+    }
+
+    /// Returns the underlying writer or the first I/O error that occurred
+    /// while writing.
+    pub fn into_inner(self) -> std::io::Result<W> {
+        match self.error {
+            Some(error) => Err(error),
+            None => Ok(self.writer),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: std::io::Write> Write for IoWriter<W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        if self.error.is_some() {
+            return Err(fmt::Error);
+        }
+        self.writer.write_all(s.as_bytes()).map_err(|error| {
+            self.error = Some(error);
+            fmt::Error
+        })
+    }
+}
+
+/// Stack buffer for formatting a single number.
+struct NumberBuf {
+    buf: [u8; 64],
+    len: usize,
+}
+
+impl Default for NumberBuf {
+    fn default() -> Self {
+        Self {
+            buf: [0; 64],
+            len: 0,
+        }
+    }
+}
+
+impl NumberBuf {
+    fn format(&mut self, value: f32, precision: Option<usize>) -> &str {
+        self.len = 0;
+        // Infinite values cannot be represented in path data.
+        let value = if value.is_finite() { value } else { 0. };
+        let _ = match precision {
+            Some(precision) => write!(self, "{:.*}", precision.min(16), value),
+            None => write!(self, "{}", value),
+        };
+        let mut s = core::str::from_utf8(&self.buf[..self.len]).unwrap_or("0");
+        if s.contains('.') {
+            s = s.trim_end_matches('0').trim_end_matches('.');
+        }
+        if s == "-0" {
+            s = "0";
+        }
+        s
+    }
+}
+
+impl Write for NumberBuf {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let bytes = s.as_bytes();
+        let end = self.len + bytes.len();
+        if end > self.buf.len() {
+            return Err(fmt::Error);
+        }
+        self.buf[self.len..end].copy_from_slice(bytes);
+        self.len = end;
+        Ok(())
+    }
+}
@@ -0,0 +1,235 @@
+//! Seeded generation of adversarial inputs for testing.
+
+use super::geometry::{Angle, Point, Transform};
+use super::path_builder::PathBuilder;
+use super::style::{Cap, Fill, Join, Stroke, Style};
+
+use crate::lib::Vec;
+
+/// Deterministic generator of valid but numerically challenging paths,
+/// styles and transforms.
+///
+/// Generated paths contain degenerate segments, huge and tiny coordinates,
+/// tiny loops, empty subpaths and large numbers of subpaths. The same seed
+/// always produces the same sequence of values on every platform.
+///
+/// ```rust
+/// use zeno::{Command, Generator, Mask};
+///
+/// let mut gen = Generator::new(42);
+/// gen.range(1000.0);
+/// for _ in 0..10 {
+///     let mut path: Vec<Command> = Vec::new();
+///     let mut dashes = Vec::new();
+///     gen.path(&mut path);
+///     let style = gen.style(&mut dashes);
+///     Mask::new(&path).style(style).size(32, 32).render();
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct Generator {
+    state: u64,
+    range: f32,
+    max_subpaths: usize,
+    max_segments: usize,
+}
+
+impl Generator {
+    /// Creates a new generator with the specified seed.
+    pub fn new(seed: u64) -> Self {
+        Self {
+            state: seed,
+            range: 1e5,
+            max_subpaths: 16,
+            max_segments: 32,
+        }
+    }
+
+    /// Sets the maximum magnitude of generated coordinates. The default is
+    /// 100000.
+    pub fn range(&mut self, range: f32) -> &mut Self {
+        self.range = range.abs();
+        self
+    }
+
+    /// Sets the maximum number of subpaths in a generated path. The default
+    /// is 16.
+    pub fn max_subpaths(&mut self, count: usize) -> &mut Self {
+        self.max_subpaths = count.max(1);
+        self
+    }
+
+    /// Sets the maximum number of segments in a generated subpath. The
+    /// default is 32.
+    pub fn max_segments(&mut self, count: usize) -> &mut Self {
+        self.max_segments = count.max(1);
+        self
+    }
+
+    /// Generates a random path and emits it to the specified sink.
+    pub fn path(&mut self, sink: &mut impl PathBuilder) {
+        let subpaths = 1 + self.below(self.max_subpaths as u32) as usize;
+        for _ in 0..subpaths {
+            match self.below(6) {
+                0 => self.tiny_loop(sink),
+                1 => self.degenerate(sink),
+                2 => {
+                    // Lone move or empty closed subpath.
+                    let p = self.point(self.range);
+                    sink.move_to(p);
+                    if self.chance(2) {
+                        sink.close();
+                    }
+                }
+                3 => {
+                    let range = self.range;
+                    self.subpath(sink, range);
+                }
+                _ => {
+                    let range = self.float(1., 512.);
+                    self.subpath(sink, range);
+                }
+            }
+        }
+    }
+
+    /// Generates a random fill rule.
+    pub fn fill(&mut self) -> Fill {
+        if self.chance(2) {
+            Fill::NonZero
+        } else {
+            Fill::EvenOdd
+        }
+    }
+
+    /// Generates a random stroke. Any dash array is generated into the
+    /// specified vector.
+    pub fn stroke<'a>(&mut self, dashes: &'a mut Vec<f32>) -> Stroke<'a> {
+        let mut stroke = Stroke::new(self.pick(&[0., 0.001, 0.5, 1., 4., 64.]));
+        stroke.join(self.pick(&[Join::Bevel, Join::Miter, Join::Round]));
+        stroke.miter_limit(self.pick(&[0.5, 1., 4., 100.]));
+        let caps = [Cap::Butt, Cap::Square, Cap::Round];
+        stroke.caps(self.pick(&caps), self.pick(&caps));
+        dashes.clear();
+        if self.chance(2) {
+            let count = 1 + self.below(6);
+            for _ in 0..count {
+                let dash = self.pick(&[0., 0.01, 0.5, 1., 3., 10.]);
+                dashes.push(dash);
+            }
+        }
+        let offset = self.pick(&[0., -7.5, 0.25, 1000.]);
+        stroke.scale(self.chance(2));
+        stroke.dash(dashes, offset);
+        stroke
+    }
+
+    /// Generates a random fill or stroke style. Any dash array is generated
+    /// into the specified vector.
+    pub fn style<'a>(&mut self, dashes: &'a mut Vec<f32>) -> Style<'a> {
+        if self.chance(2) {
+            Style::Fill(self.fill())
+        } else {
+            Style::Stroke(self.stroke(dashes))
+        }
+    }
+
+    /// Generates a random invertible or degenerate transform.
+    pub fn transform(&mut self) -> Transform {
+        match self.below(6) {
+            0 => Transform::IDENTITY,
+            1 => Transform::scale(self.float(-100., 100.), self.float(-100., 100.)),
+            2 => Transform::scale(1e-4, 1e-4),
+            3 => Transform::rotation(Angle::from_degrees(self.float(-360., 360.))),
+            4 => Transform::skew(
+                Angle::from_degrees(self.float(-89., 89.)),
+                Angle::from_degrees(self.float(-89., 89.)),
+            ),
+            _ => Transform::scale(0., 1.),
+        }
+        .then_translate(self.float(-64., 64.), self.float(-64., 64.))
+    }
+
+    fn subpath(&mut self, sink: &mut impl PathBuilder, range: f32) {
+        let count = 1 + self.below(self.max_segments as u32);
+        sink.move_to(self.point(range));
+        for _ in 0..count {
+            match self.below(3) {
+                0 => {
+                    sink.line_to(self.point(range));
+                }
+                1 => {
+                    sink.quad_to(self.point(range), self.point(range));
+                }
+                _ => {
+                    sink.curve_to(self.point(range), self.point(range), self.point(range));
+                }
+            }
+        }
+        if self.chance(2) {
+            sink.close();
+        }
+    }
+
+    fn tiny_loop(&mut self, sink: &mut impl PathBuilder) {
+        let c = self.point(self.range);
+        let r = self.pick(&[1e-4, 0.01, 0.3]);
+        sink.move_to(c);
+        sink.curve_to(
+            c + Point::new(r, -r),
+            c + Point::new(r, r),
+            c + Point::new(-r * 0.5, 0.),
+        );
+        sink.line_to(c);
+        sink.close();
+    }
+
+    fn degenerate(&mut self, sink: &mut impl PathBuilder) {
+        let p = self.point(self.range);
+        let q = self.point(self.range);
+        sink.move_to(p);
+        sink.line_to(p);
+        sink.quad_to(p, p);
+        sink.curve_to(p, p, q);
+        sink.curve_to(q, q, q);
+        // Reversal along the same line.
+        sink.line_to(p);
+        if self.chance(2) {
+            sink.close();
+        }
+    }
+
+    fn point(&mut self, range: f32) -> Point {
+        Point::new(self.float(-range, range), self.float(-range, range))
+    }
+
+    fn pick<T: Copy>(&mut self, values: &[T]) -> T {
+        values[self.below(values.len() as u32) as usize]
+    }
+
+    fn chance(&mut self, n: u32) -> bool {
+        self.below(n) == 0
+    }
+
+    fn below(&mut self, n: u32) -> u32 {
+        if n == 0 {
+            0
+        } else {
+            ((self.next_u64() >> 32) % n as u64) as u32
+        }
+    }
+
+    fn float(&mut self, min: f32, max: f32) -> f32 {
+        let t = (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32;
+        min + (max - min) * t
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        // SplitMix64
+        self.state = self.state.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+}
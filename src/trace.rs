@@ -0,0 +1,338 @@
+//! Conversion of bitmaps into paths.
+
+use super::geometry::{Angle, Point, Vector};
+use super::path_builder::PathBuilder;
+#[cfg(not(feature = "std"))]
+use super::F32Ext;
+
+use crate::lib::Vec;
+
+/// Builder for configuring and tracing the outline of a bitmap.
+///
+/// The bitmap is thresholded into a set of inside pixels and the boundaries
+/// between inside and outside pixels are traced into closed polygons. Each
+/// polygon is simplified to remove the staircase pattern of the pixel grid
+/// and then smoothed into curves, retaining sharp turns as corners.
+///
+/// Outer boundaries and holes are emitted with opposite orientations so the
+/// result should be filled with the non-zero fill rule. Coordinates are in
+/// pixels with the origin at the top left corner of the bitmap.
+///
+/// ```rust
+/// use zeno::{Command, Mask, Trace};
+///
+/// let (mask, placement) = Mask::new("M8,0 A8,8 0 1,1 8,16 A8,8 0 1,1 8,0 Z").render();
+/// let mut path: Vec<Command> = Vec::new();
+/// Trace::new(&mask, placement.width, placement.height)
+///     .offset([placement.left, placement.top])
+///     .trace(&mut path);
+///
+/// // The traced outline covers the same area as the original.
+/// let (traced, _) = Mask::new(&path).render();
+/// let area = |mask: &[u8]| mask.iter().map(|&c| c as u32).sum::<u32>();
+/// assert!(area(&traced).abs_diff(area(&mask)) < area(&mask) / 25);
+/// ```
+pub struct Trace<'a> {
+    data: &'a [u8],
+    width: u32,
+    height: u32,
+    threshold: u8,
+    tolerance: f32,
+    corner: f32,
+    offset: Vector,
+}
+
+impl<'a> Trace<'a> {
+    /// Creates a new trace builder for the specified tightly packed 8-bit
+    /// bitmap with the given dimensions.
+    pub fn new(data: &'a [u8], width: u32, height: u32) -> Self {
+        Self {
+            data,
+            width,
+            height,
+            threshold: 128,
+            tolerance: 0.75,
+            corner: Angle::from_degrees(60.).to_radians(),
+            offset: Vector::ZERO,
+        }
+    }
+
+    /// Sets the threshold at or above which a pixel is considered to be
+    /// inside the shape. The default is 128.
+    pub fn threshold(&mut self, threshold: u8) -> &mut Self {
+        self.threshold = threshold;
+        self
+    }
+
+    /// Sets the maximum distance in pixels that the simplified outline may
+    /// deviate from the pixel boundaries. Larger values produce fewer
+    /// segments at the cost of fidelity. The default is 0.75.
+    pub fn tolerance(&mut self, tolerance: f32) -> &mut Self {
+        self.tolerance = tolerance.max(0.);
+        self
+    }
+
+    /// Sets the minimum turning angle at which a vertex of the simplified
+    /// outline is retained as a sharp corner rather than smoothed into a
+    /// curve. The default is 60 degrees.
+    pub fn corner_angle(&mut self, angle: Angle) -> &mut Self {
+        self.corner = angle.to_radians().abs();
+        self
+    }
+
+    /// Sets an offset that is applied to the traced path.
+    pub fn offset(&mut self, offset: impl Into<Vector>) -> &mut Self {
+        self.offset = offset.into();
+        self
+    }
+
+    /// Traces the bitmap and emits the resulting path to the specified sink.
+    pub fn trace(&self, sink: &mut impl PathBuilder) {
+        let mut edges = self.edges();
+        let stride = self.width as usize + 1;
+        let mut contour = Vec::new();
+        let mut simplified = Vec::new();
+        for start in 0..edges.len() {
+            while edges[start] != 0 {
+                contour.clear();
+                trace_contour(&mut edges, stride, start, &mut contour);
+                simplified.clear();
+                simplify(&contour, self.tolerance, &mut simplified);
+                let points = if simplified.len() >= 3 {
+                    &simplified
+                } else {
+                    &contour
+                };
+                if points.len() >= 2 {
+                    self.emit(points, sink);
+                }
+            }
+        }
+    }
+
+    /// Returns a grid of directed boundary edges. Each entry contains a bit
+    /// for every outgoing edge at the corresponding pixel corner.
+    fn edges(&self) -> Vec<u8> {
+        let w = self.width as usize;
+        let h = self.height as usize;
+        let stride = w + 1;
+        let mut edges = alloc::vec![0u8; stride * (h + 1)];
+        if self.data.len() < w * h {
+            return edges;
+        }
+        let inside = |x: isize, y: isize| {
+            x >= 0
+                && y >= 0
+                && (x as usize) < w
+                && (y as usize) < h
+                && self.data[y as usize * w + x as usize] >= self.threshold
+        };
+        for y in 0..h {
+            for x in 0..w {
+                let (xi, yi) = (x as isize, y as isize);
+                if !inside(xi, yi) {
+                    continue;
+                }
+                let corner = y * stride + x;
+                if !inside(xi, yi - 1) {
+                    edges[corner] |= 1 << RIGHT;
+                }
+                if !inside(xi + 1, yi) {
+                    edges[corner + 1] |= 1 << DOWN;
+                }
+                if !inside(xi, yi + 1) {
+                    edges[corner + stride + 1] |= 1 << LEFT;
+                }
+                if !inside(xi - 1, yi) {
+                    edges[corner + stride] |= 1 << UP;
+                }
+            }
+        }
+        edges
+    }
+
+    fn emit(&self, points: &[Point], sink: &mut impl PathBuilder) {
+        let n = points.len();
+        let offset = self.offset;
+        let mid = |i: usize| (points[i % n] + points[(i + 1) % n]) * 0.5 + offset;
+        sink.move_to(mid(n - 1));
+        for i in 0..n {
+            let prev = points[(i + n - 1) % n];
+            let p = points[i];
+            let next = points[(i + 1) % n];
+            let turn = (p - prev).angle_to(next - p).to_radians().abs();
+            if turn < self.corner {
+                sink.quad_to(p + offset, mid(i));
+            } else {
+                sink.line_to(p + offset);
+                sink.line_to(mid(i));
+            }
+        }
+        sink.close();
+    }
+}
+
+const RIGHT: u8 = 0;
+const DOWN: u8 = 1;
+const LEFT: u8 = 2;
+const UP: u8 = 3;
+
+/// Follows and consumes a closed loop of boundary edges beginning at the
+/// specified corner, recording the corners where the direction changes.
+fn trace_contour(edges: &mut [u8], stride: usize, start: usize, contour: &mut Vec<Point>) {
+    let point = |corner: usize| Point::new((corner % stride) as f32, (corner / stride) as f32);
+    let start_dir = edges[start].trailing_zeros() as u8;
+    let mut corner = start;
+    let mut dir = start_dir;
+    loop {
+        edges[corner] &= !(1 << dir);
+        corner = match dir {
+            RIGHT => corner + 1,
+            DOWN => corner + stride,
+            LEFT => corner - 1,
+            _ => corner - stride,
+        };
+        if corner == start {
+            if dir != start_dir {
+                contour.push(point(start));
+            }
+            break;
+        }
+        // Prefer turning right at ambiguous corners so that pixels touching
+        // diagonally produce separate contours.
+        let bits = edges[corner];
+        let next = match [(dir + 1) % 4, dir, (dir + 3) % 4]
+            .iter()
+            .find(|&&d| bits & (1 << d) != 0)
+        {
+            Some(&next) => next,
+            None => break,
+        };
+        if next != dir {
+            contour.push(point(corner));
+        }
+        dir = next;
+    }
+}
+
+/// Simplifies a closed polygon using the Douglas-Peucker algorithm.
+fn simplify(points: &[Point], tolerance: f32, result: &mut Vec<Point>) {
+    let n = points.len();
+    if n < 3 || tolerance == 0. {
+        result.extend_from_slice(points);
+        return;
+    }
+    // Split the loop at the point farthest from the first.
+    let far = (1..n)
+        .max_by(|&a, &b| {
+            let da = (points[a] - points[0]).length_squared();
+            let db = (points[b] - points[0]).length_squared();
+            da.partial_cmp(&db).unwrap_or(core::cmp::Ordering::Equal)
+        })
+        .unwrap_or(n / 2);
+    let mut keep = alloc::vec![false; n + 1];
+    keep[0] = true;
+    keep[far] = true;
+    mark(points, 0, far, tolerance, &mut keep);
+    mark(points, far, n, tolerance, &mut keep);
+    for (i, p) in points.iter().enumerate() {
+        if keep[i] {
+            result.push(*p);
+        }
+    }
+}
+
+fn mark(points: &[Point], first: usize, last: usize, tolerance: f32, keep: &mut [bool]) {
+    if last <= first + 1 {
+        return;
+    }
+    let n = points.len();
+    let a = points[first];
+    let b = points[last % n];
+    let ab = b - a;
+    let len = ab.length();
+    let mut max = 0.;
+    let mut index = first;
+    for (i, p) in points.iter().enumerate().take(last).skip(first + 1) {
+        let d = if len == 0. {
+            (*p - a).length()
+        } else {
+            ab.cross(*p - a).abs() / len
+        };
+        if d > max {
+            max = d;
+            index = i;
+        }
+    }
+    if max > tolerance {
+        keep[index] = true;
+        mark(points, first, index, tolerance, keep);
+        mark(points, index, last, tolerance, keep);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Command, Mask};
+
+    /// Traces the rendered path and returns the vertices of each subpath.
+    fn trace_path(path: &str) -> Vec<Vec<Point>> {
+        let (mask, placement) = Mask::new(path).render();
+        let mut commands: Vec<Command> = Vec::new();
+        Trace::new(&mask, placement.width, placement.height)
+            .offset([placement.left, placement.top])
+            .trace(&mut commands);
+        let mut contours = Vec::new();
+        for cmd in commands {
+            match cmd {
+                Command::MoveTo(p) => contours.push(alloc::vec![p]),
+                Command::LineTo(p) => contours.last_mut().unwrap().push(p),
+                Command::Close => {}
+                _ => panic!("unexpected curve {:?}", cmd),
+            }
+        }
+        contours
+    }
+
+    fn signed_area(points: &[Point]) -> f32 {
+        let mut area = 0.;
+        for (i, a) in points.iter().enumerate() {
+            area += a.cross(points[(i + 1) % points.len()]);
+        }
+        area * 0.5
+    }
+
+    #[test]
+    fn square_corners() {
+        let contours = trace_path("M2,2 L12,2 L12,12 L2,12 Z");
+        assert_eq!(contours.len(), 1);
+        let points = &contours[0];
+        for corner in [[2., 2.], [12., 2.], [12., 12.], [2., 12.]] {
+            let corner = Point::from(corner);
+            assert!(
+                points.iter().any(|p| (*p - corner).length() < 0.25),
+                "{:?}",
+                corner
+            );
+        }
+        // Every other vertex lies on an edge of the square.
+        for p in points {
+            let on_x = (p.x - 2.).abs() < 0.25 || (p.x - 12.).abs() < 0.25;
+            let on_y = (p.y - 2.).abs() < 0.25 || (p.y - 12.).abs() < 0.25;
+            assert!(on_x || on_y, "{:?}", p);
+        }
+        assert!((signed_area(points).abs() - 100.).abs() < 1.);
+    }
+
+    #[test]
+    fn hole_winding() {
+        let contours = trace_path("M2,2 L22,2 L22,22 L2,22 Z M8,8 L8,16 L16,16 L16,8 Z");
+        assert_eq!(contours.len(), 2);
+        let outer = signed_area(&contours[0]);
+        let hole = signed_area(&contours[1]);
+        assert!((outer.abs() - 400.).abs() < 1.);
+        assert!((hole.abs() - 64.).abs() < 1.);
+        assert!(outer.signum() != hole.signum());
+    }
+}
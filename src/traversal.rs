@@ -3,7 +3,8 @@
 use super::command::{Command, TransformCommands};
 use super::geometry::*;
 use super::path_data::PathData;
-use super::segment::{segments, Segment, Segments};
+use super::segment::{segments, Measure, Segment, Segments};
+use super::stroke::validate_dashes;
 
 use core::borrow::Borrow;
 use core::cell::RefCell;
@@ -21,6 +22,17 @@ pub enum Vertex {
     End(Vector, Point, bool),
 }
 
+/// The result of a step along a path.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Step {
+    /// The location and left-ward normal vector of a step that ended within
+    /// the path.
+    Within(Point, Vector),
+    /// The end point and normal vector of the path for a step that reached
+    /// the end, along with the distance that was left over.
+    End(Point, Vector, f32),
+}
+
 /// An iterator over the vertices of a path.
 #[derive(Clone)]
 pub struct Vertices<D> {
@@ -88,7 +100,16 @@ where
                 }
             }
         } else {
-            match self.segments.next()?.borrow() {
+            let segment = match self.segments.next() {
+                Some(segment) => segment,
+                None => {
+                    // The final subpath is not terminated by the segment
+                    // iterator.
+                    self.is_first = true;
+                    return Some(Vertex::End(self.prev_dir, self.prev_point, false));
+                }
+            };
+            match segment.borrow() {
                 End(closed) => {
                     self.is_first = true;
                     return Some(Vertex::End(self.prev_dir, self.prev_point, *closed));
@@ -105,6 +126,154 @@ where
     }
 }
 
+impl<D> Vertices<D>
+where
+    D: Iterator + Clone,
+    D::Item: Borrow<Command>,
+{
+    /// Creates a new iterator over the vertices of a sequence of commands.
+    pub(super) fn from_commands(commands: D) -> Self {
+        Self {
+            segments: segments(commands, false),
+            prev_point: Point::ZERO,
+            prev_dir: Vector::new(1., 0.),
+            is_first: true,
+        }
+    }
+
+    /// Converts the iterator into one that yields marker placement data for
+    /// each vertex.
+    pub fn markers(self) -> Markers<D> {
+        Markers {
+            vertices: self,
+            start_dir: Vector::ZERO,
+            seam_dir: None,
+        }
+    }
+}
+
+/// Position of a marker within a subpath.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum MarkerPosition {
+    /// The first vertex of a subpath.
+    Start,
+    /// An intermediate vertex of a subpath.
+    Middle,
+    /// The final vertex of a subpath.
+    End,
+}
+
+/// Placement data for an SVG style marker at a vertex of a path.
+///
+/// For the start and end vertices of a closed subpath, the directions span
+/// the seam: the start vertex is entered by the closing segment and the end
+/// vertex is exited by the first segment. For the start and end vertices of
+/// an open subpath, both directions are the same.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Marker {
+    /// Position of the vertex within its subpath.
+    pub position: MarkerPosition,
+    /// Location of the vertex.
+    pub point: Point,
+    /// Direction of the path entering the vertex.
+    pub incoming: Vector,
+    /// Direction of the path leaving the vertex.
+    pub outgoing: Vector,
+    /// True if the vertex belongs to a closed subpath.
+    pub closed: bool,
+}
+
+impl Marker {
+    /// Returns the orientation of the marker for `orient="auto"`. This is the
+    /// angle bisecting the incoming and outgoing directions.
+    pub fn angle(&self) -> Angle {
+        let a = Angle::from_vector(self.incoming).to_radians();
+        let b = Angle::from_vector(self.outgoing).to_radians();
+        let delta = Angle::from_radians(b - a).normalized().to_radians();
+        Angle::from_radians(a + delta * 0.5).normalized()
+    }
+
+    /// Returns the orientation of the marker for `orient="auto-start-reverse"`.
+    /// This is the same as [angle](#method.angle) except that markers at the
+    /// start of a subpath are rotated by 180 degrees.
+    pub fn start_reverse_angle(&self) -> Angle {
+        let angle = self.angle();
+        if self.position == MarkerPosition::Start {
+            Angle::from_radians(angle.to_radians() + core::f32::consts::PI).normalized()
+        } else {
+            angle
+        }
+    }
+}
+
+/// An iterator over the marker placements of a path.
+///
+/// ```rust
+/// use zeno::{MarkerPosition, Vertices};
+///
+/// let markers: Vec<_> = Vertices::new("M0,0 10,0 10,10 Z").markers().collect();
+/// assert_eq!(markers.len(), 4);
+/// assert_eq!(markers[0].position, MarkerPosition::Start);
+/// // The start of the closed subpath bisects the closing segment and the
+/// // first segment.
+/// assert_eq!(markers[0].angle().to_degrees(), -67.5);
+/// assert_eq!(markers[3].angle(), markers[0].angle());
+/// assert_eq!(markers[1].angle().to_degrees(), 45.);
+/// ```
+#[derive(Clone)]
+pub struct Markers<D> {
+    vertices: Vertices<D>,
+    start_dir: Vector,
+    seam_dir: Option<Vector>,
+}
+
+impl<D> Iterator for Markers<D>
+where
+    D: Iterator + Clone,
+    D::Item: Borrow<Command>,
+{
+    type Item = Marker;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(match self.vertices.next()? {
+            Vertex::Start(point, dir) => {
+                // Find the direction entering the seam of a closed subpath.
+                self.start_dir = dir;
+                self.seam_dir = None;
+                for vertex in self.vertices.clone() {
+                    if let Vertex::End(in_dir, _, closed) = vertex {
+                        if closed {
+                            self.seam_dir = Some(in_dir);
+                        }
+                        break;
+                    }
+                }
+                Marker {
+                    position: MarkerPosition::Start,
+                    point,
+                    incoming: self.seam_dir.unwrap_or(dir),
+                    outgoing: dir,
+                    closed: self.seam_dir.is_some(),
+                }
+            }
+            Vertex::Middle(in_dir, point, out_dir) => Marker {
+                position: MarkerPosition::Middle,
+                point,
+                incoming: in_dir,
+                outgoing: out_dir,
+                closed: self.seam_dir.is_some(),
+            },
+            Vertex::End(in_dir, point, closed) => Marker {
+                position: MarkerPosition::End,
+                point,
+                incoming: in_dir,
+                outgoing: if closed { self.start_dir } else { in_dir },
+                closed,
+            },
+        })
+    }
+}
+
 fn get_components(segment: &Segment) -> (Point, Vector, Vector, Point) {
     match segment {
         Segment::Curve(_, curve) => {
@@ -131,6 +300,7 @@ pub struct Walk<D> {
     first: bool,
     length: RefCell<Option<f32>>,
     walked: f32,
+    measure: Measure,
 }
 
 impl<D> Walk<D>
@@ -148,6 +318,7 @@ where
             first: true,
             length: RefCell::new(None),
             walked: 0.,
+            measure: Measure::Fast,
         }
     }
 }
@@ -168,6 +339,7 @@ where
             first: true,
             length: RefCell::new(None),
             walked: 0.,
+            measure: Measure::Fast,
         }
     }
 }
@@ -177,6 +349,14 @@ where
     D: Iterator + Clone,
     D::Item: Borrow<Command>,
 {
+    /// Sets the method used for measuring distances along curves. The
+    /// default is a fast approximation.
+    pub fn measure(&mut self, measure: Measure) -> &mut Self {
+        self.measure = measure;
+        *self.length.borrow_mut() = None;
+        self
+    }
+
     /// Steps by the specified distance and returns the point at the new
     /// location and the normal vector describing the left-ward direction at
     /// that point. Returns `None` if the distance steps beyond the end
@@ -192,7 +372,7 @@ where
         let mut segment = self.segment;
         let mut remaining = distance;
         loop {
-            let dt = segment.time(offset + remaining, 1.);
+            let dt = segment.time_by(offset + remaining, self.measure);
             remaining -= dt.distance - offset;
             t = dt.time;
             offset = dt.distance;
@@ -209,6 +389,65 @@ where
         Some((p, n))
     }
 
+    /// Steps by the specified distance, stopping at the end of the path.
+    ///
+    /// Unlike [step](#method.step), a step that reaches the end of the path
+    /// produces the end point along with the distance that could not be
+    /// walked. Once the end has been reached, every subsequent step returns
+    /// the end point with the full distance left over. Returns `None` only
+    /// if the path is empty.
+    ///
+    /// ```rust
+    /// use zeno::{Step, Walk};
+    ///
+    /// let mut walk = Walk::new("M0,0 L10,0");
+    /// assert!(matches!(walk.advance(4.0), Some(Step::Within(..))));
+    /// match walk.advance(10.0) {
+    ///     Some(Step::End(point, _, leftover)) => {
+    ///         assert_eq!(point.x, 10.0);
+    ///         assert!((leftover - 4.0).abs() < 1e-4);
+    ///     }
+    ///     _ => unreachable!(),
+    /// }
+    /// ```
+    pub fn advance(&mut self, distance: f32) -> Option<Step> {
+        if self.first {
+            self.segment = self.next_segment()?;
+            self.segment_offset = 0.;
+            self.first = false;
+        }
+        let mut t;
+        let mut offset = self.segment_offset;
+        let mut segment = self.segment;
+        let mut remaining = distance.max(0.);
+        loop {
+            let dt = segment.time_by(offset + remaining, self.measure);
+            remaining -= dt.distance - offset;
+            t = dt.time;
+            offset = dt.distance;
+            if remaining <= 0. {
+                break;
+            }
+            match self.next_segment() {
+                Some(next) => {
+                    segment = next;
+                    offset = 0.;
+                }
+                None => break,
+            }
+        }
+        let leftover = remaining.max(0.);
+        self.segment = segment;
+        self.segment_offset = offset;
+        self.walked += distance.max(0.) - leftover;
+        let (p, n) = segment.point_normal(t);
+        if leftover > 0. || (t >= 1. && self.at_end()) {
+            Some(Step::End(p, n, leftover))
+        } else {
+            Some(Step::Within(p, n))
+        }
+    }
+
     /// Returns the remaining distance available to walk on the path.
     pub fn remaining(&self) -> f32 {
         let mut l = self.length.borrow_mut();
@@ -216,7 +455,7 @@ where
             let iter = self.init.clone();
             let mut sum = 0.;
             for s in iter {
-                sum += s.length();
+                sum += s.length_by(self.measure);
             }
             *l = Some(sum);
         }
@@ -232,4 +471,165 @@ where
         }
         None
     }
+
+    /// Returns true if there are no segments following the current one.
+    fn at_end(&self) -> bool {
+        self.iter.clone().all(|s| matches!(s, Segment::End(..)))
+    }
+}
+
+/// An iterator like type that walks along the visible portions of a dashed
+/// path by arbitrary steps.
+///
+/// Distances are measured only along the "on" dashes of the pattern; gaps
+/// are skipped entirely. The dash pattern follows the same rules as
+/// [Stroke::dash](struct.Stroke.html#method.dash) and restarts at the
+/// beginning of each subpath.
+pub struct DashedWalk<'a, D> {
+    iter: Segments<D>,
+    segment: Option<Segment>,
+    segment_offset: f32,
+    segment_length: f32,
+    dashes: &'a [f32],
+    offset: f32,
+    dash_index: usize,
+    dash_remaining: f32,
+    on: bool,
+    measure: Measure,
+}
+
+impl<'a, D> DashedWalk<'a, D>
+where
+    D: Iterator<Item = Command> + Clone,
+{
+    /// Creates a new iterator like type that steps along the dashes of a
+    /// path by arbitrary distances.
+    pub fn new(data: impl PathData<Commands = D>, dashes: &'a [f32], offset: f32) -> Self {
+        Self::from_segments(segments(data.commands(), false), dashes, offset)
+    }
+}
+
+impl<'a, D> DashedWalk<'a, TransformCommands<D>>
+where
+    D: Iterator<Item = Command> + Clone,
+{
+    /// Creates a new iterator like type that steps along the dashes of a
+    /// transformed path by arbitrary distances.
+    pub fn with_transform(
+        data: impl PathData<Commands = D>,
+        transform: Transform,
+        dashes: &'a [f32],
+        offset: f32,
+    ) -> Self {
+        let data = TransformCommands {
+            data: data.commands(),
+            transform,
+        };
+        Self::from_segments(segments(data, false), dashes, offset)
+    }
+}
+
+impl<'a, D> DashedWalk<'a, D>
+where
+    D: Iterator + Clone,
+    D::Item: Borrow<Command>,
+{
+    fn from_segments(iter: Segments<D>, dashes: &'a [f32], offset: f32) -> Self {
+        let (dashes, offset, _) = validate_dashes(dashes, offset);
+        let mut walk = Self {
+            iter,
+            segment: None,
+            segment_offset: 0.,
+            segment_length: 0.,
+            dashes,
+            offset,
+            dash_index: 0,
+            dash_remaining: 0.,
+            on: true,
+            measure: Measure::Fast,
+        };
+        walk.reset_dashes();
+        walk
+    }
+
+    /// Sets the method used for measuring distances along curves. The
+    /// default is a fast approximation.
+    pub fn measure(&mut self, measure: Measure) -> &mut Self {
+        self.measure = measure;
+        self
+    }
+
+    /// Steps by the specified distance along the visible dashes and returns
+    /// the point at the new location and the normal vector describing the
+    /// left-ward direction at that point. Returns `None` if the distance
+    /// steps beyond the end of the path.
+    pub fn step(&mut self, distance: f32) -> Option<(Point, Vector)> {
+        let mut remaining = distance.max(0.);
+        loop {
+            let segment = match self.segment {
+                Some(segment) => segment,
+                None => {
+                    let segment = self.iter.next()?;
+                    if let Segment::End(..) = segment {
+                        self.reset_dashes();
+                        continue;
+                    }
+                    self.segment = Some(segment);
+                    self.segment_offset = 0.;
+                    self.segment_length = segment.length_by(self.measure);
+                    segment
+                }
+            };
+            let segment_left = self.segment_length - self.segment_offset;
+            let advance = if self.on {
+                remaining.min(self.dash_remaining).min(segment_left)
+            } else {
+                self.dash_remaining.min(segment_left)
+            };
+            self.segment_offset += advance;
+            self.dash_remaining -= advance;
+            if self.on {
+                remaining -= advance;
+                if remaining <= 0. {
+                    let t = segment.time_by(self.segment_offset, self.measure).time;
+                    return Some(segment.point_normal(t));
+                }
+            }
+            if self.dash_remaining <= 0. {
+                self.next_dash();
+            }
+            if self.segment_offset >= self.segment_length {
+                self.segment = None;
+            }
+        }
+    }
+
+    fn reset_dashes(&mut self) {
+        self.dash_index = 0;
+        self.on = true;
+        if self.dashes.is_empty() {
+            self.dash_remaining = f32::INFINITY;
+            return;
+        }
+        self.dash_remaining = self.dashes[0];
+        let mut offset = self.offset;
+        while offset > 0. {
+            if offset >= self.dash_remaining {
+                offset -= self.dash_remaining;
+                self.next_dash();
+            } else {
+                self.dash_remaining -= offset;
+                offset = 0.;
+            }
+        }
+    }
+
+    fn next_dash(&mut self) {
+        if self.dashes.is_empty() {
+            return;
+        }
+        self.dash_index += 1;
+        self.dash_remaining = self.dashes[self.dash_index % self.dashes.len()];
+        self.on = !self.on;
+    }
 }
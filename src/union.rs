@@ -0,0 +1,308 @@
+//! Removal of overlapping regions from polygonal outlines.
+
+use super::command::Command;
+use super::geometry::{Point, Vector};
+use super::path_builder::PathBuilder;
+use super::path_data::PathData;
+use super::segment::{segments, Segment};
+use super::style::Fill;
+#[cfg(not(feature = "std"))]
+use super::F32Ext;
+
+use crate::lib::Vec;
+use core::cmp::Ordering;
+
+/// Maximum number of flattened edges for which overlaps are removed.
+const MAX_EDGES: usize = 1 << 16;
+
+/// Flattens the commands and emits the boundary of the region they cover
+/// according to the fill rule as a set of non-overlapping polygons. The
+/// inside of the region is on the same side of every edge so the result
+/// produces the same coverage with either fill rule and covers each pixel
+/// only once.
+///
+/// The output consists only of line segments, even when the input contains
+/// curves. Intersections are found with a sweep over the edges sorted by
+/// their horizontal extent and each piece is classified against the edges
+/// that share its horizontal band, so the cost is roughly proportional to
+/// the number of edges that overlap each other rather than to the square of
+/// the number of edges. Inputs that flatten to more than `MAX_EDGES` edges
+/// are emitted unchanged.
+pub(super) fn union_into(
+    commands: &[Command],
+    fill: Fill,
+    flatness: f32,
+    sink: &mut impl PathBuilder,
+) {
+    let edges = flatten(commands, flatness);
+    if edges.len() > MAX_EDGES {
+        commands.copy_to(sink);
+        return;
+    }
+    let pieces = split(&edges);
+    let bands = Bands::new(&edges);
+    let mut boundary = Vec::new();
+    for &(a, b) in &pieces {
+        let d = b - a;
+        let len = d.length();
+        let mid = (a + b) * 0.5;
+        let magnitude = mid.x.abs().max(mid.y.abs()).max(1.);
+        let eps = (len * 0.01).min(0.01).max(magnitude * 4e-6);
+        let n = Vector::new(-d.y, d.x) * (eps / len);
+        let left = bands.is_inside(&edges, mid + n, fill);
+        let right = bands.is_inside(&edges, mid - n, fill);
+        match (left, right) {
+            (true, false) => boundary.push((a, b)),
+            (false, true) => boundary.push((b, a)),
+            _ => {}
+        }
+    }
+    boundary.sort_by(compare_edges);
+    boundary.dedup();
+    link(&boundary, sink);
+}
+
+/// Flattens the commands into a list of edges with implicitly closed
+/// subpaths.
+fn flatten(commands: &[Command], flatness: f32) -> Vec<(Point, Point)> {
+    let mut edges = Vec::new();
+    let mut points = Vec::new();
+    // Points this close together are merged. Slivers between them have no
+    // meaningful inside and would be classified arbitrarily.
+    let merge = (flatness * 0.01) * (flatness * 0.01);
+    let close = |points: &mut Vec<Point>, edges: &mut Vec<(Point, Point)>| {
+        points.dedup_by(|p, prev| (*p - *prev).length_squared() <= merge);
+        while points.len() > 1 && (points[points.len() - 1] - points[0]).length_squared() <= merge {
+            points.pop();
+        }
+        if let Some(&first) = points.first() {
+            points.push(first);
+        }
+        for pair in points.windows(2) {
+            if pair[0] != pair[1] {
+                edges.push((pair[0], pair[1]));
+            }
+        }
+        points.clear();
+    };
+    for segment in segments(commands.iter(), false) {
+        match segment {
+            Segment::Line(_, line) => {
+                if points.is_empty() {
+                    points.push(line.a);
+                }
+                points.push(line.b);
+            }
+            Segment::Curve(_, curve) => {
+                if points.is_empty() {
+                    points.push(curve.a);
+                }
+                curve.flatten(flatness, &mut points);
+            }
+            Segment::End(..) => close(&mut points, &mut edges),
+        }
+    }
+    close(&mut points, &mut edges);
+    edges
+}
+
+/// Splits the edges at every point where they intersect or touch another
+/// edge. Points shared by multiple pieces are bitwise identical.
+fn split(edges: &[(Point, Point)]) -> Vec<(Point, Point)> {
+    let mut splits: Vec<Vec<(f32, Point)>> = edges.iter().map(|_| Vec::new()).collect();
+    // Sweep from left to right, testing each edge against the edges whose
+    // horizontal extent overlaps its own.
+    let min_x = |i: usize| edges[i].0.x.min(edges[i].1.x);
+    let mut order: Vec<usize> = (0..edges.len()).collect();
+    order.sort_by(|&i, &j| min_x(i).partial_cmp(&min_x(j)).unwrap_or(Ordering::Equal));
+    let mut active: Vec<usize> = Vec::new();
+    for &i in &order {
+        let x = min_x(i);
+        active.retain(|&j| edges[j].0.x.max(edges[j].1.x) >= x);
+        for &j in &active {
+            // Intersections are computed in the same order regardless of
+            // the sweep so that the result does not depend on it.
+            intersect(edges, i.min(j), i.max(j), &mut splits);
+        }
+        active.push(i);
+    }
+    let mut pieces = Vec::new();
+    for (&(a, b), points) in edges.iter().zip(splits.iter_mut()) {
+        points.sort_by(|x, y| x.0.partial_cmp(&y.0).unwrap_or(Ordering::Equal));
+        let mut prev = a;
+        for &(_, p) in points.iter() {
+            if p != prev {
+                pieces.push((prev, p));
+                prev = p;
+            }
+        }
+        if prev != b {
+            pieces.push((prev, b));
+        }
+    }
+    pieces
+}
+
+/// Records the points where two edges intersect or touch in the split lists
+/// of both edges.
+fn intersect(edges: &[(Point, Point)], i: usize, j: usize, splits: &mut [Vec<(f32, Point)>]) {
+    const EPS: f32 = 1e-5;
+    // Returns the parameter of the projection of a point onto an edge.
+    let project = |(a, b): (Point, Point), p: Point| {
+        let d = b - a;
+        (p - a).dot(d) / d.length_squared()
+    };
+    let (a, b) = edges[i];
+    let (c, e) = edges[j];
+    if a.y.max(b.y) < c.y.min(e.y) || c.y.max(e.y) < a.y.min(b.y) {
+        return;
+    }
+    let d1 = b - a;
+    let d2 = e - c;
+    let denom = d1.cross(d2);
+    let ac = c - a;
+    let scale = d1.length() * d2.length();
+    if denom.abs() <= EPS * scale {
+        // Parallel edges only interact when they are collinear.
+        if ac.cross(d1).abs() > EPS * d1.length() * ac.length().max(1.) {
+            return;
+        }
+        for &p in &[c, e] {
+            let t = project(edges[i], p);
+            if t > EPS && t < 1. - EPS {
+                splits[i].push((t, p));
+            }
+        }
+        for &p in &[a, b] {
+            let u = project(edges[j], p);
+            if u > EPS && u < 1. - EPS {
+                splits[j].push((u, p));
+            }
+        }
+        return;
+    }
+    let t = ac.cross(d2) / denom;
+    let u = ac.cross(d1) / denom;
+    let range = -EPS..=1. + EPS;
+    if !range.contains(&t) || !range.contains(&u) {
+        return;
+    }
+    // Snap to existing end points so that pieces connect exactly.
+    let p = if t <= EPS {
+        a
+    } else if t >= 1. - EPS {
+        b
+    } else if u <= EPS {
+        c
+    } else if u >= 1. - EPS {
+        e
+    } else {
+        a + d1 * t
+    };
+    if p != a && p != b {
+        splits[i].push((project(edges[i], p), p));
+    }
+    if p != c && p != e {
+        splits[j].push((project(edges[j], p), p));
+    }
+}
+
+/// Edges grouped into horizontal bands of equal height for winding number
+/// queries.
+struct Bands {
+    top: f32,
+    scale: f32,
+    bands: Vec<Vec<u32>>,
+}
+
+impl Bands {
+    fn new(edges: &[(Point, Point)]) -> Self {
+        let (mut top, mut bottom) = (f32::MAX, f32::MIN);
+        for &(a, b) in edges {
+            top = top.min(a.y).min(b.y);
+            bottom = bottom.max(a.y).max(b.y);
+        }
+        let count = (edges.len() as f32).sqrt().ceil().max(1.) as usize;
+        let height = bottom - top;
+        let scale = if height > 0. {
+            count as f32 / height
+        } else {
+            0.
+        };
+        let mut bands = Self {
+            top,
+            scale,
+            bands: (0..count).map(|_| Vec::new()).collect(),
+        };
+        for (i, &(a, b)) in edges.iter().enumerate() {
+            let first = bands.index(a.y.min(b.y));
+            let last = bands.index(a.y.max(b.y));
+            for band in &mut bands.bands[first..=last] {
+                band.push(i as u32);
+            }
+        }
+        bands
+    }
+
+    fn index(&self, y: f32) -> usize {
+        (((y - self.top) * self.scale) as usize).min(self.bands.len() - 1)
+    }
+
+    /// Returns true if the point is inside the region bounded by the edges.
+    fn is_inside(&self, edges: &[(Point, Point)], p: Point, fill: Fill) -> bool {
+        let mut winding = 0i32;
+        for &i in &self.bands[self.index(p.y)] {
+            let (a, b) = edges[i as usize];
+            let side = (b - a).cross(p - a);
+            if a.y <= p.y {
+                if b.y > p.y && side > 0. {
+                    winding += 1;
+                }
+            } else if b.y <= p.y && side < 0. {
+                winding -= 1;
+            }
+        }
+        match fill {
+            Fill::NonZero => winding != 0,
+            Fill::EvenOdd => winding & 1 != 0,
+        }
+    }
+}
+
+fn compare_points(a: &Point, b: &Point) -> Ordering {
+    a.x.partial_cmp(&b.x)
+        .unwrap_or(Ordering::Equal)
+        .then(a.y.partial_cmp(&b.y).unwrap_or(Ordering::Equal))
+}
+
+fn compare_edges(a: &(Point, Point), b: &(Point, Point)) -> Ordering {
+    compare_points(&a.0, &b.0).then(compare_points(&a.1, &b.1))
+}
+
+/// Links directed edges sorted by start point into closed polygons.
+fn link(edges: &[(Point, Point)], sink: &mut impl PathBuilder) {
+    let mut used: Vec<bool> = edges.iter().map(|_| false).collect();
+    for i in 0..edges.len() {
+        if used[i] {
+            continue;
+        }
+        used[i] = true;
+        let (start, mut end) = edges[i];
+        sink.move_to(start);
+        while end != start {
+            sink.line_to(end);
+            let first = edges.partition_point(|e| compare_points(&e.0, &end) == Ordering::Less);
+            let next = (first..edges.len())
+                .take_while(|&j| edges[j].0 == end)
+                .find(|&j| !used[j]);
+            match next {
+                Some(j) => {
+                    used[j] = true;
+                    end = edges[j].1;
+                }
+                None => break,
+            }
+        }
+        sink.close();
+    }
+}
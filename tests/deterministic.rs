@@ -0,0 +1,49 @@
+//! Golden mask test for the `deterministic` feature.
+//!
+//! The expected hashes were produced with the portable `libm` routines and
+//! must match on every target.
+#![cfg(feature = "deterministic")]
+
+use zeno::{Angle, Cap, Join, Mask, Stroke, Transform};
+
+/// 64-bit FNV-1a hash of a mask.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hash = 0xcbf29ce484222325u64;
+    for &b in bytes {
+        hash ^= b as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+#[test]
+fn golden_fill() {
+    // Arcs, rotation, skew and gamma correction all depend on transcendental
+    // functions.
+    let path = "M10,30 A20,20 0 0,1 50,30 A20,20 0 0,1 90,30 Q90,60 50,90 Q10,60 10,30 Z";
+    let transform = Transform::rotation(Angle::from_degrees(17.))
+        .then(&Transform::skew(Angle::from_degrees(8.), Angle::ZERO))
+        .then_translate(40., 10.);
+    let (mask, placement) = Mask::new(path)
+        .transform(Some(transform))
+        .gamma(1.8)
+        .render();
+    assert_eq!((placement.width, placement.height), (85, 89));
+    assert_eq!(fnv1a(&mask), 0x30a774e16f68d6b0);
+}
+
+#[test]
+fn golden_stroke() {
+    let path = "M8,40 C20,0 60,80 92,20 L60,70 A30,15 30 1,0 20,60";
+    let mut stroke = Stroke::new(5.);
+    stroke
+        .join(Join::Round)
+        .cap(Cap::Round)
+        .dash(&[14., 6.], 3.);
+    let (mask, placement) = Mask::new(path)
+        .style(stroke)
+        .transform(Some(Transform::rotation(Angle::from_degrees(-23.))))
+        .render();
+    assert_eq!((placement.width, placement.height), (79, 72));
+    assert_eq!(fnv1a(&mask), 0xede19845483272d4);
+}